@@ -87,6 +87,13 @@ impl Ast {
             .to_const()
             .unwrap()
     }
+
+    #[cfg(test)]
+    pub fn import(&self, index: usize) -> &Import {
+        self.files.last().unwrap().elements[index]
+            .to_import()
+            .unwrap()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -113,6 +120,7 @@ pub enum Elem {
     ElemImpl(Impl),
     ElemGlobal(Global),
     ElemConst(Const),
+    ElemImport(Import),
 }
 
 impl Elem {
@@ -125,6 +133,20 @@ impl Elem {
             &ElemImpl(ref i) => i.id,
             &ElemGlobal(ref g) => g.id,
             &ElemConst(ref c) => c.id,
+            &ElemImport(ref i) => i.id,
+        }
+    }
+
+    pub fn pos(&self) -> Position {
+        match self {
+            &ElemFunction(ref fct) => fct.pos,
+            &ElemClass(ref class) => class.pos,
+            &ElemStruct(ref s) => s.pos,
+            &ElemTrait(ref t) => t.pos,
+            &ElemImpl(ref i) => i.pos,
+            &ElemGlobal(ref g) => g.pos,
+            &ElemConst(ref c) => c.pos,
+            &ElemImport(ref i) => i.pos,
         }
     }
 
@@ -176,6 +198,28 @@ impl Elem {
             _ => None,
         }
     }
+
+    pub fn to_import(&self) -> Option<&Import> {
+        match self {
+            &ElemImport(ref import) => Some(import),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub id: NodeId,
+    pub pos: Position,
+    pub path: Path,
+    pub alias: Option<Name>,
+}
+
+impl Import {
+    // name under which the imported item is visible
+    pub fn name(&self) -> Name {
+        self.alias.unwrap_or(*self.path.path.last().unwrap())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -183,9 +227,10 @@ pub struct Global {
     pub id: NodeId,
     pub pos: Position,
     pub name: Name,
+    pub is_pub: bool,
     pub reassignable: bool,
     pub data_type: Type,
-    pub expr: Option<Box<Expr>>,
+    pub initializer: Option<Function>,
 }
 
 #[derive(Clone, Debug)]
@@ -193,6 +238,7 @@ pub struct Const {
     pub id: NodeId,
     pub pos: Position,
     pub name: Name,
+    pub is_pub: bool,
     pub data_type: Type,
     pub expr: Box<Expr>,
 }
@@ -202,6 +248,7 @@ pub struct Struct {
     pub id: NodeId,
     pub pos: Position,
     pub name: Name,
+    pub is_pub: bool,
     pub fields: Vec<StructField>,
 }
 
@@ -248,6 +295,8 @@ pub struct TypeLambdaType {
 pub struct TypeBasicType {
     pub id: NodeId,
     pub pos: Position,
+    // modules before the type name, empty if the type isn't qualified
+    pub module: Vec<Name>,
     pub name: Name,
     pub params: Vec<Box<Type>>,
 }
@@ -271,10 +320,16 @@ impl Type {
         Type::TypeSelf(TypeSelfType { id: id, pos: pos })
     }
 
-    pub fn create_basic(id: NodeId, pos: Position, name: Name, params: Vec<Box<Type>>) -> Type {
+    pub fn create_basic(id: NodeId,
+                        pos: Position,
+                        module: Vec<Name>,
+                        name: Name,
+                        params: Vec<Box<Type>>)
+                        -> Type {
         Type::TypeBasic(TypeBasicType {
                             id: id,
                             pos: pos,
+                            module: module,
                             name: name,
                             params: params,
                         })
@@ -359,7 +414,15 @@ impl Type {
     pub fn to_string(&self, interner: &Interner) -> String {
         match *self {
             Type::TypeSelf(_) => "Self".into(),
-            Type::TypeBasic(ref val) => format!("{}", *interner.str(val.name)),
+            Type::TypeBasic(ref val) => {
+                let mut names: Vec<String> = val.module
+                    .iter()
+                    .map(|&name| interner.str(name).to_string())
+                    .collect();
+                names.push(interner.str(val.name).to_string());
+
+                names.join("::")
+            }
 
             Type::TypeTuple(ref val) => {
                 let types: Vec<String> = val.subtypes
@@ -413,7 +476,9 @@ impl Type {
 pub struct Impl {
     pub id: NodeId,
     pub trait_name: Name,
+    pub trait_type_params: Vec<Type>,
     pub class_name: Name,
+    pub class_type_params: Option<Vec<TypeParam>>,
     pub pos: Position,
     pub methods: Vec<Function>,
 }
@@ -423,6 +488,8 @@ pub struct Trait {
     pub id: NodeId,
     pub name: Name,
     pub pos: Position,
+    pub is_pub: bool,
+    pub type_params: Option<Vec<TypeParam>>,
     pub methods: Vec<Function>,
 }
 
@@ -435,10 +502,12 @@ pub struct Class {
     pub has_open: bool,
     pub is_abstract: bool,
     pub internal: bool,
+    pub is_pub: bool,
     pub has_constructor: bool,
 
     pub constructor: Option<Function>,
     pub fields: Vec<Field>,
+    pub static_fields: Vec<Global>,
     pub methods: Vec<Function>,
     pub initializers: Vec<Box<Stmt>>,
     pub type_params: Option<Vec<TypeParam>>,
@@ -488,6 +557,7 @@ pub struct Field {
     pub primary_ctor: bool,
     pub expr: Option<Box<Expr>>,
     pub reassignable: bool,
+    pub is_pub: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -500,18 +570,25 @@ pub struct Function {
     pub has_override: bool,
     pub has_final: bool,
     pub has_optimize: bool,
+    pub is_tailrec: bool,
     pub is_pub: bool,
     pub is_static: bool,
     pub is_abstract: bool,
     pub internal: bool,
     pub is_constructor: bool,
     pub is_external: bool,
+    pub is_test: bool,
+    pub is_ignored: bool,
+    pub is_inline: bool,
+    pub is_noinline: bool,
+    pub deprecated: Option<String>,
     pub params: Vec<Param>,
     pub throws: bool,
 
     pub return_type: Option<Type>,
     pub block: Option<Box<Stmt>>,
     pub type_params: Option<Vec<TypeParam>>,
+    pub where_clauses: Vec<TypeParam>,
 }
 
 impl Function {
@@ -532,11 +609,20 @@ impl Modifiers {
         self.0.iter().find(|el| el.value == modifier).is_some()
     }
 
+    pub fn get(&self, modifier: Modifier) -> Option<&ModifierElement> {
+        self.0.iter().find(|el| el.value == modifier)
+    }
+
     pub fn add(&mut self, modifier: Modifier, pos: Position) {
+        self.add_with_arg(modifier, pos, None);
+    }
+
+    pub fn add_with_arg(&mut self, modifier: Modifier, pos: Position, arg: Option<String>) {
         self.0
             .push(ModifierElement {
                       value: modifier,
                       pos: pos,
+                      arg: arg,
                   });
     }
 
@@ -549,6 +635,9 @@ impl Modifiers {
 pub struct ModifierElement {
     pub value: Modifier,
     pub pos: Position,
+
+    // string argument of an attribute, e.g. the message of `#[deprecated("msg")]`
+    pub arg: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -560,8 +649,16 @@ pub enum Modifier {
     Internal,
     Extern,
     Optimize,
+    Tailrec,
     Pub,
     Static,
+
+    // attributes of the form `#[name]`
+    Test,
+    Ignore,
+    Inline,
+    NoInline,
+    Deprecated,
 }
 
 impl Modifier {
@@ -573,9 +670,15 @@ impl Modifier {
             Modifier::Final => "final",
             Modifier::Internal => "internal",
             Modifier::Optimize => "optimize",
+            Modifier::Tailrec => "tailrec",
             Modifier::Pub => "pub",
             Modifier::Static => "static",
-            Modifier::Extern => "extern"
+            Modifier::Extern => "extern",
+            Modifier::Test => "#[test]",
+            Modifier::Ignore => "#[ignore]",
+            Modifier::Inline => "#[inline]",
+            Modifier::NoInline => "#[noinline]",
+            Modifier::Deprecated => "#[deprecated]",
         }
     }
 }
@@ -606,6 +709,7 @@ pub enum Stmt {
     StmtDo(StmtDoType),
     StmtSpawn(StmtSpawnType),
     StmtFor(StmtForType),
+    StmtVarTuple(StmtVarTupleType),
 }
 
 impl Stmt {
@@ -626,29 +730,58 @@ impl Stmt {
                       })
     }
 
-    pub fn create_for(id: NodeId, pos: Position, name: Name, expr: Box<Expr>, block: Box<Stmt>) -> Stmt {
+    pub fn create_var_tuple(id: NodeId,
+                            pos: Position,
+                            names: Vec<TupleBinding>,
+                            reassignable: bool,
+                            expr: Box<Expr>)
+                            -> Stmt {
+        Stmt::StmtVarTuple(StmtVarTupleType {
+                               id: id,
+                               pos: pos,
+                               names: names,
+                               reassignable: reassignable,
+                               expr: expr,
+                           })
+    }
+
+    pub fn create_for(id: NodeId,
+                      pos: Position,
+                      label: Option<Name>,
+                      name: Name,
+                      expr: Box<Expr>,
+                      block: Box<Stmt>)
+                      -> Stmt {
         Stmt::StmtFor(StmtForType {
                             id: id,
                             pos: pos,
+                            label: label,
                             name: name,
                             expr: expr,
                             block: block,
                         })
     }
 
-    pub fn create_while(id: NodeId, pos: Position, cond: Box<Expr>, block: Box<Stmt>) -> Stmt {
+    pub fn create_while(id: NodeId,
+                        pos: Position,
+                        label: Option<Name>,
+                        cond: Box<Expr>,
+                        block: Box<Stmt>)
+                        -> Stmt {
         Stmt::StmtWhile(StmtWhileType {
                             id: id,
                             pos: pos,
+                            label: label,
                             cond: cond,
                             block: block,
                         })
     }
 
-    pub fn create_loop(id: NodeId, pos: Position, block: Box<Stmt>) -> Stmt {
+    pub fn create_loop(id: NodeId, pos: Position, label: Option<Name>, block: Box<Stmt>) -> Stmt {
         Stmt::StmtLoop(StmtLoopType {
                            id: id,
                            pos: pos,
+                           label: label,
                            block: block,
                        })
     }
@@ -684,12 +817,20 @@ impl Stmt {
                         })
     }
 
-    pub fn create_break(id: NodeId, pos: Position) -> Stmt {
-        Stmt::StmtBreak(StmtBreakType { id: id, pos: pos })
+    pub fn create_break(id: NodeId, pos: Position, label: Option<Name>) -> Stmt {
+        Stmt::StmtBreak(StmtBreakType {
+                            id: id,
+                            pos: pos,
+                            label: label,
+                        })
     }
 
-    pub fn create_continue(id: NodeId, pos: Position) -> Stmt {
-        Stmt::StmtContinue(StmtContinueType { id: id, pos: pos })
+    pub fn create_continue(id: NodeId, pos: Position, label: Option<Name>) -> Stmt {
+        Stmt::StmtContinue(StmtContinueType {
+                               id: id,
+                               pos: pos,
+                               label: label,
+                           })
     }
 
     pub fn create_return(id: NodeId, pos: Position, expr: Option<Box<Expr>>) -> Stmt {
@@ -755,6 +896,7 @@ impl Stmt {
             Stmt::StmtDefer(ref stmt) => stmt.id,
             Stmt::StmtDo(ref stmt) => stmt.id,
             Stmt::StmtSpawn(ref stmt) => stmt.id,
+            Stmt::StmtVarTuple(ref stmt) => stmt.id,
         }
     }
 
@@ -774,6 +916,7 @@ impl Stmt {
             Stmt::StmtDefer(ref stmt) => stmt.pos,
             Stmt::StmtDo(ref stmt) => stmt.pos,
             Stmt::StmtSpawn(ref stmt) => stmt.pos,
+            Stmt::StmtVarTuple(ref stmt) => stmt.pos,
         }
     }
 
@@ -833,6 +976,20 @@ impl Stmt {
         }
     }
 
+    pub fn to_var_tuple(&self) -> Option<&StmtVarTupleType> {
+        match *self {
+            Stmt::StmtVarTuple(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_var_tuple(&self) -> bool {
+        match *self {
+            Stmt::StmtVarTuple(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn to_while(&self) -> Option<&StmtWhileType> {
         match *self {
             Stmt::StmtWhile(ref val) => Some(val),
@@ -985,11 +1142,29 @@ pub struct StmtVarType {
     pub expr: Option<Box<Expr>>,
 }
 
+// destructures a tuple: `let (a, b) = expr;`
+#[derive(Clone, Debug)]
+pub struct StmtVarTupleType {
+    pub id: NodeId,
+    pub pos: Position,
+    pub names: Vec<TupleBinding>,
+    pub reassignable: bool,
+
+    pub expr: Box<Expr>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TupleBinding {
+    pub id: NodeId,
+    pub pos: Position,
+    pub name: Name,
+}
 
 #[derive(Clone, Debug)]
 pub struct StmtForType {
     pub id: NodeId,
     pub pos: Position,
+    pub label: Option<Name>,
 
     pub name: Name,
     pub expr: Box<Expr>,
@@ -1000,6 +1175,7 @@ pub struct StmtForType {
 pub struct StmtWhileType {
     pub id: NodeId,
     pub pos: Position,
+    pub label: Option<Name>,
 
     pub cond: Box<Expr>,
     pub block: Box<Stmt>,
@@ -1009,6 +1185,7 @@ pub struct StmtWhileType {
 pub struct StmtLoopType {
     pub id: NodeId,
     pub pos: Position,
+    pub label: Option<Name>,
     pub block: Box<Stmt>,
 }
 
@@ -1046,12 +1223,18 @@ pub struct StmtReturnType {
 pub struct StmtBreakType {
     pub id: NodeId,
     pub pos: Position,
+
+    // label of the loop to leave, innermost loop if none
+    pub label: Option<Name>,
 }
 
 #[derive(Clone, Debug)]
 pub struct StmtContinueType {
     pub id: NodeId,
     pub pos: Position,
+
+    // label of the loop to continue, innermost loop if none
+    pub label: Option<Name>,
 }
 
 #[derive(Clone, Debug)]
@@ -1236,6 +1419,8 @@ pub enum Expr {
     ExprConv(ExprConvType),
     ExprTry(ExprTryType),
     ExprLambda(ExprLambdaType),
+    ExprTuple(ExprTupleType),
+    ExprTemplate(ExprTemplateType),
 }
 
 impl Expr {
@@ -1287,6 +1472,22 @@ impl Expr {
                        })
     }
 
+    pub fn create_tuple(id: NodeId, pos: Position, values: Vec<Box<Expr>>) -> Expr {
+        Expr::ExprTuple(ExprTupleType {
+                            id: id,
+                            pos: pos,
+                            values: values,
+                        })
+    }
+
+    pub fn create_template(id: NodeId, pos: Position, parts: Vec<Box<Expr>>) -> Expr {
+        Expr::ExprTemplate(ExprTemplateType {
+                               id: id,
+                               pos: pos,
+                               parts: parts,
+                           })
+    }
+
     pub fn create_array(id: NodeId, pos: Position, object: Box<Expr>, index: Box<Expr>) -> Expr {
         Expr::ExprArray(ExprArrayType {
                             id: id,
@@ -1690,6 +1891,34 @@ impl Expr {
         }
     }
 
+    pub fn to_tuple(&self) -> Option<&ExprTupleType> {
+        match *self {
+            Expr::ExprTuple(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        match *self {
+            Expr::ExprTuple(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn to_template(&self) -> Option<&ExprTemplateType> {
+        match *self {
+            Expr::ExprTemplate(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_template(&self) -> bool {
+        match *self {
+            Expr::ExprTemplate(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn to_delegation(&self) -> Option<&ExprDelegationType> {
         match *self {
             Expr::ExprDelegation(ref val) => Some(val),
@@ -1799,6 +2028,8 @@ impl Expr {
             Expr::ExprConv(ref val) => val.pos,
             Expr::ExprTry(ref val) => val.pos,
             Expr::ExprLambda(ref val) => val.pos,
+            Expr::ExprTuple(ref val) => val.pos,
+            Expr::ExprTemplate(ref val) => val.pos,
         }
     }
 
@@ -1827,6 +2058,8 @@ impl Expr {
             Expr::ExprConv(ref val) => val.id,
             Expr::ExprTry(ref val) => val.id,
             Expr::ExprLambda(ref val) => val.id,
+            Expr::ExprTuple(ref val) => val.id,
+            Expr::ExprTemplate(ref val) => val.id,
         }
     }
 }
@@ -1951,6 +2184,24 @@ pub struct ExprBinType {
     pub rhs: Box<Expr>,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExprTupleType {
+    pub id: NodeId,
+    pub pos: Position,
+
+    pub values: Vec<Box<Expr>>,
+}
+
+// string with interpolated expressions: `"a${b}c"`, the string parts
+// are stored as string literals
+#[derive(Clone, Debug)]
+pub struct ExprTemplateType {
+    pub id: NodeId,
+    pub pos: Position,
+
+    pub parts: Vec<Box<Expr>>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ExprArrayType {
     pub id: NodeId,
@@ -74,6 +74,7 @@ impl<'a> AstDumper<'a> {
                 ElemImpl(ref ximpl) => self.dump_impl(ximpl),
                 ElemGlobal(ref global) => self.dump_global(global),
                 ElemConst(ref xconst) => self.dump_const(xconst),
+                ElemImport(ref import) => self.dump_import(import),
             }
         }
     }
@@ -88,10 +89,10 @@ impl<'a> AstDumper<'a> {
         self.indent(|d| {
             d.dump_type(&global.data_type);
 
-            if let Some(ref expr) = global.expr {
-                d.dump_expr(expr);
+            if let Some(ref initializer) = global.initializer {
+                d.dump_fct(initializer);
             } else {
-                dump!(d, "<no expr given>");
+                dump!(d, "<no initializer given>");
             }
         });
     }
@@ -109,6 +110,21 @@ impl<'a> AstDumper<'a> {
                     });
     }
 
+    fn dump_import(&mut self, import: &Import) {
+        let path: Vec<String> = import.path
+            .path
+            .iter()
+            .map(|&name| self.str(name).to_string())
+            .collect();
+
+        dump!(self,
+              "import {} as {} @ {} {}",
+              path.join("::"),
+              self.str(import.name()),
+              import.pos,
+              import.id);
+    }
+
     fn dump_impl(&mut self, ximpl: &Impl) {
         dump!(self,
               "impl {} for {} @ {} {}",
@@ -173,6 +189,12 @@ impl<'a> AstDumper<'a> {
                          d.dump_field(field);
                      });
 
+            dump!(d, "static fields");
+
+            d.indent(|d| for global in &cls.static_fields {
+                         d.dump_global(global);
+                     });
+
             dump!(d, "constructor");
             if let Some(ctor) = &cls.constructor {
                 d.indent(|d| d.dump_fct(ctor));
@@ -263,6 +285,7 @@ impl<'a> AstDumper<'a> {
             StmtDo(ref stmt) => self.dump_stmt_do(stmt),
             StmtSpawn(ref stmt) => self.dump_stmt_spawn(stmt),
             StmtFor(ref stmt) => self.dump_stmt_for(stmt),
+            StmtVarTuple(ref stmt) => self.dump_stmt_var_tuple(stmt),
         }
     }
 
@@ -290,8 +313,20 @@ impl<'a> AstDumper<'a> {
         });
     }
 
+    fn dump_stmt_var_tuple(&mut self, stmt: &StmtVarTupleType) {
+        let names = stmt.names
+            .iter()
+            .map(|binding| self.str(binding.name).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        dump!(self, "let ({}) @ {} {}", names, stmt.pos, stmt.id);
+
+        self.indent(|d| d.dump_expr(&stmt.expr));
+    }
+
     fn dump_stmt_for(&mut self, stmt: &StmtForType) {
         dump!(self, "for @ {} {}", stmt.pos, stmt.id);
+        self.dump_label(stmt.label);
 
         self.indent(|d| {
                         dump!(d, "name {:?}", stmt.name);
@@ -304,6 +339,7 @@ impl<'a> AstDumper<'a> {
 
     fn dump_stmt_while(&mut self, stmt: &StmtWhileType) {
         dump!(self, "while @ {} {}", stmt.pos, stmt.id);
+        self.dump_label(stmt.label);
 
         self.indent(|d| {
                         dump!(d, "cond");
@@ -316,6 +352,7 @@ impl<'a> AstDumper<'a> {
 
     fn dump_stmt_loop(&mut self, stmt: &StmtLoopType) {
         dump!(self, "loop @ {} {}", stmt.pos, stmt.id);
+        self.dump_label(stmt.label);
         self.indent(|d| { d.dump_stmt(&stmt.block); });
     }
 
@@ -366,10 +403,18 @@ impl<'a> AstDumper<'a> {
 
     fn dump_stmt_break(&mut self, stmt: &StmtBreakType) {
         dump!(self, "break @ {} {}", stmt.pos, stmt.id);
+        self.dump_label(stmt.label);
     }
 
     fn dump_stmt_continue(&mut self, stmt: &StmtContinueType) {
         dump!(self, "continue @ {} {}", stmt.pos, stmt.id);
+        self.dump_label(stmt.label);
+    }
+
+    fn dump_label(&mut self, label: Option<Name>) {
+        if let Some(label) = label {
+            self.indent(|d| dump!(d, "label {}", d.str(label)));
+        }
     }
 
     fn dump_stmt_throw(&mut self, stmt: &StmtThrowType) {
@@ -430,6 +475,8 @@ impl<'a> AstDumper<'a> {
             ExprConv(ref expr) => self.dump_expr_conv(expr),
             ExprTry(ref expr) => self.dump_expr_try(expr),
             ExprLambda(ref expr) => self.dump_expr_lambda(expr),
+            ExprTuple(ref expr) => self.dump_expr_tuple(expr),
+            ExprTemplate(ref expr) => self.dump_expr_template(expr),
         }
     }
 
@@ -528,6 +575,22 @@ impl<'a> AstDumper<'a> {
         self.indent(|d| d.dump_expr(&expr.index));
     }
 
+    fn dump_expr_tuple(&mut self, expr: &ExprTupleType) {
+        dump!(self, "tuple @ {} {}", expr.pos, expr.id);
+
+        self.indent(|d| for value in &expr.values {
+                        d.dump_expr(value);
+                    });
+    }
+
+    fn dump_expr_template(&mut self, expr: &ExprTemplateType) {
+        dump!(self, "template @ {} {}", expr.pos, expr.id);
+
+        self.indent(|d| for part in &expr.parts {
+                        d.dump_expr(part);
+                    });
+    }
+
     fn dump_expr_lambda(&mut self, expr: &ExprLambdaType) {
         dump!(self, "lambda @ {} {}", expr.pos, expr.id);
         self.indent(|d| d.dump_stmt(&expr.block));
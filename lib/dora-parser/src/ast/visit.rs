@@ -37,6 +37,8 @@ pub trait Visitor<'v>: Sized {
         walk_const(self, c);
     }
 
+    fn visit_import(&mut self, _i: &'v Import) {}
+
     fn visit_struct_field(&mut self, f: &'v StructField) {
         walk_struct_field(self, f);
     }
@@ -94,6 +96,7 @@ pub fn walk_file<'v, V: Visitor<'v>>(v: &mut V, f: &'v File) {
             ElemImpl(ref i) => v.visit_impl(i),
             ElemGlobal(ref g) => v.visit_global(g),
             ElemConst(ref c) => v.visit_const(c),
+            ElemImport(ref i) => v.visit_import(i),
         }
     }
 }
@@ -101,8 +104,8 @@ pub fn walk_file<'v, V: Visitor<'v>>(v: &mut V, f: &'v File) {
 pub fn walk_global<'v, V: Visitor<'v>>(v: &mut V, g: &'v Global) {
     v.visit_type(&g.data_type);
 
-    if let Some(ref expr) = g.expr {
-        v.visit_expr_top(expr);
+    if let Some(ref initializer) = g.initializer {
+        v.visit_fct(initializer);
     }
 }
 
@@ -123,6 +126,10 @@ pub fn walk_class<'v, V: Visitor<'v>>(v: &mut V, c: &'v Class) {
         v.visit_field(f);
     }
 
+    for g in &c.static_fields {
+        v.visit_global(g);
+    }
+
     if let Some(ctor) = &c.constructor {
         v.visit_ctor(ctor);
     }
@@ -209,6 +216,10 @@ pub fn walk_stmt<'v, V: Visitor<'v>>(v: &mut V, s: &'v Stmt) {
             }
         }
 
+        StmtVarTuple(ref value) => {
+            v.visit_expr_top(&value.expr);
+        }
+
         StmtFor(ref value) => {
             v.visit_expr_top(&value.expr);
             v.visit_stmt(&value.block);   
@@ -346,6 +357,18 @@ pub fn walk_expr<'v, V: Visitor<'v>>(v: &mut V, e: &'v Expr) {
             v.visit_expr(&value.object);
         }
 
+        ExprTuple(ref value) => {
+            for element in &value.values {
+                v.visit_expr(element);
+            }
+        }
+
+        ExprTemplate(ref value) => {
+            for part in &value.parts {
+                v.visit_expr(part);
+            }
+        }
+
         ExprConv(ref value) => {
             v.visit_expr(&value.object);
             v.visit_type(&value.data_type);
@@ -155,16 +155,23 @@ impl<'a> BuilderFct<'a> {
             has_override: false,
             has_final: false,
             has_optimize: false,
+            is_tailrec: false,
             is_pub: self.is_public,
             is_static: false,
             is_abstract: false,
             internal: false,
             is_constructor: self.is_constructor,
+            is_test: false,
+            is_ignored: false,
+            is_inline: false,
+            is_noinline: false,
+            deprecated: None,
             params: self.params,
             throws: false,
             return_type: self.return_type,
             block: self.block,
             type_params: None,
+            where_clauses: Vec::new(),
         }
     }
 }
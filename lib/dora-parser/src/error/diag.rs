@@ -5,17 +5,30 @@ use crate::lexer::position::Position;
 
 pub struct Diagnostic {
     errors: Vec<MsgWithPos>,
+    warnings: Vec<MsgWithPos>,
 }
 
 impl Diagnostic {
     pub fn new() -> Diagnostic {
-        Diagnostic { errors: Vec::new() }
+        Diagnostic {
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
     }
 
     pub fn errors(&self) -> &[MsgWithPos] {
         &self.errors
     }
 
+    pub fn warnings(&self) -> &[MsgWithPos] {
+        &self.warnings
+    }
+
+    // warnings are reported but do not stop compilation
+    pub fn warn_without_path(&mut self, pos: Position, msg: Msg) {
+        self.warnings.push(MsgWithPos::without_path(pos, msg));
+    }
+
     pub fn report_without_path(&mut self, pos: Position, msg: Msg) {
         self.errors.push(MsgWithPos::without_path(pos, msg));
     }
@@ -38,4 +51,10 @@ impl Diagnostic {
             println!("{}", &err.message());
         }
     }
+
+    pub fn dump_warnings(&self) {
+        for warning in &self.warnings {
+            println!("{}", &warning.warning_message());
+        }
+    }
 }
@@ -27,6 +27,7 @@ pub enum Msg {
     ShadowField(String),
     ShadowGlobal(String),
     ShadowConst(String),
+    ShadowModule(String),
     VarNeedsTypeInfo(String),
     ParamTypesIncompatible(String, Vec<String>, Vec<String>),
     WhileCondType(String),
@@ -88,7 +89,7 @@ pub enum Msg {
     NoSuperDelegationWithPrimaryCtor(String),
     NoSuperClass(String),
     RecursiveStructure,
-    TraitMethodWithBody,
+    StaticTraitMethodWithBody,
     TryNeedsCall,
     TryCallNonThrowing,
     ThrowingCallWithoutTry,
@@ -113,10 +114,28 @@ pub enum Msg {
     NewAbstractClass,
     MissingAbstractOverride(String, String),
     ModifierNotAllowedForStaticMethod(String),
-    GlobalInitializerNotSupported,
     MakeIteratorReturnType(String),
     UnknownStructField(String, String),
     StructFieldNotInitialized(String, String),
+    NotTailCall(String),
+    TailCallNotPossible(String, String),
+    ModuleExpected(String),
+    NotPublic(String),
+    UnresolvedImport(String),
+    BoundNotAllowed,
+    GenericTraitBound(String),
+    UnknownTypeParam(String),
+    TraitBoundExpected,
+    MultipleCandidatesForMethod(String, String),
+    TupleExpected(String),
+    WrongNumberOfTupleElements(usize, usize),
+    ToStringReturnType(String),
+    UnknownAttribute(String),
+    DeprecatedFct(String, String),
+    InvalidTestFunction(String),
+    LoopExpected(String),
+    UnknownLabel(String),
+    BoxNotPossible(String, String),
 }
 
 impl Msg {
@@ -162,6 +181,7 @@ impl Msg {
             ShadowField(ref name) => format!("field with name `{}` already exists.", name),
             ShadowGlobal(ref name) => format!("can not shadow global variable `{}`.", name),
             ShadowConst(ref name) => format!("can not shadow const `{}`", name),
+            ShadowModule(ref name) => format!("can not shadow module `{}`.", name),
             VarNeedsTypeInfo(ref name) => {
                 format!("variable `{}` needs either type declaration or expression.",
                         name)
@@ -287,7 +307,9 @@ impl Msg {
             }
             NoSuperClass(ref name) => format!("class `{}` does not have super class.", name),
             RecursiveStructure => "recursive structure is not allowed.".into(),
-            TraitMethodWithBody => "trait method is not allowed to have definition".into(),
+            StaticTraitMethodWithBody => {
+                "static trait method is not allowed to have definition".into()
+            }
             TryNeedsCall => "`try` expects function or method call.".into(),
             TryCallNonThrowing => "given function or method call for `try` does not throw.".into(),
             ThrowingCallWithoutTry => {
@@ -356,9 +378,6 @@ impl Msg {
             ModifierNotAllowedForStaticMethod(ref modifier) => {
                 format!("modifier `{}` not allowed for static method.", modifier)
             }
-            GlobalInitializerNotSupported => {
-                "global variables do no support initial assignment for now.".into()
-            }
             MakeIteratorReturnType(ref ty) => {
                 format!("makeIterator() returns `{}` which does not implement Iterator.",
                         ty)
@@ -369,6 +388,48 @@ impl Msg {
             StructFieldNotInitialized(ref struc, ref field) => {
                 format!("field `{}` in struct `{}` not initialized.", field, struc)
             }
+            NotTailCall(ref name) => {
+                format!("recursive call of tailrec function `{}` is not a tail call.", name)
+            }
+            TailCallNotPossible(ref name, ref reason) => {
+                format!("tail call of `{}` is not possible: {}.", name, reason)
+            }
+            ModuleExpected(ref name) => format!("`{}` is not a module.", name),
+            NotPublic(ref name) => format!("`{}` is not public.", name),
+            UnresolvedImport(ref name) => format!("unresolved import `{}`.", name),
+            BoundNotAllowed => "bounds are not allowed for these type params.".into(),
+            GenericTraitBound(ref name) => {
+                format!("generic trait `{}` cannot be used as bound.", name)
+            }
+            UnknownTypeParam(ref name) => format!("type param `{}` does not exist.", name),
+            TraitBoundExpected => "trait bound expected.".into(),
+            MultipleCandidatesForMethod(ref ty, ref name) => {
+                format!("multiple candidates for method `{}` on type `{}`.", name, ty)
+            }
+            TupleExpected(ref ty) => format!("tuple expected but got type `{}`.", ty),
+            WrongNumberOfTupleElements(expected, got) => {
+                format!("tuple has {} elements but {} were expected.", expected, got)
+            }
+            ToStringReturnType(ref ty) => {
+                format!("toString() returns `{}` instead of `String`.", ty)
+            }
+            UnknownAttribute(ref name) => format!("unknown attribute `{}`.", name),
+            DeprecatedFct(ref name, ref msg) => {
+                if msg.is_empty() {
+                    format!("function `{}` is deprecated.", name)
+                } else {
+                    format!("function `{}` is deprecated: {}", name, msg)
+                }
+            }
+            InvalidTestFunction(ref name) => format!(
+                "test `{}` needs a single parameter of type `Testing` and no return type.",
+                name
+            ),
+            LoopExpected(ref token) => format!("loop expected after label but got {}.", token),
+            UnknownLabel(ref name) => format!("no enclosing loop with label `{}`.", name),
+            BoxNotPossible(ref ty, ref reason) => {
+                format!("`{}` cannot be used as trait object: {}.", ty, reason)
+            }
         }
     }
 }
@@ -393,6 +454,14 @@ impl MsgWithPos {
         }
     }
 
+    pub fn warning_message(&self) -> String {
+        if self.path.is_empty() {
+            format!("warning at {}: {}", self.pos, self.msg.message())
+        } else {
+            format!("warning in {} at {}: {}", self.path, self.pos, self.msg.message())
+        }
+    }
+
     pub fn without_path(pos: Position, msg: Msg,) -> MsgWithPos {
         MsgWithPos { path: "".to_string(), pos, msg }
     }
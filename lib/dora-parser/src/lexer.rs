@@ -13,6 +13,9 @@ pub mod position;
 pub struct Lexer {
     reader: Reader,
     keywords: HashMap<&'static str, TokenKind>,
+
+    // number of open braces for each `${` in a string literal
+    open_braces: Vec<usize>,
 }
 
 impl Lexer {
@@ -28,6 +31,7 @@ impl Lexer {
         Lexer {
             reader: reader,
             keywords: keywords,
+            open_braces: Vec::new(),
         }
     }
 
@@ -59,7 +63,12 @@ impl Lexer {
                 return self.read_identifier();
 
             } else if is_quote(ch) {
-                return self.read_string();
+                return self.read_string(false);
+
+            } else if ch == Some('}') && self.open_braces.last() == Some(&0) {
+                // closing brace of `${expr}`, continue with the string literal
+                self.open_braces.pop();
+                return self.read_string(true);
 
             } else if is_char_quote(ch) {
                 return self.read_char_literal();
@@ -184,6 +193,7 @@ impl Lexer {
                     'r' => Ok('\r'),
                     '\"' => Ok('\"'),
                     '\'' => Ok('\''),
+                    '$' => Ok('$'),
                     '0' => Ok('\0'),
                     _ => {
                         let msg = Msg::InvalidEscapeSequence(ch);
@@ -200,13 +210,23 @@ impl Lexer {
         }
     }
 
-    fn read_string(&mut self) -> Result<Token, MsgWithPos> {
+    fn read_string(&mut self, continuation: bool) -> Result<Token, MsgWithPos> {
         let pos = self.reader.pos();
         let mut value = String::new();
 
+        // skip opening quote or closing brace of `${expr}`
         self.read_char();
 
         while !self.cur().is_none() && !is_quote(self.cur()) {
+            if self.cur() == Some('$') && self.next() == Some('{') {
+                self.read_char();
+                self.read_char();
+                self.open_braces.push(0);
+
+                let ttype = TokenKind::StringExpr(value);
+                return Ok(Token::new(ttype, pos));
+            }
+
             let ch = self.read_escaped_char(pos, Msg::UnclosedString)?;
             value.push(ch);
         }
@@ -214,7 +234,12 @@ impl Lexer {
         if is_quote(self.cur()) {
             self.read_char();
 
-            let ttype = TokenKind::String(value);
+            let ttype = if continuation {
+                TokenKind::StringTail(value)
+            } else {
+                TokenKind::String(value)
+            };
+
             Ok(Token::new(ttype, pos))
 
         } else {
@@ -249,8 +274,21 @@ impl Lexer {
             ')' => TokenKind::RParen,
             '[' => TokenKind::LBracket,
             ']' => TokenKind::RBracket,
-            '{' => TokenKind::LBrace,
-            '}' => TokenKind::RBrace,
+            '{' => {
+                if let Some(open) = self.open_braces.last_mut() {
+                    *open += 1;
+                }
+
+                TokenKind::LBrace
+            }
+
+            '}' => {
+                if let Some(open) = self.open_braces.last_mut() {
+                    *open -= 1;
+                }
+
+                TokenKind::RBrace
+            }
 
             '|' => {
                 if nch == '|' {
@@ -272,6 +310,7 @@ impl Lexer {
 
             '^' => TokenKind::Caret,
             '~' => TokenKind::Tilde,
+            '#' => TokenKind::Hash,
             ',' => TokenKind::Comma,
             ';' => TokenKind::Semicolon,
             ':' => {
@@ -520,7 +559,7 @@ fn is_char_quote(ch: Option<char>) -> bool {
 }
 
 fn is_operator(ch: Option<char>) -> bool {
-    ch.map(|ch| "^+-*/%&|,=!~;:.()[]{}<>".contains(ch))
+    ch.map(|ch| "^+-*/%&|,=!~;:.()[]{}<>#".contains(ch))
         .unwrap_or(false)
 }
 
@@ -551,6 +590,8 @@ fn keywords_in_map() -> HashMap<&'static str, TokenKind> {
     keywords.insert("for", TokenKind::For);
     keywords.insert("in", TokenKind::In);
     keywords.insert("impl", TokenKind::Impl);
+    keywords.insert("import", TokenKind::Import);
+    keywords.insert("where", TokenKind::Where);
     keywords.insert("loop", TokenKind::Loop);
     keywords.insert("break", TokenKind::Break);
     keywords.insert("continue", TokenKind::Continue);
@@ -578,6 +619,7 @@ fn keywords_in_map() -> HashMap<&'static str, TokenKind> {
     keywords.insert("as", TokenKind::As);
     keywords.insert("internal", TokenKind::Internal);
     keywords.insert("optimize", TokenKind::Optimize);
+    keywords.insert("tailrec", TokenKind::Tailrec);
     keywords.insert("pub", TokenKind::Pub);
     keywords.insert("static", TokenKind::Static);
     keywords.insert("spawn", TokenKind::Spawn);
@@ -923,6 +965,24 @@ mod tests {
         assert_end(&mut reader, 1, 6);
     }
 
+    #[test]
+    fn test_string_template() {
+        let mut reader = Lexer::from_str("\"a${b}c${{d}}\\${e}\"");
+        assert_tok(&mut reader, TokenKind::StringExpr("a".into()), 1, 1);
+        assert_tok(&mut reader, TokenKind::Identifier("b".into()), 1, 5);
+        assert_tok(&mut reader, TokenKind::StringExpr("c".into()), 1, 6);
+        assert_tok(&mut reader, TokenKind::LBrace, 1, 10);
+        assert_tok(&mut reader, TokenKind::Identifier("d".into()), 1, 11);
+        assert_tok(&mut reader, TokenKind::RBrace, 1, 12);
+        assert_tok(&mut reader, TokenKind::StringTail("${e}".into()), 1, 13);
+        assert_end(&mut reader, 1, 20);
+
+        let mut reader = Lexer::from_str("\"a${b");
+        assert_tok(&mut reader, TokenKind::StringExpr("a".into()), 1, 1);
+        assert_tok(&mut reader, TokenKind::Identifier("b".into()), 1, 5);
+        assert_end(&mut reader, 1, 6);
+    }
+
     #[test]
     fn test_keywords() {
         let mut reader = Lexer::from_str("fun let while if else self class");
@@ -970,7 +1030,7 @@ mod tests {
 
     #[test]
     fn test_operators() {
-        let mut reader = Lexer::from_str("==+=-*/%~.");
+        let mut reader = Lexer::from_str("==+=-*/%~.#");
         assert_tok(&mut reader, TokenKind::EqEq, 1, 1);
         assert_tok(&mut reader, TokenKind::Add, 1, 3);
         assert_tok(&mut reader, TokenKind::Eq, 1, 4);
@@ -980,6 +1040,7 @@ mod tests {
         assert_tok(&mut reader, TokenKind::Mod, 1, 8);
         assert_tok(&mut reader, TokenKind::Tilde, 1, 9);
         assert_tok(&mut reader, TokenKind::Dot, 1, 10);
+        assert_tok(&mut reader, TokenKind::Hash, 1, 11);
 
         let mut reader = Lexer::from_str("<=<>=><");
         assert_tok(&mut reader, TokenKind::Le, 1, 1);
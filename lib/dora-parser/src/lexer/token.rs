@@ -6,6 +6,8 @@ use crate::lexer::position::Position;
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum TokenKind {
     String(String),
+    StringExpr(String),
+    StringTail(String),
     LitChar(char),
     LitInt(String, IntBase, IntSuffix),
     LitFloat(String, FloatSuffix),
@@ -55,6 +57,8 @@ pub enum TokenKind {
     Trait,
     Impl,
     Const,
+    Import,
+    Where,
 
     Underscore,
     Defer,
@@ -64,6 +68,7 @@ pub enum TokenKind {
     Open,
     Override,
     Optimize,
+    Tailrec,
     Extern,
     // Operators
     Add,
@@ -88,6 +93,7 @@ pub enum TokenKind {
     BitOr,
     BitAnd,
     Caret,
+    Hash,
     And,
     Or,
     Internal,
@@ -113,6 +119,8 @@ impl TokenKind {
     pub fn name(&self) -> &str {
         match *self {
             TokenKind::String(_) => "string",
+            TokenKind::StringExpr(_) => "string",
+            TokenKind::StringTail(_) => "string",
             TokenKind::LitInt(_, _, suffix) => {
                 match suffix {
                     IntSuffix::Byte => "byte number",
@@ -176,6 +184,8 @@ impl TokenKind {
             TokenKind::Trait => "trait",
             TokenKind::Impl => "impl",
             TokenKind::Const => "const",
+            TokenKind::Import => "import",
+            TokenKind::Where => "where",
 
             TokenKind::Underscore => "_",
             TokenKind::Defer => "defer",
@@ -185,6 +195,7 @@ impl TokenKind {
             TokenKind::Open => "open",
             TokenKind::Override => "override",
             TokenKind::Optimize => "optimize",
+            TokenKind::Tailrec => "tailrec",
             TokenKind::Extern => "extern",
             // Operators
             TokenKind::Add => "+",
@@ -209,6 +220,7 @@ impl TokenKind {
             TokenKind::BitOr => "|",
             TokenKind::BitAnd => "&",
             TokenKind::Caret => "^",
+            TokenKind::Hash => "#",
             TokenKind::And => "&&",
             TokenKind::Or => "||",
             TokenKind::Internal => "internal",
@@ -281,6 +293,8 @@ impl Token {
             }
 
             TokenKind::String(ref val) => format!("\"{}\"", &val),
+            TokenKind::StringExpr(ref val) => format!("\"{}${{", &val),
+            TokenKind::StringTail(ref val) => format!("}}{}\"", &val),
             TokenKind::Identifier(ref val) => val.clone(),
 
             _ => self.kind.name().into(),
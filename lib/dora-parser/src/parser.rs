@@ -86,27 +86,40 @@ impl<'a> Parser<'a> {
 
         match self.token.kind {
             TokenKind::Fun => {
-                self.restrict_modifiers(&modifiers, &[Modifier::Internal, Modifier::Optimize,Modifier::Extern])?;
+                self.restrict_modifiers(&modifiers,
+                                        &[Modifier::Internal,
+                                          Modifier::Optimize,
+                                          Modifier::Extern,
+                                          Modifier::Tailrec,
+                                          Modifier::Pub,
+                                          Modifier::Test,
+                                          Modifier::Ignore,
+                                          Modifier::Inline,
+                                          Modifier::NoInline,
+                                          Modifier::Deprecated])?;
                 let fct = self.parse_function(&modifiers)?;
                 elements.push(ElemFunction(fct));
             }
 
             TokenKind::Class => {
                 self.restrict_modifiers(&modifiers,
-                                        &[Modifier::Abstract, Modifier::Open, Modifier::Internal])?;
+                                        &[Modifier::Abstract,
+                                          Modifier::Open,
+                                          Modifier::Internal,
+                                          Modifier::Pub])?;
                 let class = self.parse_class(&modifiers)?;
                 elements.push(ElemClass(class));
             }
 
             TokenKind::Struct => {
-                self.ban_modifiers(&modifiers)?;
-                let struc = self.parse_struct()?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let struc = self.parse_struct(&modifiers)?;
                 elements.push(ElemStruct(struc))
             }
 
             TokenKind::Trait => {
-                self.ban_modifiers(&modifiers)?;
-                let xtrait = self.parse_trait()?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let xtrait = self.parse_trait(&modifiers)?;
                 elements.push(ElemTrait(xtrait));
             }
 
@@ -117,16 +130,23 @@ impl<'a> Parser<'a> {
             }
 
             TokenKind::Let | TokenKind::Var => {
-                self.ban_modifiers(&modifiers)?;
-                self.parse_global(elements)?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let global = self.parse_global(&modifiers)?;
+                elements.push(ElemGlobal(global));
             }
 
             TokenKind::Const => {
-                self.ban_modifiers(&modifiers)?;
-                let xconst = self.parse_const()?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let xconst = self.parse_const(&modifiers)?;
                 elements.push(ElemConst(xconst));
             }
 
+            TokenKind::Import => {
+                self.ban_modifiers(&modifiers)?;
+                let import = self.parse_import()?;
+                elements.push(ElemImport(import));
+            }
+
             _ => {
                 let msg = Msg::ExpectedTopLevelElement(self.token.name());
                 return Err(MsgWithPos::new(self.lexer.path().to_string(), self.token.position, msg));
@@ -136,7 +156,33 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
-    fn parse_const(&mut self) -> Result<Const, MsgWithPos> {
+    fn parse_import(&mut self) -> Result<Import, MsgWithPos> {
+        let pos = self.expect_token(TokenKind::Import)?.position;
+        let mut path = vec![self.expect_identifier()?];
+
+        while self.token.is(TokenKind::Sep) {
+            self.advance_token()?;
+            path.push(self.expect_identifier()?);
+        }
+
+        let alias = if self.token.is(TokenKind::As) {
+            self.advance_token()?;
+            Some(self.expect_identifier()?)
+        } else {
+            None
+        };
+
+        self.expect_semicolon()?;
+
+        Ok(Import {
+               id: self.generate_id(),
+               pos: pos,
+               path: Path { path: path },
+               alias: alias,
+           })
+    }
+
+    fn parse_const(&mut self, modifiers: &Modifiers) -> Result<Const, MsgWithPos> {
         let pos = self.expect_token(TokenKind::Const)?.position;
         let name = self.expect_identifier()?;
         self.expect_token(TokenKind::Colon)?;
@@ -149,6 +195,7 @@ impl<'a> Parser<'a> {
                id: self.generate_id(),
                pos: pos,
                name: name,
+               is_pub: modifiers.contains(Modifier::Pub),
                data_type: ty,
                expr: expr,
            })
@@ -157,8 +204,17 @@ impl<'a> Parser<'a> {
     fn parse_impl(&mut self) -> Result<Impl, MsgWithPos> {
         let pos = self.expect_token(TokenKind::Impl)?.position;
         let trait_name = self.expect_identifier()?;
+
+        let trait_type_params = if self.token.is(TokenKind::Lt) {
+            self.advance_token()?;
+            self.parse_comma_list(TokenKind::Gt, |p| p.parse_type())?
+        } else {
+            Vec::new()
+        };
+
         self.expect_token(TokenKind::For)?;
         let class_name = self.expect_identifier()?;
+        let class_type_params = self.parse_type_params()?;
 
         self.expect_token(TokenKind::LBrace)?;
 
@@ -166,7 +222,11 @@ impl<'a> Parser<'a> {
 
         while !self.token.is(TokenKind::RBrace) {
             let modifiers = self.parse_modifiers()?;
-            let mods = &[Modifier::Static, Modifier::Internal];
+            let mods = &[Modifier::Static,
+                         Modifier::Internal,
+                         Modifier::Inline,
+                         Modifier::NoInline,
+                         Modifier::Deprecated];
             self.restrict_modifiers(&modifiers, mods)?;
 
             methods.push(self.parse_function(&modifiers)?);
@@ -177,13 +237,15 @@ impl<'a> Parser<'a> {
         Ok(Impl {
                id: self.generate_id(),
                trait_name: trait_name,
+               trait_type_params: trait_type_params,
                class_name: class_name,
+               class_type_params: class_type_params,
                pos: pos,
                methods: methods,
            })
     }
 
-    fn parse_global(&mut self, elements: &mut Vec<Elem>) -> Result<(), MsgWithPos> {
+    fn parse_global(&mut self, modifiers: &Modifiers) -> Result<Global, MsgWithPos> {
         let pos = self.token.position;
         let reassignable = self.token.is(TokenKind::Var);
 
@@ -193,35 +255,32 @@ impl<'a> Parser<'a> {
         self.expect_token(TokenKind::Colon)?;
         let data_type = self.parse_type()?;
 
-        let expr = if self.token.is(TokenKind::Eq) {
+        let initializer = if self.token.is(TokenKind::Eq) {
             self.advance_token()?;
 
-            Some(self.parse_expression()?)
+            let expr = self.parse_expression()?;
+            Some(self.generate_global_initializer(name, &data_type, expr))
         } else {
             None
         };
 
         self.expect_semicolon()?;
 
-        let _builder = Builder::new(self.id_generator);
-
-        let global = Global {
+        Ok(Global {
             id: self.generate_id(),
             name: name,
             pos: pos,
+            is_pub: modifiers.contains(Modifier::Pub),
             data_type: data_type,
             reassignable: reassignable,
-            expr: expr,
-        };
-
-        elements.push(ElemGlobal(global));
-
-        Ok(())
+            initializer: initializer,
+        })
     }
 
-    fn parse_trait(&mut self) -> Result<Trait, MsgWithPos> {
+    fn parse_trait(&mut self, modifiers: &Modifiers) -> Result<Trait, MsgWithPos> {
         let pos = self.expect_token(TokenKind::Trait)?.position;
         let ident = self.expect_identifier()?;
+        let type_params = self.parse_type_params()?;
 
         self.expect_token(TokenKind::LBrace)?;
 
@@ -229,7 +288,7 @@ impl<'a> Parser<'a> {
 
         while !self.token.is(TokenKind::RBrace) {
             let modifiers = self.parse_modifiers()?;
-            let mods = &[Modifier::Static];
+            let mods = &[Modifier::Static, Modifier::Deprecated];
             self.restrict_modifiers(&modifiers, mods)?;
 
             methods.push(self.parse_function(&modifiers)?);
@@ -241,11 +300,13 @@ impl<'a> Parser<'a> {
                id: self.generate_id(),
                name: ident,
                pos: pos,
+               is_pub: modifiers.contains(Modifier::Pub),
+               type_params: type_params,
                methods: methods,
            })
     }
 
-    fn parse_struct(&mut self) -> Result<Struct, MsgWithPos> {
+    fn parse_struct(&mut self, modifiers: &Modifiers) -> Result<Struct, MsgWithPos> {
         let pos = self.expect_token(TokenKind::Struct)?.position;
         let ident = self.expect_identifier()?;
 
@@ -256,6 +317,7 @@ impl<'a> Parser<'a> {
                id: self.generate_id(),
                name: ident,
                pos: pos,
+               is_pub: modifiers.contains(Modifier::Pub),
                fields: fields,
            })
     }
@@ -291,10 +353,12 @@ impl<'a> Parser<'a> {
             has_open: has_open,
             internal: internal,
             is_abstract: is_abstract,
+            is_pub: modifiers.contains(Modifier::Pub),
             has_constructor: false,
             parent_class: None,
             constructor: None,
             fields: Vec::new(),
+            static_fields: Vec::new(),
             methods: Vec::new(),
             initializers: Vec::new(),
             type_params: type_params,
@@ -340,20 +404,7 @@ impl<'a> Parser<'a> {
 
         let bounds = if self.token.is(TokenKind::Colon) {
             self.advance_token()?;
-
-            let mut bounds = Vec::new();
-
-            loop {
-                bounds.push(self.parse_type()?);
-
-                if self.token.is(TokenKind::Add) {
-                    self.advance_token()?;
-                } else {
-                    break;
-                }
-            }
-
-            bounds
+            self.parse_type_param_bounds()?
         } else {
             Vec::new()
         };
@@ -365,6 +416,53 @@ impl<'a> Parser<'a> {
            })
     }
 
+    fn parse_type_param_bounds(&mut self) -> Result<Vec<Type>, MsgWithPos> {
+        let mut bounds = Vec::new();
+
+        loop {
+            bounds.push(self.parse_type()?);
+
+            if self.token.is(TokenKind::Add) {
+                self.advance_token()?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(bounds)
+    }
+
+    fn parse_where_clauses(&mut self) -> Result<Vec<TypeParam>, MsgWithPos> {
+        let mut clauses = Vec::new();
+
+        if !self.token.is(TokenKind::Where) {
+            return Ok(clauses);
+        }
+
+        self.advance_token()?;
+
+        loop {
+            let pos = self.token.position;
+            let name = self.expect_identifier()?;
+            self.expect_token(TokenKind::Colon)?;
+            let bounds = self.parse_type_param_bounds()?;
+
+            clauses.push(TypeParam {
+                             name: name,
+                             pos: pos,
+                             bounds: bounds,
+                         });
+
+            if self.token.is(TokenKind::Comma) {
+                self.advance_token()?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(clauses)
+    }
+
     fn parse_parent_class_params(&mut self) -> Result<Vec<Box<Expr>>, MsgWithPos> {
         if !self.token.is(TokenKind::LParen) {
             return Ok(Vec::new());
@@ -393,7 +491,22 @@ impl<'a> Parser<'a> {
     fn parse_constructor_param(&mut self,
                                cls: &mut Class)
                                -> Result<ConstructorParam, MsgWithPos> {
+        let pub_pos = self.token.position;
+        let is_pub = self.token.is(TokenKind::Pub);
+
+        if is_pub {
+            self.advance_token()?;
+        }
+
         let field = self.token.is(TokenKind::Var) || self.token.is(TokenKind::Let);
+
+        // only parameters that are also fields can be public
+        if is_pub && !field {
+            return Err(MsgWithPos::new(self.lexer.path().to_string(),
+                                       pub_pos,
+                                       Msg::MisplacedModifier("pub".into())));
+        }
+
         let reassignable = self.token.is(TokenKind::Var);
 
         // consume var and let
@@ -417,6 +530,7 @@ impl<'a> Parser<'a> {
                           primary_ctor: true,
                           expr: None,
                           reassignable: reassignable,
+                          is_pub: is_pub,
                       })
         }
 
@@ -447,17 +561,28 @@ impl<'a> Parser<'a> {
                                  Modifier::Override,
                                  Modifier::Final,
                                  Modifier::Pub,
-                                 Modifier::Static];
+                                 Modifier::Static,
+                                 Modifier::Tailrec,
+                                 Modifier::Inline,
+                                 Modifier::NoInline,
+                                 Modifier::Deprecated];
                     self.restrict_modifiers(&modifiers, mods)?;
 
                     let fct = self.parse_function(&modifiers)?;
                     cls.methods.push(fct);
                 }
 
+                TokenKind::Var | TokenKind::Let if modifiers.contains(Modifier::Static) => {
+                    self.restrict_modifiers(&modifiers, &[Modifier::Pub, Modifier::Static])?;
+
+                    let global = self.parse_global(&modifiers)?;
+                    cls.static_fields.push(global);
+                }
+
                 TokenKind::Var | TokenKind::Let => {
-                    self.ban_modifiers(&modifiers)?;
+                    self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
 
-                    let field = self.parse_field()?;
+                    let field = self.parse_field(&modifiers)?;
                     cls.fields.push(field);
                 }
 
@@ -484,7 +609,12 @@ impl<'a> Parser<'a> {
                 TokenKind::Pub => Modifier::Pub,
                 TokenKind::Static => Modifier::Static,
                 TokenKind::Optimize => Modifier::Optimize,
+                TokenKind::Tailrec => Modifier::Tailrec,
                 TokenKind::Extern => Modifier::Extern,
+                TokenKind::Hash => {
+                    self.parse_attribute(&mut modifiers)?;
+                    continue;
+                }
                 _ => {
                     break;
                 }
@@ -503,6 +633,67 @@ impl<'a> Parser<'a> {
         Ok(modifiers)
     }
 
+    fn parse_attribute(&mut self, modifiers: &mut Modifiers) -> Result<(), MsgWithPos> {
+        let pos = self.expect_token(TokenKind::Hash)?.position;
+        self.expect_token(TokenKind::LBracket)?;
+        let tok = self.advance_token()?;
+
+        let modifier = match tok.kind {
+            TokenKind::Identifier(ref name) => {
+                match &name[..] {
+                    "test" => Modifier::Test,
+                    "ignore" => Modifier::Ignore,
+                    "inline" => Modifier::Inline,
+                    "noinline" => Modifier::NoInline,
+                    "deprecated" => Modifier::Deprecated,
+                    _ => {
+                        return Err(MsgWithPos::new(self.lexer.path().to_string(),
+                                                   tok.position,
+                                                   Msg::UnknownAttribute(name.clone())));
+                    }
+                }
+            }
+
+            _ => {
+                return Err(MsgWithPos::new(self.lexer.path().to_string(),
+                                           tok.position,
+                                           Msg::ExpectedIdentifier(tok.name())));
+            }
+        };
+
+        // only `#[deprecated]` takes an (optional) message
+        let arg = if modifier == Modifier::Deprecated && self.token.is(TokenKind::LParen) {
+            self.advance_token()?;
+            let tok = self.advance_token()?;
+
+            let msg = if let TokenKind::String(value) = tok.kind {
+                value
+            } else {
+                return Err(MsgWithPos::new(self.lexer.path().to_string(),
+                                           tok.position,
+                                           Msg::ExpectedToken("string".into(), tok.name())));
+            };
+
+            self.expect_token(TokenKind::RParen)?;
+
+            Some(msg)
+        } else {
+            None
+        };
+
+        self.expect_token(TokenKind::RBracket)?;
+
+        if modifiers.contains(modifier) {
+            return Err(MsgWithPos::new(self.lexer.path().to_string(),
+                                       pos,
+                                       Msg::RedundantModifier(modifier.name().into())));
+        }
+
+        modifiers.add_with_arg(modifier, pos, arg);
+
+        Ok(())
+    }
+
     fn ban_modifiers(&mut self, modifiers: &Modifiers) -> Result<(), MsgWithPos> {
         self.restrict_modifiers(modifiers, &[])
     }
@@ -522,7 +713,7 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
-    fn parse_field(&mut self) -> Result<Field, MsgWithPos> {
+    fn parse_field(&mut self, modifiers: &Modifiers) -> Result<Field, MsgWithPos> {
         let pos = self.token.position;
         let reassignable = if self.token.is(TokenKind::Var) {
             self.expect_token(TokenKind::Var)?;
@@ -556,6 +747,7 @@ impl<'a> Parser<'a> {
                primary_ctor: false,
                expr: expr,
                reassignable: reassignable,
+               is_pub: modifiers.contains(Modifier::Pub),
            })
     }
 
@@ -567,6 +759,7 @@ impl<'a> Parser<'a> {
         let params = self.parse_function_params()?;
         let throws = self.parse_throws()?;
         let return_type = self.parse_function_type()?;
+        let where_clauses = self.parse_where_clauses()?;
         let block = self.parse_function_block()?;
 
         Ok(Function {
@@ -578,17 +771,25 @@ impl<'a> Parser<'a> {
                has_override: modifiers.contains(Modifier::Override),
                has_final: modifiers.contains(Modifier::Final),
                has_optimize: modifiers.contains(Modifier::Optimize),
+               is_tailrec: modifiers.contains(Modifier::Tailrec),
                is_pub: modifiers.contains(Modifier::Pub),
                is_static: modifiers.contains(Modifier::Static),
                internal: modifiers.contains(Modifier::Internal),
                is_abstract: modifiers.contains(Modifier::Abstract),
                is_external: modifiers.contains(Modifier::Extern),
                is_constructor: false,
+               is_test: modifiers.contains(Modifier::Test),
+               is_ignored: modifiers.contains(Modifier::Ignore),
+               is_inline: modifiers.contains(Modifier::Inline),
+               is_noinline: modifiers.contains(Modifier::NoInline),
+               deprecated: modifiers.get(Modifier::Deprecated)
+                   .map(|el| el.arg.clone().unwrap_or_default()),
                params: params,
                throws: throws,
                return_type: return_type,
                block: block,
                type_params: type_params,
+               where_clauses: where_clauses,
            })
     }
 
@@ -724,7 +925,14 @@ impl<'a> Parser<'a> {
 
             TokenKind::Identifier(_) => {
                 let pos = self.token.position;
-                let name = self.expect_identifier()?;
+                let mut module = Vec::new();
+                let mut name = self.expect_identifier()?;
+
+                while self.token.is(TokenKind::Sep) {
+                    self.advance_token()?;
+                    module.push(name);
+                    name = self.expect_identifier()?;
+                }
 
                 let params = if self.token.is(TokenKind::Lt) {
                     self.advance_token()?;
@@ -733,7 +941,7 @@ impl<'a> Parser<'a> {
                     Vec::new()
                 };
 
-                Ok(Type::create_basic(self.generate_id(), pos, name, params))
+                Ok(Type::create_basic(self.generate_id(), pos, module, name, params))
             }
 
             TokenKind::LParen => {
@@ -763,8 +971,8 @@ impl<'a> Parser<'a> {
             TokenKind::Let | TokenKind::Var => self.parse_var(),
             TokenKind::LBrace => self.parse_block(),
             TokenKind::If => self.parse_if(),
-            TokenKind::While => self.parse_while(),
-            TokenKind::Loop => self.parse_loop(),
+            TokenKind::While => self.parse_while(None),
+            TokenKind::Loop => self.parse_loop(None),
             TokenKind::Break => self.parse_break(),
             TokenKind::Continue => self.parse_continue(),
             TokenKind::Return => self.parse_return(),
@@ -773,7 +981,7 @@ impl<'a> Parser<'a> {
             TokenKind::Defer => self.parse_defer(),
             TokenKind::Do => self.parse_do(),
             TokenKind::Spawn => self.parse_spawn(),
-            TokenKind::For => self.parse_for(),
+            TokenKind::For => self.parse_for(None),
             _ => self.parse_expression_statement(),
         }
     }
@@ -855,6 +1063,11 @@ impl<'a> Parser<'a> {
         };
 
         let pos = self.advance_token()?.position;
+
+        if self.token.is(TokenKind::LParen) {
+            return self.parse_var_tuple(pos, reassignable);
+        }
+
         let ident = self.expect_identifier()?;
         let data_type = self.parse_var_type()?;
         let expr = self.parse_var_assignment()?;
@@ -869,6 +1082,27 @@ impl<'a> Parser<'a> {
                                      expr)))
     }
 
+    fn parse_var_tuple(&mut self, pos: Position, reassignable: bool) -> StmtResult {
+        self.expect_token(TokenKind::LParen)?;
+
+        let names = self.parse_comma_list(TokenKind::RParen, |p| {
+                let pos = p.token.position;
+                let name = p.expect_identifier()?;
+
+                Ok(TupleBinding {
+                       id: p.generate_id(),
+                       pos: pos,
+                       name: name,
+                   })
+            })?;
+
+        self.expect_token(TokenKind::Eq)?;
+        let expr = self.parse_expression()?;
+        self.expect_semicolon()?;
+
+        Ok(Box::new(Stmt::create_var_tuple(self.generate_id(), pos, names, reassignable, expr)))
+    }
+
     fn parse_var_type(&mut self) -> Result<Option<Type>, MsgWithPos> {
         if self.token.is(TokenKind::Colon) {
             self.advance_token()?;
@@ -931,7 +1165,7 @@ impl<'a> Parser<'a> {
         Ok(Box::new(Stmt::create_if(self.generate_id(), pos, cond, then_block, else_block)))
     }
 
-    fn parse_for(&mut self) -> StmtResult {
+    fn parse_for(&mut self, label: Option<Name>) -> StmtResult {
         let pos = self.expect_token(TokenKind::For)?.position;
         let name = self.expect_identifier()?;
         self.expect_token(TokenKind::In)?;
@@ -942,10 +1176,10 @@ impl<'a> Parser<'a> {
 
         let block = self.parse_block()?;
 
-        Ok(Box::new(Stmt::create_for(self.generate_id(), pos, name, expr, block)))
+        Ok(Box::new(Stmt::create_for(self.generate_id(), pos, label, name, expr, block)))
     }
 
-    fn parse_while(&mut self) -> StmtResult {
+    fn parse_while(&mut self, label: Option<Name>) -> StmtResult {
         let pos = self.expect_token(TokenKind::While)?.position;
 
         let mut opts = ExprParsingOpts::new();
@@ -954,28 +1188,54 @@ impl<'a> Parser<'a> {
 
         let block = self.parse_block()?;
 
-        Ok(Box::new(Stmt::create_while(self.generate_id(), pos, expr, block)))
+        Ok(Box::new(Stmt::create_while(self.generate_id(), pos, label, expr, block)))
     }
 
-    fn parse_loop(&mut self) -> StmtResult {
+    fn parse_loop(&mut self, label: Option<Name>) -> StmtResult {
         let pos = self.expect_token(TokenKind::Loop)?.position;
         let block = self.parse_block()?;
 
-        Ok(Box::new(Stmt::create_loop(self.generate_id(), pos, block)))
+        Ok(Box::new(Stmt::create_loop(self.generate_id(), pos, label, block)))
+    }
+
+    // parses the loop after a label like `outer: while ... { }`
+    fn parse_labeled_loop(&mut self, label: Name) -> StmtResult {
+        self.expect_token(TokenKind::Colon)?;
+
+        match self.token.kind {
+            TokenKind::While => self.parse_while(Some(label)),
+            TokenKind::Loop => self.parse_loop(Some(label)),
+            TokenKind::For => self.parse_for(Some(label)),
+            _ => {
+                Err(MsgWithPos::new(self.lexer.path().to_string(),
+                                    self.token.position,
+                                    Msg::LoopExpected(self.token.name())))
+            }
+        }
     }
 
     fn parse_break(&mut self) -> StmtResult {
         let pos = self.expect_token(TokenKind::Break)?.position;
+        let label = self.parse_jump_label()?;
         self.expect_semicolon()?;
 
-        Ok(Box::new(Stmt::create_break(self.generate_id(), pos)))
+        Ok(Box::new(Stmt::create_break(self.generate_id(), pos, label)))
     }
 
     fn parse_continue(&mut self) -> StmtResult {
         let pos = self.expect_token(TokenKind::Continue)?.position;
+        let label = self.parse_jump_label()?;
         self.expect_semicolon()?;
 
-        Ok(Box::new(Stmt::create_continue(self.generate_id(), pos)))
+        Ok(Box::new(Stmt::create_continue(self.generate_id(), pos, label)))
+    }
+
+    fn parse_jump_label(&mut self) -> Result<Option<Name>, MsgWithPos> {
+        if let TokenKind::Identifier(_) = self.token.kind {
+            Ok(Some(self.expect_identifier()?))
+        } else {
+            Ok(None)
+        }
     }
 
     fn parse_return(&mut self) -> StmtResult {
@@ -995,6 +1255,13 @@ impl<'a> Parser<'a> {
     fn parse_expression_statement(&mut self) -> StmtResult {
         let pos = self.token.position;
         let expr = self.parse_expression()?;
+
+        if self.token.is(TokenKind::Colon) {
+            if let Some(ident) = expr.to_ident() {
+                return self.parse_labeled_loop(ident.name);
+            }
+        }
+
         self.expect_semicolon()?;
 
         Ok(Box::new(Stmt::create_expr(self.generate_id(), pos, expr)))
@@ -1081,6 +1348,20 @@ impl<'a> Parser<'a> {
             left = match self.token.kind {
                 TokenKind::Dot => {
                     let tok = self.advance_token()?;
+
+                    // element of a tuple: `tuple.0`
+                    if let TokenKind::LitInt(ref value, IntBase::Dec, IntSuffix::Int) = self.token
+                           .kind {
+                        let name = self.interner.intern(value);
+                        self.advance_token()?;
+
+                        left = Box::new(Expr::create_field(self.generate_id(),
+                                                           tok.position,
+                                                           left,
+                                                           name));
+                        continue;
+                    }
+
                     let ident = self.expect_identifier()?;
 
                     let type_params = if self.token.is(TokenKind::Sep) {
@@ -1170,6 +1451,7 @@ impl<'a> Parser<'a> {
             TokenKind::LitInt(_, _, _) => self.parse_lit_int(),
             TokenKind::LitFloat(_, _) => self.parse_lit_float(),
             TokenKind::String(_) => self.parse_string(),
+            TokenKind::StringExpr(_) => self.parse_template(),
             TokenKind::Identifier(_) => self.parse_identifier_or_call(opts),
             TokenKind::True => self.parse_bool_literal(),
             TokenKind::False => self.parse_bool_literal(),
@@ -1217,10 +1499,21 @@ impl<'a> Parser<'a> {
             self.parse_lit_struct(pos, Path { path: path })
 
             // if not we have a simple identifier
-        } else {
-            assert_eq!(1, path.len());
+        } else if path.len() == 1 {
             let name = path[0];
             Ok(Box::new(Expr::create_ident(self.generate_id(), pos, name, type_params)))
+
+            // or a qualified identifier like `util::Foo`
+        } else {
+            assert!(type_params.is_none());
+            let mut expr = Box::new(Expr::create_ident(self.generate_id(), pos, path[0], None));
+
+            for &name in &path[1..] {
+                let rhs = Box::new(Expr::create_ident(self.generate_id(), pos, name, None));
+                expr = Box::new(Expr::create_path(self.generate_id(), pos, expr, rhs));
+            }
+
+            Ok(expr)
         }
     }
 
@@ -1261,8 +1554,17 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_parentheses(&mut self) -> ExprResult {
-        self.advance_token()?;
+        let pos = self.advance_token()?.position;
         let exp = self.parse_expression()?;
+
+        if self.token.is(TokenKind::Comma) {
+            self.advance_token()?;
+            let mut values = self.parse_comma_list(TokenKind::RParen, |p| p.parse_expression())?;
+            values.insert(0, exp);
+
+            return Ok(Box::new(Expr::create_tuple(self.generate_id(), pos, values)));
+        }
+
         self.expect_token(TokenKind::RParen)?;
 
         Ok(exp)
@@ -1374,6 +1676,45 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_template(&mut self) -> ExprResult {
+        let pos = self.token.position;
+        let mut parts = Vec::new();
+
+        loop {
+            let string = self.advance_token()?;
+
+            let (value, done) = match string.kind {
+                TokenKind::StringExpr(value) => (value, false),
+                TokenKind::StringTail(value) => (value, true),
+                _ => unreachable!(),
+            };
+
+            if !value.is_empty() {
+                parts.push(Box::new(Expr::create_lit_str(self.generate_id(),
+                                                         string.position,
+                                                         value)));
+            }
+
+            if done {
+                break;
+            }
+
+            parts.push(self.parse_expression()?);
+
+            match self.token.kind {
+                TokenKind::StringExpr(_) | TokenKind::StringTail(_) => {}
+                _ => {
+                    return Err(MsgWithPos::new(self.lexer.path().to_string(),
+                                               self.token.position,
+                                               Msg::ExpectedToken("}".into(),
+                                                                  self.token.name())));
+                }
+            }
+        }
+
+        Ok(Box::new(Expr::create_template(self.generate_id(), pos, parts)))
+    }
+
     fn parse_bool_literal(&mut self) -> ExprResult {
         let tok = self.advance_token()?;
         let value = tok.is(TokenKind::True);
@@ -1510,6 +1851,28 @@ impl<'a> Parser<'a> {
 
         fct.build()
     }
+
+    // the initializer of a global is a function returning the initial value,
+    // it gets called on the first access of the global
+    fn generate_global_initializer(&mut self,
+                                   name: Name,
+                                   data_type: &Type,
+                                   expr: Box<Expr>)
+                                   -> Function {
+        let builder = Builder::new(self.id_generator);
+        let mut block = builder.build_block();
+
+        let pos = expr.pos();
+        block.add_stmt(Box::new(Stmt::create_return(self.generate_id(), pos, Some(expr))));
+
+        let mut fct = builder.build_fct(name);
+        fct.return_type(data_type.clone()).block(block.build());
+
+        let mut fct = fct.build();
+        fct.pos = pos;
+
+        fct
+    }
 }
 
 struct ExprParsingOpts {
@@ -1694,6 +2057,29 @@ mod tests {
         assert_eq!("abc", &lit.value);
     }
 
+    #[test]
+    fn parse_template() {
+        let (expr, _) = parse_expr("\"a${b}c${d + 1}\"");
+        let template = expr.to_template().unwrap();
+
+        assert_eq!(4, template.parts.len());
+        assert_eq!("a", &template.parts[0].to_lit_str().unwrap().value);
+        assert!(template.parts[1].is_ident());
+        assert_eq!("c", &template.parts[2].to_lit_str().unwrap().value);
+        assert!(template.parts[3].is_bin());
+
+        let (expr, _) = parse_expr("\"${a}\"");
+        let template = expr.to_template().unwrap();
+
+        assert_eq!(1, template.parts.len());
+        assert!(template.parts[0].is_ident());
+    }
+
+    #[test]
+    fn parse_template_unclosed() {
+        err_expr("\"a${b)\"", Msg::ExpectedToken("}".into(), ")".into()), 1, 6);
+    }
+
     #[test]
     fn parse_true() {
         let (expr, _) = parse_expr("true");
@@ -1720,6 +2106,50 @@ mod tests {
         assert_eq!("field", *interner.str(field.name));
     }
 
+    #[test]
+    fn parse_tuple() {
+        let (expr, _) = parse_expr("(1, a, (b, c))");
+        let tuple = expr.to_tuple().unwrap();
+
+        assert_eq!(3, tuple.values.len());
+        assert!(tuple.values[0].is_lit_int());
+        assert!(tuple.values[2].is_tuple());
+    }
+
+    #[test]
+    fn parse_parentheses_without_tuple() {
+        let (expr, _) = parse_expr("(a)");
+        assert!(expr.is_ident());
+    }
+
+    #[test]
+    fn parse_tuple_element() {
+        let (expr, interner) = parse_expr("pair.1");
+        let field = expr.to_field().unwrap();
+
+        assert!(field.object.is_ident());
+        assert_eq!("1", *interner.str(field.name));
+    }
+
+    #[test]
+    fn parse_let_tuple() {
+        let stmt = parse_stmt("let (a, b) = pair;");
+        let var = stmt.to_var_tuple().unwrap();
+
+        assert_eq!(false, var.reassignable);
+        assert_eq!(2, var.names.len());
+        assert!(var.expr.is_ident());
+    }
+
+    #[test]
+    fn parse_var_tuple() {
+        let stmt = parse_stmt("var (a, b, c) = x;");
+        let var = stmt.to_var_tuple().unwrap();
+
+        assert_eq!(true, var.reassignable);
+        assert_eq!(3, var.names.len());
+    }
+
     #[test]
     fn parse_field_negated() {
         let (expr, _) = parse_expr("-obj.field");
@@ -1728,7 +2158,7 @@ mod tests {
 
     #[test]
     fn parse_field_non_ident() {
-        err_expr("obj.12", Msg::ExpectedIdentifier("12".into()), 1, 5);
+        err_expr("obj.12L", Msg::ExpectedIdentifier("12L".into()), 1, 5);
     }
 
     #[test]
@@ -2329,6 +2759,47 @@ mod tests {
         assert!(stmt.is_continue());
     }
 
+    #[test]
+    fn parse_labeled_loops() {
+        let (prog, interner) = parse("fun f() { outer: while true { inner: loop { break outer; } }
+                                                all: for x in y { continue all; continue; } }");
+        let block = prog.fct0().block().to_block().unwrap();
+
+        let outer = block.stmts[0].to_while().unwrap();
+        assert_eq!("outer", *interner.str(outer.label.unwrap()));
+
+        let inner = outer.block.to_block().unwrap().stmts[0].to_loop().unwrap();
+        assert_eq!("inner", *interner.str(inner.label.unwrap()));
+
+        let brk = inner.block.to_block().unwrap().stmts[0].to_break().unwrap();
+        assert_eq!("outer", *interner.str(brk.label.unwrap()));
+
+        let all = block.stmts[1].to_for().unwrap();
+        assert_eq!("all", *interner.str(all.label.unwrap()));
+
+        let stmts = &all.block.to_block().unwrap().stmts;
+        let cont = stmts[0].to_continue().unwrap();
+        assert_eq!("all", *interner.str(cont.label.unwrap()));
+        assert!(stmts[1].to_continue().unwrap().label.is_none());
+    }
+
+    #[test]
+    fn parse_unlabeled_loops() {
+        let stmt = parse_stmt("while true { break; }");
+        let whilestmt = stmt.to_while().unwrap();
+        assert!(whilestmt.label.is_none());
+
+        let brk = whilestmt.block.to_block().unwrap().stmts[0].to_break().unwrap();
+        assert!(brk.label.is_none());
+    }
+
+    #[test]
+    fn parse_label_without_loop() {
+        let err = parse_err("fun f() { outer: if true { } }");
+        assert_eq!(Msg::LoopExpected("if".into()), err.msg);
+        assert_eq!(Position::new(1, 18), err.pos);
+    }
+
     #[test]
     fn parse_return_value() {
         let stmt = parse_stmt("return 1;");
@@ -2685,6 +3156,24 @@ mod tests {
         assert_eq!(false, f2.reassignable);
     }
 
+    #[test]
+    fn parse_pub_field() {
+        let (prog, _) = parse("class A(pub let a: Int, let b: Int) { pub var c: Int; var d: Int; }");
+        let cls = prog.cls0();
+
+        assert_eq!(true, cls.fields[0].is_pub);
+        assert_eq!(false, cls.fields[1].is_pub);
+        assert_eq!(true, cls.fields[2].is_pub);
+        assert_eq!(false, cls.fields[3].is_pub);
+
+        let err = parse_err("class A(pub a: Int)");
+        assert_eq!(Msg::MisplacedModifier("pub".into()), err.msg);
+        assert_eq!(Position::new(1, 9), err.pos);
+
+        let err = parse_err("class A { open var a: Int; }");
+        assert_eq!(Msg::MisplacedModifier("open".into()), err.msg);
+    }
+
     #[test]
     fn parse_open_method() {
         let (prog, _) = parse("class A { open fun f() {} fun g() {} }");
@@ -2717,6 +3206,16 @@ mod tests {
         assert_eq!(true, m3.has_open);
     }
 
+    #[test]
+    fn parse_tailrec_function() {
+        let (prog, _) = parse("tailrec fun f() {} fun g() {}");
+        assert_eq!(true, prog.fct0().is_tailrec);
+        assert_eq!(false, prog.fct(1).is_tailrec);
+
+        let (prog, _) = parse("class A { tailrec fun f() {} }");
+        assert_eq!(true, prog.cls0().methods[0].is_tailrec);
+    }
+
     #[test]
     fn parse_parent_class_params() {
         let (prog, _) = parse("class A: B(1, 2)");
@@ -2960,6 +3459,17 @@ mod tests {
         assert_eq!(true, xtrait.methods[0].is_static);
     }
 
+    #[test]
+    fn parse_generic_trait() {
+        let (prog, interner) = parse("trait Foo<T> { fun get() -> T; }");
+        let xtrait = prog.trait0();
+
+        assert_eq!("Foo", *interner.str(xtrait.name));
+        let type_params = xtrait.type_params.as_ref().unwrap();
+        assert_eq!(1, type_params.len());
+        assert_eq!("T", *interner.str(type_params[0].name));
+    }
+
     #[test]
     fn parse_empty_impl() {
         let (prog, interner) = parse("impl Foo for A {}");
@@ -2992,6 +3502,25 @@ mod tests {
         assert_eq!(true, ximpl.methods[0].is_static);
     }
 
+    #[test]
+    fn parse_generic_impl() {
+        let (prog, interner) = parse("impl Foo<Int> for A {}");
+        let ximpl = prog.impl0();
+
+        assert_eq!("Foo", *interner.str(ximpl.trait_name));
+        assert_eq!(1, ximpl.trait_type_params.len());
+        assert_eq!("Int", ximpl.trait_type_params[0].to_string(&interner));
+        assert!(ximpl.class_type_params.is_none());
+
+        let (prog, interner) = parse("impl Foo<T> for A<T> {}");
+        let ximpl = prog.impl0();
+
+        assert_eq!("T", ximpl.trait_type_params[0].to_string(&interner));
+        let type_params = ximpl.class_type_params.as_ref().unwrap();
+        assert_eq!(1, type_params.len());
+        assert_eq!("T", *interner.str(type_params[0].name));
+    }
+
     #[test]
     fn parse_global_var() {
         let (prog, interner) = parse("var a: int = 0;");
@@ -3010,6 +3539,25 @@ mod tests {
         assert_eq!(false, global.reassignable);
     }
 
+    #[test]
+    fn parse_global_initializer() {
+        let (prog, interner) = parse("let b: int = 1 + 2;");
+        let global = prog.global0();
+
+        let initializer = global.initializer.as_ref().unwrap();
+        assert_eq!("b", *interner.str(initializer.name));
+        assert!(initializer.params.is_empty());
+        assert_eq!("int", initializer.return_type.as_ref().unwrap().to_string(&interner));
+
+        let block = initializer.block().to_block().unwrap();
+        let ret = block.stmts[0].to_return().unwrap();
+        assert!(ret.expr.as_ref().unwrap().is_bin());
+        assert_eq!(Position::new(1, 16), ret.pos);
+
+        let (prog, _) = parse("var c: int;");
+        assert!(prog.global0().initializer.is_none());
+    }
+
     #[test]
     fn parse_lit_char() {
         let (expr, _) = parse_expr("'a'");
@@ -3104,6 +3652,25 @@ mod tests {
         assert_eq!(2, type_param.bounds.len());
     }
 
+    #[test]
+    fn parse_where_clauses() {
+        let (prog, interner) = parse("fun f<T, U>() where T: Foo + Bar, U: Baz {}");
+        let fct = prog.fct0();
+
+        assert_eq!(2, fct.where_clauses.len());
+        assert_eq!("T", *interner.str(fct.where_clauses[0].name));
+        assert_eq!(2, fct.where_clauses[0].bounds.len());
+        assert_eq!("U", *interner.str(fct.where_clauses[1].name));
+        assert_eq!(1, fct.where_clauses[1].bounds.len());
+    }
+
+    #[test]
+    fn parse_where_clause_without_bound() {
+        let err = parse_err("fun f<T>() where T {}");
+        assert_eq!(Msg::ExpectedToken(":".into(), "{".into()), err.msg);
+        assert_eq!(Position::new(1, 20), err.pos);
+    }
+
     #[test]
     fn parse_generic_super_class() {
         let (prog, _) = parse("class A: B<SomeType, SomeOtherType>");
@@ -3187,4 +3754,136 @@ mod tests {
         let stmt = parse_stmt("for i in a+b {}");
         assert!(stmt.is_for());
     }
+
+    #[test]
+    fn parse_import() {
+        let (prog, interner) = parse("import util; import util::net::Socket as S;");
+
+        let import = prog.import(0);
+        assert_eq!(1, import.path.len());
+        assert_eq!("util", *interner.str(import.name()));
+
+        let import = prog.import(1);
+        assert_eq!(3, import.path.len());
+        assert_eq!("Socket", *interner.str(import.path[2]));
+        assert_eq!("S", *interner.str(import.name()));
+    }
+
+    #[test]
+    fn parse_import_with_modifier() {
+        let err = parse_err("pub import util;");
+        assert_eq!(Msg::MisplacedModifier("pub".into()), err.msg);
+    }
+
+    #[test]
+    fn parse_pub_elements() {
+        let (prog, _) = parse("pub fun f() {} pub class A pub struct B {} pub trait C {}
+                               pub let d: Int = 0; pub const e: Int = 0; class F");
+        assert!(prog.fct0().is_pub);
+        assert!(prog.cls(1).is_pub);
+        assert!(prog.files[0].elements[2].to_struct().unwrap().is_pub);
+        assert!(prog.trai(3).is_pub);
+        assert!(prog.files[0].elements[4].to_global().unwrap().is_pub);
+        assert!(prog.files[0].elements[5].to_const().unwrap().is_pub);
+        assert!(!prog.cls(6).is_pub);
+    }
+
+    #[test]
+    fn parse_type_qualified() {
+        let (ty, interner) = parse_type("util::Foo<A>");
+        let basic = ty.to_basic().unwrap();
+
+        assert_eq!(1, basic.module.len());
+        assert_eq!("util", *interner.str(basic.module[0]));
+        assert_eq!("Foo", *interner.str(basic.name));
+        assert_eq!(1, basic.params.len());
+        assert_eq!("util::Foo", ty.to_string(&interner));
+    }
+
+    #[test]
+    fn parse_qualified_ident() {
+        let (expr, interner) = parse_expr("util::net::PORT");
+        let path = expr.to_path().unwrap();
+        assert_eq!("PORT", *interner.str(path.rhs.to_ident().unwrap().name));
+
+        let path = path.lhs.to_path().unwrap();
+        assert_eq!("util", *interner.str(path.lhs.to_ident().unwrap().name));
+        assert_eq!("net", *interner.str(path.rhs.to_ident().unwrap().name));
+    }
+
+    #[test]
+    fn parse_attributes() {
+        let (prog, _) = parse("#[test] #[ignore] fun f(t: Testing) {}
+                               #[inline] pub fun g() {}
+                               #[noinline] fun h() {}
+                               #[deprecated(\"use g\")] fun i() {}
+                               #[deprecated] fun j() {}");
+        let fct = prog.fct(0);
+        assert!(fct.is_test);
+        assert!(fct.is_ignored);
+        assert!(!fct.is_inline);
+
+        let fct = prog.fct(1);
+        assert!(fct.is_inline);
+        assert!(fct.is_pub);
+        assert!(!fct.is_test);
+
+        assert!(prog.fct(2).is_noinline);
+        assert_eq!(Some("use g".into()), prog.fct(3).deprecated);
+        assert_eq!(Some("".into()), prog.fct(4).deprecated);
+        assert_eq!(None, prog.fct(1).deprecated);
+    }
+
+    #[test]
+    fn parse_attributes_on_methods() {
+        let (prog, _) = parse("class A { #[inline] fun f() {} #[deprecated(\"x\")] fun g() {} }");
+        let cls = prog.cls0();
+        assert!(cls.methods[0].is_inline);
+        assert_eq!(Some("x".into()), cls.methods[1].deprecated);
+    }
+
+    #[test]
+    fn parse_attribute_errors() {
+        let err = parse_err("#[foo] fun f() {}");
+        assert_eq!(Msg::UnknownAttribute("foo".into()), err.msg);
+        assert_eq!(Position::new(1, 3), err.pos);
+
+        let err = parse_err("#[test] class A");
+        assert_eq!(Msg::MisplacedModifier("#[test]".into()), err.msg);
+
+        let err = parse_err("class A { #[test] fun f() {} }");
+        assert_eq!(Msg::MisplacedModifier("#[test]".into()), err.msg);
+
+        let err = parse_err("#[inline] #[inline] fun f() {}");
+        assert_eq!(Msg::RedundantModifier("#[inline]".into()), err.msg);
+
+        let err = parse_err("#[inline(\"x\")] fun f() {}");
+        assert_eq!(Msg::ExpectedToken("]".into(), "(".into()), err.msg);
+    }
+
+    #[test]
+    fn parse_class_static_fields() {
+        let (prog, interner) = parse("class A { static var x: int; static let y: Str = \"a\"; var z: int; }");
+        let cls = prog.cls0();
+        assert_eq!(1, cls.fields.len());
+        assert_eq!(2, cls.static_fields.len());
+
+        let x = &cls.static_fields[0];
+        assert_eq!("x", *interner.str(x.name));
+        assert!(x.reassignable);
+        assert!(x.initializer.is_none());
+
+        let y = &cls.static_fields[1];
+        assert_eq!("y", *interner.str(y.name));
+        assert!(!y.reassignable);
+        assert!(y.initializer.is_some());
+
+        let (prog, _) = parse("class A { pub static var x: int; static var y: int; }");
+        let cls = prog.cls0();
+        assert!(cls.static_fields[0].is_pub);
+        assert!(!cls.static_fields[1].is_pub);
+
+        let err = parse_err("class A { open static var x: int; }");
+        assert_eq!(Msg::MisplacedModifier("open".into()), err.msg);
+    }
 }
@@ -1,12 +1,18 @@
 pub use crate::baseline::codegen::{generate, generate_fct};
 
 mod asm;
+pub mod aot;
+pub mod bounds_check;
 pub mod codegen;
+pub mod compile_queue;
 pub mod dora_compile;
 pub mod dora_entry;
 pub mod dora_native;
 pub mod dora_throw;
+pub mod dora_unbox;
 pub mod expr;
 pub mod fct;
 pub mod info;
 pub mod map;
+pub mod regalloc;
+pub mod unload;
@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use crate::baseline;
+use crate::baseline::fct::BailoutInfo;
+use crate::baseline::map::CodeDescriptor;
+use crate::class::{ClassId, TypeParams};
+use crate::ctxt::{FctId, FctKind, VM};
+use crate::gc::Address;
+
+pub struct CompileStats {
+    pub fcts: usize,
+    pub code_size: usize,
+}
+
+// Compiles all functions reachable from `main` with the baseline compiler
+// instead of compiling them lazily on their first invocation. Direct calls
+// are followed through the compile bailouts of already compiled functions,
+// this includes all generic specializations. Virtual methods are compiled
+// for every class specialization created so far, compiling them can create
+// new class specializations, so both steps are repeated until nothing new
+// gets compiled.
+pub fn compile_reachable(vm: &VM, main: FctId) -> CompileStats {
+    let mut compiler = Compiler {
+        vm: vm,
+        compiled: HashSet::new(),
+        worklist: vec![(main, TypeParams::empty(), TypeParams::empty())],
+        stats: CompileStats {
+            fcts: 0,
+            code_size: 0,
+        },
+    };
+
+    compiler.run();
+
+    compiler.stats
+}
+
+struct Compiler<'a, 'ast: 'a> {
+    vm: &'a VM<'ast>,
+    compiled: HashSet<(FctId, TypeParams, TypeParams)>,
+    worklist: Vec<(FctId, TypeParams, TypeParams)>,
+    stats: CompileStats,
+}
+
+impl<'a, 'ast> Compiler<'a, 'ast> {
+    fn run(&mut self) {
+        loop {
+            while let Some((fct_id, cls_tps, fct_tps)) = self.worklist.pop() {
+                self.compile(fct_id, cls_tps, fct_tps);
+            }
+
+            self.compile_vtables();
+
+            if self.worklist.is_empty() {
+                break;
+            }
+        }
+    }
+
+    fn compile(&mut self, fct_id: FctId, cls_tps: TypeParams, fct_tps: TypeParams) -> Address {
+        let fct_ptr = baseline::generate(self.vm, fct_id, &cls_tps, &fct_tps);
+
+        if !self.compiled.insert((fct_id, cls_tps, fct_tps)) {
+            return fct_ptr;
+        }
+
+        // extern functions aren't compiled
+        let jit_fct_id = match self.vm.code_map.lock().get(fct_ptr) {
+            Some(CodeDescriptor::DoraFct(jit_fct_id)) => jit_fct_id,
+            _ => return fct_ptr,
+        };
+
+        let jit_fct = self.vm.jit_fcts.idx(jit_fct_id);
+        let jit_fct = jit_fct.to_base().expect("baseline expected");
+
+        self.stats.fcts += 1;
+        self.stats.code_size += jit_fct.fct_len();
+
+        for bailout in jit_fct.bailouts.iter() {
+            if let BailoutInfo::Compile(fct_id, _, ref cls_tps, ref fct_tps) = *bailout {
+                let key = (fct_id, cls_tps.clone(), fct_tps.clone());
+
+                if !self.compiled.contains(&key) {
+                    self.worklist.push(key);
+                }
+            }
+        }
+
+        fct_ptr
+    }
+
+    // compiles all methods of class specializations whose vtable entries
+    // still point to the compile stub and stores their addresses in the
+    // vtable, new class specializations are visited by the iterator as well
+    fn compile_vtables(&mut self) {
+        let compiler_thunk = self.vm.compiler_thunk().to_usize();
+
+        for cls_def in self.vm.class_defs.iter() {
+            let (cls_id, entries) = {
+                let cls_def = cls_def.read();
+
+                match (cls_def.cls_id, &cls_def.vtable) {
+                    (Some(cls_id), &Some(ref vtable)) => (cls_id, vtable.table().to_vec()),
+                    _ => continue,
+                }
+            };
+
+            for (vtable_index, &entry) in entries.iter().enumerate() {
+                if entry != compiler_thunk {
+                    continue;
+                }
+
+                let fct_id = match self.method(cls_id, vtable_index as u32) {
+                    Some(fct_id) => fct_id,
+                    None => continue,
+                };
+
+                let fct_ptr = self.compile(fct_id, TypeParams::empty(), TypeParams::empty());
+
+                let mut cls_def = cls_def.write();
+                let vtable = cls_def.vtable.as_mut().unwrap();
+                vtable.table_mut()[vtable_index] = fct_ptr.to_usize();
+            }
+        }
+    }
+
+    // same lookup as for the compile stub of virtual calls, generic
+    // methods are only compiled once they are called
+    fn method(&self, cls_id: ClassId, vtable_index: u32) -> Option<FctId> {
+        let cls = self.vm.classes.idx(cls_id);
+        let cls = cls.read();
+
+        for &fct_id in &cls.methods {
+            let fct = self.vm.fcts.idx(fct_id);
+            let fct = fct.read();
+
+            if fct.vtable_index != Some(vtable_index) {
+                continue;
+            }
+
+            return match fct.kind {
+                FctKind::Source(_) if fct.type_params.is_empty() => Some(fct_id),
+                _ => None,
+            };
+        }
+
+        None
+    }
+}
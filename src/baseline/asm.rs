@@ -41,6 +41,10 @@ where
         self.masm.debug();
     }
 
+    pub fn load_fence(&mut self) {
+        self.masm.load_fence();
+    }
+
     pub fn prolog(&mut self, stacksize: i32) {
         self.masm.prolog(stacksize);
     }
@@ -419,15 +423,31 @@ where
         self.masm.emit_lineno(lineno);
     }
 
+    pub fn set_inlined_lineno(&mut self, lineno: Option<i32>) {
+        self.masm.set_inlined_lineno(lineno);
+    }
+
     pub fn var_store(&mut self, jit_info: &JitInfo, src: ExprStore, var_id: VarId) {
-        let offset = jit_info.offset(var_id);
         let ty = jit_info.ty(var_id);
+
+        if let Some(reg) = jit_info.reg(var_id) {
+            self.masm.copy_reg(ty.mode(), reg, src.reg());
+            return;
+        }
+
+        let offset = jit_info.offset(var_id);
         self.masm.store_mem(ty.mode(), Mem::Local(offset), src);
     }
 
     pub fn var_load(&mut self, jit_info: &JitInfo, var_id: VarId, dest: ExprStore) {
-        let offset = jit_info.offset(var_id);
         let ty = jit_info.ty(var_id);
+
+        if let Some(reg) = jit_info.reg(var_id) {
+            self.masm.copy_reg(ty.mode(), dest.reg(), reg);
+            return;
+        }
+
+        let offset = jit_info.offset(var_id);
         self.masm.load_mem(ty.mode(), dest, Mem::Local(offset));
     }
 
@@ -465,6 +485,23 @@ where
         self.call_epilog(pos, ty, dest, gcpoint);
     }
 
+    pub fn direct_tail_call(
+        &mut self,
+        fct_id: FctId,
+        ptr: *const u8,
+        cls_tps: TypeParams,
+        fct_tps: TypeParams,
+        stacksize: i32,
+        pos: Position,
+        gcpoint: GcPoint,
+        ty: BuiltinType,
+        dest: ExprStore,
+    ) {
+        self.masm
+            .direct_tail_call(fct_id, ptr, cls_tps, fct_tps, stacksize);
+        self.call_epilog(pos, ty, dest, gcpoint);
+    }
+
     pub fn indirect_call(
         &mut self,
         index: u32,
@@ -477,6 +514,18 @@ where
         self.call_epilog(pos, ty, dest, gcpoint);
     }
 
+    pub fn trait_call(
+        &mut self,
+        fct_id: FctId,
+        pos: Position,
+        gcpoint: GcPoint,
+        ty: BuiltinType,
+        dest: ExprStore,
+    ) {
+        self.masm.trait_call(pos.line as i32, fct_id);
+        self.call_epilog(pos, ty, dest, gcpoint);
+    }
+
     fn call_epilog(&mut self, pos: Position, ty: BuiltinType, dest: ExprStore, gcpoint: GcPoint) {
         self.masm.emit_lineno(pos.line as i32);
         self.masm.emit_gcpoint(gcpoint);
@@ -532,8 +581,8 @@ where
             desc: InternalFctDescriptor::AllocThunk,
         };
 
+        // the alloc thunk throws OutOfMemoryError when allocation fails
         self.native_call(internal_fct, pos, gcpoint, dest.into());
-        self.masm.test_if_nil_bailout(pos, dest, Trap::OOM);
     }
 
     pub fn verify_refs(&mut self, obj: Reg, value: Reg, pos: Position, gcpoint: GcPoint) {
@@ -0,0 +1,343 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ctxt::{FctKind, FctSrc, IdentType, Intrinsic, VarId, VM};
+use crate::ty::BuiltinType;
+use dora_parser::ast::visit::*;
+use dora_parser::ast::Expr::*;
+use dora_parser::ast::Stmt::*;
+use dora_parser::ast::*;
+use dora_parser::lexer::token::IntSuffix;
+
+// Determines array accesses in counted loops that are known to be in bounds
+// and therefore don't need a bounds check. Recognized patterns:
+//
+//   var i = 0; while i < a.length() { ... a[i] ... i = i + 1; }
+//   for i in range(0, a.length()) { ... a[i] ... }
+//
+// For strings `s.getByte(i)` is recognized as well.
+//
+// In both cases the array variable must not be assigned in the loop.
+pub fn analyze<'ast>(vm: &VM<'ast>, src: &FctSrc, ast: &'ast Function) -> HashSet<NodeId> {
+    let mut assignments = AssignmentCollector::new(src);
+    assignments.visit_fct(ast);
+
+    let mut analysis = BoundsCheckAnalysis {
+        vm: vm,
+        src: src,
+        assignments: assignments.assignments,
+        nonnegative: assignments.nonnegative,
+        result: HashSet::new(),
+    };
+
+    analysis.visit_fct(ast);
+
+    analysis.result
+}
+
+struct BoundsCheckAnalysis<'a, 'ast: 'a> {
+    vm: &'a VM<'ast>,
+    src: &'a FctSrc,
+    assignments: HashMap<VarId, Vec<NodeId>>,
+    nonnegative: HashSet<VarId>,
+    result: HashSet<NodeId>,
+}
+
+impl<'a, 'ast> Visitor<'ast> for BoundsCheckAnalysis<'a, 'ast> {
+    fn visit_stmt(&mut self, s: &'ast Stmt) {
+        match *s {
+            StmtWhile(ref stmt) => self.check_while(stmt),
+            StmtFor(ref stmt) => self.check_for(stmt),
+            _ => {}
+        }
+
+        visit::walk_stmt(self, s);
+    }
+}
+
+impl<'a, 'ast> BoundsCheckAnalysis<'a, 'ast> {
+    fn check_while(&mut self, stmt: &'ast StmtWhileType) {
+        // condition needs to be `i < a.length()`
+        let (index, array) = match self.length_comparison(&stmt.cond) {
+            Some(vars) => vars,
+            None => return,
+        };
+
+        let block = match *stmt.block {
+            StmtBlock(ref block) => block,
+            _ => return,
+        };
+
+        // the body needs to end with `i = i + 1`
+        let (last, body) = match block.stmts.split_last() {
+            Some(stmts) => stmts,
+            None => return,
+        };
+
+        let increment = match self.increment(last, index) {
+            Some(id) => id,
+            None => return,
+        };
+
+        // index starts non-negative and is never changed except for the
+        // increment, which can't overflow since `i < a.length()` holds
+        let only_increment = match self.assignments.get(&index) {
+            Some(assignments) => assignments.len() == 1 && assignments[0] == increment,
+            None => false,
+        };
+
+        if !self.nonnegative.contains(&index) || !only_increment {
+            return;
+        }
+
+        if is_assigned(self.src, &stmt.block, array) {
+            return;
+        }
+
+        for stmt in body {
+            self.collect_accesses(stmt, array, index);
+        }
+    }
+
+    fn check_for(&mut self, stmt: &'ast StmtForType) {
+        // iterated expression needs to be `range(0, a.length())`
+        let call = match *stmt.expr {
+            ExprCall(ref call) => call,
+            _ => return,
+        };
+
+        let fct_id = match self.src.map_calls.get(call.id) {
+            Some(call_type) => call_type.fct_id(),
+            None => return,
+        };
+
+        if Some(fct_id) != self.vm.vips.range_fct
+            || call.args.len() != 2
+            || !is_nonnegative_literal(&call.args[0])
+        {
+            return;
+        }
+
+        let array = match self.length_of(&call.args[1]) {
+            Some(array) => array,
+            None => return,
+        };
+
+        let index = *self.src.map_vars.get(stmt.id).unwrap();
+
+        if is_assigned(self.src, &stmt.block, array) || is_assigned(self.src, &stmt.block, index) {
+            return;
+        }
+
+        self.collect_accesses(&stmt.block, array, index);
+    }
+
+    fn collect_accesses(&mut self, stmt: &'ast Stmt, array: VarId, index: VarId) {
+        let mut collector = AccessCollector {
+            analysis: self,
+            array: array,
+            index: index,
+        };
+
+        collector.visit_stmt(stmt);
+    }
+
+    fn length_comparison(&self, expr: &'ast Expr) -> Option<(VarId, VarId)> {
+        let bin = match *expr {
+            ExprBin(ref bin) if bin.op == BinOp::Cmp(CmpOp::Lt) => bin,
+            _ => return None,
+        };
+
+        let index = self.var(&bin.lhs)?;
+
+        if self.src.vars[index].ty != BuiltinType::Int {
+            return None;
+        }
+
+        let array = self.length_of(&bin.rhs)?;
+
+        Some((index, array))
+    }
+
+    fn length_of(&self, expr: &'ast Expr) -> Option<VarId> {
+        let call = match *expr {
+            ExprCall(ref call) if call.args.is_empty() => call,
+            _ => return None,
+        };
+
+        match self.intrinsic(call.id) {
+            Some(Intrinsic::GenericArrayLen) | Some(Intrinsic::StrLen) => {}
+            _ => return None,
+        }
+
+        self.var(call.object.as_ref()?)
+    }
+
+    fn increment(&self, stmt: &'ast Stmt, index: VarId) -> Option<NodeId> {
+        let assign = match *stmt {
+            StmtExpr(ref stmt) => stmt.expr.to_assign()?,
+            _ => return None,
+        };
+
+        let bin = assign.rhs.to_bin()?;
+        let one = bin.rhs.to_lit_int()?;
+
+        if self.var(&assign.lhs) == Some(index)
+            && bin.op == BinOp::Add
+            && self.var(&bin.lhs) == Some(index)
+            && one.value == 1
+            && one.suffix == IntSuffix::Int
+        {
+            Some(assign.id)
+        } else {
+            None
+        }
+    }
+
+    fn var(&self, expr: &'ast Expr) -> Option<VarId> {
+        let ident = expr.to_ident()?;
+
+        match self.src.map_idents.get(ident.id) {
+            Some(&IdentType::Var(var)) => Some(var),
+            _ => None,
+        }
+    }
+
+    fn intrinsic(&self, id: NodeId) -> Option<Intrinsic> {
+        let fct_id = self.src.map_calls.get(id)?.fct_id();
+        let fct = self.vm.fcts.idx(fct_id);
+        let fct = fct.read();
+
+        match fct.kind {
+            FctKind::Builtin(intrinsic) => Some(intrinsic),
+            _ => None,
+        }
+    }
+}
+
+struct AccessCollector<'b, 'a: 'b, 'ast: 'a> {
+    analysis: &'b mut BoundsCheckAnalysis<'a, 'ast>,
+    array: VarId,
+    index: VarId,
+}
+
+impl<'b, 'a, 'ast> AccessCollector<'b, 'a, 'ast> {
+    fn is_access(&self, array: &'ast ExprArrayType) -> bool {
+        self.analysis.var(&array.object) == Some(self.array)
+            && self.analysis.var(&array.index) == Some(self.index)
+    }
+}
+
+impl<'b, 'a, 'ast> Visitor<'ast> for AccessCollector<'b, 'a, 'ast> {
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        match *e {
+            ExprArray(ref array) if self.is_access(array) => {
+                match self.analysis.intrinsic(array.id) {
+                    Some(Intrinsic::GenericArrayGet) | Some(Intrinsic::StrGet) => {
+                        self.analysis.result.insert(array.id);
+                    }
+
+                    _ => {}
+                }
+            }
+
+            ExprCall(ref call) if call.args.len() == 1 => {
+                let is_access = match call.object {
+                    Some(ref object) => {
+                        self.analysis.var(object) == Some(self.array)
+                            && self.analysis.var(&call.args[0]) == Some(self.index)
+                    }
+
+                    None => false,
+                };
+
+                if is_access && self.analysis.intrinsic(call.id) == Some(Intrinsic::StrGet) {
+                    self.analysis.result.insert(call.id);
+                }
+            }
+
+            ExprAssign(ref assign) => {
+                if let Some(array) = assign.lhs.to_array() {
+                    if self.is_access(array) {
+                        match self.analysis.intrinsic(assign.id) {
+                            Some(Intrinsic::GenericArraySet) | Some(Intrinsic::StrSet) => {
+                                self.analysis.result.insert(assign.id);
+                            }
+
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            _ => {}
+        }
+
+        visit::walk_expr(self, e);
+    }
+}
+
+fn is_assigned(src: &FctSrc, stmt: &Stmt, var: VarId) -> bool {
+    let mut collector = AssignmentCollector::new(src);
+    collector.visit_stmt(stmt);
+
+    collector.assignments.contains_key(&var)
+}
+
+fn is_nonnegative_literal(expr: &Expr) -> bool {
+    match *expr {
+        ExprLitInt(ref lit) => lit.suffix == IntSuffix::Int && lit.value <= i32::max_value() as u64,
+
+        _ => false,
+    }
+}
+
+// collects all assignments of local variables and the variables
+// initialized with a non-negative literal
+struct AssignmentCollector<'a> {
+    src: &'a FctSrc,
+    assignments: HashMap<VarId, Vec<NodeId>>,
+    nonnegative: HashSet<VarId>,
+}
+
+impl<'a> AssignmentCollector<'a> {
+    fn new(src: &'a FctSrc) -> AssignmentCollector<'a> {
+        AssignmentCollector {
+            src: src,
+            assignments: HashMap::new(),
+            nonnegative: HashSet::new(),
+        }
+    }
+}
+
+impl<'a, 'ast> Visitor<'ast> for AssignmentCollector<'a> {
+    fn visit_stmt(&mut self, s: &'ast Stmt) {
+        if let StmtVar(ref stmt) = *s {
+            let var = *self.src.map_vars.get(stmt.id).unwrap();
+
+            match stmt.expr {
+                Some(ref expr) if is_nonnegative_literal(expr) => {
+                    self.nonnegative.insert(var);
+                }
+
+                _ => {}
+            }
+        }
+
+        visit::walk_stmt(self, s);
+    }
+
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        if let ExprAssign(ref assign) = *e {
+            if let Some(ident) = assign.lhs.to_ident() {
+                if let Some(&IdentType::Var(var)) = self.src.map_idents.get(ident.id) {
+                    self.assignments
+                        .entry(var)
+                        .or_insert_with(Vec::new)
+                        .push(assign.id);
+                }
+            }
+        }
+
+        visit::walk_expr(self, e);
+    }
+}
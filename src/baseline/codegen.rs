@@ -12,9 +12,11 @@ use capstone::{Engine, Error};
 use dora_parser::ast::visit::*;
 use dora_parser::ast::Stmt::*;
 use dora_parser::ast::*;
+use dora_parser::interner::Name;
 use dora_parser::lexer::position::Position;
 
 use crate::baseline::asm::BaselineAssembler;
+use crate::baseline::compile_queue;
 use crate::baseline::expr::*;
 use crate::baseline::fct::{
     CatchType, Comment, CommentFormat, GcPoint, JitBaselineFct, JitDescriptor, JitFct,
@@ -22,9 +24,9 @@ use crate::baseline::fct::{
 use crate::baseline::info::{self, JitInfo};
 use crate::baseline::map::CodeDescriptor;
 use crate::class::{ClassDef, TypeParams};
-use crate::cpu::{Mem, FREG_PARAMS, FREG_RESULT, REG_PARAMS, REG_RESULT};
+use crate::cpu::{Mem, FREG_PARAMS, FREG_RESULT, REG_PARAMS, REG_RESULT, REG_TMP1};
 use crate::ctxt::VM;
-use crate::ctxt::{CallSite, Fct, FctId, FctParent, FctSrc, VarId};
+use crate::ctxt::{CallSite, Fct, FctId, FctSrc, VarId};
 use crate::driver::cmd::AsmSyntax;
 use crate::gc::Address;
 use crate::masm::*;
@@ -33,6 +35,7 @@ use crate::os;
 use crate::os::signal::Trap;
 use crate::semck::always_returns;
 use crate::semck::specialize::specialize_class_ty;
+use crate::timer::Timer;
 use crate::ty::{BuiltinType, MachineMode};
 use std::ffi::CString;
 
@@ -86,6 +89,9 @@ pub fn generate<'ast>(
     cls_type_params: &TypeParams,
     fct_type_params: &TypeParams,
 ) -> Address {
+    // unloading must not free code while its address is embedded or patched
+    let _compilation = vm.compilation_lock.read_recursive();
+
     let fct = vm.fcts.idx(id);
     let fct = fct.read();
     if fct.is_extern {
@@ -93,6 +99,7 @@ pub fn generate<'ast>(
 
         return Address::from(address as usize);
     }
+
     let src = fct.src();
     let mut src = src.write();
 
@@ -124,6 +131,7 @@ pub fn generate_fct<'ast>(
     }
 
     let ast = fct.ast;
+    let timer = Timer::new(vm.args.flag_print_compilation);
 
     let mut jit_info = JitInfo::new();
     info::generate(
@@ -143,13 +151,14 @@ pub fn generate_fct<'ast>(
         src: src,
         jit_info: jit_info,
 
-        lbl_break: None,
-        lbl_continue: None,
+        loops: Vec::new(),
 
         active_finallys: Vec::new(),
         active_upper: None,
-        active_loop: None,
         lbl_return: None,
+        lbl_tail_call: None,
+        tail_recursive: false,
+        in_try: false,
 
         cls_type_params: cls_type_params,
         fct_type_params: fct_type_params,
@@ -173,6 +182,24 @@ pub fn generate_fct<'ast>(
     debug_assert!(mem::is_aligned(ptr_start.to_usize(), 16));
     debug_assert!(mem::is_aligned(fct_ptr.to_usize(), 16));
 
+    vm.compile_queue.enqueue_callees(vm, &jit_fct);
+
+    timer.stop_with(|ms| {
+        let thread = if compile_queue::is_compiler_thread() {
+            "background"
+        } else {
+            "mutator"
+        };
+
+        println!(
+            "compiled {} with baseline ({} bytes, {:.3} ms, {})",
+            fct.full_name(vm),
+            jit_fct.fct_len(),
+            ms,
+            thread
+        );
+    });
+
     let jit_fct_id = {
         let mut jit_fcts = vm.jit_fcts.lock();
         let jit_fct_id = jit_fcts.len().into();
@@ -181,18 +208,20 @@ pub fn generate_fct<'ast>(
         jit_fct_id
     };
 
-    {
-        let mut specials = src.specializations.write();
-        let key = (cls_type_params.clone(), fct_type_params.clone());
-        specials.insert(key, jit_fct_id);
-    }
-
+    // other threads may call the function as soon as it is published in
+    // specializations, so it needs to be in the code map before that
     {
         let mut code_map = vm.code_map.lock();
         let cdata = CodeDescriptor::DoraFct(jit_fct_id);
         code_map.insert(ptr_start, ptr_end, cdata);
     }
 
+    {
+        let mut specials = src.specializations.write();
+        let key = (cls_type_params.clone(), fct_type_params.clone());
+        specials.insert(key, jit_fct_id);
+    }
+
     fct_ptr
 }
 
@@ -328,8 +357,8 @@ pub struct CodeGen<'a, 'ast: 'a> {
     src: &'a mut FctSrc,
     jit_info: JitInfo<'ast>,
 
-    lbl_break: Option<Label>,
-    lbl_continue: Option<Label>,
+    // all enclosing loops, innermost loop last
+    loops: Vec<LoopLabels>,
 
     // stores all active finally blocks
     active_finallys: Vec<&'ast Stmt>,
@@ -340,12 +369,6 @@ pub struct CodeGen<'a, 'ast: 'a> {
     // leave the current function
     lbl_return: Option<Label>,
 
-    // length of active_finallys in last loop
-    // default: 0
-    // break/continue need to emit finally blocks up to the last loop
-    // see tests/finally/break-while.dora
-    active_loop: Option<usize>,
-
     // upper length of active_finallys in emitting finally-blocks for break/continue
     // default: active_finallys.len()
     // break/continue needs to execute finally-blocks in loop, return in these blocks
@@ -353,6 +376,16 @@ pub struct CodeGen<'a, 'ast: 'a> {
     // see emit_finallys_within_loop and tests/finally/continue-return.dora
     active_upper: Option<usize>,
 
+    // self-calls in tail position jump to this label right behind the prolog
+    lbl_tail_call: Option<Label>,
+
+    // set when a self-call was emitted as jump
+    tail_recursive: bool,
+
+    // calls in do-blocks can't be tail calls, the exception handler
+    // needs the frame of the function
+    in_try: bool,
+
     cls_type_params: &'a TypeParams,
     fct_type_params: &'a TypeParams,
 }
@@ -367,6 +400,11 @@ where
         }
 
         self.emit_prolog();
+
+        let lbl_tail_call = self.asm.create_label();
+        self.asm.bind_label(lbl_tail_call);
+        self.lbl_tail_call = Some(lbl_tail_call);
+
         self.store_register_params_on_stack();
         self.visit_fct(self.ast);
 
@@ -376,14 +414,20 @@ where
             self.emit_epilog();
         }
 
-        let jit_fct = self.asm.jit(
+        let mut jit_fct = self.asm.jit(
             self.jit_info.stacksize(),
             JitDescriptor::DoraFct(self.fct.id),
             self.ast.throws,
         );
+        jit_fct.saved_regs = self.jit_info.saved_regs.clone();
+        jit_fct.tail_recursive = self.tail_recursive;
 
         if self.vm.args.flag_enable_perf {
-            os::perf::register_with_perf(&jit_fct, self.vm, self.ast.name);
+            os::perf::register_with_perf(&jit_fct, self.vm, self.fct);
+        }
+
+        if self.vm.args.flag_enable_gdb {
+            os::gdb::register_with_gdb(&jit_fct, self.vm, self.fct);
         }
 
         jit_fct
@@ -450,6 +494,12 @@ where
     fn emit_prolog(&mut self) {
         let stacksize = self.jit_info.stacksize();
         self.asm.prolog(stacksize);
+
+        for &(reg, offset) in &self.jit_info.saved_regs {
+            self.asm
+                .store_mem(MachineMode::Ptr, Mem::Local(offset), reg.into());
+        }
+
         self.asm.emit_comment(Comment::Lit("prolog end"));
         self.asm.emit_comment(Comment::Newline);
     }
@@ -458,6 +508,11 @@ where
         self.asm.emit_comment(Comment::Newline);
         self.asm.emit_comment(Comment::Lit("epilog"));
 
+        for &(reg, offset) in &self.jit_info.saved_regs {
+            self.asm
+                .load_mem(MachineMode::Ptr, reg.into(), Mem::Local(offset));
+        }
+
         let stacksize = self.jit_info.stacksize();
         let polling_page = self.vm.polling_page.addr();
         self.asm.epilog_with_polling(stacksize, polling_page);
@@ -468,6 +523,24 @@ where
         let return_type = self.specialize_type(self.fct.return_type);
 
         if let Some(ref expr) = s.expr {
+            // boxing the result requires code after the call
+            let boxed = self.src.map_boxes.get(expr.id()).is_some();
+
+            if len == 0 && self.lbl_return.is_none() && !self.in_try && !boxed {
+                match self.emit_tail_call(expr) {
+                    Some(TailCall::SelfCall) => {
+                        self.tail_recursive = true;
+                        return;
+                    }
+                    Some(TailCall::Sibling) => {
+                        // callee wasn't compiled and was called instead
+                        self.emit_epilog();
+                        return;
+                    }
+                    None => {}
+                }
+            }
+
             self.emit_expr(expr);
 
             if len > 0 {
@@ -514,9 +587,6 @@ where
         let lbl_start = self.asm.create_label();
         let lbl_end = self.asm.create_label();
 
-        let saved_active_loop = self.active_loop;
-
-        self.active_loop = Some(self.active_finallys.len());
         self.asm.bind_label(lbl_start);
 
         if s.cond.is_lit_true() {
@@ -530,7 +600,7 @@ where
                 .test_and_jump_if(CondCode::Zero, REG_RESULT, lbl_end);
         }
 
-        self.save_label_state(lbl_end, lbl_start, |this| {
+        self.save_label_state(s.label, lbl_end, lbl_start, |this| {
             // execute while body, then jump back to condition
             this.visit_stmt(&s.block);
 
@@ -539,14 +609,13 @@ where
         });
 
         self.asm.bind_label(lbl_end);
-        self.active_loop = saved_active_loop;
     }
 
     fn emit_stmt_for(&mut self, s: &'ast StmtForType) {
         let for_info = self.jit_info.map_fors.get(s.id).unwrap().clone();
 
         // emit: <iterator> = obj.makeIterator()
-        let dest = self.emit_call_site(&for_info.make_iterator, s.pos);
+        let dest = self.emit_call_site(None, &for_info.make_iterator, s.pos);
 
         // offset of iterator storage
         let offset = *self.jit_info.map_offsets.get(s.id).unwrap();
@@ -556,23 +625,21 @@ where
         let lbl_start = self.asm.create_label();
         let lbl_end = self.asm.create_label();
 
-        let saved_active_loop = self.active_loop;
-
-        self.active_loop = Some(self.active_finallys.len());
         self.asm.bind_label(lbl_start);
 
         // emit: iterator.hasNext() & jump to lbl_end if false
-        let dest = self.emit_call_site(&for_info.has_next, s.pos);
+        // (could be inlined, stored under the id of the for-loop)
+        let dest = self.emit_call_site(Some(s.id), &for_info.has_next, s.pos);
         self.asm
             .test_and_jump_if(CondCode::Zero, dest.reg(), lbl_end);
 
         // emit: <for_var> = iterator.next()
-        let dest = self.emit_call_site(&for_info.next, s.pos);
+        let dest = self.emit_call_site(None, &for_info.next, s.pos);
 
         let for_var_id = *self.src.map_vars.get(s.id).unwrap();
         self.asm.var_store(&self.jit_info, dest, for_var_id);
 
-        self.save_label_state(lbl_end, lbl_start, |this| {
+        self.save_label_state(s.label, lbl_end, lbl_start, |this| {
             // execute while body, then jump back to condition
             this.visit_stmt(&s.block);
 
@@ -581,19 +648,15 @@ where
         });
 
         self.asm.bind_label(lbl_end);
-        self.active_loop = saved_active_loop;
     }
 
     fn emit_stmt_loop(&mut self, s: &'ast StmtLoopType) {
         let lbl_start = self.asm.create_label();
         let lbl_end = self.asm.create_label();
 
-        let saved_active_loop = self.active_loop;
-
-        self.active_loop = Some(self.active_finallys.len());
         self.asm.bind_label(lbl_start);
 
-        self.save_label_state(lbl_end, lbl_start, |this| {
+        self.save_label_state(s.label, lbl_end, lbl_start, |this| {
             this.visit_stmt(&s.block);
 
             this.emit_safepoint();
@@ -601,7 +664,6 @@ where
         });
 
         self.asm.bind_label(lbl_end);
-        self.active_loop = saved_active_loop;
     }
 
     fn emit_safepoint(&mut self) {
@@ -613,20 +675,39 @@ where
         self.asm.emit_gcpoint(gcpoint);
     }
 
-    fn save_label_state<F>(&mut self, lbl_break: Label, lbl_continue: Label, f: F)
-    where
+    fn save_label_state<F>(
+        &mut self,
+        label: Option<Name>,
+        lbl_break: Label,
+        lbl_continue: Label,
+        f: F,
+    ) where
         F: FnOnce(&mut CodeGen<'a, 'ast>),
     {
-        let old_lbl_break = self.lbl_break;
-        let old_lbl_continue = self.lbl_continue;
-
-        self.lbl_break = Some(lbl_break);
-        self.lbl_continue = Some(lbl_continue);
+        self.loops.push(LoopLabels {
+            label: label,
+            lbl_break: lbl_break,
+            lbl_continue: lbl_continue,
+            finallys: self.active_finallys.len(),
+        });
 
         f(self);
 
-        self.lbl_break = old_lbl_break;
-        self.lbl_continue = old_lbl_continue;
+        self.loops.pop();
+    }
+
+    // returns the loop a break/continue refers to, semck
+    // already checked that a loop with this label exists
+    fn find_loop(&self, label: Option<Name>) -> &LoopLabels {
+        match label {
+            Some(_) => self
+                .loops
+                .iter()
+                .rev()
+                .find(|l| l.label == label)
+                .expect("loop with label not found"),
+            None => self.loops.last().expect("no loop found"),
+        }
     }
 
     fn emit_stmt_if(&mut self, s: &'ast StmtIfType) {
@@ -653,27 +734,36 @@ where
         self.asm.bind_label(lbl_end);
     }
 
-    fn emit_stmt_break(&mut self, _: &'ast StmtBreakType) {
-        // emit finallys between loop and break
-        self.emit_finallys_within_loop();
+    fn emit_stmt_break(&mut self, s: &'ast StmtBreakType) {
+        let (lbl_break, finallys) = {
+            let l = self.find_loop(s.label);
+            (l.lbl_break, l.finallys)
+        };
+
+        // emit finallys between loop and break, for a labeled
+        // break this includes finallys of all inner loops
+        self.emit_finallys_within_loop(finallys);
 
         // now jump out of loop
-        let lbl_break = self.lbl_break.unwrap();
         self.asm.jump(lbl_break);
     }
 
-    fn emit_stmt_continue(&mut self, _: &'ast StmtContinueType) {
+    fn emit_stmt_continue(&mut self, s: &'ast StmtContinueType) {
+        let (lbl_continue, finallys) = {
+            let l = self.find_loop(s.label);
+            (l.lbl_continue, l.finallys)
+        };
+
         // emit finallys between loop and continue
-        self.emit_finallys_within_loop();
+        self.emit_finallys_within_loop(finallys);
 
         // now jump to start of loop
-        let lbl_continue = self.lbl_continue.unwrap();
         self.asm.jump(lbl_continue);
     }
 
-    fn emit_finallys_within_loop(&mut self) {
+    // `start` is the length of active_finallys when entering the loop
+    fn emit_finallys_within_loop(&mut self, start: usize) {
         let finallys_len = self.active_upper.unwrap_or(self.active_finallys.len());
-        let start = self.active_loop.unwrap_or(0);
 
         if finallys_len == 0 || start >= finallys_len {
             return;
@@ -741,6 +831,35 @@ where
         }
     }
 
+    fn emit_stmt_var_tuple(&mut self, s: &'ast StmtVarTupleType) {
+        self.emit_expr(&s.expr);
+
+        let ty = self.specialize_type(self.src.ty(s.expr.id()));
+        let cls_def_id = specialize_class_ty(self.vm, ty);
+        let cls = self.vm.class_defs.idx(cls_def_id);
+        let cls = cls.read();
+
+        // the tuple stays in REG_RESULT while its elements are copied into the variables
+        for (binding, field) in s.names.iter().zip(&cls.fields) {
+            let var = *self.src.map_vars.get(binding.id).unwrap();
+            let mode = field.ty.mode();
+            let value: ExprStore = if mode.is_float() {
+                FREG_RESULT.into()
+            } else {
+                REG_TMP1.into()
+            };
+
+            self.asm
+                .load_field(mode, value, REG_RESULT, field.offset, s.pos.line as i32);
+            self.asm.var_store(&self.jit_info, value, var);
+
+            if field.ty.reference_type() {
+                let offset = self.jit_info.offset(var);
+                self.scopes.add_var(var, offset);
+            }
+        }
+    }
+
     fn emit_stmt_throw(&mut self, s: &'ast StmtThrowType) {
         self.emit_expr(&s.expr);
         self.asm.test_if_nil_bailout(s.pos, REG_RESULT, Trap::NIL);
@@ -751,7 +870,11 @@ where
     fn emit_stmt_do(&mut self, s: &'ast StmtDoType) {
         let lbl_after = self.asm.create_label();
 
+        let saved_in_try = self.in_try;
+        self.in_try = true;
         let do_span = self.stmt_with_finally(s, &s.do_block, lbl_after);
+        self.in_try = saved_in_try;
+
         let catch_spans = self.emit_do_catch_blocks(s, do_span, lbl_after);
         let finally_start = self.emit_do_finally_block(s);
 
@@ -871,8 +994,9 @@ where
             .unwrap_or(BuiltinType::Int);
 
         let ty = self.specialize_type(ty);
+        let boxed = self.src.map_boxes.get(e.id()).is_some();
 
-        let dest: ExprStore = if ty.is_float() {
+        let dest: ExprStore = if ty.is_float() && !boxed {
             FREG_RESULT.into()
         } else {
             REG_RESULT.into()
@@ -895,7 +1019,40 @@ where
         dest
     }
 
-    fn emit_call_site(&mut self, call_site: &CallSite<'ast>, pos: Position) -> ExprStore {
+    fn emit_tail_call(&mut self, e: &'ast Expr) -> Option<TailCall> {
+        let call = match *e {
+            Expr::ExprCall(ref call) => call,
+            _ => return None,
+        };
+
+        if self.jit_info.map_inlines.get(call.id).is_some() {
+            return None;
+        }
+
+        let csite = self.jit_info.map_csites.get(call.id)?.clone();
+        let lbl_tail_call = self.lbl_tail_call.unwrap();
+
+        let mut expr_gen = ExprGen::new(
+            self.vm,
+            self.fct,
+            self.src,
+            self.ast,
+            &mut self.asm,
+            &mut self.scopes,
+            &self.jit_info,
+            self.cls_type_params,
+            self.fct_type_params,
+        );
+
+        expr_gen.emit_tail_call(&csite, call.pos, lbl_tail_call)
+    }
+
+    fn emit_call_site(
+        &mut self,
+        id: Option<NodeId>,
+        call_site: &CallSite<'ast>,
+        pos: Position,
+    ) -> ExprStore {
         let callee = self.vm.fcts.idx(call_site.callee);
         let callee = callee.read();
         let return_type = self.specialize_type(callee.return_type);
@@ -914,7 +1071,11 @@ where
             self.fct_type_params,
         );
 
-        expr_gen.emit_call_site(call_site, pos, dest);
+        if let Some(id) = id {
+            expr_gen.emit_call_site_or_inline(id, call_site, pos, dest);
+        } else {
+            expr_gen.emit_call_site(call_site, pos, dest);
+        }
 
         dest
     }
@@ -922,7 +1083,7 @@ where
     fn specialize_type(&self, ty: BuiltinType) -> BuiltinType {
         match ty {
             BuiltinType::ClassTypeParam(cls_id, id) => {
-                debug_assert!(self.fct.parent == FctParent::Class(cls_id));
+                debug_assert!(self.fct.owner_cls_id(self.vm) == Some(cls_id));
                 self.cls_type_params[id.idx()]
             }
 
@@ -941,6 +1102,26 @@ where
                 BuiltinType::Class(cls_id, list_id)
             }
 
+            BuiltinType::Trait(trait_id, list_id) => {
+                let params = self.vm.lists.lock().get(list_id);
+
+                let params: Vec<_> = params.iter().map(|t| self.specialize_type(t)).collect();
+
+                let list_id = self.vm.lists.lock().insert(params.into());
+
+                BuiltinType::Trait(trait_id, list_id)
+            }
+
+            BuiltinType::Tuple(list_id) => {
+                let elements = self.vm.lists.lock().get(list_id);
+
+                let elements: Vec<_> = elements.iter().map(|t| self.specialize_type(t)).collect();
+
+                let list_id = self.vm.lists.lock().insert(elements.into());
+
+                BuiltinType::Tuple(list_id)
+            }
+
             BuiltinType::Lambda(_) => unimplemented!(),
 
             _ => ty,
@@ -969,6 +1150,7 @@ impl<'a, 'ast> visit::Visitor<'ast> for CodeGen<'a, 'ast> {
             StmtContinue(ref stmt) => self.emit_stmt_continue(stmt),
             StmtBlock(ref stmt) => self.emit_stmt_block(stmt),
             StmtVar(ref stmt) => self.emit_stmt_var(stmt),
+            StmtVarTuple(ref stmt) => self.emit_stmt_var_tuple(stmt),
             StmtThrow(ref stmt) => self.emit_stmt_throw(stmt),
             StmtDefer(_) => unimplemented!(),
             StmtDo(ref stmt) => self.emit_stmt_do(stmt),
@@ -997,6 +1179,17 @@ pub enum CondCode {
     UnsignedLessEq,
 }
 
+struct LoopLabels {
+    label: Option<Name>,
+    lbl_break: Label,
+    lbl_continue: Label,
+
+    // length of active_finallys when entering the loop
+    // break/continue need to emit finally blocks up to this loop
+    // see tests/finally/break-while.dora
+    finallys: usize,
+}
+
 pub struct Scopes {
     scopes: Vec<Scope>,
 }
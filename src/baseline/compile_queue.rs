@@ -0,0 +1,190 @@
+use parking_lot::{Condvar, Mutex};
+use std::cell::Cell;
+use std::collections::HashMap;
+use threadpool::ThreadPool;
+
+use crate::baseline;
+use crate::baseline::fct::{BailoutInfo, JitBaselineFct};
+use crate::class::TypeParams;
+use crate::ctxt::{get_vm, FctId, FctKind, VM};
+
+thread_local! {
+    static COMPILER_THREAD: Cell<bool> = Cell::new(false);
+}
+
+// Compiles functions on background threads before they are called for the
+// first time. Whenever a function gets compiled on a mutator thread, all of
+// its callees that aren't compiled yet are queued. Background compilations
+// don't queue their callees themselves, otherwise the whole program would be
+// compiled eagerly.
+//
+// Callers still go through the compile stub, the stub finds the already
+// compiled function and only needs to patch the call site. When the stub
+// needs a function that is still queued, it takes the job over and compiles
+// the function itself, a job that is already running is waited for.
+pub struct CompileQueue {
+    pool: Option<Mutex<ThreadPool>>,
+    jobs: Mutex<HashMap<JobKey, JobState>>,
+    finished: Condvar,
+}
+
+type JobKey = (FctId, TypeParams, TypeParams);
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum JobState {
+    Queued,
+    Running,
+}
+
+impl CompileQueue {
+    pub fn new(threads: usize) -> CompileQueue {
+        let pool = if threads > 0 {
+            let pool = ThreadPool::with_name("compiler".into(), threads);
+            Some(Mutex::new(pool))
+        } else {
+            None
+        };
+
+        CompileQueue {
+            pool: pool,
+            jobs: Mutex::new(HashMap::new()),
+            finished: Condvar::new(),
+        }
+    }
+
+    pub fn enqueue_callees(&self, vm: &VM, jit_fct: &JitBaselineFct) {
+        let pool = match self.pool {
+            Some(ref pool) => pool,
+            None => return,
+        };
+
+        if is_compiler_thread() {
+            return;
+        }
+
+        for bailout in jit_fct.bailouts.iter() {
+            let (fct_id, cls_type_params, fct_type_params) = match *bailout {
+                BailoutInfo::Compile(fct_id, _, ref cls_tps, ref fct_tps) => {
+                    (fct_id, cls_tps.clone(), fct_tps.clone())
+                }
+
+                BailoutInfo::VirtCompile(..) | BailoutInfo::TraitCompile(..) => continue,
+            };
+
+            if is_compiled(vm, fct_id, &cls_type_params, &fct_type_params) {
+                continue;
+            }
+
+            let key = (fct_id, cls_type_params, fct_type_params);
+
+            {
+                let mut jobs = self.jobs.lock();
+
+                if jobs.contains_key(&key) {
+                    continue;
+                }
+
+                jobs.insert(key.clone(), JobState::Queued);
+            }
+
+            pool.lock().execute(move || {
+                COMPILER_THREAD.with(|compiler_thread| compiler_thread.set(true));
+
+                let vm = get_vm();
+
+                // the compile stub took the job over
+                if !vm.compile_queue.start(&key) {
+                    return;
+                }
+
+                baseline::generate(vm, key.0, &key.1, &key.2);
+                vm.compile_queue.finish(&key);
+            });
+        }
+    }
+
+    // called by the compile stub before it compiles a function: removes the
+    // function from the queue or waits until its compilation is finished
+    pub fn steal_or_wait(
+        &self,
+        fct_id: FctId,
+        cls_type_params: &TypeParams,
+        fct_type_params: &TypeParams,
+    ) {
+        if self.pool.is_none() {
+            return;
+        }
+
+        let key = (fct_id, cls_type_params.clone(), fct_type_params.clone());
+        let mut jobs = self.jobs.lock();
+
+        loop {
+            match jobs.get(&key) {
+                Some(JobState::Queued) => {
+                    jobs.remove(&key);
+                    return;
+                }
+
+                Some(JobState::Running) => self.finished.wait(&mut jobs),
+
+                None => return,
+            }
+        }
+    }
+
+    fn start(&self, key: &JobKey) -> bool {
+        let mut jobs = self.jobs.lock();
+
+        match jobs.get_mut(key) {
+            Some(state) => {
+                *state = JobState::Running;
+                true
+            }
+
+            None => false,
+        }
+    }
+
+    fn finish(&self, key: &JobKey) {
+        self.jobs.lock().remove(key);
+        self.finished.notify_all();
+    }
+
+    // waits until all queued compilations are finished
+    pub fn join(&self) {
+        if let Some(ref pool) = self.pool {
+            pool.lock().join();
+        }
+    }
+}
+
+pub fn is_compiler_thread() -> bool {
+    COMPILER_THREAD.with(|compiler_thread| compiler_thread.get())
+}
+
+fn is_compiled(
+    vm: &VM,
+    fct_id: FctId,
+    cls_type_params: &TypeParams,
+    fct_type_params: &TypeParams,
+) -> bool {
+    let fct = vm.fcts.idx(fct_id);
+    let fct = fct.read();
+
+    let src = match fct.kind {
+        FctKind::Source(ref src) => src,
+        _ => return true,
+    };
+
+    // someone else holds the lock, the function isn't known to be compiled:
+    // the job finds the compiled function once the lock is released
+    let src = match src.try_read() {
+        Some(src) => src,
+        None => return false,
+    };
+
+    let specials = src.specializations.read();
+    let key = (cls_type_params.clone(), fct_type_params.clone());
+
+    specials.contains_key(&key)
+}
@@ -1,16 +1,19 @@
 use std::mem::size_of;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::baseline;
 use crate::baseline::dora_native::{finish_native_call, start_native_call};
+use crate::baseline::dora_unbox;
 use crate::baseline::fct::{BailoutInfo, JitBaselineFct, JitDescriptor, JitFct};
 use crate::baseline::map::CodeDescriptor;
-use crate::class::TypeParams;
+use crate::baseline::unload;
+use crate::class::{ClassDefId, TypeParams};
 use crate::cpu::{Mem, FREG_PARAMS, REG_FP, REG_PARAMS, REG_RESULT, REG_SP, REG_THREAD, REG_TMP1};
 use crate::ctxt::FctId;
 use crate::ctxt::{get_vm, VM};
 use crate::exception::DoraToNativeInfo;
 use crate::gc::Address;
-use crate::masm::MacroAssembler;
+use crate::masm::{MacroAssembler, INLINE_CACHE_ENTRIES};
 use crate::mem;
 use crate::object::Obj;
 use crate::ty::MachineMode;
@@ -175,6 +178,11 @@ where
 
 fn compile_request(ra: usize, receiver: Address) -> Address {
     let vm = get_vm();
+    unload::reclaim_if_needed(vm);
+
+    // the call site is patched with the address of the compiled function,
+    // no code may be unloaded until the call site is updated
+    let _compilation = vm.compilation_lock.read_recursive();
 
     let bailout = {
         let data = {
@@ -203,9 +211,182 @@ fn compile_request(ra: usize, receiver: Address) -> Address {
             patch_fct_call(vm, ra, fct_id, cls_tps, fct_tps, disp)
         }
 
-        BailoutInfo::VirtCompile(vtable_index, ref fct_tps) => {
-            patch_vtable_call(vm, receiver, vtable_index, fct_tps)
+        BailoutInfo::VirtCompile(vtable_index, ref fct_tps, disp) => {
+            patch_inline_cache(vm, ra, receiver, vtable_index, fct_tps, disp)
         }
+
+        BailoutInfo::TraitCompile(fct_id, disp) => patch_trait_call(vm, ra, receiver, fct_id, disp),
+    }
+}
+
+fn patch_inline_cache(
+    vm: &VM,
+    ra: usize,
+    receiver: Address,
+    vtable_index: u32,
+    fct_tps: &TypeParams,
+    disp: i32,
+) -> Address {
+    let obj = unsafe { &mut *receiver.to_mut_ptr::<Obj>() };
+    let vtable = obj.header().vtbl();
+    let mut fct_ptr: Address = vtable.table()[vtable_index as usize].into();
+
+    if fct_ptr == vm.compiler_thunk() {
+        fct_ptr = patch_vtable_call(vm, receiver, vtable_index, fct_tps);
+    }
+
+    let cache = InlineCache::new((ra as isize - disp as isize) as usize);
+
+    if !cache.is_megamorphic() {
+        cache.insert(obj.header().vtblptr(), fct_ptr);
+    }
+
+    fct_ptr
+}
+
+fn patch_trait_call(
+    vm: &VM,
+    ra: usize,
+    receiver: Address,
+    trait_fct_id: FctId,
+    disp: i32,
+) -> Address {
+    let obj = unsafe { &mut *receiver.to_mut_ptr::<Obj>() };
+    let cls_def_id = obj.header().vtbl().class().id;
+    let fct_ptr = itable_entry(vm, cls_def_id, trait_fct_id);
+
+    // trait calls invoke the compiler thunk on every cache miss,
+    // so the call site doesn't care whether the cache is full
+    let cache = InlineCache::new((ra as isize - disp as isize) as usize);
+    cache.insert(obj.header().vtblptr(), fct_ptr);
+
+    fct_ptr
+}
+
+// Returns the implementation of a trait method for a class from its
+// interface table. The method is compiled on its first invocation,
+// methods of primitive types are invoked through an unbox thunk.
+fn itable_entry(vm: &VM, cls_def_id: ClassDefId, trait_fct_id: FctId) -> Address {
+    let (trait_id, idx) = {
+        let fct = vm.fcts.idx(trait_fct_id);
+        let fct = fct.read();
+        let trait_id = fct.trait_id();
+
+        let xtrait = vm.traits[trait_id].read();
+        let idx = xtrait
+            .methods
+            .iter()
+            .position(|&method| method == trait_fct_id)
+            .expect("method not found in trait");
+
+        (trait_id, idx)
+    };
+
+    let cls_def = vm.class_defs.idx(cls_def_id);
+
+    let (cls_id, impl_id, type_params) = {
+        let cls_def = cls_def.read();
+        let itable = cls_def
+            .itables
+            .iter()
+            .find(|itable| itable.trait_id == trait_id)
+            .expect("no interface table for trait");
+
+        if itable.entries[idx] != 0 {
+            return itable.entries[idx].into();
+        }
+
+        let cls_id = cls_def.cls_id.expect("no corresponding class");
+        (cls_id, itable.impl_id, itable.type_params.clone())
+    };
+
+    let impl_fct_id = {
+        let ximpl = vm.impls[impl_id].read();
+        ximpl
+            .find_implements(vm, trait_fct_id)
+            .expect("no impl found for trait method")
+    };
+
+    let mut fct_ptr = baseline::generate(vm, impl_fct_id, &type_params, &TypeParams::empty());
+
+    let cls_ty = vm.classes.idx(cls_id).read().ty;
+
+    if !cls_ty.is_cls() {
+        fct_ptr = dora_unbox::generate(vm, impl_fct_id, cls_ty, fct_ptr);
+    }
+
+    // another thread might have filled the entry in the meantime
+    let mut cls_def = cls_def.write();
+    let itable = cls_def
+        .itables
+        .iter_mut()
+        .find(|itable| itable.trait_id == trait_id)
+        .unwrap();
+
+    if itable.entries[idx] == 0 {
+        itable.entries[idx] = fct_ptr.to_usize();
+    }
+
+    itable.entries[idx].into()
+}
+
+// Constant pool entries of an inline cache at a virtual call site,
+// reserved by `MacroAssembler::add_inline_cache`. The constant pool is
+// located before the code, so entries are stored at decreasing addresses.
+struct InlineCache {
+    start: usize,
+}
+
+// marks an entry as reserved, no vtable is stored at this address
+const INLINE_CACHE_RESERVED: usize = 1;
+
+impl InlineCache {
+    fn new(start: usize) -> InlineCache {
+        InlineCache { start: start }
+    }
+
+    fn entry(&self, idx: usize) -> &AtomicUsize {
+        let addr = self.start - idx * mem::ptr_width_usize();
+        unsafe { &*(addr as *const AtomicUsize) }
+    }
+
+    fn vtable(&self, idx: usize) -> &AtomicUsize {
+        self.entry(2 * idx)
+    }
+
+    fn target(&self, idx: usize) -> &AtomicUsize {
+        self.entry(2 * idx + 1)
+    }
+
+    fn miss_target(&self) -> &AtomicUsize {
+        self.entry(2 * INLINE_CACHE_ENTRIES)
+    }
+
+    fn is_megamorphic(&self) -> bool {
+        self.miss_target().load(Ordering::Relaxed) == 0
+    }
+
+    fn insert(&self, vtblptr: Address, fct_ptr: Address) {
+        for idx in 0..INLINE_CACHE_ENTRIES {
+            // reserve empty entry first, the call site only compares vtables
+            // so the target needs to be stored before the vtable
+            let reserved = self.vtable(idx).compare_exchange(
+                0,
+                INLINE_CACHE_RESERVED,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            );
+
+            if reserved.is_ok() {
+                self.target(idx).store(fct_ptr.to_usize(), Ordering::SeqCst);
+                self.vtable(idx).store(vtblptr.to_usize(), Ordering::SeqCst);
+                return;
+            }
+        }
+
+        // all entries are in use, from now on the call site
+        // loads the target from the vtable
+        self.miss_target().store(0, Ordering::SeqCst);
     }
 }
 
@@ -234,8 +415,12 @@ fn patch_vtable_call(
         }
     }
 
+    // other threads might load the entry concurrently
     let methodtable = vtable.table_mut();
-    methodtable[vtable_index as usize] = fct_ptr.to_usize();
+    let entry = &methodtable[vtable_index as usize] as *const usize as *const AtomicUsize;
+    unsafe {
+        (*entry).store(fct_ptr.to_usize(), Ordering::SeqCst);
+    }
 
     fct_ptr
 }
@@ -248,12 +433,14 @@ fn patch_fct_call(
     fct_tps: &TypeParams,
     disp: i32,
 ) -> Address {
+    vm.compile_queue.steal_or_wait(fct_id, cls_tps, fct_tps);
     let fct_ptr = baseline::generate(vm, fct_id, cls_tps, fct_tps);
-    let fct_addr: *mut usize = (ra as isize - disp as isize) as *mut _;
+    let fct_addr: *const AtomicUsize = (ra as isize - disp as isize) as *const _;
 
-    // update function pointer in data segment
+    // update function pointer in data segment, other threads
+    // might execute the same call site concurrently
     unsafe {
-        *fct_addr = fct_ptr.to_usize();
+        (*fct_addr).store(fct_ptr.to_usize(), Ordering::SeqCst);
     }
 
     fct_ptr
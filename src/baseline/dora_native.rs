@@ -7,11 +7,12 @@ use crate::baseline::fct::{JitBaselineFct, JitDescriptor, JitFct, JitFctId};
 use crate::baseline::map::CodeDescriptor;
 use crate::cpu::{Mem, FREG_PARAMS, REG_FP, REG_PARAMS, REG_RESULT, REG_SP, REG_THREAD};
 use crate::ctxt::VM;
-use crate::ctxt::{exception_get_and_clear, FctId};
+use crate::ctxt::{exception_get_and_clear, get_vm, FctId};
 use crate::exception::DoraToNativeInfo;
 use crate::gc::Address;
 use crate::masm::MacroAssembler;
 use crate::mem;
+use crate::safepoint;
 use crate::threads::THREAD;
 use crate::ty::{BuiltinType, MachineMode};
 
@@ -33,6 +34,10 @@ impl NativeThunks {
     pub fn insert_fct(&mut self, ptr: Address, fct: JitFctId) {
         self.map.entry(ptr).or_insert(fct);
     }
+
+    pub fn remove_fct(&mut self, fct: JitFctId) {
+        self.map.retain(|_, &mut jit_fct_id| jit_fct_id != fct);
+    }
 }
 
 #[derive(Clone)]
@@ -41,6 +46,7 @@ pub enum InternalFctDescriptor {
     AllocThunk,
     VerifyThunk,
     TrapThunk,
+    GlobalInitThunk,
 }
 
 pub struct InternalFct<'a> {
@@ -71,6 +77,7 @@ pub fn generate<'a, 'ast: 'a>(vm: &'a VM<'ast>, fct: InternalFct, dbg: bool) ->
         InternalFctDescriptor::TrapThunk => CodeDescriptor::TrapThunk,
         InternalFctDescriptor::VerifyThunk => CodeDescriptor::VerifyThunk,
         InternalFctDescriptor::AllocThunk => CodeDescriptor::AllocThunk,
+        InternalFctDescriptor::GlobalInitThunk => CodeDescriptor::GlobalInitThunk,
     };
 
     vm.insert_code_map(jit_start, jit_end, code_desc);
@@ -167,6 +174,7 @@ where
             InternalFctDescriptor::AllocThunk => JitDescriptor::AllocThunk,
             InternalFctDescriptor::VerifyThunk => JitDescriptor::VerifyThunk,
             InternalFctDescriptor::TrapThunk => JitDescriptor::TrapThunk,
+            InternalFctDescriptor::GlobalInitThunk => JitDescriptor::GlobalInitThunk,
         };
 
         self.masm.jit(self.vm, framesize, desc, self.fct.throws)
@@ -240,10 +248,15 @@ pub fn start_native_call(fp: *const u8, pc: usize) {
             thread.borrow().push_dtn(dtn);
             thread.borrow().handles.push_border();
         });
+
+        safepoint::leave_dora();
     }
 }
 
 pub fn finish_native_call() -> *const u8 {
+    // wait for a stop-the-world pause while the stack is still walkable
+    safepoint::enter_dora(get_vm());
+
     THREAD.with(|thread| {
         thread.borrow().handles.pop_border();
         thread.borrow().pop_dtn();
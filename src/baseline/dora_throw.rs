@@ -3,7 +3,7 @@ use std::mem::size_of;
 use crate::baseline::dora_native::{finish_native_call, start_native_call};
 use crate::baseline::fct::{JitBaselineFct, JitDescriptor, JitFct};
 use crate::baseline::map::CodeDescriptor;
-use crate::cpu::{Mem, REG_FP, REG_PARAMS, REG_SP, REG_THREAD, REG_TMP1, REG_TMP2};
+use crate::cpu::{Mem, REG_FP, REG_PARAMS, REG_SP, REG_THREAD, REG_TMP1, REG_TMP2, REG_VARS};
 use crate::ctxt::VM;
 use crate::exception::throw;
 use crate::exception::DoraToNativeInfo;
@@ -43,7 +43,8 @@ where
     'ast: 'a,
 {
     pub fn generate(mut self) -> JitBaselineFct {
-        let framesize = size_of::<DoraToNativeInfo>() as i32 + 5 * mem::ptr_width();
+        let framesize =
+            size_of::<DoraToNativeInfo>() as i32 + (5 + REG_VARS.len() as i32) * mem::ptr_width();
         let framesize = mem::align_i32(framesize, 16);
 
         let offset_receiver = 0;
@@ -52,6 +53,7 @@ where
         let offset_result_pc = offset_result;
         let offset_result_sp = offset_result_pc + mem::ptr_width();
         let offset_result_fp = offset_result_sp + mem::ptr_width();
+        let offset_result_regs = offset_result_fp + mem::ptr_width();
 
         if self.dbg {
             self.masm.debug();
@@ -71,6 +73,12 @@ where
             REG_PARAMS[0].into(),
         );
 
+        for (idx, &reg) in REG_VARS.iter().enumerate() {
+            let offset = offset_result_regs + idx as i32 * mem::ptr_width();
+            self.masm
+                .store_mem(MachineMode::Ptr, Mem::Base(REG_SP, offset), reg.into());
+        }
+
         self.masm.copy_reg(MachineMode::Ptr, REG_PARAMS[0], REG_FP);
         self.masm.copy_pc(REG_PARAMS[1]);
         self.masm.raw_call(start_native_call as *const u8);
@@ -97,6 +105,12 @@ where
             Mem::Base(REG_SP, offset_thread),
         );
 
+        for (idx, &reg) in REG_VARS.iter().enumerate() {
+            let offset = offset_result_regs + idx as i32 * mem::ptr_width();
+            self.masm
+                .load_mem(MachineMode::Ptr, reg.into(), Mem::Base(REG_SP, offset));
+        }
+
         self.masm.load_mem(
             MachineMode::Ptr,
             REG_TMP1.into(),
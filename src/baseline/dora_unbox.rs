@@ -0,0 +1,100 @@
+use crate::baseline::fct::{JitBaselineFct, JitDescriptor, JitFct, JitFctId};
+use crate::baseline::map::CodeDescriptor;
+use crate::cpu::{Mem, FREG_PARAMS, REG_PARAMS, REG_TMP1};
+use crate::ctxt::{FctId, VM};
+use crate::gc::Address;
+use crate::masm::MacroAssembler;
+use crate::object::Header;
+use crate::ty::{BuiltinType, MachineMode};
+
+// Generates the unbox thunk for a method implemented by a primitive type.
+// Trait objects of primitive values are boxes, the method itself expects
+// the plain value as receiver though. The thunk loads the value out of the
+// box into the parameter register of the receiver and jumps to the method.
+// Interface tables store the address of this thunk instead of the method,
+// the thunk is registered as native thunk so the unloader can reclaim it.
+
+pub fn generate<'a, 'ast: 'a>(
+    vm: &'a VM<'ast>,
+    fct_id: FctId,
+    ty: BuiltinType,
+    target: Address,
+) -> Address {
+    let ngen = DoraUnboxGen {
+        vm: vm,
+        masm: MacroAssembler::new(),
+        fct_id: fct_id,
+        ty: ty,
+        target: target,
+    };
+
+    let jit_fct = ngen.generate();
+    let addr = jit_fct.fct_ptr();
+    let jit_start = jit_fct.ptr_start();
+    let jit_end = jit_fct.ptr_end();
+    let jit_fct_id: JitFctId = vm.jit_fcts.push(JitFct::Base(jit_fct)).into();
+
+    vm.insert_code_map(jit_start, jit_end, CodeDescriptor::NativeThunk(jit_fct_id));
+
+    addr
+}
+
+struct DoraUnboxGen<'a, 'ast: 'a> {
+    vm: &'a VM<'ast>,
+    masm: MacroAssembler,
+    fct_id: FctId,
+    ty: BuiltinType,
+    target: Address,
+}
+
+impl<'a, 'ast> DoraUnboxGen<'a, 'ast>
+where
+    'ast: 'a,
+{
+    pub fn generate(mut self) -> JitBaselineFct {
+        let mode = self.ty.mode();
+        let value = Mem::Base(REG_PARAMS[0], Header::size());
+
+        if mode.is_float() {
+            self.unbox_float(mode, value);
+        } else {
+            self.masm.load_mem(mode, REG_PARAMS[0].into(), value);
+        }
+
+        // jump to the method, the thunk doesn't need a stack frame
+        let disp = self.masm.add_addr(self.target.to_ptr());
+        let pos = self.masm.pos() as i32;
+        self.masm.load_constpool(REG_TMP1, disp + pos);
+        self.masm.jump_reg(REG_TMP1);
+
+        self.masm
+            .jit(self.vm, 0, JitDescriptor::NativeThunk(self.fct_id), false)
+    }
+
+    // the method expects its receiver in the first float register: all float
+    // arguments move up by one register, all other arguments move down by one
+    fn unbox_float(&mut self, mode: MachineMode, value: Mem) {
+        let fct = self.vm.fcts.idx(self.fct_id);
+        let fct = fct.read();
+
+        let params = fct.params_without_self();
+        let fregs = params.iter().filter(|ty| ty.is_float()).count();
+        let regs = params.len() - fregs;
+
+        if fregs >= FREG_PARAMS.len() || regs >= REG_PARAMS.len() {
+            unreachable!("semck rejects boxing when arguments are passed on the stack");
+        }
+
+        for idx in (0..fregs).rev() {
+            self.masm
+                .copy_freg(MachineMode::Float64, FREG_PARAMS[idx + 1], FREG_PARAMS[idx]);
+        }
+
+        self.masm.load_mem(mode, FREG_PARAMS[0].into(), value);
+
+        for idx in 0..regs {
+            self.masm
+                .copy_reg(MachineMode::Ptr, REG_PARAMS[idx], REG_PARAMS[idx + 1]);
+        }
+    }
+}
@@ -5,19 +5,22 @@ use crate::baseline::codegen::{
 };
 use crate::baseline::dora_native::{self, InternalFct, InternalFctDescriptor};
 use crate::baseline::fct::{CatchType, Comment, GcPoint};
-use crate::baseline::info::JitInfo;
-use crate::class::{ClassDefId, ClassSize, FieldId, TypeParams};
+use crate::baseline::info::{InlineSite, JitInfo};
+use crate::class::{ClassDefId, ClassId, ClassSize, FieldId, TypeParams};
 use crate::cpu::{
-    FReg, Mem, Reg, FREG_PARAMS, FREG_RESULT, FREG_TMP1, REG_PARAMS, REG_RESULT, REG_TMP1, REG_TMP2,
+    args_in_registers, FReg, Mem, Reg, FREG_PARAMS, FREG_RESULT, FREG_TMP1, REG_PARAMS, REG_RESULT,
+    REG_TMP1, REG_TMP2,
 };
 use crate::ctxt::VM;
 use crate::ctxt::*;
 use crate::driver::cmd::AsmSyntax;
 use crate::gc::Address;
+use crate::masm::Label;
 use crate::mem;
 use crate::object::{Header, Str};
 use crate::os::signal::Trap;
 use crate::semck::specialize::{specialize_class_id, specialize_class_ty};
+use crate::stdlib;
 use crate::ty::{BuiltinType, MachineMode};
 use crate::vtable::{VTable, DISPLAY_SIZE};
 use dora_parser::ast::Expr::*;
@@ -61,6 +64,16 @@ impl ExprStore {
     }
 }
 
+// kind of tail call emitted by `ExprGen::emit_tail_call`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TailCall {
+    // jumps to the beginning of the current function
+    SelfCall,
+
+    // jumps to another function, calls it when not yet compiled
+    Sibling,
+}
+
 impl From<Reg> for ExprStore {
     fn from(reg: Reg) -> ExprStore {
         ExprStore::Reg(reg)
@@ -89,6 +102,7 @@ where
     jit_info: &'a JitInfo<'ast>,
     cls_type_params: &'a TypeParams,
     fct_type_params: &'a TypeParams,
+    inlined: bool,
 }
 
 impl<'a, 'b, 'ast> ExprGen<'a, 'b, 'ast>
@@ -119,6 +133,7 @@ where
             jit_info: jit_info,
             cls_type_params: cls_type_params,
             fct_type_params: fct_type_params,
+            inlined: false,
         }
     }
 
@@ -131,6 +146,14 @@ where
     }
 
     fn emit_expr(&mut self, e: &'ast Expr, dest: ExprStore) {
+        if let Some(&cls_id) = self.src.map_boxes.get(e.id()) {
+            self.emit_box(e, cls_id, dest.reg());
+        } else {
+            self.emit_expr_value(e, dest);
+        }
+    }
+
+    fn emit_expr_value(&mut self, e: &'ast Expr, dest: ExprStore) {
         match *e {
             ExprLitChar(ref expr) => self.emit_lit_char(expr, dest.reg()),
             ExprLitInt(ref expr) => self.emit_lit_int(expr, dest.reg()),
@@ -145,7 +168,7 @@ where
             ExprCall(ref expr) => self.emit_call(expr, dest),
             ExprCall2(_) => unimplemented!(),
             ExprTypeParam(_) => unimplemented!(),
-            ExprPath(_) => unimplemented!(),
+            ExprPath(ref expr) => self.emit_path(expr, dest),
             ExprDelegation(ref expr) => self.emit_delegation(expr, dest),
             ExprField(ref expr) => self.emit_field(expr, dest),
             ExprSelf(_) => self.emit_self(dest.reg()),
@@ -155,9 +178,163 @@ where
             ExprConv(ref expr) => self.emit_conv(expr, dest.reg()),
             ExprTry(ref expr) => self.emit_try(expr, dest),
             ExprLambda(_) => unimplemented!(),
+            ExprTuple(ref expr) => self.emit_tuple(expr, dest.reg()),
+            ExprTemplate(ref expr) => self.emit_template(expr, dest),
+        }
+    }
+
+    // wraps a primitive value into an object of its class, such
+    // that it can be used as trait object
+    fn emit_box(&mut self, e: &'ast Expr, cls_id: ClassId, dest: Reg) {
+        let ty = self.ty(e.id());
+        let mode = ty.mode();
+        let value = result_reg(mode);
+        self.emit_expr_value(e, value);
+
+        let offset = *self.jit_info.map_boxes.get(e.id()).unwrap();
+        let offset = -(self.jit_info.localsize + offset);
+        self.asm.store_mem(mode, Mem::Local(offset), value);
+
+        let cls_def_id = specialize_class_id(self.vm, cls_id);
+        let cls = self.vm.class_defs.idx(cls_def_id);
+        let cls = cls.read();
+
+        let size = match cls.size {
+            ClassSize::Fixed(size) => size,
+            _ => unreachable!(),
+        };
+
+        self.asm.emit_comment(Comment::Alloc(cls_def_id));
+        self.asm
+            .load_int_const(MachineMode::Int32, REG_PARAMS[0], size as i64);
+
+        let gcpoint = self.create_gcpoint();
+        self.asm.allocate(
+            dest,
+            AllocationSize::Fixed(size as usize),
+            e.pos(),
+            false,
+            gcpoint,
+        );
+
+        // store classptr in object
+        let cptr = (&**cls.vtable.as_ref().unwrap()) as *const VTable as *const u8;
+        let disp = self.asm.add_addr(cptr);
+        let pos = self.asm.pos() as i32;
+
+        let temp = if dest == REG_TMP1 { REG_TMP2 } else { REG_TMP1 };
+
+        self.asm.emit_comment(Comment::StoreVTable(cls_def_id));
+        self.asm.load_constpool(temp, disp + pos);
+        self.asm
+            .store_mem(MachineMode::Ptr, Mem::Base(dest, 0), temp.into());
+
+        // clear mark/fwdptr word in header
+        assert!(Header::size() == 2 * mem::ptr_width());
+        self.asm.load_int_const(MachineMode::Ptr, temp, 0);
+        self.asm.store_mem(
+            MachineMode::Ptr,
+            Mem::Base(dest, mem::ptr_width()),
+            temp.into(),
+        );
+
+        self.asm.fill_zero(dest, size as usize);
+
+        // store value in object
+        let value: ExprStore = if mode.is_float() {
+            FREG_TMP1.into()
+        } else {
+            temp.into()
+        };
+
+        self.asm.load_mem(mode, value, Mem::Local(offset));
+        self.asm
+            .store_mem(mode, Mem::Base(dest, cls.fields[0].offset), value);
+    }
+
+    fn emit_tuple(&mut self, e: &'ast ExprTupleType, dest: Reg) {
+        let mut offsets = Vec::with_capacity(e.values.len());
+
+        // evaluate all elements before allocating the tuple
+        for value in &e.values {
+            let mode = self.ty(value.id()).mode();
+            let reg = result_reg(mode);
+
+            self.emit_expr(value, reg);
+            let offset = self.reserve_temp_for_node(value);
+            self.asm.store_mem(mode, Mem::Local(offset), reg);
+
+            offsets.push(offset);
+        }
+
+        let ty = self.ty(e.id);
+        let cls_def_id = specialize_class_ty(self.vm, ty);
+
+        let store = self.jit_info.get_store(e.id);
+        let tuple_offset = -(self.jit_info.localsize + store.offset());
+        self.emit_allocation(e.pos, &[], cls_def_id, tuple_offset, dest);
+
+        // zero filling might have modified `dest`
+        self.asm
+            .load_mem(MachineMode::Ptr, dest.into(), Mem::Local(tuple_offset));
+
+        let cls = self.vm.class_defs.idx(cls_def_id);
+        let cls = cls.read();
+        let temp = if dest == REG_TMP1 { REG_TMP2 } else { REG_TMP1 };
+
+        // copy elements into tuple
+        for ((value, field), &offset) in e.values.iter().zip(&cls.fields).zip(&offsets) {
+            let mode = field.ty.mode();
+            let value_reg: ExprStore = if mode.is_float() {
+                FREG_TMP1.into()
+            } else {
+                temp.into()
+            };
+
+            self.asm.load_mem(mode, value_reg, Mem::Local(offset));
+            self.asm
+                .store_mem(mode, Mem::Base(dest, field.offset), value_reg);
+
+            self.free_temp_for_node(value, offset);
         }
     }
 
+    fn emit_template(&mut self, e: &'ast ExprTemplateType, dest: ExprStore) {
+        let info = self.jit_info.map_templates.get(e.id).unwrap().clone();
+
+        // buf = StringBuf::empty()
+        self.emit_call_site(&info.empty, e.pos, REG_RESULT.into());
+        self.asm.store_mem(
+            MachineMode::Ptr,
+            Mem::Local(info.buf_offset),
+            REG_RESULT.into(),
+        );
+        self.temps.insert(info.buf_offset);
+
+        for (part, to_string) in e.parts.iter().zip(&info.parts) {
+            // value = part.toString() or the string itself
+            match *to_string {
+                Some(ref csite) => self.emit_call_site(csite, part.pos(), REG_RESULT.into()),
+                None => self.emit_expr(part, REG_RESULT.into()),
+            }
+
+            self.asm.store_mem(
+                MachineMode::Ptr,
+                Mem::Local(info.part_offset),
+                REG_RESULT.into(),
+            );
+            self.temps.insert(info.part_offset);
+
+            // buf.appendString(value)
+            self.emit_call_site(&info.append_string, part.pos(), REG_RESULT.into());
+            self.temps.remove(info.part_offset);
+        }
+
+        // result = buf.toString()
+        self.emit_call_site(&info.to_string, e.pos, dest);
+        self.temps.remove(info.buf_offset);
+    }
+
     fn emit_try(&mut self, e: &'ast ExprTryType, dest: ExprStore) {
         match e.mode {
             TryMode::Normal => {
@@ -365,11 +542,11 @@ where
             match intrinsic {
                 Intrinsic::GenericArrayGet => {
                     let ty = self.ty(e.id);
-                    self.emit_array_get(e.pos, ty.mode(), &e.object, &e.index, dest);
+                    self.emit_array_get(e.id, e.pos, ty.mode(), &e.object, &e.index, dest);
                 }
 
                 Intrinsic::StrGet => {
-                    self.emit_array_get(e.pos, MachineMode::Int8, &e.object, &e.index, dest)
+                    self.emit_array_get(e.id, e.pos, MachineMode::Int8, &e.object, &e.index, dest)
                 }
 
                 _ => panic!("unexpected intrinsic {:?}", intrinsic),
@@ -380,9 +557,9 @@ where
     }
 
     fn reserve_temp_for_node(&mut self, expr: &Expr) -> i32 {
-        let id = expr.id();
-        let ty = self.ty(id);
-        let offset = -(self.jit_info.localsize + self.jit_info.get_store(id).offset());
+        let store = self.jit_info.get_store(expr.id());
+        let ty = store.ty();
+        let offset = -(self.jit_info.localsize + store.offset());
 
         if ty.reference_type() {
             self.temps.insert(offset);
@@ -409,7 +586,7 @@ where
     }
 
     fn free_temp_for_node(&mut self, expr: &Expr, offset: i32) {
-        let ty = self.ty(expr.id());
+        let ty = self.jit_info.get_store(expr.id()).ty();
 
         if ty.reference_type() {
             self.temps.remove(offset);
@@ -525,22 +702,18 @@ where
 
         match ident {
             IdentType::Var(varid) => {
-                self.asm.emit_comment(Comment::LoadVar(varid));
+                if self.inlined {
+                    self.asm
+                        .emit_comment(Comment::LoadInlinedVar(self.fct.id, varid));
+                } else {
+                    self.asm.emit_comment(Comment::LoadVar(varid));
+                }
+
                 self.asm.var_load(self.jit_info, varid, dest)
             }
 
             IdentType::Global(gid) => {
-                let glob = self.vm.globals.idx(gid);
-                let glob = glob.lock();
-
-                let disp = self.asm.add_addr(glob.address_value.to_ptr());
-                let pos = self.asm.pos() as i32;
-
-                self.asm.emit_comment(Comment::LoadGlobal(gid));
-                self.asm.load_constpool(REG_TMP1, disp + pos);
-
-                self.asm
-                    .load_mem(glob.ty.mode(), dest, Mem::Base(REG_TMP1, 0));
+                self.emit_global(gid, e.pos, dest);
             }
 
             IdentType::Field(cls, field) => {
@@ -560,6 +733,112 @@ where
         }
     }
 
+    fn emit_path(&mut self, e: &'ast ExprPathType, dest: ExprStore) {
+        let &ident = self.src.map_idents.get(e.id).unwrap();
+
+        match ident {
+            IdentType::Global(gid) => self.emit_global(gid, e.pos, dest),
+            IdentType::Const(const_id) => self.emit_const(const_id, dest),
+            _ => unreachable!(),
+        }
+    }
+
+    fn emit_global(&mut self, gid: GlobalId, pos: Position, dest: ExprStore) {
+        self.emit_global_init(gid, pos);
+
+        let glob = self.vm.globals.idx(gid);
+        let glob = glob.lock();
+
+        let disp = self.asm.add_addr(glob.address_value.to_ptr());
+        let pos = self.asm.pos() as i32;
+
+        self.asm.emit_comment(Comment::LoadGlobal(gid));
+        self.asm.load_constpool(REG_TMP1, disp + pos);
+
+        self.asm
+            .load_mem(glob.ty.mode(), dest, Mem::Base(REG_TMP1, 0));
+    }
+
+    // runs the initializer of the global on its first access, the init
+    // byte is checked inline so that later accesses stay cheap
+    fn emit_global_init(&mut self, gid: GlobalId, pos: Position) {
+        let glob = self.vm.globals.idx(gid);
+        let (getter, address_init, address_value, ty) = {
+            let glob = glob.lock();
+
+            match glob.getter {
+                Some(getter) => (getter, glob.address_init, glob.address_value, glob.ty),
+                None => return,
+            }
+        };
+
+        let lbl_initialized = self.asm.create_label();
+
+        let disp = self.asm.add_addr(address_init.to_ptr());
+        let disp_pos = self.asm.pos() as i32;
+        self.asm.load_constpool(REG_TMP1, disp + disp_pos);
+        self.asm
+            .load_mem(MachineMode::Int8, REG_TMP1.into(), Mem::Base(REG_TMP1, 0));
+        // pairs with the release store in GlobalInits::finish: the value
+        // must not be loaded before the init byte
+        self.asm.load_fence();
+        self.asm
+            .test_and_jump_if(CondCode::NonZero, REG_TMP1, lbl_initialized);
+
+        // another thread might run the initializer concurrently,
+        // global_init_start waits for it to finish
+        self.asm
+            .load_int_const(MachineMode::Int32, REG_PARAMS[0], gid.to_usize() as i64);
+        let internal_fct = InternalFct {
+            ptr: Address::from_ptr(stdlib::global_init_start as *const u8),
+            args: &[BuiltinType::Int],
+            return_type: BuiltinType::Int,
+            throws: false,
+            desc: InternalFctDescriptor::GlobalInitThunk,
+        };
+        let gcpoint = self.create_gcpoint();
+        self.asm
+            .native_call(internal_fct, pos, gcpoint, REG_RESULT.into());
+        self.asm
+            .test_and_jump_if(CondCode::Zero, REG_RESULT, lbl_initialized);
+
+        let ptr = self.ptr_for_fct_id(getter, TypeParams::empty(), TypeParams::empty());
+        self.asm.emit_comment(Comment::CallDirect(getter));
+        let gcpoint = self.create_gcpoint();
+        let dest = result_reg(ty.mode());
+        self.asm.direct_call(
+            getter,
+            ptr.to_ptr(),
+            TypeParams::empty(),
+            TypeParams::empty(),
+            pos,
+            gcpoint,
+            ty,
+            dest,
+        );
+
+        let disp = self.asm.add_addr(address_value.to_ptr());
+        let disp_pos = self.asm.pos() as i32;
+        self.asm.emit_comment(Comment::StoreGlobal(gid));
+        self.asm.load_constpool(REG_TMP1, disp + disp_pos);
+        self.asm.store_mem(ty.mode(), Mem::Base(REG_TMP1, 0), dest);
+
+        self.asm
+            .load_int_const(MachineMode::Int32, REG_PARAMS[0], gid.to_usize() as i64);
+        let internal_fct = InternalFct {
+            ptr: Address::from_ptr(stdlib::global_init_finish as *const u8),
+            args: &[BuiltinType::Int],
+            return_type: BuiltinType::Unit,
+            throws: false,
+            desc: InternalFctDescriptor::GlobalInitThunk,
+        };
+        let gcpoint = self.create_gcpoint();
+        self.asm
+            .native_call(internal_fct, pos, gcpoint, REG_RESULT.into());
+
+        self.asm.bind_label(lbl_initialized);
+    }
+
     fn emit_const(&mut self, const_id: ConstId, dest: ExprStore) {
         let xconst = self.vm.consts.idx(const_id);
         let xconst = xconst.lock();
@@ -668,6 +947,7 @@ where
                     Intrinsic::GenericArraySet => {
                         let ty = self.ty(array.id);
                         self.emit_array_set(
+                            e.id,
                             e.pos,
                             ty,
                             ty.mode(),
@@ -678,6 +958,7 @@ where
                     }
 
                     Intrinsic::StrSet => self.emit_array_set(
+                        e.id,
                         e.pos,
                         BuiltinType::Byte,
                         MachineMode::Int8,
@@ -708,6 +989,8 @@ where
             }
 
             IdentType::Global(gid) => {
+                self.emit_global_init(gid, e.pos);
+
                 let glob = self.vm.globals.idx(gid);
                 let (address_value, ty) = {
                     let glob = glob.lock();
@@ -954,9 +1237,16 @@ where
             match fct.kind {
                 FctKind::Source(_) => {
                     let src = fct.src();
-                    let mut src = src.write();
 
-                    ensure_jit_or_stub_ptr(&mut src, self.vm, cls_type_params, fct_type_params)
+                    // the callee might get compiled concurrently on another
+                    // thread, don't wait for it and go through the stub instead
+                    match src.try_read() {
+                        Some(src) => {
+                            ensure_jit_or_stub_ptr(&src, self.vm, cls_type_params, fct_type_params)
+                        }
+
+                        None => self.vm.compiler_thunk(),
+                    }
                 }
                 FctKind::External(ptr) => {
                     let external_fct = InternalFct {
@@ -997,6 +1287,7 @@ where
                 Intrinsic::SetUint8 => self.emit_set_uint8(e, dest.reg()),
                 Intrinsic::StrLen => self.emit_intrinsic_len(e, dest.reg()),
                 Intrinsic::StrGet => self.emit_array_get(
+                    e.id,
                     e.pos,
                     MachineMode::Int8,
                     e.object.as_ref().unwrap(),
@@ -1158,6 +1449,7 @@ where
 
     fn emit_array_set(
         &mut self,
+        id: NodeId,
         pos: Position,
         element_type: BuiltinType,
         mode: MachineMode,
@@ -1197,7 +1489,7 @@ where
 
         self.asm.test_if_nil_bailout(pos, REG_TMP1, Trap::NIL);
 
-        if !self.vm.args.flag_omit_bounds_check {
+        if self.needs_bounds_check(id) {
             self.asm.check_index_out_of_bounds(pos, REG_TMP1, REG_TMP2);
         }
 
@@ -1220,8 +1512,13 @@ where
         self.free_temp_for_node(rhs, offset_value);
     }
 
+    fn needs_bounds_check(&self, id: NodeId) -> bool {
+        !self.vm.args.flag_omit_bounds_check && !self.jit_info.omitted_bounds_checks.contains(&id)
+    }
+
     fn emit_array_get(
         &mut self,
+        id: NodeId,
         pos: Position,
         mode: MachineMode,
         object: &'ast Expr,
@@ -1239,7 +1536,7 @@ where
 
         self.asm.test_if_nil_bailout(pos, REG_RESULT, Trap::NIL);
 
-        if !self.vm.args.flag_omit_bounds_check {
+        if self.needs_bounds_check(id) {
             self.asm
                 .check_index_out_of_bounds(pos, REG_RESULT, REG_TMP1);
         }
@@ -1623,16 +1920,79 @@ where
 
     fn emit_call_site_id(&mut self, id: NodeId, pos: Position, dest: ExprStore) {
         let csite = self.jit_info.map_csites.get(id).unwrap().clone();
-        self.emit_call_site(&csite, pos, dest);
+        self.emit_call_site_or_inline(id, &csite, pos, dest);
     }
 
-    pub fn emit_call_site(&mut self, csite: &CallSite<'ast>, pos: Position, dest: ExprStore) {
-        let mut temps: Vec<(BuiltinType, i32, Option<ClassDefId>)> = Vec::new();
+    pub fn emit_call_site_or_inline(
+        &mut self,
+        id: NodeId,
+        csite: &CallSite<'ast>,
+        pos: Position,
+        dest: ExprStore,
+    ) {
+        let jit_info = self.jit_info;
 
+        if let Some(inline) = jit_info.map_inlines.get(id) {
+            self.emit_inline(csite, inline, pos, dest);
+        } else {
+            self.emit_call_site(csite, pos, dest);
+        }
+    }
+
+    fn emit_inline(
+        &mut self,
+        csite: &CallSite<'ast>,
+        inline: &'a InlineSite<'ast>,
+        pos: Position,
+        dest: ExprStore,
+    ) {
         let fid = csite.callee;
         let fct = self.vm.fcts.idx(fid);
         let fct = fct.read();
 
+        let temps = self.emit_call_args(csite, &*fct, pos);
+
+        // arguments are stored in temporaries, the inlined body reads the
+        // parameters directly from there. Since the inlined body does not
+        // contain any gc points, the current gc points stay valid.
+        {
+            let src = fct.src();
+            let mut src = src.write();
+
+            self.asm.emit_comment(Comment::CallInline(fid));
+            self.asm.set_inlined_lineno(Some(pos.line as i32));
+
+            let mut expr_gen = ExprGen::new(
+                self.vm,
+                &*fct,
+                &mut src,
+                fct.ast,
+                &mut *self.asm,
+                &mut *self.scopes,
+                &inline.jit_info,
+                &inline.cls_type_params,
+                &inline.fct_type_params,
+            );
+
+            expr_gen.inlined = true;
+            expr_gen.generate(inline.expr, dest);
+
+            self.asm.set_inlined_lineno(None);
+        }
+
+        for temp in temps.into_iter() {
+            self.free_temp_with_type(temp.0, temp.1);
+        }
+    }
+
+    fn emit_call_args(
+        &mut self,
+        csite: &CallSite<'ast>,
+        fct: &Fct<'ast>,
+        pos: Position,
+    ) -> Vec<(BuiltinType, i32, Option<ClassDefId>)> {
+        let mut temps: Vec<(BuiltinType, i32, Option<ClassDefId>)> = Vec::new();
+
         for (idx, arg) in csite.args.iter().enumerate() {
             let mode = arg.ty().mode();
             let dest = register_for_mode(mode);
@@ -1682,6 +2042,16 @@ where
             temps.push((arg.ty(), offset, None));
         }
 
+        temps
+    }
+
+    pub fn emit_call_site(&mut self, csite: &CallSite<'ast>, pos: Position, dest: ExprStore) {
+        let fid = csite.callee;
+        let fct = self.vm.fcts.idx(fid);
+        let fct = fct.read();
+
+        let temps = self.emit_call_args(csite, &*fct, pos);
+
         let mut arg_offset = -self.jit_info.stacksize();
         let mut idx = 0;
         let mut reg_idx = 0;
@@ -1742,25 +2112,7 @@ where
         }
 
         let return_type = self.specialize_type(csite.return_type);
-        let cls_type_params: TypeParams = csite
-            .cls_type_params
-            .iter()
-            .map(|ty| self.specialize_type(ty))
-            .collect::<Vec<_>>()
-            .into();
-        let fct_type_params: TypeParams = csite
-            .fct_type_params
-            .iter()
-            .map(|ty| self.specialize_type(ty))
-            .collect::<Vec<_>>()
-            .into();
-
-        debug_assert!(cls_type_params
-            .iter()
-            .all(|ty| !ty.contains_type_param(self.vm)));
-        debug_assert!(fct_type_params
-            .iter()
-            .all(|ty| !ty.contains_type_param(self.vm)));
+        let (cls_type_params, fct_type_params) = self.specialize_type_params(csite);
 
         if csite.super_call {
             let ptr = self.ptr_for_fct_id(fid, cls_type_params.clone(), fct_type_params.clone());
@@ -1776,6 +2128,10 @@ where
                 return_type,
                 dest,
             );
+        } else if fct.in_trait() {
+            self.asm.emit_comment(Comment::CallTrait(fid));
+            let gcpoint = self.create_gcpoint();
+            self.asm.trait_call(fid, pos, gcpoint, return_type, dest);
         } else if fct.is_virtual() {
             let vtable_index = fct.vtable_index.unwrap();
             self.asm.emit_comment(Comment::CallVirtual(fid));
@@ -1810,6 +2166,128 @@ where
         }
     }
 
+    // Emits a call in tail position as jump. Self-calls reuse the current
+    // frame and jump right behind the prolog, calls of other functions
+    // remove the current frame first. Nothing is emitted when the call
+    // can't be a tail call: virtual calls, calls of native functions and
+    // calls that pass arguments on the stack.
+    pub fn emit_tail_call(
+        &mut self,
+        csite: &CallSite<'ast>,
+        pos: Position,
+        lbl_start: Label,
+    ) -> Option<TailCall> {
+        let fid = csite.callee;
+        let fct = self.vm.fcts.idx(fid);
+        let fct = fct.read();
+
+        if self.inlined
+            || fct.is_virtual()
+            || !fct.is_src()
+            || !args_in_registers(csite.args.iter().map(|arg| arg.ty()))
+        {
+            return None;
+        }
+
+        if let Some(&Arg::SelfieNew(_, _)) = csite.args.first() {
+            return None;
+        }
+
+        let return_type = self.specialize_type(csite.return_type);
+        let (cls_type_params, fct_type_params) = self.specialize_type_params(csite);
+
+        let self_call = fid == self.fct.id
+            && cls_type_params == *self.cls_type_params
+            && fct_type_params == *self.fct_type_params;
+
+        let temps = self.emit_call_args(csite, &*fct, pos);
+
+        // recursion through tail calls doesn't grow the stack anymore,
+        // these "loops" need a safepoint as well
+        self.asm.emit_comment(Comment::ReadPollingPage);
+        self.asm.check_polling_page(self.vm.polling_page.addr());
+        let gcpoint = self.create_gcpoint();
+        self.asm.emit_gcpoint(gcpoint);
+
+        let mut reg_idx = 0;
+        let mut freg_idx = 0;
+
+        for (arg, &(_, offset, _)) in csite.args.iter().zip(&temps) {
+            let mode = arg.ty().mode();
+
+            if mode.is_float() {
+                let freg = FREG_PARAMS[freg_idx];
+                self.asm.load_mem(mode, freg.into(), Mem::Local(offset));
+                freg_idx += 1;
+            } else {
+                let reg = REG_PARAMS[reg_idx];
+                self.asm.load_mem(mode, reg.into(), Mem::Local(offset));
+                reg_idx += 1;
+            }
+        }
+
+        self.asm.emit_comment(Comment::CallTail(fid));
+
+        let tail_call = if self_call {
+            self.asm.jump(lbl_start);
+
+            TailCall::SelfCall
+        } else {
+            let ptr = self.ptr_for_fct_id(fid, cls_type_params.clone(), fct_type_params.clone());
+
+            // the callee expects the callee-saved registers of our caller
+            for &(reg, offset) in &self.jit_info.saved_regs {
+                self.asm
+                    .load_mem(MachineMode::Ptr, reg.into(), Mem::Local(offset));
+            }
+
+            let gcpoint = self.create_gcpoint();
+            self.asm.direct_tail_call(
+                fid,
+                ptr.to_ptr(),
+                cls_type_params,
+                fct_type_params,
+                self.jit_info.stacksize(),
+                pos,
+                gcpoint,
+                return_type,
+                result_reg(return_type.mode()),
+            );
+
+            TailCall::Sibling
+        };
+
+        for temp in temps.into_iter() {
+            self.free_temp_with_type(temp.0, temp.1);
+        }
+
+        Some(tail_call)
+    }
+
+    fn specialize_type_params(&self, csite: &CallSite<'ast>) -> (TypeParams, TypeParams) {
+        let cls_type_params: TypeParams = csite
+            .cls_type_params
+            .iter()
+            .map(|ty| self.specialize_type(ty))
+            .collect::<Vec<_>>()
+            .into();
+        let fct_type_params: TypeParams = csite
+            .fct_type_params
+            .iter()
+            .map(|ty| self.specialize_type(ty))
+            .collect::<Vec<_>>()
+            .into();
+
+        debug_assert!(cls_type_params
+            .iter()
+            .all(|ty| !ty.contains_type_param(self.vm)));
+        debug_assert!(fct_type_params
+            .iter()
+            .all(|ty| !ty.contains_type_param(self.vm)));
+
+        (cls_type_params, fct_type_params)
+    }
+
     fn emit_allocation(
         &mut self,
         pos: Position,
@@ -1966,7 +2444,7 @@ where
     fn specialize_type(&self, ty: BuiltinType) -> BuiltinType {
         match ty {
             BuiltinType::ClassTypeParam(cls_id, id) => {
-                assert!(self.fct.parent == FctParent::Class(cls_id));
+                assert!(self.fct.owner_cls_id(self.vm) == Some(cls_id));
                 self.cls_type_params[id.idx()]
             }
 
@@ -1985,6 +2463,26 @@ where
                 BuiltinType::Class(cls_id, list_id)
             }
 
+            BuiltinType::Trait(trait_id, list_id) => {
+                let params = self.vm.lists.lock().get(list_id);
+
+                let params: Vec<_> = params.iter().map(|t| self.specialize_type(t)).collect();
+
+                let list_id = self.vm.lists.lock().insert(params.into());
+
+                BuiltinType::Trait(trait_id, list_id)
+            }
+
+            BuiltinType::Tuple(list_id) => {
+                let elements = self.vm.lists.lock().get(list_id);
+
+                let elements: Vec<_> = elements.iter().map(|t| self.specialize_type(t)).collect();
+
+                let list_id = self.vm.lists.lock().insert(elements.into());
+
+                BuiltinType::Tuple(list_id)
+            }
+
             BuiltinType::Lambda(_) => unimplemented!(),
 
             _ => ty,
@@ -2023,10 +2521,12 @@ fn check_for_nil(ty: BuiltinType) -> bool {
         BuiltinType::Nil | BuiltinType::Ptr => true,
         BuiltinType::Class(_, _) => true,
         BuiltinType::Struct(_, _) => false,
-        BuiltinType::Trait(_) => false,
+        BuiltinType::Trait(_, _) => false,
+        BuiltinType::Tuple(_) => false,
         BuiltinType::This => unreachable!(),
         BuiltinType::ClassTypeParam(_, _) => unreachable!(),
         BuiltinType::FctTypeParam(_, _) => unreachable!(),
+        BuiltinType::TraitTypeParam(_, _) => unreachable!(),
         BuiltinType::Lambda(_) => true,
     }
 }
@@ -2065,7 +2565,7 @@ pub fn ensure_native_stub(vm: &VM, fct_id: FctId, internal_fct: InternalFct) ->
 }
 
 fn ensure_jit_or_stub_ptr<'ast>(
-    src: &mut FctSrc,
+    src: &FctSrc,
     vm: &VM,
     cls_type_params: TypeParams,
     fct_type_params: TypeParams,
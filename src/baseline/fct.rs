@@ -1,19 +1,22 @@
+use std::collections::hash_map::Values;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::ptr;
 use std::sync::Arc;
 
 use crate::class::{ClassDef, ClassDefId, FieldId, TypeParams};
-use crate::cpu::flush_icache;
+use crate::cpu::{flush_icache, Reg};
 use crate::ctxt::VM;
 use crate::ctxt::{FctId, FctSrc, GlobalId, VarId};
 use crate::dseg::DSeg;
 use crate::gc::Address;
 use crate::object::{Ref, Str};
 use crate::opt::fct::JitOptFct;
+use crate::os::signal::Trap;
+use crate::stdlib;
 use crate::utils::GrowableVec;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct JitFctId(usize);
 
 impl JitFctId {
@@ -74,6 +77,7 @@ pub enum JitDescriptor {
     TrapThunk,
     AllocThunk,
     VerifyThunk,
+    GlobalInitThunk,
     NativeThunk(FctId),
     DoraEntry,
 }
@@ -98,6 +102,12 @@ pub struct JitBaselineFct {
     comments: Comments,
     linenos: LineNumberTable,
     pub exception_handlers: Vec<ExHandler>,
+
+    // callee-saved registers and the stack slots they are saved in
+    pub saved_regs: Vec<(Reg, i32)>,
+
+    // self-calls in tail position reuse the frame of this function
+    pub tail_recursive: bool,
 }
 
 impl JitBaselineFct {
@@ -119,7 +129,7 @@ impl JitBaselineFct {
         let ptr = vm.gc.alloc_code(size);
 
         if ptr.is_null() {
-            panic!("out of memory: not enough executable memory left!");
+            code_cache_full(vm, size);
         }
 
         dseg.finish(ptr.to_ptr());
@@ -152,6 +162,8 @@ impl JitBaselineFct {
             desc: desc,
             throws: throws,
             exception_handlers: exception_handlers,
+            saved_regs: Vec::new(),
+            tail_recursive: false,
         }
     }
 
@@ -159,10 +171,20 @@ impl JitBaselineFct {
         self.linenos.get(offset)
     }
 
+    pub fn linenos(&self) -> &LineNumberTable {
+        &self.linenos
+    }
+
     pub fn gcpoint_for_offset(&self, offset: i32) -> Option<&GcPoint> {
         self.gcpoints.get(offset)
     }
 
+    // the gcpoint of a safepoint directly follows the instruction
+    // that reads the polling page
+    pub fn gcpoint_for_safepoint(&self, offset: i32) -> Option<&GcPoint> {
+        self.gcpoints.next_after(offset)
+    }
+
     pub fn nil_check_for_offset(&self, offset: i32) -> bool {
         self.nil_checks.contains(&offset)
     }
@@ -212,6 +234,23 @@ impl fmt::Debug for JitBaselineFct {
     }
 }
 
+fn code_cache_full(vm: &VM, size: usize) -> ! {
+    let code_space = vm.gc.code_space();
+
+    eprintln!(
+        "code cache full: cannot allocate {} bytes, {} of {} bytes in use.",
+        size,
+        code_space.used(),
+        code_space.limit()
+    );
+    eprintln!("Use --code-size=<SIZE> to increase the size of the code cache.");
+
+    // code is unloaded before compilation starts, while compiling
+    // locks are held that prevent unloading code here
+    stdlib::trap(Trap::OOM.int());
+    unreachable!();
+}
+
 #[derive(Debug)]
 pub struct GcPoints {
     points: HashMap<i32, GcPoint>,
@@ -231,6 +270,14 @@ impl GcPoints {
     pub fn insert(&mut self, offset: i32, gcpoint: GcPoint) {
         assert!(self.points.insert(offset, gcpoint).is_none());
     }
+
+    pub fn next_after(&self, offset: i32) -> Option<&GcPoint> {
+        self.points
+            .iter()
+            .filter(|&(&point, _)| point > offset)
+            .min_by_key(|&(&point, _)| point)
+            .map(|(_, gcpoint)| gcpoint)
+    }
 }
 
 #[derive(Debug)]
@@ -277,13 +324,17 @@ pub enum Comment {
     StoreVTable(ClassDefId),
     CallSuper(FctId),
     CallVirtual(FctId),
+    CallTrait(FctId),
     CallDirect(FctId),
+    CallTail(FctId),
+    CallInline(FctId),
     StoreParam(VarId),
     Newline,
     StoreField(ClassDefId, FieldId),
     LoadField(ClassDefId, FieldId),
     StoreVar(VarId),
     LoadVar(VarId),
+    LoadInlinedVar(FctId, VarId),
     LoadSelf(VarId),
     LoadGlobal(GlobalId),
     StoreGlobal(GlobalId),
@@ -340,6 +391,14 @@ impl<'a, 'ast> fmt::Display for CommentFormat<'a, 'ast> {
                 write!(f, "call virtual {}", &name)
             }
 
+            &Comment::CallTrait(fid) => {
+                let fct = self.vm.fcts.idx(fid);
+                let fct = fct.read();
+                let name = fct.full_name(self.vm);
+
+                write!(f, "call trait {}", &name)
+            }
+
             &Comment::CallDirect(fid) => {
                 let fct = self.vm.fcts.idx(fid);
                 let fct = fct.read();
@@ -348,6 +407,22 @@ impl<'a, 'ast> fmt::Display for CommentFormat<'a, 'ast> {
                 write!(f, "call direct {}", &name)
             }
 
+            &Comment::CallTail(fid) => {
+                let fct = self.vm.fcts.idx(fid);
+                let fct = fct.read();
+                let name = fct.full_name(self.vm);
+
+                write!(f, "tail call {}", &name)
+            }
+
+            &Comment::CallInline(fid) => {
+                let fct = self.vm.fcts.idx(fid);
+                let fct = fct.read();
+                let name = fct.full_name(self.vm);
+
+                write!(f, "inline {}", &name)
+            }
+
             &Comment::StoreParam(vid) => {
                 let var = &self.fct_src.unwrap().vars[vid];
                 let name = self.vm.interner.str(var.name);
@@ -377,7 +452,12 @@ impl<'a, 'ast> fmt::Display for CommentFormat<'a, 'ast> {
                 let cls_def = cls_def.read();
                 let cname = cls_def.name(self.vm);
 
-                let cls_id = cls_def.cls_id.expect("no corresponding class");
+                // elements of tuples have no corresponding class
+                let cls_id = match cls_def.cls_id {
+                    Some(cls_id) => cls_id,
+                    None => return write!(f, "load from {}.{}", cname, fid.idx()),
+                };
+
                 let cls = self.vm.classes.idx(cls_id);
                 let cls = cls.read();
                 let field = &cls.fields[fid];
@@ -401,6 +481,17 @@ impl<'a, 'ast> fmt::Display for CommentFormat<'a, 'ast> {
                 write!(f, "load var {}", name)
             }
 
+            &Comment::LoadInlinedVar(fid, vid) => {
+                let fct = self.vm.fcts.idx(fid);
+                let fct = fct.read();
+                let src = fct.src();
+                let src = src.read();
+                let var = &src.vars[vid];
+                let name = self.vm.interner.str(var.name);
+
+                write!(f, "load var {}", name)
+            }
+
             &Comment::StoreGlobal(gid) => {
                 let glob = self.vm.globals.idx(gid);
                 let glob = glob.lock();
@@ -447,6 +538,18 @@ impl LineNumberTable {
             0
         }
     }
+
+    // (offset, lineno) pairs sorted by offset
+    pub fn entries(&self) -> Vec<(i32, i32)> {
+        let mut entries: Vec<(i32, i32)> = self
+            .map
+            .iter()
+            .map(|(&offset, &lineno)| (offset, lineno))
+            .collect();
+        entries.sort();
+
+        entries
+    }
 }
 
 #[derive(Debug)]
@@ -483,10 +586,15 @@ impl Bailouts {
     pub fn get(&self, offset: i32) -> Option<&BailoutInfo> {
         self.map.get(&offset)
     }
+
+    pub fn iter(&self) -> Values<i32, BailoutInfo> {
+        self.map.values()
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum BailoutInfo {
     Compile(FctId, i32, TypeParams, TypeParams),
-    VirtCompile(u32, TypeParams),
+    VirtCompile(u32, TypeParams, i32),
+    TraitCompile(FctId, i32),
 }
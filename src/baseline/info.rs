@@ -1,15 +1,17 @@
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use crate::baseline::bounds_check;
+use crate::baseline::regalloc::{self, LiveRange};
 use crate::class::TypeParams;
 use crate::cpu::*;
 use crate::ctxt::VM;
 use crate::ctxt::{
-    Arg, CallSite, CallType, Fct, FctId, FctKind, FctParent, FctSrc, Intrinsic, NodeMap, Store,
-    TraitId, VarId,
+    Arg, CallSite, CallType, Fct, FctId, FctKind, FctParent, FctSrc, IdentType, Intrinsic, NodeMap,
+    Store, TraitId, VarId,
 };
 use crate::mem;
-use crate::semck::specialize::specialize_type;
+use crate::semck::specialize::{specialize_for_trait, specialize_type};
 use crate::ty::BuiltinType;
 use dora_parser::ast::visit::*;
 use dora_parser::ast::Expr::*;
@@ -48,11 +50,21 @@ pub fn generate<'a, 'ast: 'a>(
 
         cls_type_params: cls_type_params,
         fct_type_params: fct_type_params,
+
+        inline: vm.args.inline_limit() > 0,
+        inline_params: Vec::new(),
+
+        pos: 0,
+        live_ranges: HashMap::new(),
+        loops: Vec::new(),
     };
 
     ig.generate();
 }
 
+// maximum number of expression nodes in the body of an inlined function
+pub const DEFAULT_INLINE_LIMIT: usize = 12;
+
 pub struct JitInfo<'ast> {
     pub tempsize: i32,                // size of temporary variables on stack
     pub localsize: i32,               // size of local variables on stack
@@ -67,6 +79,22 @@ pub struct JitInfo<'ast> {
     pub map_var_types: HashMap<VarId, BuiltinType>,
     pub map_intrinsics: NodeMap<Intrinsic>,
     pub map_fors: NodeMap<ForInfo<'ast>>,
+    pub map_templates: NodeMap<TemplateInfo<'ast>>,
+
+    // call sites that are replaced by the body of the callee, the
+    // `hasNext()` call of a for-loop is stored under the id of the loop
+    pub map_inlines: NodeMap<InlineSite<'ast>>,
+
+    // local variables held in callee-saved registers and the stack slots
+    // used to save these registers in prolog
+    pub map_var_regs: HashMap<VarId, Reg>,
+    pub saved_regs: Vec<(Reg, i32)>,
+
+    // array accesses that are known to be in bounds
+    pub omitted_bounds_checks: HashSet<NodeId>,
+
+    // temporaries holding primitive values while their box is allocated
+    pub map_boxes: NodeMap<i32>,
 }
 
 impl<'ast> JitInfo<'ast> {
@@ -110,8 +138,25 @@ impl<'ast> JitInfo<'ast> {
             map_var_types: HashMap::new(),
             map_intrinsics: NodeMap::new(),
             map_fors: NodeMap::new(),
+            map_templates: NodeMap::new(),
+            map_inlines: NodeMap::new(),
+            map_var_regs: HashMap::new(),
+            saved_regs: Vec::new(),
+            omitted_bounds_checks: HashSet::new(),
+            map_boxes: NodeMap::new(),
         }
     }
+
+    pub fn reg(&self, var_id: VarId) -> Option<Reg> {
+        self.map_var_regs.get(&var_id).cloned()
+    }
+}
+
+pub struct InlineSite<'ast> {
+    pub expr: &'ast Expr,
+    pub jit_info: JitInfo<'ast>,
+    pub cls_type_params: TypeParams,
+    pub fct_type_params: TypeParams,
 }
 
 struct InfoGenerator<'a, 'ast: 'a> {
@@ -136,6 +181,15 @@ struct InfoGenerator<'a, 'ast: 'a> {
 
     cls_type_params: &'a TypeParams,
     fct_type_params: &'a TypeParams,
+
+    inline: bool,
+    inline_params: Vec<(NodeId, Vec<(VarId, i32)>)>,
+
+    // live ranges of local variables for register allocation, positions
+    // are assigned in visiting order of statements and expressions
+    pos: usize,
+    live_ranges: HashMap<VarId, (usize, usize)>,
+    loops: Vec<(usize, Vec<VarId>)>,
 }
 
 impl<'a, 'ast> Visitor<'ast> for InfoGenerator<'a, 'ast> {
@@ -170,10 +224,30 @@ impl<'a, 'ast> Visitor<'ast> for InfoGenerator<'a, 'ast> {
     }
 
     fn visit_stmt(&mut self, s: &'ast Stmt) {
+        self.pos += 1;
+
+        let is_loop = match s {
+            &StmtWhile(_) | &StmtLoop(_) | &StmtFor(_) => true,
+            _ => false,
+        };
+
+        if is_loop {
+            self.loops.push((self.pos, Vec::new()));
+        }
+
         match s {
-            &StmtVar(ref var) => {
-                let var = *self.src.map_vars.get(var.id).unwrap();
+            &StmtVar(ref stmt) => {
+                let var = *self.src.map_vars.get(stmt.id).unwrap();
                 self.reserve_stack_for_var(var);
+                self.define_var(var, stmt.expr.is_some());
+            }
+
+            &StmtVarTuple(ref stmt) => {
+                for binding in &stmt.names {
+                    let var = *self.src.map_vars.get(binding.id).unwrap();
+                    self.reserve_stack_for_var(var);
+                    self.define_var(var, true);
+                }
             }
 
             &StmtDo(ref r#try) => {
@@ -182,12 +256,20 @@ impl<'a, 'ast> Visitor<'ast> for InfoGenerator<'a, 'ast> {
 
             &StmtFor(ref sfor) => {
                 self.reserve_stmt_for(sfor);
+
+                let var = *self.src.map_vars.get(sfor.id).unwrap();
+                self.define_var(var, true);
             }
 
             _ => {}
         }
 
         visit::walk_stmt(self, s);
+
+        if is_loop {
+            self.pos += 1;
+            self.finish_loop();
+        }
     }
 
     fn visit_expr_top(&mut self, e: &'ast Expr) {
@@ -197,6 +279,8 @@ impl<'a, 'ast> Visitor<'ast> for InfoGenerator<'a, 'ast> {
     }
 
     fn visit_expr(&mut self, e: &'ast Expr) {
+        self.pos += 1;
+
         match *e {
             ExprCall(ref expr) => self.expr_call(expr),
             ExprDelegation(ref expr) => self.expr_delegation(expr),
@@ -206,9 +290,17 @@ impl<'a, 'ast> Visitor<'ast> for InfoGenerator<'a, 'ast> {
             ExprUn(ref expr) => self.expr_un(expr),
             ExprConv(ref expr) => self.expr_conv(expr),
             ExprLitStruct(ref expr) => self.expr_lit_struct(expr),
+            ExprTuple(ref expr) => self.expr_tuple(expr),
+            ExprTemplate(ref expr) => self.expr_template(expr),
+            ExprIdent(ref expr) => self.use_ident(expr.id),
+            ExprPath(ref expr) => self.use_global(expr.id),
 
             _ => visit::walk_expr(self, e),
         }
+
+        if self.src.map_boxes.get(e.id()).is_some() {
+            self.expr_box(e);
+        }
     }
 }
 
@@ -219,6 +311,14 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         }
 
         self.visit_fct(self.ast);
+        self.allocate_registers();
+
+        if !self.vm.args.flag_omit_bounds_check {
+            self.jit_info.omitted_bounds_checks =
+                bounds_check::analyze(self.vm, self.src, self.ast);
+        }
+
+        self.finish_inline_sites();
 
         self.jit_info.localsize = self.localsize;
         self.jit_info.tempsize = self.max_tempsize;
@@ -227,6 +327,95 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         self.jit_info.eh_return_value = self.eh_return_value;
     }
 
+    fn define_var(&mut self, var: VarId, initialized: bool) {
+        // a variable declared without initializer in a loop body might
+        // still hold the value of the previous iteration
+        let start = if initialized {
+            self.pos
+        } else {
+            self.loops
+                .last()
+                .map(|&(start, _)| start)
+                .unwrap_or(self.pos)
+        };
+
+        self.live_ranges.insert(var, (start, self.pos));
+    }
+
+    fn use_ident(&mut self, id: NodeId) {
+        self.use_global(id);
+
+        let var = match self.src.map_idents.get(id) {
+            Some(&IdentType::Var(var)) => var,
+            _ => return,
+        };
+
+        if let Some(range) = self.live_ranges.get_mut(&var) {
+            range.1 = max(range.1, self.pos);
+
+            for loop_vars in &mut self.loops {
+                loop_vars.1.push(var);
+            }
+        }
+    }
+
+    fn use_global(&mut self, id: NodeId) {
+        if let Some(&IdentType::Global(gid)) = self.src.map_idents.get(id) {
+            let glob = self.vm.globals.idx(gid);
+
+            // the first access of the global calls its initializer
+            if glob.lock().getter.is_some() {
+                self.leaf = false;
+            }
+        }
+    }
+
+    fn finish_loop(&mut self) {
+        let (start, vars) = self.loops.pop().unwrap();
+
+        // variables defined before the loop and used in it need to be
+        // alive until the end of the loop
+        for var in vars {
+            let range = self.live_ranges.get_mut(&var).unwrap();
+
+            if range.0 <= start {
+                range.1 = max(range.1, self.pos);
+            }
+        }
+    }
+
+    fn allocate_registers(&mut self) {
+        if self.vm.args.flag_disable_regalloc {
+            return;
+        }
+
+        let mut ranges = Vec::new();
+
+        for (&var, &(start, end)) in &self.live_ranges {
+            match self.jit_info.ty(var) {
+                BuiltinType::Int | BuiltinType::Long => {
+                    ranges.push(LiveRange::new(var, start, end));
+                }
+
+                // gc points only record stack slots, references stay on the stack
+                _ => {}
+            }
+        }
+
+        let map_var_regs = regalloc::allocate(ranges, &REG_VARS);
+
+        let mut regs: Vec<Reg> = map_var_regs.values().cloned().collect();
+        regs.sort_by_key(|reg| reg.0);
+        regs.dedup();
+
+        for reg in regs {
+            let offset = self.reserve_stack_for_type(BuiltinType::Ptr);
+            self.jit_info.saved_regs.push((reg, offset));
+        }
+
+        self.jit_info.map_var_regs = map_var_regs;
+    }
+
     fn reserve_stmt_do(&mut self, r#try: &'ast StmtDoType) {
         let ret = self.fct.return_type;
 
@@ -278,6 +467,7 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         );
         let args = vec![Arg::Stack(offset, BuiltinType::Unit, 0)];
         let has_next = self.build_call_site(&ctype, for_type_info.has_next, args);
+        self.inline_call_site(stmt.id, &has_next);
 
         // build next() call
         let ctype = CallType::Method(
@@ -360,6 +550,15 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         }
     }
 
+    fn expr_box(&mut self, e: &'ast Expr) {
+        // allocating the box may trigger a collection
+        self.leaf = false;
+
+        let ty = self.specialize_type(self.src.ty(e.id()));
+        let offset = self.reserve_temp_for_type(ty);
+        self.jit_info.map_boxes.insert(e.id(), offset);
+    }
+
     fn expr_conv(&mut self, e: &'ast ExprConvType) {
         self.visit_expr(&e.object);
         let is_valid = self.src.map_convs.get(e.id).unwrap().valid;
@@ -377,6 +576,85 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         }
     }
 
+    fn expr_tuple(&mut self, e: &'ast ExprTupleType) {
+        // allocating the tuple may trigger a collection
+        self.leaf = false;
+
+        for value in &e.values {
+            self.visit_expr(value);
+            self.reserve_temp_for_node(value);
+        }
+
+        self.reserve_temp_for_node_id(e.id);
+    }
+
+    fn expr_template(&mut self, e: &'ast ExprTemplateType) {
+        let template_type_info = self.src.map_templates.get(e.id).unwrap().clone();
+
+        // reserve stack slots for the StringBuf and the currently appended part
+        let buf_type = self.vm.cls(self.vm.vips.string_buf_class);
+        let buf_offset = self.reserve_stack_for_type(buf_type);
+        let part_offset = self.reserve_stack_for_type(BuiltinType::Ptr);
+
+        // build StringBuf::empty() call
+        let ctype = CallType::Fct(
+            template_type_info.empty,
+            TypeParams::empty(),
+            TypeParams::empty(),
+        );
+        let empty = self.build_call_site(&ctype, template_type_info.empty, Vec::new());
+
+        // build toString() call for every part that is not a string
+        let parts = e
+            .parts
+            .iter()
+            .zip(&template_type_info.parts)
+            .map(|(part, to_string)| match *to_string {
+                Some(fct_id) => {
+                    let part_type = self.ty(part.id());
+                    let ctype = CallType::Method(part_type, fct_id, TypeParams::empty());
+                    let args = vec![Arg::Expr(part, BuiltinType::Unit, 0)];
+
+                    Some(self.build_call_site(&ctype, fct_id, args))
+                }
+
+                None => {
+                    self.visit_expr(part);
+                    None
+                }
+            })
+            .collect();
+
+        // build buf.appendString(part) call
+        let ctype = CallType::Method(
+            buf_type,
+            template_type_info.append_string,
+            TypeParams::empty(),
+        );
+        let args = vec![
+            Arg::Stack(buf_offset, BuiltinType::Unit, 0),
+            Arg::Stack(part_offset, BuiltinType::Unit, 0),
+        ];
+        let append_string = self.build_call_site(&ctype, template_type_info.append_string, args);
+
+        // build buf.toString() call
+        let ctype = CallType::Method(buf_type, template_type_info.to_string, TypeParams::empty());
+        let args = vec![Arg::Stack(buf_offset, BuiltinType::Unit, 0)];
+        let to_string = self.build_call_site(&ctype, template_type_info.to_string, args);
+
+        self.jit_info.map_templates.insert(
+            e.id,
+            TemplateInfo {
+                empty: empty,
+                parts: parts,
+                append_string: append_string,
+                to_string: to_string,
+                buf_offset: buf_offset,
+                part_offset: part_offset,
+            },
+        );
+    }
+
     fn get_intrinsic(&self, id: NodeId) -> Option<Intrinsic> {
         let fid = self.src.map_calls.get(id).unwrap().fct_id();
 
@@ -440,7 +718,7 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         let fct = self.vm.fcts.idx(fct_id);
         let fct = fct.read();
 
-        let callee_id = if fct.kind.is_definition() && !fct.is_extern {
+        let callee_id = if fct.in_trait() {
             let trait_id = fct.trait_id();
             let object_type = match *call_type {
                 CallType::Method(ty, _, _) => ty,
@@ -449,7 +727,14 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
 
             let object_type = self.specialize_type(object_type);
 
-            self.find_trait_impl(fct_id, trait_id, object_type)
+            // calls on trait objects are dispatched at runtime, just like
+            // calls whose impl isn't known for the static type
+            if object_type.is_trait() {
+                fct_id
+            } else {
+                self.find_trait_impl(fct_id, trait_id, object_type)
+                    .unwrap_or(fct_id)
+            }
         } else {
             fct_id
         };
@@ -464,6 +749,9 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         }
 
         self.universal_call(expr.id, args, Some(callee_id));
+
+        let csite = self.jit_info.map_csites.get(expr.id).unwrap().clone();
+        self.inline_call_site(expr.id, &csite);
     }
 
     fn reserve_args(&mut self, expr: &'ast ExprCallType) {
@@ -478,29 +766,189 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         }
     }
 
-    fn find_trait_impl(&self, fct_id: FctId, trait_id: TraitId, object_type: BuiltinType) -> FctId {
-        let cls_id = object_type.cls_id(self.vm).unwrap();
-        let cls = self.vm.classes.idx(cls_id);
-        let cls = cls.read();
+    fn find_trait_impl(
+        &self,
+        fct_id: FctId,
+        trait_id: TraitId,
+        object_type: BuiltinType,
+    ) -> Option<FctId> {
+        let mut cls_id = object_type.cls_id(self.vm);
 
-        for &impl_id in &cls.impls {
-            let ximpl = self.vm.impls[impl_id].read();
+        // the trait could also be implemented by one of the super classes
+        while let Some(id) = cls_id {
+            let cls = self.vm.classes.idx(id);
+            let cls = cls.read();
 
-            if ximpl.trait_id() != trait_id {
-                continue;
-            }
+            for &impl_id in &cls.impls {
+                let ximpl = self.vm.impls[impl_id].read();
+
+                if ximpl.trait_id() != trait_id {
+                    continue;
+                }
 
-            for &mtd_id in &ximpl.methods {
-                let mtd = self.vm.fcts.idx(mtd_id);
-                let mtd = mtd.read();
+                for &mtd_id in &ximpl.methods {
+                    let mtd = self.vm.fcts.idx(mtd_id);
+                    let mtd = mtd.read();
 
-                if mtd.impl_for == Some(fct_id) {
-                    return mtd_id;
+                    if mtd.impl_for == Some(fct_id) {
+                        return Some(mtd_id);
+                    }
                 }
             }
+
+            cls_id = cls.parent_class;
         }
 
-        panic!("no impl found for generic trait call")
+        None
+    }
+
+    fn inline_call_site(&mut self, id: NodeId, csite: &CallSite<'ast>) {
+        if !self.inline || csite.super_call || csite.callee == self.fct.id {
+            return;
+        }
+
+        let callee = self.vm.fcts.idx(csite.callee);
+        let callee = callee.read();
+
+        if callee.is_virtual()
+            || callee.in_trait()
+            || callee.throws
+            || callee.is_constructor
+            || callee.ast.is_noinline
+            || !callee.kind.is_src()
+        {
+            return;
+        }
+
+        let expr = match inline_expr(callee.ast) {
+            Some(expr) => expr,
+            None => return,
+        };
+
+        // `#[inline]` ignores the size limit, the body still needs to be inlinable
+        let limit = if callee.ast.is_inline {
+            usize::max_value()
+        } else {
+            self.vm.args.inline_limit()
+        };
+
+        if !InlineChecker::check(expr, limit) {
+            return;
+        }
+
+        // the callee could be locked right now, simply don't inline it then
+        let src = callee.src();
+        let src = match src.try_read() {
+            Some(src) => src,
+            None => return,
+        };
+
+        let cls_type_params: TypeParams = csite
+            .cls_type_params
+            .iter()
+            .map(|ty| self.specialize_type(ty))
+            .collect::<Vec<_>>()
+            .into();
+        let fct_type_params: TypeParams = csite
+            .fct_type_params
+            .iter()
+            .map(|ty| self.specialize_type(ty))
+            .collect::<Vec<_>>()
+            .into();
+
+        let mut jit_info = JitInfo::new();
+
+        let max_tempsize = {
+            let mut ig = InfoGenerator {
+                vm: self.vm,
+                fct: &*callee,
+                ast: callee.ast,
+                src: &*src,
+                jit_info: &mut jit_info,
+
+                localsize: 0,
+                max_tempsize: 0,
+                cur_tempsize: self.cur_tempsize,
+                argsize: 0,
+
+                param_offset: PARAM_OFFSET,
+                leaf: true,
+                eh_return_value: None,
+                eh_status: None,
+
+                param_reg_idx: 0,
+                param_freg_idx: 0,
+
+                cls_type_params: &cls_type_params,
+                fct_type_params: &fct_type_params,
+
+                inline: false,
+                inline_params: Vec::new(),
+
+                pos: 0,
+                live_ranges: HashMap::new(),
+                loops: Vec::new(),
+            };
+
+            ig.visit_expr(expr);
+
+            // the inlined code must not contain any gc points, otherwise the
+            // temporaries of the callee would need to be part of the caller's
+            // gc point
+            if !ig.leaf {
+                return;
+            }
+
+            ig.cur_tempsize
+        };
+
+        // parameters of the callee live in the temporaries of the arguments
+        let mut vars = Vec::new();
+
+        if callee.has_self() {
+            vars.push(src.var_self().id);
+        }
+
+        for param in &callee.ast.params {
+            vars.push(*src.map_vars.get(param.id).unwrap());
+        }
+
+        assert!(vars.len() == csite.args.len());
+        let mut params = Vec::new();
+
+        for (&var, arg) in vars.iter().zip(&csite.args) {
+            jit_info.map_var_types.insert(var, arg.ty());
+            params.push((var, arg.offset()));
+        }
+
+        self.max_tempsize = max(self.max_tempsize, max_tempsize);
+        self.cur_tempsize = max(self.cur_tempsize, max_tempsize);
+        self.inline_params.push((id, params));
+
+        self.jit_info.map_inlines.insert_or_replace(
+            id,
+            InlineSite {
+                expr: expr,
+                jit_info: jit_info,
+                cls_type_params: cls_type_params,
+                fct_type_params: fct_type_params,
+            },
+        );
+    }
+
+    fn finish_inline_sites(&mut self) {
+        // offsets of temporaries are only known when the size of all
+        // local variables is known
+        for (id, params) in self.inline_params.drain(..) {
+            let site = self.jit_info.map_inlines.get_mut(id).unwrap();
+            site.jit_info.localsize = self.localsize;
+
+            for (var, offset) in params {
+                site.jit_info
+                    .map_var_offsets
+                    .insert(var, -(self.localsize + offset));
+            }
+        }
     }
 
     fn expr_delegation(&mut self, expr: &'ast ExprDelegationType) {
@@ -666,11 +1114,15 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
             self.visit_expr(&e.rhs);
 
             let lhs = e.lhs.to_ident().unwrap();
+            self.use_ident(lhs.id);
             let field = self.src.map_idents.get(lhs.id).unwrap().is_field();
 
             if field {
                 self.reserve_temp_for_node_with_type(lhs.id, BuiltinType::Ptr);
             }
+        } else if e.lhs.is_path() {
+            self.visit_expr(&e.rhs);
+            self.use_global(e.lhs.id());
         } else if e.lhs.is_field() {
             let lhs = e.lhs.to_field().unwrap();
 
@@ -786,12 +1238,20 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
     }
 
     fn ty(&self, id: NodeId) -> BuiltinType {
+        // boxed values are passed around as reference to the box
+        if self.src.map_boxes.get(id).is_some() {
+            return BuiltinType::Ptr;
+        }
+
         let ty = self.src.ty(id);
         self.specialize_type(ty)
     }
 
     fn specialize_type_for_call(&self, call_type: &CallType, ty: BuiltinType) -> BuiltinType {
         let ty = match *call_type {
+            // self of trait methods is the trait object itself
+            CallType::Method(obj_ty, _, _) if ty == BuiltinType::This => obj_ty,
+
             CallType::Fct(_, ref cls_type_params, ref fct_type_params) => {
                 specialize_type(self.vm, ty, cls_type_params, fct_type_params)
             }
@@ -802,6 +1262,11 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
                     specialize_type(self.vm, ty, &params, type_params)
                 }
 
+                BuiltinType::Trait(_, list_id) => {
+                    let params = self.vm.lists.lock().get(list_id);
+                    specialize_for_trait(self.vm, ty, cls_ty, &params)
+                }
+
                 _ => ty,
             },
 
@@ -818,6 +1283,70 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
     }
 }
 
+// returns the expression of functions with a body of the form
+// `= <expr>;` or `{ return <expr>; }`
+fn inline_expr(ast: &Function) -> Option<&Expr> {
+    let stmt = match ast.block {
+        Some(ref block) => &**block,
+        None => return None,
+    };
+
+    let stmt = match *stmt {
+        StmtBlock(ref block) if block.stmts.len() == 1 => &*block.stmts[0],
+        _ => stmt,
+    };
+
+    match *stmt {
+        StmtReturn(ref ret) => ret.expr.as_ref().map(|expr| &**expr),
+        _ => None,
+    }
+}
+
+struct InlineChecker {
+    size: usize,
+    inlinable: bool,
+}
+
+impl InlineChecker {
+    fn check(expr: &Expr, limit: usize) -> bool {
+        let mut checker = InlineChecker {
+            size: 0,
+            inlinable: true,
+        };
+
+        checker.visit_expr(expr);
+
+        checker.inlinable && checker.size <= limit
+    }
+}
+
+impl<'ast> Visitor<'ast> for InlineChecker {
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        self.size += 1;
+
+        match *e {
+            ExprAssign(_) | ExprDelegation(_) | ExprTry(_) | ExprLambda(_) | ExprLitStruct(_)
+            | ExprTemplate(_) => {
+                self.inlinable = false;
+            }
+
+            _ => visit::walk_expr(self, e),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TemplateInfo<'ast> {
+    pub empty: CallSite<'ast>,
+    pub parts: Vec<Option<CallSite<'ast>>>,
+    pub append_string: CallSite<'ast>,
+    pub to_string: CallSite<'ast>,
+
+    // stack slots for the StringBuf and the part that gets appended
+    pub buf_offset: i32,
+    pub part_offset: i32,
+}
+
 #[derive(Clone)]
 pub struct ForInfo<'ast> {
     pub make_iterator: CallSite<'ast>,
@@ -970,4 +1499,37 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn test_inline_attributes() {
+        info(
+            "fun g(a: Int) -> Int = a + 1; fun f() -> Int = g(1);",
+            |_, jit_info| {
+                assert_eq!(1, jit_info.map_inlines.iter().count());
+            },
+        );
+
+        info(
+            "#[noinline] fun g(a: Int) -> Int = a + 1; fun f() -> Int = g(1);",
+            |_, jit_info| {
+                assert_eq!(0, jit_info.map_inlines.iter().count());
+            },
+        );
+
+        // body exceeds the default inline limit
+        info(
+            "fun g(a: Int) -> Int = a + a + a + a + a + a + a + a; fun f() -> Int = g(1);",
+            |_, jit_info| {
+                assert_eq!(0, jit_info.map_inlines.iter().count());
+            },
+        );
+
+        info(
+            "#[inline] fun g(a: Int) -> Int = a + a + a + a + a + a + a + a;
+             fun f() -> Int = g(1);",
+            |_, jit_info| {
+                assert_eq!(1, jit_info.map_inlines.iter().count());
+            },
+        );
+    }
 }
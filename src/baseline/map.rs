@@ -35,6 +35,7 @@ impl CodeMap {
                 &CodeDescriptor::TrapThunk => println!("trap_thunk"),
                 &CodeDescriptor::AllocThunk => println!("alloc_thunk"),
                 &CodeDescriptor::VerifyThunk => println!("verify_thunk"),
+                &CodeDescriptor::GlobalInitThunk => println!("global_init_thunk"),
                 &CodeDescriptor::NativeThunk(jit_fct_id) => {
                     let jit_fct = vm.jit_fcts.idx(jit_fct_id);
                     let fct = vm.fcts.idx(jit_fct.fct_id());
@@ -59,6 +60,18 @@ impl CodeMap {
 
         self.tree.get(&span).map(|el| *el)
     }
+
+    pub fn remove(&mut self, ptr: Address) -> Option<CodeDescriptor> {
+        let span = CodeSpan::new(ptr, ptr.offset(1));
+
+        self.tree.remove(&span)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Address, Address, CodeDescriptor)> + '_ {
+        self.tree
+            .iter()
+            .map(|(span, &data)| (span.start, span.end, data))
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -69,6 +82,7 @@ pub enum CodeDescriptor {
     TrapThunk,
     AllocThunk,
     VerifyThunk,
+    GlobalInitThunk,
     NativeThunk(JitFctId),
     DoraEntry,
 }
@@ -169,6 +183,22 @@ mod tests {
         assert_eq!(None, map.get(9.into()));
     }
 
+    #[test]
+    fn test_remove() {
+        let mut map = CodeMap::new();
+
+        map.insert(5.into(), 7.into(), CodeDescriptor::DoraFct(1.into()));
+        map.insert(7.into(), 9.into(), CodeDescriptor::DoraFct(2.into()));
+
+        assert_eq!(
+            Some(CodeDescriptor::DoraFct(1.into())),
+            map.remove(5.into())
+        );
+        assert_eq!(None, map.get(6.into()));
+        assert_eq!(Some(CodeDescriptor::DoraFct(2.into())), map.get(7.into()));
+        assert_eq!(None, map.remove(5.into()));
+    }
+
     #[test]
     #[should_panic]
     fn test_insert_fails() {
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::cpu::Reg;
+use crate::ctxt::VarId;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LiveRange {
+    pub var: VarId,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LiveRange {
+    pub fn new(var: VarId, start: usize, end: usize) -> LiveRange {
+        LiveRange {
+            var: var,
+            start: start,
+            end: end,
+        }
+    }
+}
+
+// Linear scan register allocation: ranges are processed in order of their
+// start position, when no register is free the range that ends last is spilled.
+// A spilled variable lives on the stack for the whole function, ranges are
+// never split.
+pub fn allocate(mut ranges: Vec<LiveRange>, regs: &[Reg]) -> HashMap<VarId, Reg> {
+    let mut result = HashMap::new();
+
+    if regs.is_empty() {
+        return result;
+    }
+
+    ranges.sort_by_key(|range| (range.start, range.end));
+
+    let mut free: Vec<Reg> = regs.iter().rev().cloned().collect();
+    let mut active: Vec<(LiveRange, Reg)> = Vec::new();
+
+    for range in ranges {
+        active.retain(|&(active_range, reg)| {
+            if active_range.end < range.start {
+                free.push(reg);
+                false
+            } else {
+                true
+            }
+        });
+
+        if let Some(reg) = free.pop() {
+            active.push((range, reg));
+            result.insert(range.var, reg);
+            continue;
+        }
+
+        let idx = active
+            .iter()
+            .enumerate()
+            .max_by_key(|&(_, &(active_range, _))| active_range.end)
+            .map(|(idx, _)| idx)
+            .unwrap();
+        let (spilled, reg) = active[idx];
+
+        if spilled.end > range.end {
+            result.remove(&spilled.var);
+            result.insert(range.var, reg);
+            active[idx] = (range, reg);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(var: usize, start: usize, end: usize) -> LiveRange {
+        LiveRange::new(VarId(var), start, end)
+    }
+
+    #[test]
+    fn test_allocate_disjoint() {
+        let regs = [Reg(1), Reg(2)];
+        let result = allocate(vec![range(0, 1, 3), range(1, 4, 6), range(2, 7, 9)], &regs);
+
+        assert_eq!(Some(&Reg(1)), result.get(&VarId(0)));
+        assert_eq!(Some(&Reg(1)), result.get(&VarId(1)));
+        assert_eq!(Some(&Reg(1)), result.get(&VarId(2)));
+    }
+
+    #[test]
+    fn test_allocate_spill() {
+        let regs = [Reg(1), Reg(2)];
+        let result = allocate(vec![range(0, 1, 20), range(1, 2, 6), range(2, 3, 8)], &regs);
+
+        assert_eq!(None, result.get(&VarId(0)));
+        assert_eq!(Some(&Reg(2)), result.get(&VarId(1)));
+        assert_eq!(Some(&Reg(1)), result.get(&VarId(2)));
+    }
+
+    #[test]
+    fn test_allocate_no_regs() {
+        let result = allocate(vec![range(0, 1, 2)], &[]);
+        assert!(result.is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::baseline::compile_queue;
+use crate::baseline::fct::{JitBaselineFct, JitDescriptor, JitFctId};
+use crate::baseline::map::{CodeDescriptor, CodeMap};
+use crate::ctxt::{FctKind, VM};
+use crate::gc::{Address, Region};
+use crate::mem;
+use crate::safepoint;
+use crate::threads::{DoraThread, ThreadState};
+
+// Reclaims code when the code space grows too large. Must not be called
+// while holding the compilation lock.
+pub fn reclaim_if_needed(vm: &VM) {
+    if vm.args.flag_code_stress || vm.gc.code_space().needs_reclaim() {
+        reclaim(vm);
+    }
+}
+
+// Unloads compiled functions and native thunks that can't be executed
+// anymore and returns their memory to the code space.
+//
+// Code is reachable when it is executed by some thread, when its address
+// is stored in a vtable or interface table or when it is referenced by the
+// data segment of reachable code (direct calls and inline caches). Unloaded
+// functions are removed from their specializations, the next call compiles
+// them again.
+pub fn reclaim(vm: &VM) {
+    // background compilations only compile ahead of time, these threads
+    // never unload code themselves
+    if compile_queue::is_compiler_thread() {
+        return;
+    }
+
+    // ahead-of-time compiled code is kept for the whole program
+    if vm.args.cmd_build {
+        return;
+    }
+
+    // stop_the_world only stops threads executing Dora code, threads in the
+    // compiler could still embed or patch addresses of unloaded code
+    let _compilation = vm.compilation_lock.write();
+
+    safepoint::stop_the_world(vm, |threads| {
+        let unreachable = find_unreachable(vm, threads);
+
+        for jit_fct_id in unreachable {
+            let jit_fct = vm.jit_fcts.idx(jit_fct_id);
+            let jit_fct = jit_fct.to_base().expect("baseline expected");
+
+            if unload(vm, jit_fct_id, jit_fct) {
+                vm.code_map.lock().remove(jit_fct.ptr_start());
+                vm.gdb_jit.unregister(jit_fct.fct_ptr());
+
+                let region = Region::new(jit_fct.ptr_start(), jit_fct.ptr_end());
+                vm.gc.free_code(region);
+            }
+        }
+    });
+
+    vm.gc.code_space().adapt_reclaim_threshold();
+}
+
+fn find_unreachable(vm: &VM, threads: &[Arc<DoraThread>]) -> Vec<JitFctId> {
+    let code_map = vm.code_map.lock();
+    let mut candidates: HashMap<Address, JitFctId> = HashMap::new();
+
+    for (_, _, data) in code_map.iter() {
+        match data {
+            CodeDescriptor::DoraFct(jit_fct_id) | CodeDescriptor::NativeThunk(jit_fct_id) => {
+                let jit_fct = vm.jit_fcts.idx(jit_fct_id);
+                candidates.insert(jit_fct.fct_ptr(), jit_fct_id);
+            }
+
+            _ => {}
+        }
+    }
+
+    let mut reachable = Reachable {
+        candidates: &candidates,
+        marked: HashSet::new(),
+        worklist: Vec::new(),
+    };
+
+    for thread in threads {
+        mark_stack(&mut reachable, &code_map, thread);
+    }
+
+    mark_vtables(vm, &mut reachable);
+    mark_itables(vm, &mut reachable);
+
+    while let Some(jit_fct_id) = reachable.worklist.pop() {
+        let jit_fct = vm.jit_fcts.idx(jit_fct_id);
+        let jit_fct = jit_fct.to_base().expect("baseline expected");
+        mark_dseg(&mut reachable, jit_fct);
+    }
+
+    let marked = reachable.marked;
+
+    candidates
+        .values()
+        .filter(|jit_fct_id| !marked.contains(jit_fct_id))
+        .cloned()
+        .collect()
+}
+
+struct Reachable<'a> {
+    candidates: &'a HashMap<Address, JitFctId>,
+    marked: HashSet<JitFctId>,
+    worklist: Vec<JitFctId>,
+}
+
+impl<'a> Reachable<'a> {
+    fn mark(&mut self, jit_fct_id: JitFctId) {
+        if self.marked.insert(jit_fct_id) {
+            self.worklist.push(jit_fct_id);
+        }
+    }
+
+    fn mark_address(&mut self, addr: Address) {
+        if let Some(&jit_fct_id) = self.candidates.get(&addr) {
+            self.mark(jit_fct_id);
+        }
+    }
+}
+
+fn mark_stack(reachable: &mut Reachable, code_map: &CodeMap, thread: &Arc<DoraThread>) {
+    // a thread blocked in a safepoint stopped in Dora code, the frames
+    // above its last native call are only known from the saved registers
+    if let ThreadState::Blocked = thread.state() {
+        let pc = thread.saved_pc.load(Ordering::Relaxed);
+        let fp = thread.saved_fp.load(Ordering::Relaxed);
+        mark_frames(reachable, code_map, pc, fp);
+    }
+
+    let mut dtn = thread.dtn();
+
+    while !dtn.is_null() {
+        let info = unsafe { &*dtn };
+        mark_frames(reachable, code_map, info.pc, info.fp);
+        dtn = info.last;
+    }
+}
+
+fn mark_frames(reachable: &mut Reachable, code_map: &CodeMap, mut pc: usize, mut fp: usize) {
+    while fp != 0 {
+        match code_map.get(pc.into()) {
+            Some(CodeDescriptor::DoraFct(jit_fct_id))
+            | Some(CodeDescriptor::NativeThunk(jit_fct_id)) => {
+                reachable.mark(jit_fct_id);
+            }
+
+            Some(CodeDescriptor::DoraEntry) | None => break,
+
+            _ => {}
+        }
+
+        pc = unsafe { *((fp + mem::ptr_width_usize()) as *const usize) };
+        fp = unsafe { *(fp as *const usize) };
+    }
+}
+
+fn mark_vtables(vm: &VM, reachable: &mut Reachable) {
+    for cls_def in vm.class_defs.iter() {
+        let cls_def = cls_def.read();
+
+        if let Some(ref vtable) = cls_def.vtable {
+            for &entry in vtable.table() {
+                reachable.mark_address(entry.into());
+            }
+        }
+    }
+}
+
+// interface tables of primitive types store unbox thunks, the method a
+// thunk jumps to is kept alive through the thunk's data segment
+fn mark_itables(vm: &VM, reachable: &mut Reachable) {
+    for cls_def in vm.class_defs.iter() {
+        let cls_def = cls_def.read();
+
+        for itable in &cls_def.itables {
+            for &entry in &itable.entries {
+                reachable.mark_address(entry.into());
+            }
+        }
+    }
+}
+
+// the data segment is stored right before the function's code
+fn mark_dseg(reachable: &mut Reachable, jit_fct: &JitBaselineFct) {
+    let mut addr = jit_fct.ptr_start();
+
+    while addr < jit_fct.fct_ptr() {
+        let value = unsafe { *addr.to_ptr::<usize>() };
+        reachable.mark_address(value.into());
+        addr = addr.offset(mem::ptr_width_usize());
+    }
+}
+
+fn unload(vm: &VM, jit_fct_id: JitFctId, jit_fct: &JitBaselineFct) -> bool {
+    match jit_fct.desc {
+        JitDescriptor::DoraFct(fct_id) => {
+            let fct = vm.fcts.idx(fct_id);
+            let fct = fct.read();
+
+            let src = match fct.kind {
+                FctKind::Source(ref src) => src,
+                _ => unreachable!(),
+            };
+
+            // function is compiled right now
+            let src = match src.try_read() {
+                Some(src) => src,
+                None => return false,
+            };
+
+            src.specializations
+                .write()
+                .retain(|_, &mut id| id != jit_fct_id);
+
+            if vm.args.flag_print_compilation {
+                println!(
+                    "unloaded {} ({} bytes)",
+                    fct.full_name(vm),
+                    jit_fct.fct_len()
+                );
+            }
+        }
+
+        JitDescriptor::NativeThunk(_) => {
+            vm.native_thunks.lock().remove_fct(jit_fct_id);
+        }
+
+        // only compiled functions and native thunks are candidates,
+        // keep everything else
+        _ => return false,
+    }
+
+    true
+}
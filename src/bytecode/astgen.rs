@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use dora_parser::ast::Expr::*;
 use dora_parser::ast::Stmt::*;
 use dora_parser::ast::*;
+use dora_parser::interner::Name;
 use dora_parser::lexer::token::{FloatSuffix, IntSuffix};
 
 use crate::bytecode::generate::{
@@ -14,13 +15,15 @@ use crate::semck::specialize::{specialize_class_id_params, specialize_class_ty,
 use crate::ty::BuiltinType;
 
 pub struct LoopLabels {
+    label: Option<Name>,
     cond: Label,
     end: Label,
 }
 
 impl LoopLabels {
-    fn new(cond: Label, end: Label) -> LoopLabels {
+    fn new(label: Option<Name>, cond: Label, end: Label) -> LoopLabels {
         LoopLabels {
+            label: label,
             cond: cond,
             end: end,
         }
@@ -122,6 +125,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             // StmtDo(ref stmt) => {},
             // StmtSpawn(ref stmt) => {},
             // StmtFor(ref stmt) => {},
+            // StmtVarTuple(ref stmt) => {},
             _ => unimplemented!(),
         }
     }
@@ -143,7 +147,8 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         let end_lbl = self.gen.create_label();
         let cond_reg = self.visit_expr(&stmt.cond, DataDest::Alloc);
         self.gen.emit_jump_if_false(cond_reg, end_lbl);
-        self.loops.push(LoopLabels::new(cond_lbl, end_lbl));
+        self.loops
+            .push(LoopLabels::new(stmt.label, cond_lbl, end_lbl));
         self.visit_stmt(&stmt.block);
         self.loops.pop();
         self.gen.emit_jump(cond_lbl);
@@ -153,7 +158,8 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
     fn visit_stmt_loop(&mut self, stmt: &StmtLoopType) {
         let start_lbl = self.gen.define_label();
         let end_lbl = self.gen.create_label();
-        self.loops.push(LoopLabels::new(start_lbl, end_lbl));
+        self.loops
+            .push(LoopLabels::new(stmt.label, start_lbl, end_lbl));
         self.visit_stmt(&stmt.block);
         self.loops.pop();
         self.gen.emit_jump(start_lbl);
@@ -213,16 +219,23 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         }
     }
 
-    fn visit_stmt_break(&mut self, _stmt: &StmtBreakType) {
-        let end = self.loops.pop().unwrap().end;
+    fn visit_stmt_break(&mut self, stmt: &StmtBreakType) {
+        let end = self.find_loop(stmt.label).end;
         self.gen.emit_jump(end);
     }
 
-    fn visit_stmt_continue(&mut self, _stmt: &StmtContinueType) {
-        let cond = self.loops.last().unwrap().cond;
+    fn visit_stmt_continue(&mut self, stmt: &StmtContinueType) {
+        let cond = self.find_loop(stmt.label).cond;
         self.gen.emit_jump(cond);
     }
 
+    fn find_loop(&self, label: Option<Name>) -> &LoopLabels {
+        match label {
+            Some(_) => self.loops.iter().rev().find(|l| l.label == label).unwrap(),
+            None => self.loops.last().unwrap(),
+        }
+    }
+
     // TODO - implement other expressions
     fn visit_expr(&mut self, expr: &Expr, dest: DataDest) -> Register {
         match *expr {
@@ -246,6 +259,8 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             // ExprConv(ref expr) => {},
             // ExprTry(ref expr) => {},
             // ExprLambda(ref expr) => {},
+            // ExprTuple(ref expr) => {},
+            // ExprTemplate(ref expr) => {},
             _ => unimplemented!(),
         }
     }
@@ -1145,6 +1160,20 @@ mod tests {
         assert_eq!(expected, fct.code());
     }
 
+    #[test]
+    fn gen_stmt_labeled_break() {
+        let fct = code("fun f() { outer: while true { loop { break outer; } } }");
+        let expected = vec![
+            ConstTrue(r(0)),
+            JumpIfFalse(r(0), bc(5)),
+            Jump(bc(5)),
+            Jump(bc(2)),
+            Jump(bc(0)),
+            RetVoid,
+        ];
+        assert_eq!(expected, fct.code());
+    }
+
     #[test]
     fn gen_stmt_continue() {
         let fct = code("fun f() { while true { continue; } }");
@@ -6,7 +6,8 @@ use std::ops::{Index, IndexMut};
 use std::sync::Arc;
 
 use crate::ctxt::VM;
-use crate::ctxt::{FctId, ImplId, TraitId, TypeParam};
+use crate::ctxt::{FctId, GlobalId, ImplId, TraitId, TypeParam};
+use crate::sym::ModuleId;
 use crate::ty::BuiltinType;
 use crate::utils::GrowableVec;
 use crate::vtable::VTableBox;
@@ -54,9 +55,11 @@ pub struct Class {
     pub internal: bool,
     pub internal_resolved: bool,
     pub has_constructor: bool,
+    pub module: Option<ModuleId>,
 
     pub constructor: Option<FctId>,
     pub fields: Vec<Field>,
+    pub static_fields: Vec<GlobalId>,
     pub methods: Vec<FctId>,
 
     pub traits: Vec<TraitId>,
@@ -137,6 +140,16 @@ impl Class {
         }
     }
 
+    pub fn find_static_field(&self, vm: &VM, name: Name) -> Option<GlobalId> {
+        for &global_id in &self.static_fields {
+            if vm.globals.idx(global_id).lock().name == name {
+                return Some(global_id);
+            }
+        }
+
+        None
+    }
+
     pub fn find_method(&self, vm: &VM, name: Name, is_static: bool) -> Option<FctId> {
         let mut classid = self.id;
 
@@ -282,6 +295,7 @@ pub struct Field {
     pub ty: BuiltinType,
     pub offset: i32,
     pub reassignable: bool,
+    pub is_pub: bool,
 }
 
 impl Index<FieldId> for Vec<Field> {
@@ -338,6 +352,7 @@ pub struct ClassDef {
     pub size: ClassSize,
     pub ref_fields: Vec<i32>,
     pub vtable: Option<VTableBox>,
+    pub itables: Vec<ITable>,
 }
 
 impl ClassDef {
@@ -364,6 +379,17 @@ impl ClassDef {
     }
 }
 
+// Interface table for a trait implemented by a class (or one of its super
+// classes), used for calls on trait objects. Entries are in the order of
+// the trait's methods and are filled when the method is called first.
+#[derive(Debug, Clone)]
+pub struct ITable {
+    pub trait_id: TraitId,
+    pub impl_id: ImplId,
+    pub type_params: TypeParams, // type params of the class that declares the impl
+    pub entries: Vec<usize>,     // 0 when not compiled yet
+}
+
 #[derive(Debug, Clone)]
 pub struct FieldDef {
     pub offset: i32,
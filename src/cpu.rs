@@ -1,5 +1,7 @@
 use std::convert::From;
 
+use crate::ty::BuiltinType;
+
 #[cfg(target_arch = "x86_64")]
 pub use self::x64::*;
 
@@ -43,3 +45,23 @@ pub enum Mem {
     // reg1 * val1 + val2
     Offset(Reg, i32, i32),
 }
+
+// true when arguments of these types are all passed in registers,
+// none of them ends up on the stack
+pub fn args_in_registers<I>(types: I) -> bool
+where
+    I: IntoIterator<Item = BuiltinType>,
+{
+    let mut regs = 0;
+    let mut fregs = 0;
+
+    for ty in types {
+        if ty.is_float() {
+            fregs += 1;
+        } else {
+            regs += 1;
+        }
+    }
+
+    regs <= REG_PARAMS.len() && fregs <= FREG_PARAMS.len()
+}
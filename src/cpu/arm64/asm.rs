@@ -158,6 +158,11 @@ pub fn nop() -> u32 {
     cls_system(0)
 }
 
+// dmb ishld: orders earlier loads before later loads and stores
+pub fn dmb_ishld() -> u32 {
+    0xD50339BF
+}
+
 fn cls_system(imm: u32) -> u32 {
     assert!(fits_u7(imm));
 
@@ -1312,6 +1317,11 @@ mod tests {
         assert_emit!(0xd503201f; nop());
     }
 
+    #[test]
+    fn test_dmb_ishld() {
+        assert_emit!(0xd50339bf; dmb_ishld());
+    }
+
     #[test]
     fn test_ret() {
         assert_emit!(0xd65f03c0; ret());
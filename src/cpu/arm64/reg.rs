@@ -4,6 +4,9 @@ pub const REG_COUNT: usize = 31;
 pub static REG_PARAMS: [Reg; 8] = [R0, R1, R2, R3, R4, R5, R6, R7];
 pub static SCRATCH: [Reg; 5] = [R9, R12, R13, R14, R15];
 
+// callee-saved registers used for local variables
+pub const REG_VARS: [Reg; 4] = [R19, R20, R21, R22];
+
 pub const REG_RESULT: Reg = R0;
 pub const REG_TMP1: Reg = R10;
 pub const REG_TMP2: Reg = R11;
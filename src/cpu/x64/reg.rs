@@ -4,6 +4,9 @@ pub const REG_COUNT: usize = 16;
 pub static REG_PARAMS: [Reg; 6] = [RDI, RSI, RDX, RCX, R8, R9];
 pub static SCRATCH: [Reg; 3] = [R9, R8, RDI];
 
+// callee-saved registers used for local variables
+pub const REG_VARS: [Reg; 4] = [RBX, R12, R13, R14];
+
 pub const REG_RESULT: Reg = RAX;
 pub const REG_TMP1: Reg = R10;
 pub const REG_TMP2: Reg = R11;
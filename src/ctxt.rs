@@ -1,36 +1,45 @@
-use parking_lot::{Mutex, RwLock};
+use parking_lot::{Condvar, Mutex, RwLock};
 use std::collections::hash_map::Iter;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::mem;
 use std::ops::{Index, IndexMut};
 use std::ptr;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
+use std::thread::{self, ThreadId};
 
 use crate::driver::cmd::Args;
 use dora_parser::error::diag::Diagnostic;
 
 use crate::baseline;
+use crate::baseline::compile_queue::CompileQueue;
 use crate::baseline::dora_compile;
 use crate::baseline::dora_entry;
 use crate::baseline::dora_native::{self, InternalFct, InternalFctDescriptor, NativeThunks};
 use crate::baseline::dora_throw;
 use crate::baseline::fct::{JitFct, JitFctId};
 use crate::baseline::map::{CodeDescriptor, CodeMap};
-use crate::class::{Class, ClassDef, ClassDefId, ClassId, FieldId, TypeParams};
+use crate::baseline::unload;
+use crate::class::{Class, ClassDef, ClassDefId, ClassId, FieldId, TypeParamId, TypeParams};
 use crate::exception::DoraToNativeInfo;
 use crate::gc::{Address, Gc};
 use crate::object::{Ref, Testing};
+use crate::os::gdb::GdbJit;
 use crate::os::perf::counters::PerfCounters;
-use crate::safepoint::{PollingPage, Safepoint};
-use crate::semck::specialize::{specialize_class_id, specialize_class_id_params};
+use crate::os::perf::jitdump::JitDump;
+use crate::safepoint::{self, PollingPage, Safepoint};
+use crate::semck::specialize::{
+    specialize_class_id, specialize_class_id_params, specialize_for_trait,
+};
 use crate::stdlib;
 use crate::sym::Sym::*;
 use crate::sym::*;
 use crate::threads::{Threads, THREAD};
-use crate::ty::{BuiltinType, LambdaTypes, TypeLists};
+use crate::ty::{BuiltinType, LambdaTypes, TypeListId, TypeLists};
 use crate::utils::GrowableVec;
 use dora_parser::ast;
+use dora_parser::ast::visit::Visitor;
 use dora_parser::interner::*;
 use dora_parser::lexer::position::Position;
 
@@ -96,6 +105,7 @@ pub struct SemContext<'ast> {
     pub native_thunks: Mutex<NativeThunks>,
     pub polling_page: PollingPage,
     pub lists: Mutex<TypeLists>,
+    pub tuple_defs: Mutex<HashMap<TypeListId, ClassDefId>>, // class definitions of tuples
     pub lambda_types: Mutex<LambdaTypes>,
     pub compiler_thunk: Mutex<Address>,
     pub dora_entry: Mutex<Address>,
@@ -103,7 +113,12 @@ pub struct SemContext<'ast> {
     pub throw_thunk: Mutex<Address>,
     pub threads: Threads,
     pub safepoint: Safepoint,
+    pub global_inits: GlobalInits,
     pub perf_counters: PerfCounters,
+    pub perf_jitdump: JitDump,
+    pub compile_queue: CompileQueue,
+    pub compilation_lock: RwLock<()>, // held while compiling, unloading code holds it exclusively
+    pub gdb_jit: GdbJit,
 }
 
 impl<'ast> SemContext<'ast> {
@@ -113,6 +128,8 @@ impl<'ast> SemContext<'ast> {
         let empty_trait_id: TraitId = 0.into();
         let gc = Gc::new(&args);
         let perf_counters = PerfCounters::new(args.flag_gc_counters);
+        let perf_jitdump = JitDump::new(args.flag_enable_perf);
+        let compile_queue = CompileQueue::new(args.flag_compiler_threads);
 
         let ctxt = Box::new(SemContext {
             args: args,
@@ -135,22 +152,32 @@ impl<'ast> SemContext<'ast> {
                 double_class: empty_class_id,
                 object_class: empty_class_id,
                 string_class: empty_class_id,
+                string_buf_class: empty_class_id,
 
                 array_class: empty_class_id,
 
                 testing_class: empty_class_id,
                 exception_class: empty_class_id,
                 stack_trace_element_class: empty_class_id,
+                gc_stats_class: empty_class_id,
+                gc_space_stats_class: empty_class_id,
+                out_of_memory_error_class: empty_class_id,
 
                 equals_trait: empty_trait_id,
                 comparable_trait: empty_trait_id,
                 iterator_trait: Mutex::new(None),
 
+                range_fct: None,
+
                 int_array_def: Mutex::new(None),
                 str_class_def: Mutex::new(None),
                 obj_class_def: Mutex::new(None),
                 ste_class_def: Mutex::new(None),
                 ex_class_def: Mutex::new(None),
+                gc_stats_class_def: Mutex::new(None),
+                gc_space_stats_class_def: Mutex::new(None),
+                gc_space_stats_array_def: Mutex::new(None),
+                oom_class_def: Mutex::new(None),
 
                 free_object_class_def: empty_class_def_id,
                 free_array_class_def: empty_class_def_id,
@@ -165,6 +192,7 @@ impl<'ast> SemContext<'ast> {
             polling_page: PollingPage::new(),
             lists: Mutex::new(TypeLists::new()),
             lambda_types: Mutex::new(LambdaTypes::new()),
+            tuple_defs: Mutex::new(HashMap::new()),
             native_thunks: Mutex::new(NativeThunks::new()),
             compiler_thunk: Mutex::new(Address::null()),
             dora_entry: Mutex::new(Address::null()),
@@ -172,7 +200,12 @@ impl<'ast> SemContext<'ast> {
             throw_thunk: Mutex::new(Address::null()),
             threads: Threads::new(),
             safepoint: Safepoint::new(),
+            global_inits: GlobalInits::new(),
             perf_counters: perf_counters,
+            perf_jitdump: perf_jitdump,
+            compile_queue: compile_queue,
+            compilation_lock: RwLock::new(()),
+            gdb_jit: GdbJit::new(),
         });
 
         set_vm(&ctxt);
@@ -191,7 +224,12 @@ impl<'ast> SemContext<'ast> {
         let dora_entry_thunk = self.dora_entry_thunk();
         let fct: extern "C" fn(Address, Address) -> i32 =
             unsafe { mem::transmute(dora_entry_thunk) };
-        fct(tld, ptr)
+
+        safepoint::enter_dora(self);
+        let ret = fct(tld, ptr);
+        safepoint::leave_dora();
+
+        ret
     }
 
     pub fn run_test(&self, fct_id: FctId, testing: Ref<Testing>) {
@@ -205,7 +243,10 @@ impl<'ast> SemContext<'ast> {
         let dora_entry_thunk = self.dora_entry_thunk();
         let fct: extern "C" fn(Address, Address, Ref<Testing>) -> i32 =
             unsafe { mem::transmute(dora_entry_thunk) };
+
+        safepoint::enter_dora(self);
         fct(tld, ptr, testing);
+        safepoint::leave_dora();
     }
 
     fn ensure_compiled(&self, fct_id: FctId) -> Address {
@@ -214,6 +255,7 @@ impl<'ast> SemContext<'ast> {
 
         THREAD.with(|thread| {
             thread.borrow().use_dtn(&mut dtn, || {
+                unload::reclaim_if_needed(self);
                 baseline::generate(self, fct_id, &type_params, &type_params)
             })
         })
@@ -245,13 +287,9 @@ impl<'ast> SemContext<'ast> {
 
         let mut sym = self.sym.lock();
 
-        match sym.get(name) {
-            Some(sym) => Err(sym),
-            None => {
-                assert!(sym.insert(name, SymFct(fctid)).is_none());
-
-                Ok(fctid)
-            }
+        match sym.try_insert(name, SymFct(fctid)) {
+            Ok(()) => Ok(fctid),
+            Err(sym) => Err(sym),
         }
     }
 
@@ -410,6 +448,12 @@ pub struct StructFieldDef {
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GlobalId(u32);
 
+impl GlobalId {
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
 impl From<u32> for GlobalId {
     fn from(data: u32) -> GlobalId {
         GlobalId(data)
@@ -423,6 +467,8 @@ pub struct GlobalData<'ast> {
     pub pos: Position,
     pub ty: BuiltinType,
     pub reassignable: bool,
+    pub is_pub: bool,
+    pub module: Option<ModuleId>,
     pub name: Name,
     pub getter: Option<FctId>,
     pub address_init: Address,
@@ -435,6 +481,76 @@ impl<'ast> GrowableVec<Mutex<GlobalData<'ast>>> {
     }
 }
 
+// guards the initializers of globals: each initializer runs exactly once,
+// threads accessing the global meanwhile wait until it has finished
+pub struct GlobalInits {
+    // thread running the initializer and frame pointer of the function
+    // that accessed the global
+    running: Mutex<HashMap<GlobalId, (ThreadId, usize)>>,
+    finished: Condvar,
+}
+
+impl GlobalInits {
+    pub fn new() -> GlobalInits {
+        GlobalInits {
+            running: Mutex::new(HashMap::new()),
+            finished: Condvar::new(),
+        }
+    }
+
+    // returns true when the caller needs to run the initializer
+    pub fn start(&self, id: GlobalId, address_init: Address, fp: usize) -> bool {
+        let current = thread::current().id();
+        let mut running = self.running.lock();
+
+        loop {
+            if init_flag(address_init).load(Ordering::Acquire) != 0 {
+                return false;
+            }
+
+            match running.get(&id) {
+                // the initializer accesses its own global: like
+                // in Java this access observes the zero value
+                Some(&(thread, _)) if thread == current => return false,
+                Some(_) => self.finished.wait(&mut running),
+                None => {
+                    running.insert(id, (current, fp));
+                    return true;
+                }
+            }
+        }
+    }
+
+    pub fn finish(&self, id: GlobalId, address_init: Address) {
+        let mut running = self.running.lock();
+
+        // publishes the value stored by the initializer, compiled code
+        // fences its loads after reading the flag
+        init_flag(address_init).store(1, Ordering::Release);
+        running.remove(&id);
+        self.finished.notify_all();
+    }
+
+    // an exception unwinds the current thread's stack up to the frame `fp`:
+    // initializers started from this frame or from unwound frames never
+    // finish, the next access of their global runs them again
+    pub fn unwind(&self, fp: usize) {
+        let current = thread::current().id();
+        let mut running = self.running.lock();
+        let len = running.len();
+
+        running.retain(|_, &mut (thread, start_fp)| thread != current || start_fp > fp);
+
+        if running.len() != len {
+            self.finished.notify_all();
+        }
+    }
+}
+
+fn init_flag(address_init: Address) -> &'static AtomicU8 {
+    unsafe { &*address_init.to_ptr::<AtomicU8>() }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ImplId(u32);
 
@@ -451,6 +567,13 @@ pub struct ImplData {
     pub trait_id: Option<TraitId>,
     pub class_id: Option<ClassId>,
     pub methods: Vec<FctId>,
+
+    // names given to the class's type params in the impl header
+    pub type_params: Vec<Name>,
+
+    // type arguments for the trait's type params, may refer to the
+    // type params of the class
+    pub trait_type_params: TypeParams,
 }
 
 impl ImplData {
@@ -498,6 +621,7 @@ pub struct TraitData {
     pub id: TraitId,
     pub pos: Position,
     pub name: Name,
+    pub type_params: Vec<TypeParam>,
     pub methods: Vec<FctId>,
 }
 
@@ -509,15 +633,47 @@ impl TraitData {
         name: Name,
         replace: Option<BuiltinType>,
         args: &[BuiltinType],
+    ) -> Option<FctId> {
+        self.find_method_with_type_params(
+            ctxt,
+            is_static,
+            name,
+            replace,
+            &TypeParams::empty(),
+            args,
+        )
+    }
+
+    // like `find_method` but the trait's type params in the method's
+    // params are replaced with the given types first
+    pub fn find_method_with_type_params(
+        &self,
+        ctxt: &SemContext,
+        is_static: bool,
+        name: Name,
+        replace: Option<BuiltinType>,
+        type_params: &TypeParams,
+        args: &[BuiltinType],
     ) -> Option<FctId> {
         for &method in &self.methods {
             let method = ctxt.fcts.idx(method);
             let method = method.read();
 
-            if method.name == name
-                && method.is_static == is_static
-                && params_match(replace, method.params_without_self(), args)
-            {
+            if method.name != name || method.is_static != is_static {
+                continue;
+            }
+
+            let params: Vec<_> = if type_params.len() > 0 {
+                method
+                    .params_without_self()
+                    .iter()
+                    .map(|&ty| specialize_for_trait(ctxt, ty, BuiltinType::This, type_params))
+                    .collect()
+            } else {
+                method.params_without_self().to_vec()
+            };
+
+            if params_match(replace, &params, args) {
                 return Some(method.id);
             }
         }
@@ -613,21 +769,31 @@ pub struct KnownElements {
     pub double_class: ClassId,
     pub object_class: ClassId,
     pub string_class: ClassId,
+    pub string_buf_class: ClassId,
     pub array_class: ClassId,
 
     pub testing_class: ClassId,
     pub exception_class: ClassId,
     pub stack_trace_element_class: ClassId,
+    pub gc_stats_class: ClassId,
+    pub gc_space_stats_class: ClassId,
+    pub out_of_memory_error_class: ClassId,
 
     pub equals_trait: TraitId,
     pub comparable_trait: TraitId,
     pub iterator_trait: Mutex<Option<TraitId>>,
 
+    pub range_fct: Option<FctId>,
+
     int_array_def: Mutex<Option<ClassDefId>>,
     str_class_def: Mutex<Option<ClassDefId>>,
     obj_class_def: Mutex<Option<ClassDefId>>,
     ste_class_def: Mutex<Option<ClassDefId>>,
     ex_class_def: Mutex<Option<ClassDefId>>,
+    gc_stats_class_def: Mutex<Option<ClassDefId>>,
+    gc_space_stats_class_def: Mutex<Option<ClassDefId>>,
+    gc_space_stats_array_def: Mutex<Option<ClassDefId>>,
+    oom_class_def: Mutex<Option<ClassDefId>>,
 
     pub free_object_class_def: ClassDefId,
     pub free_array_class_def: ClassDefId,
@@ -699,6 +865,55 @@ impl KnownElements {
         }
     }
 
+    pub fn out_of_memory_error(&self, ctxt: &SemContext) -> ClassDefId {
+        let mut oom_class_def = self.oom_class_def.lock();
+
+        if let Some(cls_id) = *oom_class_def {
+            cls_id
+        } else {
+            let cls_id = specialize_class_id(ctxt, self.out_of_memory_error_class);
+            *oom_class_def = Some(cls_id);
+            cls_id
+        }
+    }
+
+    pub fn gc_stats(&self, ctxt: &SemContext) -> ClassDefId {
+        let mut gc_stats_class_def = self.gc_stats_class_def.lock();
+
+        if let Some(cls_id) = *gc_stats_class_def {
+            cls_id
+        } else {
+            let cls_id = specialize_class_id(ctxt, self.gc_stats_class);
+            *gc_stats_class_def = Some(cls_id);
+            cls_id
+        }
+    }
+
+    pub fn gc_space_stats(&self, ctxt: &SemContext) -> ClassDefId {
+        let mut gc_space_stats_class_def = self.gc_space_stats_class_def.lock();
+
+        if let Some(cls_id) = *gc_space_stats_class_def {
+            cls_id
+        } else {
+            let cls_id = specialize_class_id(ctxt, self.gc_space_stats_class);
+            *gc_space_stats_class_def = Some(cls_id);
+            cls_id
+        }
+    }
+
+    pub fn gc_space_stats_array(&self, ctxt: &SemContext) -> ClassDefId {
+        let mut gc_space_stats_array_def = self.gc_space_stats_array_def.lock();
+
+        if let Some(cls_id) = *gc_space_stats_array_def {
+            cls_id
+        } else {
+            let type_args: TypeParams = vec![ctxt.cls(self.gc_space_stats_class)].into();
+            let cls_id = specialize_class_id_params(ctxt, self.array_class, &type_args);
+            *gc_space_stats_array_def = Some(cls_id);
+            cls_id
+        }
+    }
+
     pub fn find_class(&self, ty: BuiltinType) -> Option<ClassId> {
         match ty {
             BuiltinType::Bool => Some(self.bool_class),
@@ -770,6 +985,7 @@ pub struct Fct<'ast> {
     pub pos: Position,
     pub name: Name,
     pub parent: FctParent,
+    pub module: Option<ModuleId>,
     pub has_open: bool,
     pub has_override: bool,
     pub has_final: bool,
@@ -786,10 +1002,12 @@ pub struct Fct<'ast> {
 
     pub vtable_index: Option<u32>,
     pub impl_for: Option<FctId>,
+    pub default_impl: bool, // copy of a trait's default method in an impl
     pub initialized: bool,
     pub throws: bool,
 
     pub type_params: Vec<TypeParam>,
+    pub where_bounds: HashMap<TypeParamId, HashSet<TraitId>>, // additional bounds on class type params
     pub kind: FctKind,
 }
 
@@ -826,6 +1044,16 @@ impl<'ast> Fct<'ast> {
         }
     }
 
+    // class whose type params are in scope, methods of impls
+    // use the type params of the implementing class
+    pub fn owner_cls_id(&self, ctxt: &SemContext) -> Option<ClassId> {
+        match self.parent {
+            FctParent::Class(cls_id) => Some(cls_id),
+            FctParent::Impl(impl_id) => ctxt.impls[impl_id].read().class_id,
+            _ => None,
+        }
+    }
+
     pub fn full_name(&self, ctxt: &SemContext) -> String {
         let mut repr = String::new();
 
@@ -892,6 +1120,24 @@ impl<'ast> Fct<'ast> {
         self.ast.pos
     }
 
+    // path of the file this function is defined in
+    pub fn source_file(&self, ctxt: &SemContext<'ast>) -> Option<&'ast str> {
+        let mut finder = FctFinder {
+            id: self.ast.id,
+            found: false,
+        };
+
+        for file in &ctxt.ast.files {
+            finder.visit_file(file);
+
+            if finder.found {
+                return Some(&file.path);
+            }
+        }
+
+        None
+    }
+
     pub fn src(&self) -> &RwLock<FctSrc> {
         match self.kind {
             FctKind::Source(ref src) => src,
@@ -920,6 +1166,25 @@ impl<'ast> Fct<'ast> {
     }
 }
 
+struct FctFinder {
+    id: ast::NodeId,
+    found: bool,
+}
+
+impl<'ast> Visitor<'ast> for FctFinder {
+    fn visit_fct(&mut self, f: &'ast ast::Function) {
+        self.found = self.found || f.id == self.id;
+    }
+
+    fn visit_method(&mut self, f: &'ast ast::Function) {
+        self.found = self.found || f.id == self.id;
+    }
+
+    fn visit_ctor(&mut self, f: &'ast ast::Function) {
+        self.found = self.found || f.id == self.id;
+    }
+}
+
 #[derive(Debug)]
 pub enum FctKind {
     Source(RwLock<FctSrc>),
@@ -1098,6 +1363,8 @@ pub struct FctSrc {
     pub map_convs: NodeMap<ConvInfo>,
     pub map_cls: NodeMap<ClassId>,
     pub map_fors: NodeMap<ForTypeInfo>,
+    pub map_templates: NodeMap<TemplateTypeInfo>,
+    pub map_boxes: NodeMap<ClassId>, // primitive values that are boxed into trait objects
 
     pub always_returns: bool, // true if function is always exited via return statement
     // false if function execution could reach the closing } of this function
@@ -1115,6 +1382,8 @@ impl Clone for FctSrc {
             map_convs: self.map_convs.clone(),
             map_cls: self.map_cls.clone(),
             map_fors: self.map_fors.clone(),
+            map_templates: self.map_templates.clone(),
+            map_boxes: self.map_boxes.clone(),
 
             vars: self.vars.clone(),
             always_returns: self.always_returns,
@@ -1133,6 +1402,8 @@ impl FctSrc {
             map_convs: NodeMap::new(),
             map_cls: NodeMap::new(),
             map_fors: NodeMap::new(),
+            map_templates: NodeMap::new(),
+            map_boxes: NodeMap::new(),
 
             vars: Vec::new(),
             always_returns: false,
@@ -1225,6 +1496,13 @@ impl Store {
             Store::Reg => panic!(),
         }
     }
+
+    pub fn ty(&self) -> BuiltinType {
+        match *self {
+            Store::Temp(_, ty) => ty,
+            Store::Reg => panic!(),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -1275,6 +1553,16 @@ pub struct ForTypeInfo {
     pub iterator_type: BuiltinType,
 }
 
+#[derive(Debug, Clone)]
+pub struct TemplateTypeInfo {
+    pub empty: FctId,
+    pub append_string: FctId,
+    pub to_string: FctId,
+
+    // `toString()` of every part, parts of type String are appended as is
+    pub parts: Vec<Option<FctId>>,
+}
+
 #[derive(Debug, Clone)]
 pub enum CallType {
     Fct(FctId, TypeParams, TypeParams),
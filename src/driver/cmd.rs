@@ -3,11 +3,12 @@ use std::cmp::{max, min};
 use std::default::Default;
 use std::ops::Deref;
 
+use crate::baseline::info::DEFAULT_INLINE_LIMIT;
 use crate::gc::M;
 use docopt::Docopt;
 use rustc_serialize;
 
-use crate::gc::{DEFAULT_CODE_SPACE_LIMIT, DEFAULT_PERM_SPACE_LIMIT};
+use crate::gc::{DEFAULT_CODE_SPACE_LIMIT, DEFAULT_PERM_SPACE_LIMIT, DEFAULT_UNCOMMIT_DELAY};
 
 pub fn parse() -> Args {
     Docopt::new(USAGE)
@@ -18,6 +19,7 @@ pub fn parse() -> Args {
 // Write the Docopt usage string.
 static USAGE: &'static str = "
 Usage: dora test [options] <file>
+       dora build [options] <file>
        dora [options] <file> [--] [<argument>...]
        dora (--version | --help)
 
@@ -34,10 +36,16 @@ Options:
     --emit-debug-throw      Emits debug instruction at beginning of throw thunk.
     --emit-debug-entry      Emits debug instruction at beginning of entry thunk.
     --omit-bounds-check     Omit array index out of bounds checks.
+    --inline-limit=<n>      Maximum size of methods inlined by the baseline compiler.
+    --no-inline             Disable inlining in the baseline compiler.
+    --compiler-threads=<num>  Compile callees of executed functions ahead of time
+                            on given number of background threads (default: 0).
+    --print-compilation     Print compiled functions with code size and compile time.
     --check                 Only type check given program.
     --asm-syntax TYPE       Emits assembly with Intel or AT&T syntax.
                             Allowed values: intel, att.
-    --enable-perf           Enable dump for perf.
+    --enable-perf           Write perf map and jitdump file for perf.
+    --enable-gdb            Register compiled code with the JIT interface of gdb and lldb.
     --gc-events             Dump GC events.
     --gc-stress             Collect garbage at every allocation.
     --gc-stress-minor       Minor collection at every allocation.
@@ -49,19 +57,24 @@ Options:
     --gc-verbose            Verbose GC.
     --gc-dev-verbose        Verbose GC for developers.
     --gc-verify             Verify heap before and after collections.
+    --gc-dump-heap-on-oom   Print histogram of live objects when out of memory.
     --gc-verify-write       Verify references when storing in the heap.
     --gc-worker=<num>       Number of GC worker threads.
     --gc=<name>             Switch GC. Possible values: zero, copy, swiper (default).
     --gc-young-size=<SIZE>  Use fixed size for young generation.
     --gc-young-appel        Use Appel dynamic resizing of young generation.
     --gc-semi-ratio=<num>   Use fixed ratio of semi space in young generation.
+    --gc-uncommit-delay=<ms>  Return unused old generation memory to the OS once
+                            heap usage stayed low for given milliseconds.
 
     --disable-tlab          Disable tlab allocation.
     --disable-barrier       Disable barriers.
+    --disable-regalloc      Keep all local variables on the stack.
 
     --min-heap-size=<SIZE>  Set minimum heap size.
     --max-heap-size=<SIZE>  Set maximum heap size.
     --code-size=<SIZE>      Set code size limit.
+    --code-stress           Unload unreachable code before every compilation.
     --perm-size=<SIZE>      Set perm size limit.
 ";
 
@@ -76,7 +89,12 @@ pub struct Args {
     pub flag_emit_llvm: bool,
     pub flag_emit_stubs: bool,
     pub flag_enable_perf: bool,
+    pub flag_enable_gdb: bool,
     pub flag_omit_bounds_check: bool,
+    flag_inline_limit: Option<usize>,
+    pub flag_no_inline: bool,
+    pub flag_compiler_threads: usize,
+    pub flag_print_compilation: bool,
     pub flag_version: bool,
     pub flag_emit_debug: Option<String>,
     pub flag_emit_debug_throw: bool,
@@ -94,20 +112,25 @@ pub struct Args {
     pub flag_gc_verbose: bool,
     pub flag_gc_dev_verbose: bool,
     pub flag_gc_verify: bool,
+    pub flag_gc_dump_heap_on_oom: bool,
     pub flag_gc_verify_write: bool,
     pub flag_gc_worker: usize,
     flag_gc_young_size: Option<MemSize>,
     pub flag_gc_semi_ratio: Option<usize>,
+    flag_gc_uncommit_delay: Option<u64>,
     pub flag_gc: Option<CollectorName>,
     pub flag_min_heap_size: Option<MemSize>,
     pub flag_max_heap_size: Option<MemSize>,
     pub flag_code_size: Option<MemSize>,
+    pub flag_code_stress: bool,
     pub flag_perm_size: Option<MemSize>,
     pub flag_check: bool,
     pub flag_disable_tlab: bool,
     pub flag_disable_barrier: bool,
+    pub flag_disable_regalloc: bool,
 
     pub cmd_test: bool,
+    pub cmd_build: bool,
 }
 
 impl Args {
@@ -152,6 +175,19 @@ impl Args {
         self.flag_gc_young_size.is_none()
     }
 
+    pub fn gc_uncommit_delay(&self) -> u64 {
+        self.flag_gc_uncommit_delay
+            .unwrap_or(DEFAULT_UNCOMMIT_DELAY)
+    }
+
+    pub fn inline_limit(&self) -> usize {
+        if self.flag_no_inline {
+            0
+        } else {
+            self.flag_inline_limit.unwrap_or(DEFAULT_INLINE_LIMIT)
+        }
+    }
+
     pub fn parallel_minor(&self) -> bool {
         self.flag_gc_parallel_minor || self.flag_gc_parallel
     }
@@ -177,7 +213,12 @@ impl Default for Args {
             flag_emit_debug_throw: false,
             flag_emit_debug_entry: false,
             flag_enable_perf: false,
+            flag_enable_gdb: false,
             flag_omit_bounds_check: false,
+            flag_inline_limit: None,
+            flag_no_inline: false,
+            flag_compiler_threads: 0,
+            flag_print_compilation: false,
             flag_version: false,
             flag_asm_syntax: None,
             flag_gc_events: false,
@@ -191,20 +232,25 @@ impl Default for Args {
             flag_gc_verbose: false,
             flag_gc_dev_verbose: false,
             flag_gc_verify: false,
+            flag_gc_dump_heap_on_oom: false,
             flag_gc_verify_write: false,
             flag_gc_worker: 0,
             flag_gc_young_size: None,
             flag_gc_semi_ratio: None,
+            flag_gc_uncommit_delay: None,
             flag_gc: None,
             flag_min_heap_size: None,
             flag_max_heap_size: None,
             flag_code_size: None,
+            flag_code_stress: false,
             flag_perm_size: None,
             flag_check: false,
             flag_disable_tlab: false,
             flag_disable_barrier: false,
+            flag_disable_regalloc: false,
 
             cmd_test: false,
+            cmd_build: false,
         }
     }
 }
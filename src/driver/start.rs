@@ -6,6 +6,7 @@ use crate::ctxt::{exception_get_and_clear, Fct, FctId};
 use dora_parser::ast::{self, Ast};
 use dora_parser::error::msg::Msg;
 
+use crate::baseline;
 use crate::driver::cmd;
 use crate::object;
 use crate::os;
@@ -74,6 +75,8 @@ pub fn start(content: Option<&str>) -> i32 {
         find_main(&vm)
     };
 
+    vm.diag.lock().dump_warnings();
+
     if vm.diag.lock().has_errors() {
         vm.diag.lock().dump();
         let no_errors = vm.diag.lock().errors().len();
@@ -92,8 +95,13 @@ pub fn start(content: Option<&str>) -> i32 {
         return 0;
     }
 
+    if vm.args.cmd_build {
+        return build(&vm, main.unwrap());
+    }
+
     let mut timer = Timer::new(vm.args.flag_gc_stats);
 
+    vm.gc.prepare_out_of_memory_error(&vm);
     vm.threads.attach_current_thread();
     vm.perf_counters.start();
 
@@ -105,6 +113,7 @@ pub fn start(content: Option<&str>) -> i32 {
 
     vm.threads.detach_current_thread();
     vm.threads.join_all();
+    vm.compile_queue.join();
     vm.perf_counters.stop();
 
     os::unregister_signals();
@@ -126,6 +135,7 @@ pub fn start(content: Option<&str>) -> i32 {
 fn run_tests<'ast>(vm: &VM<'ast>) -> i32 {
     let mut tests = 0;
     let mut passed = 0;
+    let mut ignored = 0;
 
     for fct in vm.fcts.iter() {
         let fct = fct.read();
@@ -134,6 +144,12 @@ fn run_tests<'ast>(vm: &VM<'ast>) -> i32 {
             continue;
         }
 
+        if fct.ast.is_ignored {
+            ignored += 1;
+            println!("test {} ... ignored", vm.interner.str(fct.name));
+            continue;
+        }
+
         tests += 1;
 
         print!("test {} ... ", vm.interner.str(fct.name));
@@ -147,10 +163,11 @@ fn run_tests<'ast>(vm: &VM<'ast>) -> i32 {
     }
 
     println!(
-        "{} tests executed; {} passed; {} failed.",
+        "{} tests executed; {} passed; {} failed; {} ignored.",
         tests,
         passed,
-        tests - passed
+        tests - passed,
+        ignored
     );
 
     // if all tests passed exit with 0, otherwise 1
@@ -185,9 +202,24 @@ fn is_test_fct<'ast>(vm: &VM<'ast>, fct: &Fct<'ast>) -> bool {
         return false;
     }
 
-    // the functions name needs to start with `test`
+    // the function needs to be marked with `#[test]` or its name needs to start with `test`
     let fct_name = vm.interner.str(fct.name);
-    fct_name.starts_with("test")
+    fct.ast.is_test || fct_name.starts_with("test")
+}
+
+// Compiles everything reachable from main without running the program.
+fn build<'ast>(vm: &VM<'ast>, main: FctId) -> i32 {
+    vm.threads.attach_current_thread();
+    let stats = baseline::aot::compile_reachable(vm, main);
+    vm.threads.detach_current_thread();
+    vm.compile_queue.join();
+
+    println!(
+        "compiled {} functions ({} bytes).",
+        stats.fcts, stats.code_size
+    );
+
+    0
 }
 
 fn run_main<'ast>(vm: &VM<'ast>, main: FctId) -> i32 {
@@ -220,6 +252,10 @@ fn parse_dir(
 
             if path.is_file() && path.extension().unwrap() == "dora" {
                 parse_file(path.to_str().unwrap(), id_generator, ast, interner)?;
+
+            // subdirectories are modules of the program
+            } else if path.is_dir() {
+                parse_dir(path.to_str().unwrap(), id_generator, ast, interner)?;
             }
         }
 
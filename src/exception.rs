@@ -1,9 +1,9 @@
 use std::ptr;
 
-use crate::baseline::fct::{CatchType, JitFctId};
+use crate::baseline::fct::{BailoutInfo, CatchType, JitBaselineFct, JitFctId};
 use crate::baseline::map::CodeDescriptor;
-use crate::cpu::fp_from_execstate;
-use crate::ctxt::{get_vm, VM};
+use crate::cpu::{fp_from_execstate, REG_VARS};
+use crate::ctxt::{get_vm, FctId, VM};
 use crate::execstate::ExecState;
 use crate::handle::root;
 use crate::object::{alloc, Array, Exception, IntArray, Obj, Ref, StackTraceElement, Str};
@@ -28,9 +28,32 @@ impl Stacktrace {
         self.elems.push(StackElem {
             fct_id: fct_id,
             lineno: lineno,
+            omitted: None,
         });
     }
 
+    // the call site at the return address called another function than the
+    // one of the previous frame: that function left through a tail call
+    // and its frame (and maybe more) are missing in between. When it called
+    // the same function, that function might have reused its frame for
+    // recursive calls.
+    fn check_tail_call(&mut self, vm: &VM, callee: FctId) {
+        let len = self.elems.len();
+
+        if len < 2 {
+            return;
+        }
+
+        let jit_fct = vm.jit_fcts.idx(self.elems[len - 2].fct_id);
+        let tail_recursive = jit_fct.to_base().map_or(false, |base| base.tail_recursive);
+
+        if jit_fct.fct_id() != callee {
+            self.elems[len - 1].omitted = Some(OmittedFrames::TailCall(callee));
+        } else if tail_recursive {
+            self.elems[len - 1].omitted = Some(OmittedFrames::TailRecursion);
+        }
+    }
+
     pub fn dump(&self, vm: &VM) {
         for (ind, elem) in self.elems.iter().enumerate() {
             let jit_fct = vm.jit_fcts.idx(elem.fct_id);
@@ -38,6 +61,24 @@ impl Stacktrace {
             let fct = vm.fcts.idx(fct_id);
             let fct = fct.read();
             let name = fct.full_name(vm);
+
+            match elem.omitted {
+                Some(OmittedFrames::TailCall(callee)) => {
+                    let callee = vm.fcts.idx(callee);
+                    let callee = callee.read();
+                    println!(
+                        "   (frames omitted by tail call in {})",
+                        callee.full_name(vm)
+                    );
+                }
+
+                Some(OmittedFrames::TailRecursion) => {
+                    println!("   (frames omitted by tail recursion)");
+                }
+
+                None => {}
+            }
+
             print!("{}: {}: ", ind, name);
 
             if elem.lineno == 0 {
@@ -52,6 +93,17 @@ impl Stacktrace {
 struct StackElem {
     fct_id: JitFctId,
     lineno: i32,
+
+    // frames missing between the previous frame and this one
+    omitted: Option<OmittedFrames>,
+}
+
+enum OmittedFrames {
+    // the function called at the return address made a tail call
+    TailCall(FctId),
+
+    // the function called at the return address called itself as jump
+    TailRecursion,
 }
 
 pub struct DoraToNativeInfo {
@@ -110,14 +162,14 @@ fn frames_from_dtns(stacktrace: &mut Stacktrace, vm: &VM) {
 }
 
 fn frames_from_pc(stacktrace: &mut Stacktrace, vm: &VM, pc: usize, mut fp: usize) {
-    if !determine_stack_entry(stacktrace, vm, pc) {
+    if !determine_stack_entry(stacktrace, vm, pc, false) {
         return;
     }
 
     while fp != 0 {
         let ra = unsafe { *((fp + 8) as *const usize) };
 
-        if !determine_stack_entry(stacktrace, vm, ra) {
+        if !determine_stack_entry(stacktrace, vm, ra, true) {
             return;
         }
 
@@ -125,7 +177,7 @@ fn frames_from_pc(stacktrace: &mut Stacktrace, vm: &VM, pc: usize, mut fp: usize
     }
 }
 
-fn determine_stack_entry(stacktrace: &mut Stacktrace, vm: &VM, pc: usize) -> bool {
+fn determine_stack_entry(stacktrace: &mut Stacktrace, vm: &VM, pc: usize, is_ra: bool) -> bool {
     let code_map = vm.code_map.lock();
     let data = code_map.get(pc.into());
 
@@ -143,6 +195,14 @@ fn determine_stack_entry(stacktrace: &mut Stacktrace, vm: &VM, pc: usize) -> boo
 
             stacktrace.push_entry(fct_id, lineno);
 
+            if is_ra {
+                if let Some(&BailoutInfo::Compile(callee, _, _, _)) =
+                    jit_fct.bailouts.get(offset as i32)
+                {
+                    stacktrace.check_tail_call(vm, callee);
+                }
+            }
+
             true
         }
 
@@ -159,6 +219,7 @@ fn determine_stack_entry(stacktrace: &mut Stacktrace, vm: &VM, pc: usize) -> boo
         Some(CodeDescriptor::TrapThunk) => true,
         Some(CodeDescriptor::ThrowThunk) => true,
         Some(CodeDescriptor::AllocThunk) => true,
+        Some(CodeDescriptor::GlobalInitThunk) => true,
         Some(CodeDescriptor::DoraEntry) => false,
 
         _ => {
@@ -175,10 +236,15 @@ enum HandlerFound {
     Stop,
 }
 
+#[repr(C)]
 pub struct ThrowResume {
     pc: usize,
     sp: usize,
     fp: usize,
+
+    // values of callee-saved registers used for local variables, initialized
+    // with the register values at the throw and updated for every unwound frame
+    regs: [usize; REG_VARS.len()],
 }
 
 pub extern "C" fn throw(exception: Ref<Obj>, resume: &mut ThrowResume) {
@@ -201,11 +267,17 @@ pub extern "C" fn throw(exception: Ref<Obj>, resume: &mut ThrowResume) {
         match res {
             HandlerFound::Yes => {
                 // handler found, resume from there
+                vm.global_inits.unwind(resume.fp);
                 return;
             }
 
             HandlerFound::Stop => {
                 // no handler found
+                if is_out_of_memory_error(vm, exception) {
+                    vm.gc.dump_usage();
+                    stdlib::trap(Trap::OOM.int());
+                }
+
                 stdlib::trap(Trap::THROW.int());
             }
 
@@ -265,21 +337,26 @@ fn find_handler(
                     // exception handlers are sorted, no more possible handlers
                     // in this function
 
+                    restore_saved_regs(jit_fct, fp, resume);
                     return HandlerFound::No;
                 }
             }
 
             // exception can only bubble up in stacktrace if current function
-            // is allowed to throw exceptions
-            if !jit_fct.throws {
+            // is allowed to throw exceptions, OutOfMemoryError can be raised
+            // at every allocation and is therefore allowed everywhere
+            if !jit_fct.throws && !is_out_of_memory_error(vm, exception) {
                 return HandlerFound::Stop;
             }
 
+            restore_saved_regs(jit_fct, fp, resume);
             HandlerFound::No
         }
 
         Some(CodeDescriptor::DoraEntry) => HandlerFound::Stop,
         Some(CodeDescriptor::ThrowThunk) => HandlerFound::No,
+        Some(CodeDescriptor::AllocThunk) => HandlerFound::No,
+        Some(CodeDescriptor::GlobalInitThunk) => HandlerFound::No,
 
         _ => {
             println!("data = {:?}", data);
@@ -288,6 +365,22 @@ fn find_handler(
     }
 }
 
+// frame is unwound: the caller's values of callee-saved registers are stored
+// in the frame's save slots
+fn restore_saved_regs(jit_fct: &JitBaselineFct, fp: usize, resume: &mut ThrowResume) {
+    for &(reg, offset) in &jit_fct.saved_regs {
+        let idx = REG_VARS.iter().position(|&r| r == reg).unwrap();
+        let slot = (fp as isize + offset as isize) as usize;
+
+        resume.regs[idx] = unsafe { *(slot as *const usize) };
+    }
+}
+
+fn is_out_of_memory_error(vm: &VM, exception: Ref<Obj>) -> bool {
+    let cls_def_id = exception.header().vtbl().class().id;
+    cls_def_id == vm.vips.out_of_memory_error(vm)
+}
+
 pub extern "C" fn retrieve_stack_trace(obj: Ref<Exception>) {
     let vm = get_vm();
     set_exception_backtrace(vm, obj, true);
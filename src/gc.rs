@@ -1,23 +1,31 @@
+use parking_lot::Mutex;
 use std::cmp::{Ord, Ordering, PartialOrd};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::atomic::{self, AtomicUsize};
 
+use crate::class::{ClassDef, ClassSize};
 use crate::ctxt::VM;
 use crate::driver::cmd::{Args, CollectorName};
+use crate::gc::code::CodeSpace;
 use crate::gc::compact::MarkCompactCollector;
 use crate::gc::copy::CopyCollector;
+use crate::gc::root::get_rootset;
 use crate::gc::space::{Space, SpaceConfig};
 use crate::gc::sweep::SweepCollector;
 use crate::gc::swiper::sweep::SweepSwiper;
-use crate::gc::swiper::{Swiper, CARD_SIZE};
+use crate::gc::swiper::{Swiper, CARD_SIZE, CARD_SIZE_BITS};
 use crate::gc::tlab::TLAB_OBJECT_SIZE;
 use crate::gc::zero::ZeroCollector;
 use crate::mem;
-use crate::object::{Header, Obj};
+use crate::object::{Exception, Header, Obj, Ref, Str};
 use crate::os;
+use crate::safepoint;
 use crate::vtable::VTable;
 
 pub mod arena;
 pub mod bump;
+pub mod code;
 pub mod compact;
 pub mod copy;
 pub mod freelist;
@@ -28,15 +36,20 @@ pub mod space;
 pub mod sweep;
 pub mod swiper;
 pub mod tlab;
+pub mod verify;
 pub mod zero;
 
 pub const K: usize = 1024;
 pub const M: usize = K * K;
 
 const CHUNK_SIZE: usize = 8 * K;
-pub const DEFAULT_CODE_SPACE_LIMIT: usize = 128 * K;
+pub const DEFAULT_CODE_SPACE_LIMIT: usize = 64 * M;
+pub const DEFAULT_CODE_RECLAIM_THRESHOLD: usize = 256 * K;
 pub const DEFAULT_PERM_SPACE_LIMIT: usize = 64 * K;
 
+// milliseconds heap usage needs to stay low before memory is uncommitted
+pub const DEFAULT_UNCOMMIT_DELAY: u64 = 1000;
+
 // young/old gen are aligned to at least this size
 const GEN_ALIGNMENT_BITS: usize = 17;
 const GEN_SIZE: usize = 1 << GEN_ALIGNMENT_BITS;
@@ -45,8 +58,15 @@ pub struct Gc {
     collector: Box<dyn Collector + Sync>,
     supports_tlab: bool,
 
-    code_space: Space,
+    code_space: CodeSpace,
     perm_space: Space,
+
+    // bytes handed out to mutators (includes whole TLABs)
+    allocated: AtomicUsize,
+
+    // OutOfMemoryError instance in perm space, throwing it
+    // doesn't need any heap memory
+    oom_error: Mutex<Address>,
 }
 
 impl Gc {
@@ -82,8 +102,11 @@ impl Gc {
             collector: collector,
             supports_tlab: supports_tlab,
 
-            code_space: Space::new(code_config, "code"),
+            code_space: CodeSpace::new(code_config, DEFAULT_CODE_RECLAIM_THRESHOLD),
             perm_space: Space::new(perm_config, "perm"),
+
+            allocated: AtomicUsize::new(0),
+            oom_error: Mutex::new(Address::null()),
         }
     }
 
@@ -95,10 +118,32 @@ impl Gc {
         self.collector.card_table_offset()
     }
 
+    // native code storing a reference into an object that might be older
+    // than the referenced object needs to mark the card like compiled code
+    pub fn write_barrier(&self, object: Address) {
+        if !self.needs_write_barrier() {
+            return;
+        }
+
+        let card = self.card_table_offset() + (object.to_usize() >> CARD_SIZE_BITS);
+
+        unsafe {
+            *(card as *mut u8) = 0;
+        }
+    }
+
     pub fn alloc_code(&self, size: usize) -> Address {
         self.code_space.alloc(size)
     }
 
+    pub fn free_code(&self, region: Region) {
+        self.code_space.free(region);
+    }
+
+    pub fn code_space(&self) -> &CodeSpace {
+        &self.code_space
+    }
+
     pub fn alloc_perm(&self, size: usize) -> Address {
         self.perm_space.alloc(size)
     }
@@ -115,7 +160,13 @@ impl Gc {
         if size < TLAB_OBJECT_SIZE && self.supports_tlab {
             self.alloc_tlab(vm, size, array_ref)
         } else {
-            self.collector.alloc(vm, size, array_ref)
+            let ptr = self.collector.alloc(vm, size, array_ref);
+
+            if ptr.is_non_null() {
+                self.allocated.fetch_add(size, atomic::Ordering::Relaxed);
+            }
+
+            ptr
         }
     }
 
@@ -130,6 +181,8 @@ impl Gc {
 
         // allocate new tlab
         if let Some(tlab) = self.collector.alloc_tlab_area(vm, tlab::calculate_size()) {
+            self.allocated
+                .fetch_add(tlab.size(), atomic::Ordering::Relaxed);
             let object_start = tlab.start;
             let tlab = Region::new(tlab.start.offset(size), tlab.end);
 
@@ -156,6 +209,128 @@ impl Gc {
         self.collector.dump_summary(runtime);
     }
 
+    pub fn stats(&self) -> CollectorStats {
+        let mut stats = self.collector.stats();
+        stats.spaces.push(self.code_space.stats());
+        stats.spaces.push(self.perm_space.stats());
+
+        stats
+    }
+
+    pub fn allocated_size(&self) -> usize {
+        self.allocated.load(atomic::Ordering::Relaxed)
+    }
+
+    // the OutOfMemoryError is allocated at startup: throwing it must not
+    // need any memory, neither in the heap nor in perm space. Perm space is
+    // not covered by the card table, so the object is never modified
+    // afterwards and its backtrace stays null.
+    pub fn prepare_out_of_memory_error(&self, vm: &VM) {
+        let cls_def_id = vm.vips.out_of_memory_error(vm);
+        let cls_def = vm.class_defs.idx(cls_def_id);
+        let cls_def = cls_def.read();
+
+        let size = match cls_def.size {
+            ClassSize::Fixed(size) => size as usize,
+            _ => unreachable!(),
+        };
+
+        let size = mem::align_usize(size, mem::ptr_width_usize());
+        let ptr = self.alloc_perm(size);
+
+        if ptr.is_null() {
+            panic!("could not allocate OutOfMemoryError in perm space");
+        }
+
+        let vtable: *const VTable = &**cls_def.vtable.as_ref().unwrap();
+        let object = ptr.to_mut_obj();
+        object.header_mut().set_vtblptr(Address::from_ptr(vtable));
+
+        let exception = unsafe { &mut *ptr.to_mut_ptr::<Exception>() };
+        exception.msg = Str::from_buffer_in_perm(vm, b"out of memory");
+        exception.backtrace = Ref::null();
+        exception.elements = Ref::null();
+
+        *self.oom_error.lock() = ptr;
+    }
+
+    pub fn out_of_memory_error(&self) -> Address {
+        let oom_error = *self.oom_error.lock();
+        assert!(oom_error.is_non_null());
+
+        oom_error
+    }
+
+    pub fn dump_usage(&self) {
+        let stats = self.stats();
+
+        for space in &stats.spaces {
+            println!(
+                "GC usage: {} used={} committed={}",
+                space.name,
+                formatted_size(space.used),
+                formatted_size(space.committed)
+            );
+        }
+    }
+
+    // prints a histogram of all objects reachable from the root set,
+    // sorted by the memory they occupy
+    pub fn dump_heap(&self, vm: &VM) {
+        safepoint::stop_the_world(vm, |threads| {
+            let rootset = get_rootset(vm, threads);
+            let mut visited = HashSet::new();
+            let mut worklist = Vec::new();
+            let mut histogram: HashMap<*const ClassDef, (usize, usize)> = HashMap::new();
+
+            for root in &rootset {
+                let object = root.get();
+
+                if object.is_non_null() && visited.insert(object) {
+                    worklist.push(object);
+                }
+            }
+
+            while let Some(object) = worklist.pop() {
+                let object = object.to_mut_obj();
+                let classptr = object.header().vtbl().classptr() as *const ClassDef;
+
+                let entry = histogram.entry(classptr).or_insert((0, 0));
+                entry.0 += 1;
+                entry.1 += object.size();
+
+                object.visit_reference_fields(|field| {
+                    let field = field.get();
+
+                    if field.is_non_null() && visited.insert(field) {
+                        worklist.push(field);
+                    }
+                });
+            }
+
+            let mut entries: Vec<_> = histogram.into_iter().collect();
+            entries.sort_by(|a, b| (b.1).1.cmp(&(a.1).1));
+
+            let objects: usize = entries.iter().map(|e| (e.1).0).sum();
+            let size: usize = entries.iter().map(|e| (e.1).1).sum();
+            println!(
+                "GC heap dump: {} live objects, {}",
+                objects,
+                formatted_size(size)
+            );
+
+            for (classptr, (count, size)) in entries {
+                let cls = unsafe { &*classptr };
+                println!(
+                    "GC heap dump: {:>10} {:>12} {}",
+                    count,
+                    formatted_size(size).to_string(),
+                    cls.name(vm)
+                );
+            }
+        });
+    }
+
     pub fn verify_ref(&self, vm: &VM, reference: Address) {
         if reference.is_null() {
             return;
@@ -194,6 +369,9 @@ trait Collector {
     // prints GC summary: minor/full collections, etc.
     fn dump_summary(&self, _runtime: f32);
 
+    // returns collections, pauses and sizes of all spaces
+    fn stats(&self) -> CollectorStats;
+
     // verify reference
     fn verify_ref(&self, _vm: &VM, _addr: Address) {
         // do nothing
@@ -570,6 +748,22 @@ pub fn fill_region_with_free(vm: &VM, start: Address, end: Address, next: Addres
     }
 }
 
+pub struct CollectorStats {
+    pub minor_collections: usize,
+    pub full_collections: usize,
+
+    // total pause time in ms
+    pub total_pause: f32,
+
+    pub spaces: Vec<SpaceStats>,
+}
+
+pub struct SpaceStats {
+    pub name: &'static str,
+    pub used: usize,
+    pub committed: usize,
+}
+
 struct CollectionStats {
     collections: usize,
     total_pause: f32,
@@ -0,0 +1,212 @@
+use parking_lot::Mutex;
+use std::cmp::{max, min};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::gc::space::{Space, SpaceConfig};
+use crate::gc::{Address, Region, SpaceStats};
+use crate::mem;
+
+/// Space for machine code. Memory of unloaded code is kept in a free
+/// list and reused for new code, everything else is bump-allocated
+/// in the underlying `Space`.
+pub struct CodeSpace {
+    space: Space,
+    align: usize,
+    free_list: Mutex<CodeFreeList>,
+
+    // unreachable code is unloaded once the used size exceeds this
+    // threshold. The threshold adapts to the code size that survives
+    // unloading but always leaves some headroom below the limit of the
+    // space, code can't be unloaded when an allocation fails.
+    reclaim_threshold: AtomicUsize,
+}
+
+impl CodeSpace {
+    pub fn new(config: SpaceConfig, reclaim_threshold: usize) -> CodeSpace {
+        let align = config.align;
+        let space = Space::new(config, "code");
+        let reclaim_threshold = min(reclaim_threshold, space.total().size() / 2);
+
+        CodeSpace {
+            space: space,
+            align: align,
+            free_list: Mutex::new(CodeFreeList::new()),
+            reclaim_threshold: AtomicUsize::new(reclaim_threshold),
+        }
+    }
+
+    pub fn alloc(&self, size: usize) -> Address {
+        let size = mem::align_usize(size, self.align);
+
+        if let Some(ptr) = self.free_list.lock().alloc(size) {
+            return ptr;
+        }
+
+        self.space.alloc(size)
+    }
+
+    pub fn free(&self, region: Region) {
+        debug_assert!(self.space.contains(region.start));
+        let size = mem::align_usize(region.size(), self.align);
+        let region = Region::new(region.start, region.start.offset(size));
+
+        self.free_list.lock().free(region);
+    }
+
+    pub fn used(&self) -> usize {
+        self.space.used_region().size() - self.free_list.lock().size()
+    }
+
+    pub fn limit(&self) -> usize {
+        self.space.total().size()
+    }
+
+    pub fn needs_reclaim(&self) -> bool {
+        self.used() > self.reclaim_threshold.load(Ordering::Relaxed)
+    }
+
+    // called after unloading code: allow the surviving code to double
+    // before the next attempt to reclaim memory
+    pub fn adapt_reclaim_threshold(&self) {
+        let threshold = self.reclaim_threshold.load(Ordering::Relaxed);
+        let max_threshold = self.limit() / 4 * 3;
+        let threshold = min(max(threshold, 2 * self.used()), max_threshold);
+
+        self.reclaim_threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> SpaceStats {
+        let mut stats = self.space.stats();
+        stats.used = self.used();
+
+        stats
+    }
+}
+
+// Free regions of the code space, sorted by address. Adjacent
+// regions are merged when freed.
+struct CodeFreeList {
+    regions: Vec<Region>,
+    size: usize,
+}
+
+impl CodeFreeList {
+    fn new() -> CodeFreeList {
+        CodeFreeList {
+            regions: Vec::new(),
+            size: 0,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    // first fit
+    fn alloc(&mut self, size: usize) -> Option<Address> {
+        let idx = self
+            .regions
+            .iter()
+            .position(|region| region.size() >= size)?;
+
+        let region = self.regions[idx];
+        let ptr = region.start;
+
+        if region.size() == size {
+            self.regions.remove(idx);
+        } else {
+            self.regions[idx] = Region::new(ptr.offset(size), region.end);
+        }
+
+        self.size -= size;
+
+        Some(ptr)
+    }
+
+    fn free(&mut self, region: Region) {
+        let idx = match self
+            .regions
+            .binary_search_by_key(&region.start, |region| region.start)
+        {
+            Ok(_) => panic!("region freed twice"),
+            Err(idx) => idx,
+        };
+
+        debug_assert!(idx == 0 || self.regions[idx - 1].end <= region.start);
+        debug_assert!(idx == self.regions.len() || region.end <= self.regions[idx].start);
+
+        self.size += region.size();
+
+        let merge_prev = idx > 0 && self.regions[idx - 1].end == region.start;
+        let merge_next = idx < self.regions.len() && self.regions[idx].start == region.end;
+
+        match (merge_prev, merge_next) {
+            (true, true) => {
+                let next = self.regions.remove(idx);
+                self.regions[idx - 1].end = next.end;
+            }
+
+            (true, false) => {
+                self.regions[idx - 1].end = region.end;
+            }
+
+            (false, true) => {
+                self.regions[idx].start = region.start;
+            }
+
+            (false, false) => {
+                self.regions.insert(idx, region);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start: usize, end: usize) -> Region {
+        Region::new(start.into(), end.into())
+    }
+
+    #[test]
+    fn test_free_list_alloc() {
+        let mut free_list = CodeFreeList::new();
+        assert_eq!(None, free_list.alloc(64));
+
+        free_list.free(region(128, 256));
+        assert_eq!(128, free_list.size());
+
+        assert_eq!(Some(Address::from(128)), free_list.alloc(64));
+        assert_eq!(Some(Address::from(192)), free_list.alloc(64));
+        assert_eq!(None, free_list.alloc(64));
+        assert_eq!(0, free_list.size());
+    }
+
+    #[test]
+    fn test_free_list_merge() {
+        let mut free_list = CodeFreeList::new();
+
+        free_list.free(region(0, 64));
+        free_list.free(region(128, 192));
+        assert_eq!(2, free_list.regions.len());
+
+        free_list.free(region(64, 128));
+        assert_eq!(1, free_list.regions.len());
+        assert_eq!(192, free_list.size());
+
+        assert_eq!(Some(Address::from(0)), free_list.alloc(192));
+    }
+
+    #[test]
+    fn test_free_list_first_fit() {
+        let mut free_list = CodeFreeList::new();
+
+        free_list.free(region(0, 64));
+        free_list.free(region(128, 320));
+
+        assert_eq!(Some(Address::from(128)), free_list.alloc(128));
+        assert_eq!(Some(Address::from(0)), free_list.alloc(64));
+        assert_eq!(Some(Address::from(256)), free_list.alloc(64));
+    }
+}
@@ -7,7 +7,11 @@ use crate::gc::marking;
 use crate::gc::root::{get_rootset, Slot};
 use crate::gc::space::Space;
 use crate::gc::tlab;
-use crate::gc::{formatted_size, Address, CollectionStats, Collector, GcReason, Region};
+use crate::gc::verify::HeapVerifier;
+use crate::gc::{
+    formatted_size, Address, CollectionStats, Collector, CollectorStats, GcReason, Region,
+    SpaceStats,
+};
 use crate::object::Obj;
 use crate::os;
 use crate::safepoint;
@@ -79,21 +83,22 @@ impl Collector for MarkCompactCollector {
     }
 
     fn collect(&self, vm: &VM, reason: GcReason) {
-        let mut timer = Timer::new(vm.args.flag_gc_stats);
+        let mut timer = Timer::new(true);
 
         safepoint::stop_the_world(vm, |threads| {
             vm.perf_counters.stop();
             tlab::make_iterable_all(vm, threads);
             let rootset = get_rootset(vm, threads);
+            self.verify(vm, "pre-collect", &rootset);
             self.mark_compact(vm, &rootset, reason);
+            self.verify(vm, "post-collect", &rootset);
             vm.perf_counters.start();
         });
 
-        if vm.args.flag_gc_stats {
-            let duration = timer.stop();
-            let mut stats = self.stats.lock();
-            stats.add(duration);
-        }
+        let duration = timer.stop();
+
+        let mut stats = self.stats.lock();
+        stats.add(duration);
     }
 
     fn minor_collect(&self, vm: &VM, reason: GcReason) {
@@ -122,6 +127,23 @@ impl Collector for MarkCompactCollector {
             gc,
         );
     }
+
+    fn stats(&self) -> CollectorStats {
+        let stats = self.stats.lock();
+
+        let heap = SpaceStats {
+            name: "heap",
+            used: self.alloc.top().offset_from(self.heap.start),
+            committed: self.heap.size(),
+        };
+
+        CollectorStats {
+            minor_collections: 0,
+            full_collections: stats.collections(),
+            total_pause: stats.pause(),
+            spaces: vec![heap],
+        }
+    }
 }
 
 impl Drop for MarkCompactCollector {
@@ -131,6 +153,14 @@ impl Drop for MarkCompactCollector {
 }
 
 impl MarkCompactCollector {
+    fn verify(&self, vm: &VM, phase: &str, rootset: &[Slot]) {
+        if vm.args.flag_gc_verify {
+            let mut verifier = HeapVerifier::new(vm, rootset, phase);
+            verifier.add_space("heap", Region::new(self.heap.start, self.alloc.top()));
+            verifier.verify();
+        }
+    }
+
     fn mark_compact(&self, vm: &VM, rootset: &[Slot], reason: GcReason) {
         let mut mark_compact = MarkCompact {
             vm: vm,
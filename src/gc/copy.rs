@@ -5,7 +5,11 @@ use crate::driver::cmd::Args;
 use crate::gc::bump::BumpAllocator;
 use crate::gc::root::{get_rootset, Slot};
 use crate::gc::tlab;
-use crate::gc::{formatted_size, Address, CollectionStats, Collector, GcReason, Region};
+use crate::gc::verify::HeapVerifier;
+use crate::gc::{
+    formatted_size, Address, CollectionStats, Collector, CollectorStats, GcReason, Region,
+    SpaceStats,
+};
 use crate::mem;
 use crate::object::Obj;
 use crate::os::{self, ProtType};
@@ -84,21 +88,22 @@ impl Collector for CopyCollector {
     }
 
     fn collect(&self, vm: &VM, reason: GcReason) {
-        let mut timer = Timer::new(vm.args.flag_gc_stats);
+        let mut timer = Timer::new(true);
 
         safepoint::stop_the_world(vm, |threads| {
             vm.perf_counters.stop();
             tlab::make_iterable_all(vm, &*threads);
             let rootset = get_rootset(vm, &*threads);
+            self.verify(vm, "pre-collect", &rootset);
             self.copy_collect(vm, &rootset, reason);
+            self.verify(vm, "post-collect", &rootset);
             vm.perf_counters.start();
         });
 
-        if vm.args.flag_gc_stats {
-            let duration = timer.stop();
-            let mut stats = self.stats.lock();
-            stats.add(duration);
-        }
+        let duration = timer.stop();
+
+        let mut stats = self.stats.lock();
+        stats.add(duration);
     }
 
     fn minor_collect(&self, vm: &VM, reason: GcReason) {
@@ -127,6 +132,24 @@ impl Collector for CopyCollector {
             gc,
         );
     }
+
+    fn stats(&self) -> CollectorStats {
+        let stats = self.stats.lock();
+        let from_space = self.from_space();
+
+        let heap = SpaceStats {
+            name: "heap",
+            used: self.alloc.top().offset_from(from_space.start),
+            committed: self.total.size(),
+        };
+
+        CollectorStats {
+            minor_collections: 0,
+            full_collections: stats.collections(),
+            total_pause: stats.pause(),
+            spaces: vec![heap],
+        }
+    }
 }
 
 impl Drop for CopyCollector {
@@ -136,6 +159,16 @@ impl Drop for CopyCollector {
 }
 
 impl CopyCollector {
+    fn verify(&self, vm: &VM, phase: &str, rootset: &[Slot]) {
+        if vm.args.flag_gc_verify {
+            let from_space = self.from_space();
+
+            let mut verifier = HeapVerifier::new(vm, rootset, phase);
+            verifier.add_space("heap", Region::new(from_space.start, self.alloc.top()));
+            verifier.verify();
+        }
+    }
+
     fn copy_collect(&self, vm: &VM, rootset: &[Slot], reason: GcReason) {
         let timer = Timer::new(vm.args.flag_gc_verbose);
 
@@ -1,5 +1,5 @@
 use crate::ctxt::VM;
-use crate::gc::{fill_region, fill_region_with_free, Address, K};
+use crate::gc::{fill_region, fill_region_with_free, Address, Region, K};
 
 pub const SIZE_CLASSES: usize = 6;
 
@@ -123,6 +123,44 @@ impl FreeList {
 
         self.classes[SIZE_CLASS_HUGE.idx()].find(size)
     }
+
+    /// verifies that all entries are free objects in the given heap
+    /// and that each entry is stored in the right size class.
+    pub fn verify(&self, vm: &VM, heap: Region) {
+        let max_entries = heap.size() / SIZE_SMALLEST;
+
+        for (idx, class) in self.classes.iter().enumerate() {
+            let mut curr = class.head;
+            let mut entries = 0;
+
+            while curr.is_non_null() {
+                assert!(
+                    heap.contains(curr.addr()),
+                    "free list entry {} outside of heap",
+                    curr.addr()
+                );
+
+                let cls_def_id = curr.addr().to_obj().header().vtbl().class().id;
+                assert!(
+                    cls_def_id == vm.vips.free_object_class_def
+                        || cls_def_id == vm.vips.free_array_class_def,
+                    "free list entry {} is not a free object",
+                    curr.addr()
+                );
+
+                assert!(
+                    SizeClass::next_down(curr.size()).idx() == idx,
+                    "free list entry {} in wrong size class",
+                    curr.addr()
+                );
+
+                entries += 1;
+                assert!(entries <= max_entries, "free list contains cycle");
+
+                curr = curr.next();
+            }
+        }
+    }
 }
 
 struct FreeListClass {
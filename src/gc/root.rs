@@ -1,10 +1,11 @@
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use crate::baseline::map::CodeDescriptor;
 use crate::ctxt::VM;
 use crate::exception::DoraToNativeInfo;
 use crate::gc::Address;
-use crate::threads::DoraThread;
+use crate::threads::{DoraThread, ThreadState};
 
 pub fn get_rootset(vm: &VM, threads: &[Arc<DoraThread>]) -> Vec<Slot> {
     let mut rootset = Vec::new();
@@ -41,6 +42,10 @@ fn determine_rootset_from_globals(rootset: &mut Vec<Slot>, vm: &VM) {
 
 fn determine_rootset_from_stack(rootset: &mut Vec<Slot>, vm: &VM, threads: &[Arc<DoraThread>]) {
     for thread in threads {
+        if let ThreadState::Blocked = thread.state() {
+            determine_rootset_from_safepoint(rootset, vm, thread);
+        }
+
         let dtn = Address::from_ptr(thread.dtn());
         determine_rootset_from_stack_for_thread(rootset, vm, dtn);
     }
@@ -60,10 +65,51 @@ fn from_dora_to_native_info(
     dtn: *const DoraToNativeInfo,
 ) -> *const DoraToNativeInfo {
     let dtn = unsafe { &*dtn };
+    determine_rootset_from_frames(rootset, vm, dtn.pc, dtn.fp);
+
+    dtn.last
+}
 
-    let mut pc: usize = dtn.pc;
-    let mut fp: usize = dtn.fp;
+// a thread blocked in a safepoint stopped in Dora code, the frames above
+// its last native call are only known from the saved registers
+fn determine_rootset_from_safepoint(rootset: &mut Vec<Slot>, vm: &VM, thread: &DoraThread) {
+    let pc = thread.saved_pc.load(Ordering::Relaxed);
+    let fp = thread.saved_fp.load(Ordering::Relaxed);
+
+    {
+        let code_map = vm.code_map.lock();
+
+        let fct_id = match code_map.get(pc.into()) {
+            Some(CodeDescriptor::DoraFct(fct_id)) | Some(CodeDescriptor::NativeThunk(fct_id)) => {
+                fct_id
+            }
+
+            data => {
+                println!("data = {:?}", data);
+                panic!("invalid safepoint");
+            }
+        };
 
+        let jit_fct = vm.jit_fcts.idx(fct_id);
+
+        let offset = pc - jit_fct.fct_ptr().to_usize();
+        let jit_fct = jit_fct.to_base().expect("baseline expected");
+        let gcpoint = jit_fct
+            .gcpoint_for_safepoint(offset as i32)
+            .expect("no gcpoint");
+
+        for &offset in &gcpoint.offsets {
+            let addr = (fp as isize + offset as isize) as usize;
+            rootset.push(Slot::at(addr.into()));
+        }
+    }
+
+    let pc = unsafe { *((fp + 8) as *const usize) };
+    let fp = unsafe { *(fp as *const usize) };
+    determine_rootset_from_frames(rootset, vm, pc, fp);
+}
+
+fn determine_rootset_from_frames(rootset: &mut Vec<Slot>, vm: &VM, mut pc: usize, mut fp: usize) {
     while fp != 0 {
         if !determine_rootset(rootset, vm, fp, pc) {
             break;
@@ -72,8 +118,6 @@ fn from_dora_to_native_info(
         pc = unsafe { *((fp + 8) as *const usize) };
         fp = unsafe { *(fp as *const usize) };
     }
-
-    dtn.last
 }
 
 fn determine_rootset(rootset: &mut Vec<Slot>, vm: &VM, fp: usize, pc: usize) -> bool {
@@ -99,6 +143,7 @@ fn determine_rootset(rootset: &mut Vec<Slot>, vm: &VM, fp: usize, pc: usize) ->
         }
 
         Some(CodeDescriptor::AllocThunk) => true,
+        Some(CodeDescriptor::GlobalInitThunk) => true,
         Some(CodeDescriptor::NativeThunk(_)) => true,
         Some(CodeDescriptor::DoraEntry) => false,
 
@@ -2,7 +2,7 @@ use parking_lot::Mutex;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::gc::arena;
-use crate::gc::{Address, Region};
+use crate::gc::{Address, Region, SpaceStats};
 use crate::mem;
 
 /// Configuration for a space.
@@ -136,6 +136,16 @@ impl Space {
         self.total.clone()
     }
 
+    pub fn stats(&self) -> SpaceStats {
+        let end: Address = self.end.load(Ordering::Relaxed).into();
+
+        SpaceStats {
+            name: self.name,
+            used: self.used_region().size(),
+            committed: end.offset_from(self.total.start),
+        }
+    }
+
     pub fn used_region(&self) -> Region {
         let start = self.total.start;
         let end = self.top.load(Ordering::Relaxed).into();
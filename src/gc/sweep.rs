@@ -7,8 +7,10 @@ use crate::gc::marking;
 use crate::gc::root::{get_rootset, Slot};
 use crate::gc::space::Space;
 use crate::gc::tlab;
+use crate::gc::verify::HeapVerifier;
 use crate::gc::{
-    fill_region_with_free, formatted_size, Address, CollectionStats, Collector, GcReason, Region,
+    formatted_size, Address, CollectionStats, Collector, CollectorStats, GcReason, Region,
+    SpaceStats,
 };
 use crate::os;
 use crate::safepoint;
@@ -80,21 +82,22 @@ impl Collector for SweepCollector {
     }
 
     fn collect(&self, vm: &VM, reason: GcReason) {
-        let mut timer = Timer::new(vm.args.flag_gc_stats);
+        let mut timer = Timer::new(true);
 
         safepoint::stop_the_world(vm, |threads| {
             vm.perf_counters.stop();
             tlab::make_iterable_all(vm, threads);
             let rootset = get_rootset(vm, threads);
+            self.verify(vm, "pre-collect", &rootset);
             self.mark_sweep(vm, &rootset, reason);
+            self.verify(vm, "post-collect", &rootset);
             vm.perf_counters.start();
         });
 
-        if vm.args.flag_gc_stats {
-            let duration = timer.stop();
-            let mut stats = self.stats.lock();
-            stats.add(duration);
-        }
+        let duration = timer.stop();
+
+        let mut stats = self.stats.lock();
+        stats.add(duration);
     }
 
     fn minor_collect(&self, vm: &VM, reason: GcReason) {
@@ -123,6 +126,23 @@ impl Collector for SweepCollector {
             gc,
         );
     }
+
+    fn stats(&self) -> CollectorStats {
+        let stats = self.stats.lock();
+
+        let heap = SpaceStats {
+            name: "heap",
+            used: self.alloc.lock().top.offset_from(self.heap.start),
+            committed: self.heap.size(),
+        };
+
+        CollectorStats {
+            minor_collections: 0,
+            full_collections: stats.collections(),
+            total_pause: stats.pause(),
+            spaces: vec![heap],
+        }
+    }
 }
 
 impl Drop for SweepCollector {
@@ -132,6 +152,18 @@ impl Drop for SweepCollector {
 }
 
 impl SweepCollector {
+    fn verify(&self, vm: &VM, phase: &str, rootset: &[Slot]) {
+        if vm.args.flag_gc_verify {
+            let alloc = self.alloc.lock();
+
+            let mut verifier = HeapVerifier::new(vm, rootset, phase);
+            verifier.add_space("heap", Region::new(self.heap.start, alloc.top));
+            verifier.verify();
+
+            alloc.free_list.verify(vm, self.heap);
+        }
+    }
+
     fn inner_alloc(&self, vm: &VM, size: usize) -> Address {
         let mut alloc = self.alloc.lock();
         alloc.allocate(vm, size)
@@ -273,7 +305,6 @@ impl SweepAllocator {
             let free_end = object.offset(free_size);
             let new_free_size = free_end.offset_from(free_start);
 
-            fill_region_with_free(vm, free_start, free_end, Address::null());
             self.free_list.add(vm, free_start, new_free_size);
             return object;
         }
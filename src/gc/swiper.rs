@@ -19,9 +19,9 @@ use crate::gc::swiper::pminor::ParallelMinorCollector;
 use crate::gc::swiper::verify::{Verifier, VerifierPhase};
 use crate::gc::swiper::young::YoungGen;
 use crate::gc::tlab;
-use crate::gc::Collector;
 use crate::gc::{align_gen, fill_region, formatted_size, Address, Region, K};
 use crate::gc::{arena, GcReason, GEN_SIZE};
+use crate::gc::{Collector, CollectorStats, SpaceStats};
 use crate::mem;
 use crate::object::Obj;
 use crate::safepoint;
@@ -441,7 +441,9 @@ impl Swiper {
             return ptr;
         }
 
-        self.perform_collection_and_choose(vm, GcReason::AllocationFailure);
+        if !self.collect_for_allocation(vm) {
+            return Address::null();
+        }
 
         self.young.bump_alloc(size)
     }
@@ -457,6 +459,26 @@ impl Swiper {
 
         self.large.alloc(size)
     }
+
+    // collects garbage after an allocation failed, returns false when
+    // even a full collection left the heap exhausted
+    fn collect_for_allocation(&self, vm: &VM) -> bool {
+        let kind = self.perform_collection_and_choose(vm, GcReason::AllocationFailure);
+
+        if !self.heap_exhausted() {
+            return true;
+        }
+
+        if kind.is_minor() {
+            self.perform_collection(vm, CollectionKind::Full, GcReason::AllocationFailure);
+        }
+
+        !self.heap_exhausted()
+    }
+
+    fn heap_exhausted(&self) -> bool {
+        self.config.lock().heap_exhausted
+    }
 }
 
 impl Collector for Swiper {
@@ -471,7 +493,9 @@ impl Collector for Swiper {
             return Some(ptr.region_start(size));
         }
 
-        self.perform_collection_and_choose(vm, GcReason::AllocationFailure);
+        if !self.collect_for_allocation(vm) {
+            return None;
+        }
 
         let ptr = self.young.bump_alloc(size);
 
@@ -480,6 +504,11 @@ impl Collector for Swiper {
         }
 
         self.perform_collection(vm, CollectionKind::Full, GcReason::AllocationFailure);
+
+        if self.heap_exhausted() {
+            return None;
+        }
+
         let ptr = self.young.bump_alloc(size);
 
         return if ptr.is_null() {
@@ -556,6 +585,25 @@ impl Collector for Swiper {
         println!("GC stats: minor-tracing={}", config.minor_tracing_all());
         println!("");
 
+        let (eden_size, semi_size) = self.young.committed_size();
+        println!(
+            "GC stats: committed-young={}",
+            formatted_size(eden_size + semi_size)
+        );
+        println!(
+            "GC stats: committed-old={}",
+            formatted_size(self.old.committed_size())
+        );
+        println!(
+            "GC stats: committed-large={}",
+            formatted_size(self.large.committed_size())
+        );
+        println!(
+            "GC stats: uncommitted={}",
+            formatted_size(config.total_uncommitted)
+        );
+        println!("");
+
         println!(
             "GC summary: {:.1}ms minor ({}), {:.1}ms full ({}), {:.1}ms collection, {:.1}ms mutator, {:.1}ms total ({}% mutator, {}% GC)",
             config.total_minor_pause,
@@ -586,6 +634,37 @@ impl Collector for Swiper {
         println!("");
     }
 
+    fn stats(&self) -> CollectorStats {
+        let config = self.config.lock();
+
+        let (eden_size, semi_size) = self.young.committed_size();
+        let young = SpaceStats {
+            name: "young",
+            used: self.young.active_size(),
+            committed: eden_size + semi_size,
+        };
+
+        let old = SpaceStats {
+            name: "old",
+            used: self.old.active_size(),
+            committed: self.old.committed_size(),
+        };
+
+        let large_size = self.large.committed_size();
+        let large = SpaceStats {
+            name: "large",
+            used: large_size,
+            committed: large_size,
+        };
+
+        CollectorStats {
+            minor_collections: config.total_minor_collections,
+            full_collections: config.total_full_collections,
+            total_pause: config.total_minor_pause + config.total_full_pause,
+            spaces: vec![young, old, large],
+        }
+    }
+
     fn verify_ref(&self, vm: &VM, reference: Address) {
         let found = self.young.eden_active().contains(reference)
             || self.young.to_active().contains(reference)
@@ -718,4 +797,8 @@ where
 pub trait CommonOldGen {
     fn active_size(&self) -> usize;
     fn committed_size(&self) -> usize;
+
+    // returns committed but unused memory to the OS,
+    // gives number of uncommitted bytes
+    fn uncommit_unused(&self) -> usize;
 }
@@ -14,8 +14,6 @@ use crate::gc::swiper::young::YoungGen;
 use crate::gc::swiper::{walk_region, walk_region_and_skip_garbage};
 use crate::gc::{Address, GcReason, Region};
 use crate::object::Obj;
-use crate::os::signal::Trap;
-use crate::stdlib;
 use crate::timer::Timer;
 
 pub struct FullCollector<'a, 'ast: 'a> {
@@ -194,8 +192,11 @@ impl<'a, 'ast> FullCollector<'a, 'ast> {
             }
         });
 
-        if !self.fits_into_heap() {
-            stdlib::trap(Trap::OOM.int());
+        // surviving objects may exceed the maximum heap size, the controller
+        // then marks the heap as exhausted and allocations fail with
+        // OutOfMemoryError until enough memory is freed
+        if !self.fits_into_heap() && self.vm.args.flag_gc_verbose {
+            println!("Full GC: live objects exceed maximum heap size");
         }
 
         self.old_protected.commit_single_region(self.old_top);
@@ -10,14 +10,20 @@ use crate::gc::swiper::young::YoungGen;
 use crate::gc::swiper::{CollectionKind, CommonOldGen};
 use crate::gc::{align_gen, align_gen_down, formatted_size, AllNumbers, GcReason, GEN_SIZE, M};
 use crate::mem;
-use crate::os::signal::Trap;
-use crate::stdlib;
 use crate::timer;
 
 const INIT_HEAP_SIZE_RATIO: usize = 2;
 const INIT_YOUNG_RATIO: usize = 4;
 const INIT_SEMI_RATIO: usize = 3;
 
+// size of eden when the heap is exhausted, allocations in this reserve
+// are allowed to exceed the maximum heap size
+const EMERGENCY_EDEN_SIZE: usize = GEN_SIZE;
+
+// unused memory in old generation is uncommitted when objects
+// take up less than this fraction of committed memory
+const UNCOMMIT_USAGE_RATIO: f64 = 0.5;
+
 pub fn init(config: &mut HeapConfig, args: &Args) {
     assert!(config.min_heap_size <= config.max_heap_size);
 
@@ -49,6 +55,8 @@ pub fn init(config: &mut HeapConfig, args: &Args) {
 
     config.old_size = 0;
     config.old_limit = old_limit;
+
+    config.uncommit_delay = args.gc_uncommit_delay();
 }
 
 fn calculate_young_size(args: &Args, young_size: usize, min_semi_size: usize) -> (usize, usize) {
@@ -61,7 +69,7 @@ fn calculate_young_size(args: &Args, young_size: usize, min_semi_size: usize) ->
 
     let semi_size = max(semi_size, min_semi_size);
     let semi_size = max(semi_size, GEN_SIZE);
-    let eden_size = young_size - semi_size;
+    let eden_size = young_size.saturating_sub(semi_size);
 
     (eden_size, semi_size)
 }
@@ -111,6 +119,10 @@ pub fn stop(
     assert!(young.eden_active().empty());
     assert!(young.from_active().empty());
 
+    if kind.is_full() {
+        uncommit_unused(&mut *config, old, large, args, gc_end);
+    }
+
     let old_size = old.committed_size() + large.committed_size();
     config.old_size = old_size;
 
@@ -120,7 +132,7 @@ pub fn stop(
         std::usize::MAX
     };
 
-    let rest = config.max_heap_size - config.old_size;
+    let rest = config.max_heap_size.saturating_sub(config.old_size);
     let target_young_size = align_gen_down(rest / 2);
     let target_young_size = min(target_young_size, max_young_size);
     let target_young_size = max(target_young_size, GEN_SIZE);
@@ -131,19 +143,33 @@ pub fn stop(
     let (eden_size, semi_size) = calculate_young_size(args, target_young_size, min_semi_size);
     let young_size = eden_size + semi_size;
 
-    if old_size + young_size > config.max_heap_size {
-        stdlib::trap(Trap::OOM.int());
-    }
+    config.heap_exhausted = eden_size == 0 || old_size + young_size > config.max_heap_size;
 
-    young.set_limit(eden_size, semi_size);
-    config.old_limit = config.max_heap_size - young_size;
-    assert!(config.old_limit >= old_size);
+    if config.heap_exhausted {
+        // live objects leave no room for the young generation: place a small
+        // eden in the emergency reserve beyond the maximum heap size, it gives
+        // the program memory to handle the OutOfMemoryError. The old generation
+        // isn't allowed to grow until a collection frees memory again.
+        let semi_size = max(min_semi_size, GEN_SIZE);
+        young.set_limit(EMERGENCY_EDEN_SIZE, semi_size);
+        config.old_limit = old_size;
+
+        if args.flag_gc_verbose {
+            println!(
+                "GC: heap exhausted, using emergency reserve of {}",
+                formatted_size(EMERGENCY_EDEN_SIZE)
+            );
+        }
+    } else {
+        young.set_limit(eden_size, semi_size);
+        config.old_limit = config.max_heap_size - young_size;
+        assert!(config.old_limit >= old_size);
+        assert!(young_size + config.old_limit <= config.max_heap_size);
+    }
 
     config.end_object_size = object_size(young, old, large);
     config.end_memory_size = memory_size(young, old, large);
 
-    assert!(young_size + config.old_limit <= config.max_heap_size);
-
     match kind {
         CollectionKind::Minor => {
             config.total_minor_collections += 1;
@@ -169,6 +195,38 @@ pub fn stop(
     }
 }
 
+fn uncommit_unused(
+    config: &mut HeapConfig,
+    old: &dyn CommonOldGen,
+    large: &LargeSpace,
+    args: &Args,
+    now: u64,
+) {
+    let used_size = old.active_size() + large.committed_size();
+    let committed_size = old.committed_size() + large.committed_size();
+
+    if used_size as f64 >= committed_size as f64 * UNCOMMIT_USAGE_RATIO {
+        config.low_usage_since = None;
+        return;
+    }
+
+    let low_usage_since = *config.low_usage_since.get_or_insert(now);
+
+    if timer::in_ms(now - low_usage_since) < config.uncommit_delay as f32 {
+        return;
+    }
+
+    // large space already discards memory when objects are freed,
+    // only the old generation keeps committed memory after its top
+    let uncommitted = old.uncommit_unused();
+    config.total_uncommitted += uncommitted;
+    config.low_usage_since = None;
+
+    if args.flag_gc_verbose && uncommitted > 0 {
+        println!("GC: uncommitted {}", formatted_size(uncommitted));
+    }
+}
+
 fn print(config: &HeapConfig, kind: CollectionKind, reason: GcReason) {
     match kind {
         CollectionKind::Minor => {
@@ -222,6 +280,10 @@ pub struct HeapConfig {
     pub old_size: usize,
     pub old_limit: usize,
 
+    // set when the last collection couldn't free enough memory,
+    // allocations that need a collection fail until it is reset
+    pub heap_exhausted: bool,
+
     gc_start: u64,
     gc_duration: f32,
 
@@ -239,6 +301,10 @@ pub struct HeapConfig {
     pub total_full_collections: usize,
     pub total_full_pause: f32,
 
+    uncommit_delay: u64,
+    low_usage_since: Option<u64>,
+    pub total_uncommitted: usize,
+
     full_phases: Vec<FullCollectorPhases>,
     minor_phases: Vec<MinorCollectorPhases>,
 }
@@ -256,6 +322,8 @@ impl HeapConfig {
             old_size: 0,
             old_limit: 0,
 
+            heap_exhausted: false,
+
             gc_start: 0,
             gc_duration: 0f32,
 
@@ -273,6 +341,10 @@ impl HeapConfig {
             total_full_collections: 0,
             total_full_pause: 0f32,
 
+            uncommit_delay: 0,
+            low_usage_since: None,
+            total_uncommitted: 0,
+
             full_phases: Vec::new(),
             minor_phases: Vec::new(),
         }
@@ -65,6 +65,14 @@ impl LargeSpace {
     ) {
         let mut space = self.space.lock();
 
+        // all objects of this chain were freed, only return their memory
+        if head.is_null() {
+            space.committed_size -= freed;
+            space.elements.append(&mut free_regions);
+            space.merge();
+            return;
+        }
+
         let old_head = space.head;
         space.head = head;
 
@@ -82,6 +82,11 @@ impl CommonOldGen for OldGen {
         let protected = self.protected.lock();
         protected.size
     }
+
+    fn uncommit_unused(&self) -> usize {
+        let mut protected = self.protected.lock();
+        protected.uncommit_unused()
+    }
 }
 
 pub struct OldGenProtected {
@@ -336,6 +341,17 @@ impl OldGenProtected {
         self.alloc_region = 0;
     }
 
+    pub fn uncommit_unused(&mut self) -> usize {
+        let mut uncommitted = 0;
+
+        for region in &mut self.regions {
+            uncommitted += region.uncommit_unused();
+        }
+
+        self.size -= uncommitted;
+        uncommitted
+    }
+
     pub fn active_size(&self) -> usize {
         let mut size = 0;
 
@@ -516,6 +532,22 @@ impl OldGenRegion {
         None
     }
 
+    // gives memory after the last generation-sized block with objects
+    // back to the OS, mapping_top stays aligned for later extends
+    fn uncommit_unused(&mut self) -> usize {
+        let start = max(self.top.align_gen(), self.mapping_start());
+
+        if start >= self.mapping_top {
+            return 0;
+        }
+
+        let size = self.mapping_top.offset_from(start);
+        arena::discard(start, size);
+        self.mapping_top = start;
+
+        size
+    }
+
     fn extend(&mut self, size: usize) -> bool {
         let new_mapping_top = self.mapping_top.offset(size);
 
@@ -17,8 +17,6 @@ use crate::gc::swiper::verify::verify_mapped_regions;
 use crate::gc::swiper::young::YoungGen;
 use crate::gc::swiper::{walk_region, walk_region_and_skip_garbage, CardIdx, CARD_REFS};
 use crate::gc::{Address, GcReason, Region};
-use crate::os::signal::Trap;
-use crate::stdlib;
 use crate::timer::Timer;
 
 pub struct ParallelFullCollector<'a, 'ast: 'a> {
@@ -247,8 +245,11 @@ impl<'a, 'ast> ParallelFullCollector<'a, 'ast> {
 
         let regions: Vec<Region> = self.regions.iter().map(|r| r.mapping).collect();
 
-        if !self.fits_into_heap(&regions) {
-            stdlib::trap(Trap::OOM.int());
+        // surviving objects may exceed the maximum heap size, the controller
+        // then marks the heap as exhausted and allocations fail with
+        // OutOfMemoryError until enough memory is freed
+        if !self.fits_into_heap(&regions) && self.vm.args.flag_gc_verbose {
+            println!("Full GC: live objects exceed maximum heap size");
         }
 
         self.compute_actual_forward(pool);
@@ -718,9 +719,7 @@ impl<'a, 'ast> ParallelFullCollector<'a, 'ast> {
                         }
                     }
 
-                    if head.is_non_null() {
-                        large.append_chain(head, tail, freed, free_regions);
-                    }
+                    large.append_chain(head, tail, freed, free_regions);
                 });
             }
         });
@@ -12,10 +12,10 @@ use crate::gc::swiper::large::LargeSpace;
 use crate::gc::swiper::sweep::old::OldGen;
 use crate::gc::swiper::verify::VerifierPhase;
 use crate::gc::swiper::young::YoungGen;
-use crate::gc::swiper::{CollectionKind, CARD_SIZE_BITS, LARGE_OBJECT_SIZE};
+use crate::gc::swiper::{CollectionKind, CommonOldGen, CARD_SIZE_BITS, LARGE_OBJECT_SIZE};
 use crate::gc::tlab;
 use crate::gc::{align_gen, arena, formatted_size, GEN_SIZE};
-use crate::gc::{Address, Collector, GcReason, Region};
+use crate::gc::{Address, Collector, CollectorStats, GcReason, Region, SpaceStats};
 use crate::mem;
 use crate::safepoint;
 
@@ -202,6 +202,37 @@ impl Collector for SweepSwiper {
         unimplemented!()
     }
 
+    fn stats(&self) -> CollectorStats {
+        let config = self.config.lock();
+
+        let (eden_size, semi_size) = self.young.committed_size();
+        let young = SpaceStats {
+            name: "young",
+            used: self.young.active_size(),
+            committed: eden_size + semi_size,
+        };
+
+        let old = SpaceStats {
+            name: "old",
+            used: self.old.active_size(),
+            committed: self.old.committed_size(),
+        };
+
+        let large_size = self.large.committed_size();
+        let large = SpaceStats {
+            name: "large",
+            used: large_size,
+            committed: large_size,
+        };
+
+        CollectorStats {
+            minor_collections: config.total_minor_collections,
+            full_collections: config.total_full_collections,
+            total_pause: config.total_minor_pause + config.total_full_pause,
+            spaces: vec![young, old, large],
+        }
+    }
+
     fn verify_ref(&self, _vm: &VM, _reference: Address) {
         unimplemented!()
     }
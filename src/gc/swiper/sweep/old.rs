@@ -104,10 +104,22 @@ impl OldGen {
 
 impl CommonOldGen for OldGen {
     fn active_size(&self) -> usize {
-        unimplemented!()
+        let prot = self.prot.lock();
+
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|&(idx, _)| prot.used_chunks.contains(ChunkId(idx)))
+            .map(|(_, chunk)| chunk.top.offset_from(chunk.region.start))
+            .sum()
     }
 
     fn committed_size(&self) -> usize {
+        let prot = self.prot.lock();
+        prot.used_chunks.count() * CHUNK_SIZE
+    }
+
+    fn uncommit_unused(&self) -> usize {
         unimplemented!()
     }
 }
@@ -208,6 +220,10 @@ impl ChunkSet {
         self.chunks.count_ones(..)
     }
 
+    fn contains(&self, chunk: ChunkId) -> bool {
+        self.chunks[chunk.to_usize()]
+    }
+
     fn add(&mut self, chunk: ChunkId) {
         self.chunks.insert(chunk.to_usize());
 
@@ -11,6 +11,7 @@ use crate::gc::swiper::old::{OldGen, OldGenProtected};
 use crate::gc::swiper::on_different_cards;
 use crate::gc::swiper::young::YoungGen;
 use crate::gc::swiper::{CARD_REFS, CARD_SIZE};
+use crate::gc::verify::has_valid_vtable;
 use crate::gc::{Address, Region};
 
 use crate::mem;
@@ -481,6 +482,10 @@ impl<'a> Verifier<'a> {
             // To make sure this isn't optimized out by the compiler,
             // make sure that the size doesn't equal 1.
             assert!(object.size() != 1, "object size shouldn't be 1");
+            assert!(
+                has_valid_vtable(reference),
+                "reference doesn't point to valid object"
+            );
 
             if self.young_total.contains(reference) {
                 self.refs_to_young_gen += 1;
@@ -0,0 +1,192 @@
+use crate::ctxt::VM;
+use crate::gc::root::Slot;
+use crate::gc::{Address, Region};
+use crate::mem;
+use crate::vtable::VTable;
+
+/// Collector-independent heap verifier. Walks all object spaces registered
+/// with `add_space`, checks that every object has a sane header and that
+/// every reference (both from objects and from the root set) points to the
+/// start of a valid object in either one of these spaces or the perm space.
+///
+/// Collectors can add their own checks (e.g. free-list integrity) after
+/// `verify` returns.
+pub struct HeapVerifier<'a, 'ast: 'a> {
+    vm: &'a VM<'ast>,
+    rootset: &'a [Slot],
+    spaces: Vec<(&'static str, Region)>,
+    phase: &'a str,
+}
+
+impl<'a, 'ast> HeapVerifier<'a, 'ast> {
+    pub fn new(vm: &'a VM<'ast>, rootset: &'a [Slot], phase: &'a str) -> HeapVerifier<'a, 'ast> {
+        HeapVerifier {
+            vm: vm,
+            rootset: rootset,
+            spaces: Vec::new(),
+            phase: phase,
+        }
+    }
+
+    /// registers region with objects that should be walked. The region needs
+    /// to be iterable, objects are only allowed to be separated by null words.
+    pub fn add_space(&mut self, name: &'static str, region: Region) {
+        self.spaces.push((name, region));
+    }
+
+    pub fn verify(&mut self) {
+        self.verify_roots();
+
+        for &(name, region) in &self.spaces {
+            self.verify_objects(name, region);
+        }
+    }
+
+    fn verify_roots(&self) {
+        for root in self.rootset {
+            self.verify_reference(*root, Address::null(), "root set");
+        }
+    }
+
+    fn verify_objects(&self, name: &str, region: Region) {
+        let mut curr = region.start;
+
+        while curr < region.end {
+            let object = curr.to_mut_obj();
+
+            if object.header().vtblptr().is_null() {
+                curr = curr.add_ptr(1);
+                continue;
+            }
+
+            self.verify_header(curr, name);
+
+            object.visit_reference_fields(|child| {
+                self.verify_reference(child, curr, name);
+            });
+
+            curr = curr.offset(object.size());
+        }
+
+        if curr != region.end {
+            self.fail(&format!(
+                "last object in {} doesn't end at {}",
+                name, region.end
+            ));
+        }
+    }
+
+    fn verify_header(&self, object_address: Address, name: &str) {
+        let header = object_address.to_obj().header();
+
+        if header.vtblptr_forwarded().is_some() {
+            self.fail(&format!(
+                "object {} in {} is still forwarded",
+                object_address, name
+            ));
+        }
+
+        if header.is_marked_non_atomic() {
+            self.fail(&format!(
+                "object {} in {} is still marked",
+                object_address, name
+            ));
+        }
+
+        if !has_valid_vtable(object_address) {
+            self.fail(&format!(
+                "object {} in {} has invalid vtable {}",
+                object_address,
+                name,
+                header.vtblptr()
+            ));
+        }
+    }
+
+    fn verify_reference(&self, slot: Slot, container_obj: Address, name: &str) {
+        let reference = slot.get();
+
+        if reference.is_null() {
+            return;
+        }
+
+        let in_heap = self
+            .spaces
+            .iter()
+            .any(|&(_, region)| region.contains(reference));
+
+        if !in_heap && !self.vm.gc.perm_space.contains(reference) {
+            self.fail_reference(slot, container_obj, name, "points outside of heap");
+        }
+
+        if !mem::is_aligned(reference.to_usize(), mem::ptr_width_usize())
+            || !has_valid_vtable(reference)
+        {
+            self.fail_reference(slot, container_obj, name, "doesn't point to valid object");
+        }
+
+        if is_free_object(self.vm, reference) {
+            self.fail_reference(slot, container_obj, name, "points to free memory");
+        }
+    }
+
+    fn fail_reference(&self, slot: Slot, container_obj: Address, name: &str, msg: &str) {
+        println!(
+            "found invalid reference to {} in {} (at {}, in object {}) during {} verification.",
+            slot.get(),
+            name,
+            slot.address(),
+            container_obj,
+            self.phase,
+        );
+
+        if container_obj.is_non_null() {
+            let object = container_obj.to_obj();
+            let cls = object.header().vtbl().class();
+            println!(
+                "\tsource object of {} (size={})",
+                cls.name(self.vm),
+                object.size()
+            );
+        }
+
+        self.fail(&format!("reference {}", msg));
+    }
+
+    fn fail(&self, msg: &str) -> ! {
+        for &(name, region) in &self.spaces {
+            println!("{}: {} (size 0x{:x})", name, region, region.size());
+        }
+
+        println!("perm: {}", self.vm.gc.perm_space.used_region());
+
+        panic!("heap verification failed during {}: {}", self.phase, msg);
+    }
+}
+
+/// checks whether the vtable of the object at this address is valid: the
+/// class of the vtable needs to point back to the same vtable.
+pub fn has_valid_vtable(object_address: Address) -> bool {
+    let vtblptr = object_address.to_obj().header().vtblptr();
+
+    if vtblptr.is_null() || !mem::is_aligned(vtblptr.to_usize(), mem::ptr_width_usize()) {
+        return false;
+    }
+
+    let vtable = unsafe { &*vtblptr.to_ptr::<VTable>() };
+
+    if vtable.classptr().is_null() {
+        return false;
+    }
+
+    match vtable.class().vtable {
+        Some(ref expected) => Address::from_ptr(&**expected as *const VTable) == vtblptr,
+        None => false,
+    }
+}
+
+fn is_free_object(vm: &VM, object_address: Address) -> bool {
+    let cls_def_id = object_address.to_obj().header().vtbl().class().id;
+
+    cls_def_id == vm.vips.free_object_class_def || cls_def_id == vm.vips.free_array_class_def
+}
@@ -1,7 +1,7 @@
 use crate::ctxt::VM;
 use crate::driver::cmd::Args;
 use crate::gc::bump::BumpAllocator;
-use crate::gc::{arena, Address, Collector, GcReason, Region};
+use crate::gc::{arena, Address, Collector, CollectorStats, GcReason, Region, SpaceStats};
 
 pub struct ZeroCollector {
     start: Address,
@@ -66,4 +66,19 @@ impl Collector for ZeroCollector {
             mutator, runtime,
         );
     }
+
+    fn stats(&self) -> CollectorStats {
+        let heap = SpaceStats {
+            name: "heap",
+            used: self.alloc.top().offset_from(self.start),
+            committed: self.end.offset_from(self.start),
+        };
+
+        CollectorStats {
+            minor_collections: 0,
+            full_collections: 0,
+            total_pause: 0f32,
+            spaces: vec![heap],
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use std::cell::Cell;
 use std::collections::HashSet;
 use std::ops::Deref;
+use std::ptr;
 use std::rc::Rc;
 
 use crate::baseline::codegen::CondCode;
@@ -31,6 +32,22 @@ pub use self::arm64::*;
 #[cfg(target_arch = "aarch64")]
 pub mod arm64;
 
+// number of (vtable, target) pairs in an inline cache, call sites
+// that see more classes become megamorphic
+pub const INLINE_CACHE_ENTRIES: usize = 4;
+
+pub fn inline_cache_vtable_disp(first: i32, idx: usize) -> i32 {
+    first + (2 * idx) as i32 * mem::ptr_width()
+}
+
+pub fn inline_cache_target_disp(first: i32, idx: usize) -> i32 {
+    first + (2 * idx + 1) as i32 * mem::ptr_width()
+}
+
+pub fn inline_cache_miss_disp(first: i32) -> i32 {
+    first + (2 * INLINE_CACHE_ENTRIES) as i32 * mem::ptr_width()
+}
+
 pub struct MacroAssembler {
     data: Vec<u8>,
     labels: Vec<Option<usize>>,
@@ -42,6 +59,7 @@ pub struct MacroAssembler {
     gcpoints: GcPoints,
     comments: Comments,
     linenos: LineNumberTable,
+    inlined_lineno: Option<i32>,
     exception_handlers: Vec<ExHandler>,
     scratch_registers: ScratchRegisters,
 }
@@ -59,6 +77,7 @@ impl MacroAssembler {
             gcpoints: GcPoints::new(),
             comments: Comments::new(),
             linenos: LineNumberTable::new(),
+            inlined_lineno: None,
             exception_handlers: Vec::new(),
             scratch_registers: ScratchRegisters::new(),
         }
@@ -127,6 +146,23 @@ impl MacroAssembler {
         self.dseg.add_addr(ptr)
     }
 
+    /// reserves constant pool entries for an inline cache: `INLINE_CACHE_ENTRIES`
+    /// pairs of (vtable, target) followed by the target used on cache misses.
+    /// Returns the displacement of the first entry, all other entries follow
+    /// it without gaps (see `InlineCache` in dora_compile.rs).
+    pub fn add_inline_cache(&mut self, miss_target: *const u8) -> i32 {
+        let first = self.dseg.add_addr(ptr::null());
+
+        for _ in 1..2 * INLINE_CACHE_ENTRIES {
+            self.dseg.add_addr(ptr::null());
+        }
+
+        let miss = self.dseg.add_addr(miss_target);
+        debug_assert_eq!(miss, inline_cache_miss_disp(first));
+
+        first
+    }
+
     pub fn pos(&self) -> usize {
         self.data.len()
     }
@@ -155,11 +191,13 @@ impl MacroAssembler {
     }
 
     pub fn emit_lineno(&mut self, lineno: i32) {
+        let lineno = self.inlined_lineno.unwrap_or(lineno);
         let pos = self.pos() as i32;
         self.linenos.insert(pos, lineno);
     }
 
     pub fn emit_lineno_if_missing(&mut self, lineno: i32) {
+        let lineno = self.inlined_lineno.unwrap_or(lineno);
         let pos = self.pos() as i32;
 
         if self.linenos.get(pos) == 0 {
@@ -167,6 +205,12 @@ impl MacroAssembler {
         }
     }
 
+    /// while code of an inlined function is emitted, all line numbers
+    /// are reported as the line of the call site.
+    pub fn set_inlined_lineno(&mut self, lineno: Option<i32>) {
+        self.inlined_lineno = lineno;
+    }
+
     pub fn emit_gcpoint(&mut self, gcpoint: GcPoint) {
         let pos = self.pos() as i32;
         self.gcpoints.insert(pos, gcpoint);
@@ -202,6 +246,13 @@ impl MacroAssembler {
     }
 
     pub fn emit_bailout(&mut self, lbl: Label, trap: Trap, pos: Position) {
+        // bailouts are emitted at the end of the function, remember
+        // the line of the call site for inlined code
+        let pos = match self.inlined_lineno {
+            Some(lineno) => Position::new(lineno as u32, pos.column),
+            None => pos,
+        };
+
         self.bailouts.push((lbl, trap, pos));
     }
 
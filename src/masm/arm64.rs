@@ -12,7 +12,10 @@ use ctxt::{get_vm, FctId};
 use dora_parser::lexer::position::Position;
 use gc::swiper::CARD_SIZE_BITS;
 use gc::Address;
-use masm::{Label, MacroAssembler};
+use masm::{
+    inline_cache_miss_disp, inline_cache_target_disp, inline_cache_vtable_disp, Label,
+    MacroAssembler, INLINE_CACHE_ENTRIES,
+};
 use mem::ptr_width;
 use object::{offset_of_array_data, offset_of_array_length, Header};
 use os::signal::Trap;
@@ -46,12 +49,14 @@ impl MacroAssembler {
     }
 
     pub fn epilog_with_polling(&mut self, stacksize: i32, polling_page: Address) {
-        self.epilog_without_return(stacksize);
+        // poll while the frame still exists, threads blocked in this
+        // safepoint need a walkable stack
         self.check_polling_page(polling_page);
 
         let gcpoint = GcPoint::new();
         self.emit_gcpoint(gcpoint);
 
+        self.epilog_without_return(stacksize);
         self.emit_u32(asm::ret());
     }
 
@@ -104,6 +109,44 @@ impl MacroAssembler {
         self.emit_bailout_info(BailoutInfo::Compile(fct_id, disp + pos, cls_tps, fct_tps));
     }
 
+    // removes the current frame and jumps to the function, parameters need
+    // to be passed in registers. As long as the function isn't compiled yet,
+    // it is called instead such that the compile stub can patch the call site.
+    pub fn direct_tail_call(
+        &mut self,
+        fct_id: FctId,
+        ptr: *const u8,
+        cls_tps: TypeParams,
+        fct_tps: TypeParams,
+        stacksize: i32,
+    ) {
+        let vm = get_vm();
+        let disp = self.add_addr(ptr);
+        let pos = self.pos() as i32;
+
+        let scratch = self.get_scratch();
+        self.load_constpool(*scratch, disp + pos);
+
+        let thunk_disp = self.add_addr(vm.compiler_thunk().to_ptr());
+        let pos = self.pos() as i32;
+
+        let thunk = self.get_scratch();
+        self.load_constpool(*thunk, thunk_disp + pos);
+        self.cmp_reg(MachineMode::Ptr, *scratch, *thunk);
+
+        let lbl_call = self.create_label();
+        self.jump_if(CondCode::Equal, lbl_call);
+
+        self.epilog_without_return(stacksize);
+        self.emit_u32(asm::br(*scratch));
+
+        self.bind_label(lbl_call);
+        self.emit_u32(asm::blr(*scratch));
+
+        let pos = self.pos() as i32;
+        self.emit_bailout_info(BailoutInfo::Compile(fct_id, disp + pos, cls_tps, fct_tps));
+    }
+
     pub fn raw_call(&mut self, ptr: *const u8) {
         let disp = self.add_addr(ptr);
         let pos = self.pos() as i32;
@@ -116,27 +159,100 @@ impl MacroAssembler {
 
     pub fn indirect_call(&mut self, line: i32, index: u32) {
         let obj = REG_PARAMS[0];
+        let vm = get_vm();
 
-        // need to use scratch register instead of REG_RESULT for calculations
+        // need to use scratch registers instead of REG_RESULT for calculations
         // since REG_RESULT (x0) is also the first parameter
-        let scratch = self.get_scratch();
+        let vtable = self.get_scratch();
+        let target = self.get_scratch();
 
-        // scratch = [obj] (load vtable)
-        self.load_base(MachineMode::Ptr, scratch.reg().into(), obj, 0, Some(line));
+        // vtable = [obj] (load vtable)
+        self.load_base(MachineMode::Ptr, vtable.reg().into(), obj, 0, Some(line));
+
+        let cache = self.add_inline_cache(vm.compiler_thunk().to_ptr());
+        let lbl_call = self.create_label();
+        self.inline_cache_lookup(cache, *vtable, *target, lbl_call);
+
+        // cache miss: the miss target is the compiler thunk that updates the cache,
+        // when the call site is megamorphic the miss target is nil and
+        // the target is loaded from the vtable
+        let pos = self.pos() as i32;
+        self.load_constpool(*target, inline_cache_miss_disp(cache) + pos);
+        self.cmp_zero(MachineMode::Ptr, *target);
+        self.jump_if(CondCode::NotEqual, lbl_call);
 
         // calculate offset of VTable entry
         let disp = VTable::offset_of_method_table() + (index as i32) * ptr_width();
 
-        // load vtable entry into scratch
+        // load vtable entry into target
         self.load_mem(
             MachineMode::Ptr,
-            scratch.reg().into(),
-            Mem::Base(*scratch, disp),
+            target.reg().into(),
+            Mem::Base(*vtable, disp),
         );
 
-        // call *scratch
-        self.emit_u32(asm::blr(*scratch));
-        self.emit_bailout_info(BailoutInfo::VirtCompile(index, TypeParams::empty()));
+        // call *target
+        self.bind_label(lbl_call);
+        self.emit_u32(asm::blr(*target));
+
+        let pos = self.pos() as i32;
+        self.emit_bailout_info(BailoutInfo::VirtCompile(
+            index,
+            TypeParams::empty(),
+            cache + pos,
+        ));
+    }
+
+    // Calls a method on a trait object. There is no fixed vtable index for
+    // trait methods, on a cache miss the compiler thunk looks up the
+    // method in the interface table of the receiver's class.
+    pub fn trait_call(&mut self, line: i32, fct_id: FctId) {
+        let obj = REG_PARAMS[0];
+        let vm = get_vm();
+
+        // need to use scratch registers instead of REG_RESULT for calculations
+        // since REG_RESULT (x0) is also the first parameter
+        let vtable = self.get_scratch();
+        let target = self.get_scratch();
+
+        // vtable = [obj] (load vtable)
+        self.load_base(MachineMode::Ptr, vtable.reg().into(), obj, 0, Some(line));
+
+        let cache = self.add_inline_cache(vm.compiler_thunk().to_ptr());
+        let lbl_call = self.create_label();
+        self.inline_cache_lookup(cache, *vtable, *target, lbl_call);
+
+        // cache miss: always invoke the compiler thunk, even when
+        // the cache is full
+        let disp = self.add_addr(vm.compiler_thunk().to_ptr());
+        let pos = self.pos() as i32;
+        self.load_constpool(*target, disp + pos);
+
+        // call *target
+        self.bind_label(lbl_call);
+        self.emit_u32(asm::blr(*target));
+
+        let pos = self.pos() as i32;
+        self.emit_bailout_info(BailoutInfo::TraitCompile(fct_id, cache + pos));
+    }
+
+    // compares vtable with all cached vtables, on hit loads the
+    // cached target and jumps to lbl_call
+    fn inline_cache_lookup(&mut self, cache: i32, vtable: Reg, target: Reg, lbl_call: Label) {
+        for idx in 0..INLINE_CACHE_ENTRIES {
+            let lbl_next = self.create_label();
+
+            let pos = self.pos() as i32;
+            self.load_constpool(target, inline_cache_vtable_disp(cache, idx) + pos);
+            self.cmp_reg(MachineMode::Ptr, vtable, target);
+            self.jump_if(CondCode::NotEqual, lbl_next);
+
+            let pos = self.pos() as i32;
+            self.load_constpool(target, inline_cache_target_disp(cache, idx) + pos);
+            self.jump(lbl_call);
+
+            self.bind_label(lbl_next);
+        }
     }
 
     pub fn load_array_elem(&mut self, mode: MachineMode, dest: ExprStore, array: Reg, index: Reg) {
@@ -1038,6 +1154,10 @@ impl MacroAssembler {
         self.emit_u32(asm::nop());
     }
 
+    pub fn load_fence(&mut self) {
+        self.emit_u32(asm::dmb_ishld());
+    }
+
     pub fn fix_forward_jumps(&mut self) {
         for jmp in &self.jumps {
             let target = self.labels[jmp.to.0].expect("label not defined");
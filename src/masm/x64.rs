@@ -8,7 +8,10 @@ use crate::ctxt::get_vm;
 use crate::ctxt::FctId;
 use crate::gc::swiper::CARD_SIZE_BITS;
 use crate::gc::Address;
-use crate::masm::{Label, MacroAssembler};
+use crate::masm::{
+    inline_cache_miss_disp, inline_cache_target_disp, inline_cache_vtable_disp, Label,
+    MacroAssembler, INLINE_CACHE_ENTRIES,
+};
 use crate::mem::{fits_i32, ptr_width};
 use crate::object::{offset_of_array_data, offset_of_array_length, Header};
 use crate::os::signal::Trap;
@@ -28,12 +31,14 @@ impl MacroAssembler {
     }
 
     pub fn epilog_with_polling(&mut self, stacksize: i32, polling_page: Address) {
-        self.epilog_without_return(stacksize);
+        // poll while the frame still exists, threads blocked in this
+        // safepoint need a walkable stack
         self.check_polling_page(polling_page);
 
         let gcpoint = GcPoint::new();
         self.emit_gcpoint(gcpoint);
 
+        self.epilog_without_return(stacksize);
         asm::emit_retq(self);
     }
 
@@ -67,6 +72,42 @@ impl MacroAssembler {
         self.emit_bailout_info(BailoutInfo::Compile(fct_id, disp + pos, cls_tps, fct_tps));
     }
 
+    // removes the current frame and jumps to the function, parameters need
+    // to be passed in registers. As long as the function isn't compiled yet,
+    // it is called instead such that the compile stub can patch the call site.
+    pub fn direct_tail_call(
+        &mut self,
+        fct_id: FctId,
+        ptr: *const u8,
+        cls_tps: TypeParams,
+        fct_tps: TypeParams,
+        stacksize: i32,
+    ) {
+        let vm = get_vm();
+        let disp = self.add_addr(ptr);
+        let pos = self.pos() as i32;
+
+        self.load_constpool(REG_RESULT, disp + pos);
+
+        let thunk_disp = self.add_addr(vm.compiler_thunk().to_ptr());
+        let pos = self.pos() as i32;
+
+        self.load_constpool(REG_TMP1, thunk_disp + pos);
+        self.cmp_reg(MachineMode::Ptr, REG_RESULT, REG_TMP1);
+
+        let lbl_call = self.create_label();
+        self.jump_if(CondCode::Equal, lbl_call);
+
+        self.epilog_without_return(stacksize);
+        self.jump_reg(REG_RESULT);
+
+        self.bind_label(lbl_call);
+        self.call_reg(REG_RESULT);
+
+        let pos = self.pos() as i32;
+        self.emit_bailout_info(BailoutInfo::Compile(fct_id, disp + pos, cls_tps, fct_tps));
+    }
+
     pub fn raw_call(&mut self, ptr: *const u8) {
         let disp = self.add_addr(ptr);
         let pos = self.pos() as i32;
@@ -77,12 +118,25 @@ impl MacroAssembler {
 
     pub fn indirect_call(&mut self, line: i32, index: u32) {
         let obj = REG_PARAMS[0];
+        let vm = get_vm();
 
         self.emit_lineno(line);
         self.emit_nil_check();
 
-        // REG_RESULT = [obj] (load vtable)
-        self.load_mem(MachineMode::Ptr, REG_RESULT.into(), Mem::Base(obj, 0));
+        // REG_TMP1 = [obj] (load vtable)
+        self.load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Base(obj, 0));
+
+        let cache = self.add_inline_cache(vm.compiler_thunk().to_ptr());
+        let lbl_call = self.create_label();
+        self.inline_cache_lookup(cache, REG_TMP1, REG_RESULT, lbl_call);
+
+        // cache miss: the miss target is the compiler thunk that updates the cache,
+        // when the call site is megamorphic the miss target is nil and
+        // the target is loaded from the vtable
+        let pos = self.pos() as i32;
+        self.load_constpool(REG_RESULT, inline_cache_miss_disp(cache) + pos);
+        self.cmp_zero(MachineMode::Ptr, REG_RESULT);
+        self.jump_if(CondCode::NotEqual, lbl_call);
 
         // calculate offset of VTable entry
         let disp = VTable::offset_of_method_table() + (index as i32) * ptr_width();
@@ -91,12 +145,69 @@ impl MacroAssembler {
         self.load_mem(
             MachineMode::Ptr,
             REG_RESULT.into(),
-            Mem::Base(REG_RESULT, disp),
+            Mem::Base(REG_TMP1, disp),
         );
 
         // call *REG_RESULT
+        self.bind_label(lbl_call);
+        self.call_reg(REG_RESULT);
+
+        let pos = self.pos() as i32;
+        self.emit_bailout_info(BailoutInfo::VirtCompile(
+            index,
+            TypeParams::empty(),
+            cache + pos,
+        ));
+    }
+
+    // Calls a method on a trait object. There is no fixed vtable index for
+    // trait methods, on a cache miss the compiler thunk looks up the
+    // method in the interface table of the receiver's class.
+    pub fn trait_call(&mut self, line: i32, fct_id: FctId) {
+        let obj = REG_PARAMS[0];
+        let vm = get_vm();
+
+        self.emit_lineno(line);
+        self.emit_nil_check();
+
+        // REG_TMP1 = [obj] (load vtable)
+        self.load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Base(obj, 0));
+
+        let cache = self.add_inline_cache(vm.compiler_thunk().to_ptr());
+        let lbl_call = self.create_label();
+        self.inline_cache_lookup(cache, REG_TMP1, REG_RESULT, lbl_call);
+
+        // cache miss: always invoke the compiler thunk, even when
+        // the cache is full
+        let disp = self.add_addr(vm.compiler_thunk().to_ptr());
+        let pos = self.pos() as i32;
+        self.load_constpool(REG_RESULT, disp + pos);
+
+        // call *REG_RESULT
+        self.bind_label(lbl_call);
         self.call_reg(REG_RESULT);
-        self.emit_bailout_info(BailoutInfo::VirtCompile(index, TypeParams::empty()));
+
+        let pos = self.pos() as i32;
+        self.emit_bailout_info(BailoutInfo::TraitCompile(fct_id, cache + pos));
+    }
+
+    // compares vtable with all cached vtables, on hit loads the
+    // cached target and jumps to lbl_call
+    fn inline_cache_lookup(&mut self, cache: i32, vtable: Reg, target: Reg, lbl_call: Label) {
+        for idx in 0..INLINE_CACHE_ENTRIES {
+            let lbl_next = self.create_label();
+
+            let pos = self.pos() as i32;
+            self.load_constpool(target, inline_cache_vtable_disp(cache, idx) + pos);
+            self.cmp_reg(MachineMode::Ptr, vtable, target);
+            self.jump_if(CondCode::NotEqual, lbl_next);
+
+            let pos = self.pos() as i32;
+            self.load_constpool(target, inline_cache_target_disp(cache, idx) + pos);
+            self.jump(lbl_call);
+
+            self.bind_label(lbl_next);
+        }
     }
 
     pub fn load_array_elem(&mut self, mode: MachineMode, dest: ExprStore, array: Reg, index: Reg) {
@@ -995,6 +1106,10 @@ impl MacroAssembler {
         asm::emit_nop(self);
     }
 
+    pub fn load_fence(&mut self) {
+        // x64 doesn't reorder loads with other loads
+    }
+
     pub fn emit_label(&mut self, lbl: Label) {
         let value = self.labels[lbl.index()];
 
@@ -23,12 +23,20 @@ pub struct Header {
 
     // forwarding ptr
     // (used during mark-compact)
+    // upper bits store the identity hash of the object
     fwdptr: AtomicUsize,
 }
 
 const MARK_BITS: usize = 2;
 const MARK_MASK: usize = (2 << MARK_BITS) - 1;
-const FWD_MASK: usize = !0 & !MARK_MASK;
+
+// addresses only use the lower 48 bits, the identity hash
+// is stored in the remaining bits of the fwdptr word
+const HASH_SHIFT: usize = 48;
+const HASH_MASK: usize = !0 << HASH_SHIFT;
+const FWD_MASK: usize = !0 & !MARK_MASK & !HASH_MASK;
+
+static IDENTITY_HASH_SEED: AtomicUsize = AtomicUsize::new(1);
 
 impl Header {
     #[cfg(test)]
@@ -164,10 +172,39 @@ impl Header {
 
     #[inline(always)]
     pub fn set_fwdptr_non_atomic(&mut self, addr: Address) {
-        debug_assert!((addr.to_usize() & MARK_MASK) == 0);
+        debug_assert!((addr.to_usize() & !FWD_MASK) == 0);
         let fwdptr = self.fwdptr.load(Ordering::Relaxed);
-        self.fwdptr
-            .store(addr.to_usize() | (fwdptr & MARK_MASK), Ordering::Relaxed);
+        self.fwdptr.store(
+            addr.to_usize() | (fwdptr & (MARK_MASK | HASH_MASK)),
+            Ordering::Relaxed,
+        );
+    }
+
+    // returns identity hash of object, assigns a new hash on first
+    // call. Hash is stored in the header and therefore survives when
+    // the object is moved by the GC.
+    pub fn identity_hash(&self) -> i32 {
+        let mut fwdptr = self.fwdptr.load(Ordering::Relaxed);
+
+        loop {
+            let hash = fwdptr >> HASH_SHIFT;
+
+            if hash != 0 {
+                return hash as i32;
+            }
+
+            let hash = next_identity_hash();
+
+            match self.fwdptr.compare_exchange(
+                fwdptr,
+                fwdptr | (hash << HASH_SHIFT),
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return hash as i32,
+                Err(actual) => fwdptr = actual,
+            }
+        }
     }
 
     #[inline(always)]
@@ -179,7 +216,7 @@ impl Header {
     #[inline(always)]
     pub fn unmark_non_atomic(&mut self) {
         let fwdptr = self.fwdptr.load(Ordering::Relaxed);
-        self.fwdptr.store(fwdptr & FWD_MASK, Ordering::Relaxed);
+        self.fwdptr.store(fwdptr & !MARK_MASK, Ordering::Relaxed);
     }
 
     #[inline(always)]
@@ -209,6 +246,20 @@ impl Header {
     }
 }
 
+fn next_identity_hash() -> usize {
+    let seed = IDENTITY_HASH_SEED.fetch_add(1, Ordering::Relaxed);
+
+    // spread consecutive seeds over all hash bits (Fibonacci hashing),
+    // 0 is reserved for objects without identity hash
+    let hash = (seed as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> HASH_SHIFT;
+
+    if hash == 0 {
+        1
+    } else {
+        hash as usize
+    }
+}
+
 // is used to reference any object
 #[repr(C)]
 pub struct Obj {
@@ -630,6 +681,10 @@ impl ArrayElement for Ref<Str> {
     const REF: bool = true;
 }
 
+impl ArrayElement for Ref<Obj> {
+    const REF: bool = true;
+}
+
 #[repr(C)]
 pub struct Array<T: Copy> {
     header: Header,
@@ -717,6 +772,7 @@ pub type LongArray = Array<i64>;
 pub type FloatArray = Array<f32>;
 pub type DoubleArray = Array<f64>;
 pub type StrArray = Array<Ref<Str>>;
+pub type ObjArray = Array<Ref<Obj>>;
 
 pub fn alloc(vm: &VM, clsid: ClassDefId) -> Ref<Obj> {
     let cls_def = vm.class_defs.idx(clsid);
@@ -750,6 +806,24 @@ pub struct StackTraceElement {
     pub line: i32,
 }
 
+#[repr(C)]
+pub struct GcStats {
+    pub header: Header,
+    pub minor_collections: i32,
+    pub full_collections: i32,
+    pub total_pause: f64,
+    pub allocated: i64,
+    pub spaces: Ref<ObjArray>,
+}
+
+#[repr(C)]
+pub struct GcSpaceStats {
+    pub header: Header,
+    pub name: Ref<Str>,
+    pub used: i64,
+    pub committed: i64,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::object::Header;
@@ -770,4 +844,25 @@ mod tests {
         assert_eq!(false, h.is_marked_non_atomic());
         assert_eq!(16, h.fwdptr_non_atomic().to_usize());
     }
+
+    #[test]
+    fn header_identity_hash() {
+        let mut h = Header::new();
+        let hash = h.identity_hash();
+        assert!(hash != 0);
+        assert_eq!(hash, h.identity_hash());
+
+        h.mark_non_atomic();
+        h.set_fwdptr_non_atomic(0x7fff_ffff_fff8.into());
+        assert_eq!(true, h.is_marked_non_atomic());
+        assert_eq!(0x7fff_ffff_fff8, h.fwdptr_non_atomic().to_usize());
+        assert_eq!(hash, h.identity_hash());
+
+        h.unmark_non_atomic();
+        assert_eq!(false, h.is_marked_non_atomic());
+        assert_eq!(hash, h.identity_hash());
+
+        let other = Header::new();
+        assert!(hash != other.identity_hash());
+    }
 }
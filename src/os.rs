@@ -1,6 +1,7 @@
 pub use self::mem::*;
 pub use self::signal::*;
 
+pub mod gdb;
 pub mod mem;
 pub mod perf;
 pub mod signal;
@@ -0,0 +1,629 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::ptr;
+
+use crate::baseline::fct::JitBaselineFct;
+use crate::ctxt::{Fct, VM};
+use crate::gc::Address;
+
+// Registers compiled code with the JIT interface of GDB, which is also
+// supported by LLDB. For every function an in-memory ELF object is created
+// that contains a symbol for the function and DWARF line information built
+// from the function's line number table.
+//
+// see https://sourceware.org/gdb/onlinedocs/gdb/JIT-Interface.html
+
+const JIT_NOACTION: u32 = 0;
+const JIT_REGISTER_FN: u32 = 1;
+const JIT_UNREGISTER_FN: u32 = 2;
+
+#[repr(C)]
+struct JitCodeEntry {
+    next_entry: *mut JitCodeEntry,
+    prev_entry: *mut JitCodeEntry,
+    symfile_addr: *const u8,
+    symfile_size: u64,
+}
+
+#[repr(C)]
+pub struct JitDescriptor {
+    version: u32,
+    action_flag: u32,
+    relevant_entry: *mut JitCodeEntry,
+    first_entry: *mut JitCodeEntry,
+}
+
+// the debugger looks up both symbols by name
+#[no_mangle]
+#[allow(non_upper_case_globals)]
+pub static mut __jit_debug_descriptor: JitDescriptor = JitDescriptor {
+    version: 1,
+    action_flag: JIT_NOACTION,
+    relevant_entry: ptr::null_mut(),
+    first_entry: ptr::null_mut(),
+};
+
+#[no_mangle]
+#[inline(never)]
+pub extern "C" fn __jit_debug_register_code() {
+    // the debugger sets a breakpoint in this function,
+    // make sure it isn't optimized away
+    unsafe {
+        ptr::read_volatile(&__jit_debug_descriptor.action_flag);
+    }
+}
+
+pub struct GdbJit {
+    entries: Mutex<HashMap<Address, Registration>>,
+}
+
+struct Registration {
+    entry: Box<JitCodeEntry>,
+    _symfile: Vec<u8>,
+}
+
+// entries are only accessed while holding the lock in `GdbJit`
+unsafe impl Send for Registration {}
+
+impl GdbJit {
+    pub fn new() -> GdbJit {
+        GdbJit {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn register(&self, fct_ptr: Address, symfile: Vec<u8>) {
+        let mut entries = self.entries.lock();
+
+        let mut entry = Box::new(JitCodeEntry {
+            next_entry: ptr::null_mut(),
+            prev_entry: ptr::null_mut(),
+            symfile_addr: symfile.as_ptr(),
+            symfile_size: symfile.len() as u64,
+        });
+
+        unsafe {
+            let descriptor = &mut __jit_debug_descriptor;
+            let entry: *mut JitCodeEntry = &mut *entry;
+
+            (*entry).next_entry = descriptor.first_entry;
+
+            if !descriptor.first_entry.is_null() {
+                (*descriptor.first_entry).prev_entry = entry;
+            }
+
+            descriptor.first_entry = entry;
+            descriptor.relevant_entry = entry;
+            descriptor.action_flag = JIT_REGISTER_FN;
+            __jit_debug_register_code();
+        }
+
+        let registration = Registration {
+            entry: entry,
+            _symfile: symfile,
+        };
+
+        entries.insert(fct_ptr, registration);
+    }
+
+    pub fn unregister(&self, fct_ptr: Address) {
+        let mut entries = self.entries.lock();
+
+        let mut registration = match entries.remove(&fct_ptr) {
+            Some(registration) => registration,
+            None => return,
+        };
+
+        unsafe {
+            let descriptor = &mut __jit_debug_descriptor;
+            let entry: *mut JitCodeEntry = &mut *registration.entry;
+
+            let prev = (*entry).prev_entry;
+            let next = (*entry).next_entry;
+
+            if prev.is_null() {
+                descriptor.first_entry = next;
+            } else {
+                (*prev).next_entry = next;
+            }
+
+            if !next.is_null() {
+                (*next).prev_entry = prev;
+            }
+
+            descriptor.relevant_entry = entry;
+            descriptor.action_flag = JIT_UNREGISTER_FN;
+            __jit_debug_register_code();
+        }
+
+        // the ELF object is freed after the debugger was notified
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn register_with_gdb(jit_fct: &JitBaselineFct, vm: &VM, fct: &Fct) {
+    let name = fct.full_name(vm);
+    let file = fct.source_file(vm).unwrap_or("<unknown>");
+
+    let symfile = elf_object(
+        &name,
+        file,
+        jit_fct.fct_ptr(),
+        jit_fct.fct_len(),
+        fct.pos().line as i32,
+        &jit_fct.linenos().entries(),
+    );
+
+    vm.gdb_jit.register(jit_fct.fct_ptr(), symfile);
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn register_with_gdb(_: &JitBaselineFct, _: &VM, _: &Fct) {
+    // nothing to do
+}
+
+#[cfg(target_arch = "x86_64")]
+pub const EM_MACHINE: u16 = 62;
+
+#[cfg(target_arch = "aarch64")]
+pub const EM_MACHINE: u16 = 183;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_NOBITS: u32 = 8;
+
+const SHF_ALLOC: u64 = 2;
+const SHF_EXECINSTR: u64 = 4;
+
+const SHN_ABS: u16 = 0xfff1;
+
+const STB_LOCAL: u8 = 0;
+const STB_GLOBAL: u8 = 1;
+const STT_FUNC: u8 = 2;
+const STT_FILE: u8 = 4;
+
+const ELF_HEADER_SIZE: usize = 64;
+const SECTION_HEADER_SIZE: usize = 64;
+const SYMBOL_SIZE: usize = 24;
+
+// index of the .text section
+const TEXT_SECTION: u16 = 1;
+
+struct Section {
+    name: u32,
+    ty: u32,
+    flags: u64,
+    addr: u64,
+    data: Vec<u8>,
+    size: u64,
+    link: u32,
+    info: u32,
+    entsize: u64,
+}
+
+impl Section {
+    fn new(name: u32, ty: u32, data: Vec<u8>) -> Section {
+        let size = data.len() as u64;
+
+        Section {
+            name: name,
+            ty: ty,
+            flags: 0,
+            addr: 0,
+            data: data,
+            size: size,
+            link: 0,
+            info: 0,
+            entsize: 0,
+        }
+    }
+}
+
+struct StringTable {
+    data: Vec<u8>,
+}
+
+impl StringTable {
+    fn new() -> StringTable {
+        StringTable { data: vec![0] }
+    }
+
+    fn add(&mut self, value: &str) -> u32 {
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(value.as_bytes());
+        self.data.push(0);
+
+        offset
+    }
+}
+
+// Builds a relocatable ELF object for a single function. The .text section
+// has no content, it only describes the address range of the machine code.
+fn elf_object(
+    name: &str,
+    file: &str,
+    start: Address,
+    size: usize,
+    first_line: i32,
+    linenos: &[(i32, i32)],
+) -> Vec<u8> {
+    let start = start.to_usize() as u64;
+    let size = size as u64;
+
+    let mut shstrtab = StringTable::new();
+    let mut strtab = StringTable::new();
+
+    let file_name = strtab.add(file);
+    let fct_name = strtab.add(name);
+
+    let mut symtab = Vec::with_capacity(3 * SYMBOL_SIZE);
+    write_symbol(&mut symtab, 0, 0, 0, 0, 0);
+    write_symbol(
+        &mut symtab,
+        file_name,
+        STT_FILE | (STB_LOCAL << 4),
+        SHN_ABS,
+        0,
+        0,
+    );
+    write_symbol(
+        &mut symtab,
+        fct_name,
+        STT_FUNC | (STB_GLOBAL << 4),
+        TEXT_SECTION,
+        start,
+        size,
+    );
+
+    let mut text = Section::new(shstrtab.add(".text"), SHT_NOBITS, Vec::new());
+    text.flags = SHF_ALLOC | SHF_EXECINSTR;
+    text.addr = start;
+    text.size = size;
+
+    let mut symtab = Section::new(shstrtab.add(".symtab"), SHT_SYMTAB, symtab);
+    // link to .strtab, index of first non-local symbol
+    symtab.link = 3;
+    symtab.info = 2;
+    symtab.entsize = SYMBOL_SIZE as u64;
+
+    let strtab = Section::new(shstrtab.add(".strtab"), SHT_STRTAB, strtab.data);
+
+    let debug_abbrev = Section::new(shstrtab.add(".debug_abbrev"), SHT_PROGBITS, debug_abbrev());
+
+    let debug_info = debug_info(file, start, size);
+    let debug_info = Section::new(shstrtab.add(".debug_info"), SHT_PROGBITS, debug_info);
+
+    let debug_line = debug_line(file, start, size, first_line, linenos);
+    let debug_line = Section::new(shstrtab.add(".debug_line"), SHT_PROGBITS, debug_line);
+
+    let shstrtab_name = shstrtab.add(".shstrtab");
+    let shstrtab = Section::new(shstrtab_name, SHT_STRTAB, shstrtab.data);
+
+    let null = Section::new(0, 0, Vec::new());
+
+    let sections = vec![
+        null,
+        text,
+        symtab,
+        strtab,
+        debug_abbrev,
+        debug_info,
+        debug_line,
+        shstrtab,
+    ];
+    let shstrndx = (sections.len() - 1) as u16;
+
+    // section contents follow the ELF header, the section headers are at the end
+    let mut offsets = Vec::with_capacity(sections.len());
+    let mut offset = ELF_HEADER_SIZE;
+
+    for section in &sections {
+        offset = align8(offset);
+        offsets.push(offset);
+        offset += section.data.len();
+    }
+
+    let shoff = align8(offset);
+
+    let mut buf = Vec::with_capacity(shoff + sections.len() * SECTION_HEADER_SIZE);
+    write_elf_header(&mut buf, shoff as u64, sections.len() as u16, shstrndx);
+
+    for (section, &offset) in sections.iter().zip(&offsets) {
+        buf.resize(offset, 0);
+        buf.extend_from_slice(&section.data);
+    }
+
+    buf.resize(shoff, 0);
+
+    for (section, &offset) in sections.iter().zip(&offsets) {
+        write_section_header(&mut buf, section, offset as u64);
+    }
+
+    buf
+}
+
+fn align8(value: usize) -> usize {
+    (value + 7) & !7
+}
+
+fn write_elf_header(buf: &mut Vec<u8>, shoff: u64, shnum: u16, shstrndx: u16) {
+    // magic, 64-bit, little endian, version 1, System V ABI
+    buf.extend_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1, 0]);
+    buf.extend_from_slice(&[0; 8]);
+
+    // relocatable file
+    buf.write_u16::<LittleEndian>(1).unwrap();
+    buf.write_u16::<LittleEndian>(EM_MACHINE).unwrap();
+    buf.write_u32::<LittleEndian>(1).unwrap();
+
+    // entry, program header offset, section header offset
+    buf.write_u64::<LittleEndian>(0).unwrap();
+    buf.write_u64::<LittleEndian>(0).unwrap();
+    buf.write_u64::<LittleEndian>(shoff).unwrap();
+
+    // flags
+    buf.write_u32::<LittleEndian>(0).unwrap();
+    buf.write_u16::<LittleEndian>(ELF_HEADER_SIZE as u16)
+        .unwrap();
+
+    // no program headers
+    buf.write_u16::<LittleEndian>(0).unwrap();
+    buf.write_u16::<LittleEndian>(0).unwrap();
+
+    buf.write_u16::<LittleEndian>(SECTION_HEADER_SIZE as u16)
+        .unwrap();
+    buf.write_u16::<LittleEndian>(shnum).unwrap();
+    buf.write_u16::<LittleEndian>(shstrndx).unwrap();
+}
+
+fn write_section_header(buf: &mut Vec<u8>, section: &Section, offset: u64) {
+    let offset = if section.ty == 0 { 0 } else { offset };
+    let align = if section.ty == SHT_PROGBITS || section.ty == SHT_SYMTAB {
+        8
+    } else {
+        1
+    };
+
+    buf.write_u32::<LittleEndian>(section.name).unwrap();
+    buf.write_u32::<LittleEndian>(section.ty).unwrap();
+    buf.write_u64::<LittleEndian>(section.flags).unwrap();
+    buf.write_u64::<LittleEndian>(section.addr).unwrap();
+    buf.write_u64::<LittleEndian>(offset).unwrap();
+    buf.write_u64::<LittleEndian>(section.size).unwrap();
+    buf.write_u32::<LittleEndian>(section.link).unwrap();
+    buf.write_u32::<LittleEndian>(section.info).unwrap();
+    buf.write_u64::<LittleEndian>(align).unwrap();
+    buf.write_u64::<LittleEndian>(section.entsize).unwrap();
+}
+
+fn write_symbol(buf: &mut Vec<u8>, name: u32, info: u8, shndx: u16, value: u64, size: u64) {
+    buf.write_u32::<LittleEndian>(name).unwrap();
+    buf.push(info);
+    buf.push(0);
+    buf.write_u16::<LittleEndian>(shndx).unwrap();
+    buf.write_u64::<LittleEndian>(value).unwrap();
+    buf.write_u64::<LittleEndian>(size).unwrap();
+}
+
+const DW_TAG_COMPILE_UNIT: u8 = 0x11;
+const DW_CHILDREN_NO: u8 = 0;
+const DW_AT_NAME: u8 = 0x03;
+const DW_AT_STMT_LIST: u8 = 0x10;
+const DW_AT_LOW_PC: u8 = 0x11;
+const DW_AT_HIGH_PC: u8 = 0x12;
+const DW_FORM_ADDR: u8 = 0x01;
+const DW_FORM_DATA4: u8 = 0x06;
+const DW_FORM_STRING: u8 = 0x08;
+
+const DW_LNS_COPY: u8 = 1;
+const DW_LNS_ADVANCE_PC: u8 = 2;
+const DW_LNS_ADVANCE_LINE: u8 = 3;
+const DW_LNE_END_SEQUENCE: u8 = 1;
+const DW_LNE_SET_ADDRESS: u8 = 2;
+
+// DWARF 2 is sufficient and supported by all debuggers
+const DWARF_VERSION: u16 = 2;
+
+fn debug_abbrev() -> Vec<u8> {
+    vec![
+        1,
+        DW_TAG_COMPILE_UNIT,
+        DW_CHILDREN_NO,
+        DW_AT_NAME,
+        DW_FORM_STRING,
+        DW_AT_LOW_PC,
+        DW_FORM_ADDR,
+        DW_AT_HIGH_PC,
+        DW_FORM_ADDR,
+        DW_AT_STMT_LIST,
+        DW_FORM_DATA4,
+        0,
+        0,
+        0,
+    ]
+}
+
+fn debug_info(file: &str, start: u64, size: u64) -> Vec<u8> {
+    let mut unit = Vec::new();
+    unit.write_u16::<LittleEndian>(DWARF_VERSION).unwrap();
+    // offset into .debug_abbrev
+    unit.write_u32::<LittleEndian>(0).unwrap();
+    // address size
+    unit.push(8);
+
+    // compile unit
+    unit.push(1);
+    write_cstr(&mut unit, file);
+    unit.write_u64::<LittleEndian>(start).unwrap();
+    unit.write_u64::<LittleEndian>(start + size).unwrap();
+    // offset into .debug_line
+    unit.write_u32::<LittleEndian>(0).unwrap();
+
+    with_unit_length(unit)
+}
+
+// Line numbers are recorded at the return addresses of calls. A row starts
+// at the last byte of the call, since debuggers look up the caller's line
+// with the return address minus one.
+fn debug_line(
+    file: &str,
+    start: u64,
+    size: u64,
+    first_line: i32,
+    linenos: &[(i32, i32)],
+) -> Vec<u8> {
+    let mut header = Vec::new();
+    // minimum instruction length
+    header.push(1);
+    // default is_stmt
+    header.push(1);
+    // line base, line range and opcode base. Special opcodes are never used.
+    header.push((-5i8) as u8);
+    header.push(14);
+    header.push(13);
+    // number of arguments for standard opcodes
+    header.extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+    // no include directories
+    header.push(0);
+    // single file without directory, modification time and length
+    write_cstr(&mut header, file);
+    header.extend_from_slice(&[0, 0, 0]);
+    header.push(0);
+
+    let mut program = Vec::new();
+    program.push(0);
+    write_uleb128(&mut program, 9);
+    program.push(DW_LNE_SET_ADDRESS);
+    program.write_u64::<LittleEndian>(start).unwrap();
+
+    let mut address = 0;
+    let mut line = 1;
+
+    let rows = Some((0, first_line)).into_iter().chain(
+        linenos
+            .iter()
+            .map(|&(offset, line)| (offset.max(1) as u64 - 1, line)),
+    );
+
+    for (row_address, row_line) in rows {
+        if row_address < address || row_address >= size {
+            continue;
+        }
+
+        if row_address > address {
+            program.push(DW_LNS_ADVANCE_PC);
+            write_uleb128(&mut program, row_address - address);
+            address = row_address;
+        }
+
+        if row_line != line {
+            program.push(DW_LNS_ADVANCE_LINE);
+            write_sleb128(&mut program, (row_line - line) as i64);
+            line = row_line;
+        }
+
+        program.push(DW_LNS_COPY);
+    }
+
+    program.push(DW_LNS_ADVANCE_PC);
+    write_uleb128(&mut program, size - address);
+    program.push(0);
+    write_uleb128(&mut program, 1);
+    program.push(DW_LNE_END_SEQUENCE);
+
+    let mut unit = Vec::new();
+    unit.write_u16::<LittleEndian>(DWARF_VERSION).unwrap();
+    unit.write_u32::<LittleEndian>(header.len() as u32).unwrap();
+    unit.extend_from_slice(&header);
+    unit.extend_from_slice(&program);
+
+    with_unit_length(unit)
+}
+
+fn with_unit_length(unit: Vec<u8>) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(unit.len() + 4);
+    buf.write_u32::<LittleEndian>(unit.len() as u32).unwrap();
+    buf.extend_from_slice(&unit);
+
+    buf
+}
+
+fn write_cstr(buf: &mut Vec<u8>, value: &str) {
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(0);
+}
+
+fn write_uleb128(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(buf: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+
+        if done {
+            buf.push(byte);
+            return;
+        }
+
+        buf.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::ByteOrder;
+
+    fn uleb128(value: u64) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_uleb128(&mut buf, value);
+        buf
+    }
+
+    fn sleb128(value: i64) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_sleb128(&mut buf, value);
+        buf
+    }
+
+    #[test]
+    fn test_leb128() {
+        assert_eq!(vec![2], uleb128(2));
+        assert_eq!(vec![0x80, 1], uleb128(128));
+        assert_eq!(vec![0xe5, 0x8e, 0x26], uleb128(624485));
+
+        assert_eq!(vec![2], sleb128(2));
+        assert_eq!(vec![0x7e], sleb128(-2));
+        assert_eq!(vec![0xff, 0], sleb128(127));
+        assert_eq!(vec![0x81, 0x7f], sleb128(-127));
+    }
+
+    #[test]
+    fn test_elf_object() {
+        let obj = elf_object("foo()", "foo.dora", 0x1000.into(), 64, 1, &[(10, 2)]);
+
+        assert_eq!(&[0x7f, b'E', b'L', b'F'], &obj[0..4]);
+        assert_eq!(0, obj.len() % 8);
+
+        // all section headers are at the end of the object
+        let shoff = LittleEndian::read_u64(&obj[40..48]) as usize;
+        let shnum = LittleEndian::read_u16(&obj[60..62]) as usize;
+        assert_eq!(obj.len(), shoff + shnum * SECTION_HEADER_SIZE);
+    }
+}
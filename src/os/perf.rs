@@ -1,9 +1,14 @@
 use crate::baseline::fct::JitBaselineFct;
-use crate::ctxt::VM;
+use crate::ctxt::{Fct, VM};
 use dora_parser::interner::Name;
 
+pub fn register_with_perf(jit_fct: &JitBaselineFct, vm: &VM, fct: &Fct) {
+    write_perf_map(jit_fct, vm, fct.name);
+    vm.perf_jitdump.code_load(jit_fct, vm, fct);
+}
+
 #[cfg(target_os = "linux")]
-pub fn register_with_perf(jit_fct: &JitBaselineFct, vm: &VM, name: Name) {
+fn write_perf_map(jit_fct: &JitBaselineFct, vm: &VM, name: Name) {
     use std::fs::OpenOptions;
     use std::io::prelude::*;
 
@@ -27,10 +32,211 @@ pub fn register_with_perf(jit_fct: &JitBaselineFct, vm: &VM, name: Name) {
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn register_with_perf(_: &JitBaselineFct, _: &VM, _: Name) {
+fn write_perf_map(_: &JitBaselineFct, _: &VM, _: Name) {
     // nothing to do
 }
 
+// Writes the jitdump file `/tmp/jit-<pid>.dump` read by `perf inject --jit`.
+// For every compiled function the file contains the machine code and line
+// information, perf can then annotate JIT code at instruction and source
+// line level. Needs `perf record -k mono`, since records are timestamped
+// with the monotonic clock.
+//
+// see tools/perf/Documentation/jitdump-specification.txt in the Linux sources
+#[cfg(target_os = "linux")]
+pub mod jitdump {
+    use byteorder::{LittleEndian, WriteBytesExt};
+    use parking_lot::Mutex;
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::os::unix::io::AsRawFd;
+    use std::ptr;
+
+    use crate::baseline::fct::JitBaselineFct;
+    use crate::ctxt::{Fct, VM};
+    use crate::os;
+    use crate::os::gdb::EM_MACHINE;
+
+    const JITDUMP_MAGIC: u32 = 0x4A69_5444;
+    const JITDUMP_VERSION: u32 = 1;
+    const JITDUMP_HEADER_SIZE: u32 = 40;
+    const RECORD_HEADER_SIZE: u32 = 16;
+
+    const JIT_CODE_LOAD: u32 = 0;
+    const JIT_CODE_DEBUG_INFO: u32 = 2;
+
+    pub struct JitDump {
+        file: Option<Mutex<JitDumpFile>>,
+    }
+
+    struct JitDumpFile {
+        file: File,
+        code_index: u64,
+    }
+
+    impl JitDump {
+        pub fn new(enabled: bool) -> JitDump {
+            if !enabled {
+                return JitDump { file: None };
+            }
+
+            let pid = unsafe { libc::getpid() };
+            let fname = format!("/tmp/jit-{}.dump", pid);
+
+            let mut file = OpenOptions::new()
+                .create(true)
+                .truncate(true)
+                .read(true)
+                .write(true)
+                .open(&fname)
+                .expect("cannot create jitdump file");
+
+            // perf only finds the file through an executable mapping of it,
+            // the mapping is never used and stays alive until the process exits
+            let marker = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    os::page_size() as usize,
+                    libc::PROT_READ | libc::PROT_EXEC,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+
+            if marker == libc::MAP_FAILED {
+                panic!("mmap of jitdump file failed");
+            }
+
+            let mut header = Vec::with_capacity(JITDUMP_HEADER_SIZE as usize);
+            header.write_u32::<LittleEndian>(JITDUMP_MAGIC).unwrap();
+            header.write_u32::<LittleEndian>(JITDUMP_VERSION).unwrap();
+            header
+                .write_u32::<LittleEndian>(JITDUMP_HEADER_SIZE)
+                .unwrap();
+            header.write_u32::<LittleEndian>(EM_MACHINE as u32).unwrap();
+            header.write_u32::<LittleEndian>(0).unwrap();
+            header.write_u32::<LittleEndian>(pid as u32).unwrap();
+            header.write_u64::<LittleEndian>(timestamp()).unwrap();
+            header.write_u64::<LittleEndian>(0).unwrap();
+            file.write_all(&header).unwrap();
+
+            let file = JitDumpFile {
+                file: file,
+                code_index: 0,
+            };
+
+            JitDump {
+                file: Some(Mutex::new(file)),
+            }
+        }
+
+        pub fn code_load(&self, jit_fct: &JitBaselineFct, vm: &VM, fct: &Fct) {
+            let mutex = match self.file {
+                Some(ref mutex) => mutex,
+                None => return,
+            };
+
+            let mut dump = mutex.lock();
+            let name = format!("dora::{}", fct.full_name(vm));
+            let file = fct.source_file(vm).unwrap_or("<unknown>");
+            let code_addr = jit_fct.fct_ptr().to_usize() as u64;
+            let code = unsafe {
+                std::slice::from_raw_parts(jit_fct.fct_ptr().to_ptr::<u8>(), jit_fct.fct_len())
+            };
+
+            // debug info needs to precede the code it describes. Line numbers
+            // are recorded at return addresses, an entry starts at the last
+            // byte of the call as for the GDB JIT interface.
+            let mut entries = vec![(code_addr, fct.pos().line)];
+
+            for (offset, line) in jit_fct.linenos().entries() {
+                let addr = code_addr + offset.max(1) as u64 - 1;
+                entries.push((addr, line as u32));
+            }
+
+            let entries_size: usize = entries.len() * (8 + 4 + 4 + file.len() + 1);
+            let size = RECORD_HEADER_SIZE as usize + 8 + 8 + entries_size;
+
+            let mut record = Vec::with_capacity(size);
+            write_record_header(&mut record, JIT_CODE_DEBUG_INFO, size);
+            record.write_u64::<LittleEndian>(code_addr).unwrap();
+            record
+                .write_u64::<LittleEndian>(entries.len() as u64)
+                .unwrap();
+
+            for (addr, line) in entries {
+                record.write_u64::<LittleEndian>(addr).unwrap();
+                record.write_u32::<LittleEndian>(line).unwrap();
+                // discriminator
+                record.write_u32::<LittleEndian>(0).unwrap();
+                write_cstr(&mut record, file);
+            }
+
+            dump.file.write_all(&record).unwrap();
+
+            let size = RECORD_HEADER_SIZE as usize + 40 + name.len() + 1 + code.len();
+            let pid = unsafe { libc::getpid() };
+            let tid = unsafe { libc::syscall(libc::SYS_gettid) };
+
+            let mut record = Vec::with_capacity(size);
+            write_record_header(&mut record, JIT_CODE_LOAD, size);
+            record.write_u32::<LittleEndian>(pid as u32).unwrap();
+            record.write_u32::<LittleEndian>(tid as u32).unwrap();
+            // virtual address and address of code
+            record.write_u64::<LittleEndian>(code_addr).unwrap();
+            record.write_u64::<LittleEndian>(code_addr).unwrap();
+            record.write_u64::<LittleEndian>(code.len() as u64).unwrap();
+            record.write_u64::<LittleEndian>(dump.code_index).unwrap();
+            write_cstr(&mut record, &name);
+            record.extend_from_slice(code);
+
+            dump.file.write_all(&record).unwrap();
+            dump.code_index += 1;
+        }
+    }
+
+    fn write_record_header(buf: &mut Vec<u8>, id: u32, size: usize) {
+        buf.write_u32::<LittleEndian>(id).unwrap();
+        buf.write_u32::<LittleEndian>(size as u32).unwrap();
+        buf.write_u64::<LittleEndian>(timestamp()).unwrap();
+    }
+
+    fn write_cstr(buf: &mut Vec<u8>, value: &str) {
+        buf.extend_from_slice(value.as_bytes());
+        buf.push(0);
+    }
+
+    fn timestamp() -> u64 {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+
+        unsafe {
+            libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts);
+        }
+
+        ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub mod jitdump {
+    use crate::baseline::fct::JitBaselineFct;
+    use crate::ctxt::{Fct, VM};
+
+    pub struct JitDump;
+
+    impl JitDump {
+        pub fn new(_enabled: bool) -> JitDump {
+            JitDump
+        }
+
+        pub fn code_load(&self, _: &JitBaselineFct, _: &VM, _: &Fct) {}
+    }
+}
+
 #[derive(Clone)]
 pub struct PerfValues {
     pub l1_misses: u64,
@@ -1,8 +1,9 @@
 use libc;
 use parking_lot::{Condvar, Mutex};
 use std::ptr;
-use std::sync::atomic::{fence, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::cpu::fp_from_execstate;
 use crate::ctxt::{get_vm, VM};
@@ -117,26 +118,53 @@ fn block_dora(vm: &VM, thread: &Arc<DoraThread>, es: &ExecState) {
         .state
         .store(ThreadState::Blocked as usize, Ordering::SeqCst);
 
-    let mut blocking = vm.safepoint.blocking.lock();
+    let _mtx = vm.safepoint.mtx.lock();
+    vm.safepoint.blocked.notify_all();
+}
 
-    assert!(*blocking > 0);
-    *blocking -= 1;
+// Called before a thread starts or continues executing Dora code. While
+// the world is stopped the thread waits in native state until the pause
+// is over.
+pub fn enter_dora(vm: &VM) {
+    THREAD.with(|thread| {
+        let thread = thread.borrow();
 
-    if *blocking == 0 {
-        vm.safepoint.reached_zero.notify_all();
-    }
+        loop {
+            thread.set_state(ThreadState::Dora);
+
+            if !vm.safepoint.in_progress.load(Ordering::SeqCst) {
+                return;
+            }
+
+            thread.set_state(ThreadState::Native);
+
+            // the thread that stops the world holds this lock
+            // until all threads are allowed to continue
+            let _threads = vm.threads.threads.lock();
+        }
+    });
+}
+
+// Called when a thread leaves Dora code, native code doesn't need
+// to be stopped for a safepoint.
+pub fn leave_dora() {
+    THREAD.with(|thread| {
+        thread.borrow().set_state(ThreadState::Native);
+    });
 }
 
 pub struct Safepoint {
-    blocking: Mutex<usize>,
-    reached_zero: Condvar,
+    in_progress: AtomicBool,
+    mtx: Mutex<()>,
+    blocked: Condvar,
 }
 
 impl Safepoint {
     pub fn new() -> Safepoint {
         Safepoint {
-            blocking: Mutex::new(0),
-            reached_zero: Condvar::new(),
+            in_progress: AtomicBool::new(false),
+            mtx: Mutex::new(()),
+            blocked: Condvar::new(),
         }
     }
 }
@@ -148,58 +176,40 @@ where
     // lock threads from starting or exiting
     let threads = vm.threads.threads.lock();
 
-    fence(Ordering::SeqCst);
-
-    {
-        let mut blocking = vm.safepoint.blocking.lock();
-        *blocking = threads.len();
-    }
-
+    vm.safepoint.in_progress.store(true, Ordering::SeqCst);
     vm.polling_page.arm();
-    // pause_threads(vm, &*threads);
+    pause_threads(vm, &*threads);
 
     let ret = f(&*threads);
 
     vm.polling_page.unarm();
+    vm.safepoint.in_progress.store(false, Ordering::SeqCst);
 
     ret
 }
 
+// Waits until no other thread executes Dora code anymore: threads are
+// either blocked in a safepoint or run native code. Threads in native
+// code can't continue with Dora code until the world is resumed.
 fn pause_threads(vm: &VM, threads: &[Arc<DoraThread>]) {
-    check_thread_states(vm, threads);
-    wait_until_threads_reach_safepoints(vm);
-}
-
-fn check_thread_states(vm: &VM, threads: &[Arc<DoraThread>]) {
-    for thread in threads {
-        let state = thread.state();
+    let current = THREAD.with(|thread| thread.borrow().clone());
+    let mut mtx = vm.safepoint.mtx.lock();
 
-        match state {
-            ThreadState::Dora => {
-                // let this thread continue until it reaches safepoint
-            }
-
-            ThreadState::Native => {
-                // native threads can continue to run, we don't need to
-                // wait for those threads to reach safepoints.
-                let mut blocking = vm.safepoint.blocking.lock();
-                assert!(*blocking > 0);
-                *blocking -= 1;
-            }
-
-            ThreadState::Blocked => {
-                // thread is already blocked via safepoint
-            }
-
-            ThreadState::Uninitialized => unreachable!(),
-        }
+    while threads
+        .iter()
+        .any(|thread| !Arc::ptr_eq(thread, &current) && runs_dora(thread))
+    {
+        // a thread could also leave Dora code through a native call
+        // without notifying, so don't wait forever
+        vm.safepoint
+            .blocked
+            .wait_for(&mut mtx, Duration::from_millis(1));
     }
 }
 
-fn wait_until_threads_reach_safepoints(vm: &VM) {
-    let mut blocking = vm.safepoint.blocking.lock();
-
-    while *blocking > 0 {
-        vm.safepoint.reached_zero.wait(&mut blocking);
+fn runs_dora(thread: &DoraThread) -> bool {
+    match thread.state() {
+        ThreadState::Dora => true,
+        ThreadState::Uninitialized | ThreadState::Native | ThreadState::Blocked => false,
     }
 }
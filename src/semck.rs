@@ -1,11 +1,18 @@
+use std::path::Path;
+
 use crate::class::TypeParams;
-use crate::ctxt::{NodeMap, SemContext};
+use crate::ctxt::{Fct, NodeMap, SemContext};
 use crate::mem;
-use crate::sym::Sym::{SymClass, SymClassTypeParam, SymFctTypeParam, SymStruct, SymTrait};
+use crate::sym::Sym::{
+    SymClass, SymClassTypeParam, SymFctTypeParam, SymStruct, SymTrait, SymTraitTypeParam,
+};
+use crate::sym::{ModuleId, PathError};
 use crate::ty::BuiltinType;
 use dora_parser::ast::Type::{TypeBasic, TypeLambda, TypeSelf, TypeTuple};
-use dora_parser::ast::{Stmt, Type};
+use dora_parser::ast::{File, Stmt, Type};
 use dora_parser::error::msg::Msg;
+use dora_parser::interner::Name;
+use dora_parser::lexer::position::Position;
 
 mod abstractck;
 mod clsdefck;
@@ -22,6 +29,7 @@ mod returnck;
 pub mod specialize;
 mod structdefck;
 mod superck;
+mod tailrecck;
 mod traitdefck;
 mod typeck;
 
@@ -97,6 +105,9 @@ pub fn check<'ast>(ctxt: &mut SemContext<'ast>) {
     // checks if function has a return value
     returnck::check(ctxt);
 
+    // checks that recursive calls in tailrec functions are tail calls
+    tailrecck::check(ctxt);
+
     // add size of super classes to field offsets
     superck::check(ctxt);
     return_on_error!(ctxt);
@@ -111,6 +122,12 @@ pub fn check<'ast>(ctxt: &mut SemContext<'ast>) {
     init_global_addresses(ctxt);
 }
 
+// Impls get copies of the default methods they don't override. Errors in
+// their bodies are only reported once for the default method in the trait.
+pub fn skip_default_impl(ctxt: &SemContext, fct: &Fct) -> bool {
+    fct.default_impl && ctxt.diag.lock().has_errors()
+}
+
 fn internalck<'ast>(ctxt: &SemContext<'ast>) {
     for fct in ctxt.fcts.iter() {
         let fct = fct.read();
@@ -176,20 +193,86 @@ fn init_global_addresses<'ast>(ctxt: &SemContext<'ast>) {
         let ty_align = glob.ty.align(ctxt);
 
         let offset = mem::align_i32(size, ty_align);
-        offsets.push(offset);
         size = offset + ty_size;
+
+        // globals with initializer need an additional byte that
+        // records whether the initializer already ran
+        let init_offset = if glob.getter.is_some() {
+            size += 1;
+            Some(size - 1)
+        } else {
+            None
+        };
+
+        offsets.push((offset, init_offset));
     }
 
     let ptr = ctxt.gc.alloc_perm(size as usize);
 
     for (ind, glob) in globals.iter().enumerate() {
         let mut glob = glob.lock();
-        let offset = offsets[ind];
+        let (offset, init_offset) = offsets[ind];
 
         glob.address_value = ptr.offset(offset as usize);
+
+        if let Some(init_offset) = init_offset {
+            glob.address_init = ptr.offset(init_offset as usize);
+        }
+    }
+}
+
+// Files in subdirectories of the program directory belong to the module
+// named after the subdirectory, all other files to the global module.
+fn module_path<'ast>(ctxt: &SemContext<'ast>, file: &File) -> Vec<Name> {
+    let root = Path::new(&ctxt.args.arg_file);
+
+    if !root.is_dir() {
+        return Vec::new();
+    }
+
+    let dir = match Path::new(&file.path).parent() {
+        Some(dir) => dir,
+        None => return Vec::new(),
+    };
+
+    match dir.strip_prefix(root) {
+        Ok(dir) => dir
+            .iter()
+            .map(|name| ctxt.interner.intern(&name.to_string_lossy()))
+            .collect(),
+        Err(_) => Vec::new(),
     }
 }
 
+pub fn file_module<'ast>(ctxt: &SemContext<'ast>, file: &File) -> Option<ModuleId> {
+    let path = module_path(ctxt, file);
+    ctxt.sym.lock().module_by_path(&path)
+}
+
+pub fn path_name<'ast>(ctxt: &SemContext<'ast>, path: &[Name]) -> String {
+    let names: Vec<String> = path
+        .iter()
+        .map(|&name| ctxt.interner.str(name).to_string())
+        .collect();
+
+    names.join("::")
+}
+
+pub fn report_path_error<'ast>(
+    ctxt: &SemContext<'ast>,
+    path: &[Name],
+    err: PathError,
+    pos: Position,
+) {
+    let msg = match err {
+        PathError::Unknown(idx) => Msg::UnknownIdentifier(path_name(ctxt, &path[..idx + 1])),
+        PathError::NotModule(idx) => Msg::ModuleExpected(path_name(ctxt, &path[..idx + 1])),
+        PathError::NotPublic(idx) => Msg::NotPublic(path_name(ctxt, &path[..idx + 1])),
+    };
+
+    ctxt.diag.lock().report_without_path(pos, msg);
+}
+
 pub fn read_type<'ast>(ctxt: &SemContext<'ast>, t: &'ast Type) -> Option<BuiltinType> {
     match *t {
         TypeSelf(_) => {
@@ -197,7 +280,24 @@ pub fn read_type<'ast>(ctxt: &SemContext<'ast>, t: &'ast Type) -> Option<Builtin
         }
 
         TypeBasic(ref basic) => {
-            let sym = ctxt.sym.lock().get(basic.name);
+            let sym = if basic.module.is_empty() {
+                ctxt.sym.lock().get(basic.name)
+            } else {
+                let mut path = basic.module.clone();
+                path.push(basic.name);
+
+                let result = ctxt.sym.lock().get_path(&path);
+
+                match result {
+                    Ok(sym) => Some(sym),
+                    Err(PathError::Unknown(idx)) if idx == basic.module.len() => None,
+                    Err(err) => {
+                        report_path_error(ctxt, &path, err, basic.pos);
+                        return None;
+                    }
+                }
+            };
+
             if let Some(sym) = sym {
                 match sym {
                     SymClass(cls_id) => {
@@ -226,37 +326,20 @@ pub fn read_type<'ast>(ctxt: &SemContext<'ast>, t: &'ast Type) -> Option<Builtin
                                 return None;
                             }
 
-                            for (tp, ty) in cls.type_params.iter().zip(type_params.iter()) {
-                                if let Some(cls_id) = tp.class_bound {
-                                    let cls = ctxt.cls(cls_id);
-
-                                    if !ty.subclass_from(ctxt, cls) {
-                                        let name = ty.name(ctxt);
-                                        let cls = cls.name(ctxt);
-
-                                        let msg = Msg::ClassBoundNotSatisfied(name, cls);
-                                        ctxt.diag.lock().report_without_path(basic.pos, msg);
-                                    }
-                                }
-
-                                let cls_id = if let Some(cls_id) = ty.cls_id(ctxt) {
-                                    cls_id
-                                } else {
+                            for (tp, &ty) in cls.type_params.iter().zip(type_params.iter()) {
+                                // type params can't be checked here: the function
+                                // declaring them might still be locked
+                                if ty.is_type_param() {
                                     continue;
-                                };
-
-                                let cls = ctxt.classes.idx(cls_id);
-                                let cls = cls.read();
-
-                                for &trait_bound in &tp.trait_bounds {
-                                    if !cls.traits.contains(&trait_bound) {
-                                        let bound = ctxt.traits[trait_bound].read();
-                                        let name = ty.name(ctxt);
-                                        let trait_name = ctxt.interner.str(bound.name).to_string();
-                                        let msg = Msg::TraitBoundNotSatisfied(name, trait_name);
-                                        ctxt.diag.lock().report_without_path(bound.pos, msg);
-                                    }
                                 }
+
+                                specialize::check_bounds(
+                                    ctxt,
+                                    basic.pos,
+                                    ty,
+                                    tp.class_bound,
+                                    &tp.trait_bounds,
+                                );
                             }
 
                             let list_id = ctxt.lists.lock().insert(type_params.into());
@@ -272,12 +355,31 @@ pub fn read_type<'ast>(ctxt: &SemContext<'ast>, t: &'ast Type) -> Option<Builtin
                     }
 
                     SymTrait(trait_id) => {
-                        if basic.params.len() > 0 {
-                            let msg = Msg::NoTypeParamsExpected;
+                        let mut type_params = Vec::new();
+
+                        for param in &basic.params {
+                            let param = read_type(ctxt, param);
+
+                            if let Some(param) = param {
+                                type_params.push(param);
+                            } else {
+                                return None;
+                            }
+                        }
+
+                        let xtrait = ctxt.traits[trait_id].read();
+
+                        if xtrait.type_params.len() != type_params.len() {
+                            let msg = Msg::WrongNumberTypeParams(
+                                xtrait.type_params.len(),
+                                type_params.len(),
+                            );
                             ctxt.diag.lock().report_without_path(basic.pos, msg);
+                            return None;
                         }
 
-                        return Some(BuiltinType::Trait(trait_id));
+                        let list_id = ctxt.lists.lock().insert(type_params.into());
+                        return Some(BuiltinType::Trait(trait_id, list_id));
                     }
 
                     SymStruct(struct_id) => {
@@ -308,14 +410,23 @@ pub fn read_type<'ast>(ctxt: &SemContext<'ast>, t: &'ast Type) -> Option<Builtin
                         return Some(BuiltinType::FctTypeParam(fct_id, type_param_id));
                     }
 
+                    SymTraitTypeParam(trait_id, type_param_id) => {
+                        if basic.params.len() > 0 {
+                            let msg = Msg::NoTypeParamsExpected;
+                            ctxt.diag.lock().report_without_path(basic.pos, msg);
+                        }
+
+                        return Some(BuiltinType::TraitTypeParam(trait_id, type_param_id));
+                    }
+
                     _ => {
-                        let name = ctxt.interner.str(basic.name).to_string();
+                        let name = t.to_string(&ctxt.interner);
                         let msg = Msg::ExpectedType(name);
                         ctxt.diag.lock().report_without_path(basic.pos, msg);
                     }
                 }
             } else {
-                let name = ctxt.interner.str(basic.name).to_string();
+                let name = t.to_string(&ctxt.interner);
                 let msg = Msg::UnknownType(name);
                 ctxt.diag.lock().report_without_path(basic.pos, msg);
             }
@@ -325,6 +436,26 @@ pub fn read_type<'ast>(ctxt: &SemContext<'ast>, t: &'ast Type) -> Option<Builtin
             return Some(BuiltinType::Unit);
         }
 
+        // parentheses around a single type don't create a tuple
+        TypeTuple(ref tuple) if tuple.subtypes.len() == 1 => {
+            return read_type(ctxt, &tuple.subtypes[0]);
+        }
+
+        TypeTuple(ref tuple) => {
+            let mut elements = Vec::with_capacity(tuple.subtypes.len());
+
+            for subtype in &tuple.subtypes {
+                if let Some(ty) = read_type(ctxt, subtype) {
+                    elements.push(ty);
+                } else {
+                    return None;
+                }
+            }
+
+            let list_id = ctxt.lists.lock().insert(elements.into());
+            return Some(BuiltinType::Tuple(list_id));
+        }
+
         TypeLambda(ref lambda) => {
             let mut params = vec![];
 
@@ -434,6 +565,22 @@ mod tests {
         });
     }
 
+    pub fn warnings(code: &'static str, vec: &[(Position, Msg)]) {
+        test::parse_with_errors(code, |ctxt| {
+            let diag = ctxt.diag.lock();
+            let warnings = diag.warnings();
+
+            println!("warnings = {:?}", warnings);
+            assert!(!diag.has_errors());
+            assert_eq!(vec.len(), warnings.len());
+
+            for (ind, warning) in warnings.iter().enumerate() {
+                assert_eq!(vec[ind].0, warning.pos);
+                assert_eq!(vec[ind].1, warning.msg);
+            }
+        });
+    }
+
     pub fn pos(line: u32, col: u32) -> Position {
         Position::new(line, col)
     }
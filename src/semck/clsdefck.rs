@@ -1,5 +1,5 @@
 use parking_lot::RwLock;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::class::*;
 use crate::ctxt::{Fct, FctId, FctKind, FctParent, FctSrc, NodeMap, SemContext};
@@ -35,9 +35,17 @@ struct ClsCheck<'x, 'ast: 'x> {
 impl<'x, 'ast> ClsCheck<'x, 'ast> {
     fn check(&mut self) {
         self.visit_ast(self.ast);
+        self.ctxt.sym.lock().set_module(None);
     }
 
-    fn add_field(&mut self, pos: Position, name: Name, ty: BuiltinType, reassignable: bool) {
+    fn add_field(
+        &mut self,
+        pos: Position,
+        name: Name,
+        ty: BuiltinType,
+        reassignable: bool,
+        is_pub: bool,
+    ) {
         let cls = self.ctxt.classes.idx(self.cls_id.unwrap());
         let mut cls = cls.write();
 
@@ -54,6 +62,7 @@ impl<'x, 'ast> ClsCheck<'x, 'ast> {
             ty: ty,
             offset: 0,
             reassignable: reassignable,
+            is_pub: is_pub,
         };
 
         cls.fields.push(field);
@@ -61,6 +70,13 @@ impl<'x, 'ast> ClsCheck<'x, 'ast> {
 }
 
 impl<'x, 'ast> Visitor<'ast> for ClsCheck<'x, 'ast> {
+    fn visit_file(&mut self, f: &'ast ast::File) {
+        let module = semck::file_module(self.ctxt, f);
+        self.ctxt.sym.lock().set_module(module);
+
+        visit::walk_file(self, f);
+    }
+
     fn visit_class(&mut self, c: &'ast ast::Class) {
         self.cls_id = Some(*self.map_cls_defs.get(c.id).unwrap());
 
@@ -102,8 +118,16 @@ impl<'x, 'ast> Visitor<'ast> for ClsCheck<'x, 'ast> {
                                 }
                             }
 
-                            Some(BuiltinType::Trait(trait_id)) => {
-                                if !cls.type_params[type_param_id].trait_bounds.insert(trait_id) {
+                            Some(BuiltinType::Trait(trait_id, list_id)) => {
+                                if self.ctxt.lists.lock().get(list_id).len() > 0 {
+                                    let xtrait = self.ctxt.traits[trait_id].read();
+                                    let name = self.ctxt.interner.str(xtrait.name).to_string();
+                                    let msg = Msg::GenericTraitBound(name);
+                                    self.ctxt.diag.lock().report_without_path(bound.pos(), msg);
+                                } else if !cls.type_params[type_param_id]
+                                    .trait_bounds
+                                    .insert(trait_id)
+                                {
                                     let msg = Msg::DuplicateTraitBound;
                                     self.ctxt
                                         .diag
@@ -202,7 +226,7 @@ impl<'x, 'ast> Visitor<'ast> for ClsCheck<'x, 'ast> {
 
     fn visit_field(&mut self, f: &'ast ast::Field) {
         let ty = semck::read_type(self.ctxt, &f.data_type).unwrap_or(BuiltinType::Unit);
-        self.add_field(f.pos, f.name, ty, f.reassignable);
+        self.add_field(f.pos, f.name, ty, f.reassignable, f.is_pub);
 
         if !f.reassignable && !f.primary_ctor && f.expr.is_none() {
             self.ctxt
@@ -229,6 +253,7 @@ impl<'x, 'ast> Visitor<'ast> for ClsCheck<'x, 'ast> {
             param_types: Vec::new(),
             return_type: BuiltinType::Unit,
             parent: FctParent::Class(clsid),
+            module: self.ctxt.sym.lock().module(),
             has_override: f.has_override,
             has_open: f.has_open,
             has_final: f.has_final,
@@ -244,8 +269,10 @@ impl<'x, 'ast> Visitor<'ast> for ClsCheck<'x, 'ast> {
             vtable_index: None,
             initialized: false,
             impl_for: None,
+            default_impl: false,
 
             type_params: Vec::new(),
+            where_bounds: HashMap::new(),
             kind: kind,
         };
 
@@ -276,6 +303,7 @@ impl<'x, 'ast> Visitor<'ast> for ClsCheck<'x, 'ast> {
             param_types: Vec::new(),
             return_type: BuiltinType::Unit,
             parent: FctParent::Class(self.cls_id.unwrap()),
+            module: self.ctxt.sym.lock().module(),
             has_override: f.has_override,
 
             // abstract for methods also means that method is open to
@@ -293,8 +321,10 @@ impl<'x, 'ast> Visitor<'ast> for ClsCheck<'x, 'ast> {
             vtable_index: None,
             initialized: false,
             impl_for: None,
+            default_impl: false,
 
             type_params: Vec::new(),
+            where_bounds: HashMap::new(),
             kind: kind,
         };
 
@@ -2,7 +2,7 @@ use crate::ctxt::{ConstId, NodeMap, SemContext};
 use crate::semck;
 use crate::ty::BuiltinType;
 
-use dora_parser::ast::visit::Visitor;
+use dora_parser::ast::visit::{self, Visitor};
 use dora_parser::ast::{self, Ast};
 use dora_parser::error::msg::Msg;
 use dora_parser::lexer::position::Position;
@@ -29,10 +29,18 @@ struct ConstCheck<'x, 'ast: 'x> {
 impl<'x, 'ast> ConstCheck<'x, 'ast> {
     fn check(&mut self) {
         self.visit_ast(self.ast);
+        self.ctxt.sym.lock().set_module(None);
     }
 }
 
 impl<'x, 'ast> Visitor<'ast> for ConstCheck<'x, 'ast> {
+    fn visit_file(&mut self, f: &'ast ast::File) {
+        let module = semck::file_module(self.ctxt, f);
+        self.ctxt.sym.lock().set_module(module);
+
+        visit::walk_file(self, f);
+    }
+
     fn visit_const(&mut self, c: &'ast ast::Const) {
         let const_id = *self.map_const_defs.get(c.id).unwrap();
 
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use crate::class::TypeParams;
 use crate::ctxt::{self, Fct, FctId, FctParent, FctSrc, SemContext};
 use crate::semck;
 use crate::sym::Sym;
@@ -16,6 +17,8 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
         let mut fct = fct.write();
         let ast = fct.ast;
 
+        ctxt.sym.lock().set_module(fct.module);
+
         // check modifiers for function
         check_abstract(ctxt, &*fct);
         check_static(ctxt, &*fct);
@@ -48,12 +51,24 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
                 let cls = ctxt.classes.idx(ximpl.cls_id());
                 let cls = cls.read();
 
+                for (type_param_id, &name) in ximpl.type_params.iter().enumerate() {
+                    let sym = Sym::SymClassTypeParam(cls.id, type_param_id.into());
+                    ctxt.sym.lock().insert(name, sym);
+                }
+
                 if fct.has_self() {
                     fct.param_types.push(cls.ty);
                 }
             }
 
-            FctParent::Trait(_) => {
+            FctParent::Trait(trait_id) => {
+                let xtrait = ctxt.traits[trait_id].read();
+
+                for (type_param_id, type_param) in xtrait.type_params.iter().enumerate() {
+                    let sym = Sym::SymTraitTypeParam(trait_id, type_param_id.into());
+                    ctxt.sym.lock().insert(type_param.name, sym);
+                }
+
                 if fct.has_self() {
                     fct.param_types.push(BuiltinType::This);
                 }
@@ -89,8 +104,16 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
                                 }
                             }
 
-                            Some(BuiltinType::Trait(trait_id)) => {
-                                if !fct.type_params[type_param_id].trait_bounds.insert(trait_id) {
+                            Some(BuiltinType::Trait(trait_id, list_id)) => {
+                                if ctxt.lists.lock().get(list_id).len() > 0 {
+                                    let xtrait = ctxt.traits[trait_id].read();
+                                    let name = ctxt.interner.str(xtrait.name).to_string();
+                                    let msg = Msg::GenericTraitBound(name);
+                                    ctxt.diag.lock().report_without_path(bound.pos(), msg);
+                                } else if !fct.type_params[type_param_id]
+                                    .trait_bounds
+                                    .insert(trait_id)
+                                {
                                     let msg = Msg::DuplicateTraitBound;
                                     ctxt.diag.lock().report_without_path(type_param.pos, msg);
                                 }
@@ -117,6 +140,8 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
             }
         }
 
+        check_where_clauses(ctxt, &mut *fct);
+
         for p in &ast.params {
             let ty = semck::read_type(ctxt, &p.data_type).unwrap_or(BuiltinType::Unit);
 
@@ -150,6 +175,7 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
         }
 
         fct.initialized = true;
+        check_test(ctxt, &*fct);
 
         match fct.parent {
             FctParent::Class(clsid) => {
@@ -160,13 +186,15 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
 
             FctParent::Trait(traitid) => {
                 let xtrait = ctxt.traits[traitid].read();
-                let ty = BuiltinType::Trait(traitid);
+                let list_id = ctxt.lists.lock().insert(TypeParams::empty());
+                let ty = BuiltinType::Trait(traitid, list_id);
                 check_against_methods(ctxt, ty, &*fct, &xtrait.methods);
             }
 
             FctParent::Impl(implid) => {
                 let ximpl = ctxt.impls[implid].read();
-                let ty = BuiltinType::Trait(ximpl.trait_id());
+                let list_id = ctxt.lists.lock().insert(TypeParams::empty());
+                let ty = BuiltinType::Trait(ximpl.trait_id(), list_id);
                 check_against_methods(ctxt, ty, &*fct, &ximpl.methods);
             }
 
@@ -196,9 +224,71 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
         ctxt.sym.lock().pop_level();
     }
 
+    ctxt.sym.lock().set_module(None);
+
     debug_assert!(ctxt.sym.lock().levels() == 1);
 }
 
+fn check_where_clauses<'ast>(ctxt: &SemContext<'ast>, fct: &mut Fct<'ast>) {
+    let ast = fct.ast;
+    let owner_cls_id = fct.owner_cls_id(ctxt);
+
+    for clause in &ast.where_clauses {
+        let sym = ctxt.sym.lock().get(clause.name);
+
+        let mut bounds = HashSet::new();
+
+        for bound in &clause.bounds {
+            match semck::read_type(ctxt, bound) {
+                Some(BuiltinType::Trait(trait_id, list_id)) => {
+                    if ctxt.lists.lock().get(list_id).len() > 0 {
+                        let xtrait = ctxt.traits[trait_id].read();
+                        let name = ctxt.interner.str(xtrait.name).to_string();
+                        let msg = Msg::GenericTraitBound(name);
+                        ctxt.diag.lock().report_without_path(bound.pos(), msg);
+                    } else if !bounds.insert(trait_id) {
+                        let msg = Msg::DuplicateTraitBound;
+                        ctxt.diag.lock().report_without_path(clause.pos, msg);
+                    }
+                }
+
+                None => {
+                    // unknown type, error is already thrown
+                }
+
+                _ => {
+                    let msg = Msg::TraitBoundExpected;
+                    ctxt.diag.lock().report_without_path(bound.pos(), msg);
+                }
+            }
+        }
+
+        match sym {
+            Some(Sym::SymFctTypeParam(fct_id, tpid)) if fct_id == fct.id => {
+                fct.type_params[tpid.idx()].trait_bounds.extend(bounds);
+            }
+
+            Some(Sym::SymClassTypeParam(cls_id, tpid)) if owner_cls_id == Some(cls_id) => {
+                fct.where_bounds
+                    .entry(tpid)
+                    .or_insert_with(HashSet::new)
+                    .extend(bounds);
+            }
+
+            Some(Sym::SymTraitTypeParam(_, _)) => {
+                let msg = Msg::BoundNotAllowed;
+                ctxt.diag.lock().report_without_path(clause.pos, msg);
+            }
+
+            _ => {
+                let name = ctxt.interner.str(clause.name).to_string();
+                let msg = Msg::UnknownTypeParam(name);
+                ctxt.diag.lock().report_without_path(clause.pos, msg);
+            }
+        }
+    }
+}
+
 fn check_abstract<'ast>(ctxt: &SemContext<'ast>, fct: &Fct<'ast>) {
     if !fct.is_abstract {
         return;
@@ -219,6 +309,21 @@ fn check_abstract<'ast>(ctxt: &SemContext<'ast>, fct: &Fct<'ast>) {
     }
 }
 
+fn check_test<'ast>(ctxt: &SemContext<'ast>, fct: &Fct<'ast>) {
+    if !fct.ast.is_test {
+        return;
+    }
+
+    // the test runner passes an instance of Testing as only argument
+    let testing_cls = ctxt.cls(ctxt.vips.testing_class);
+
+    if !fct.return_type.is_unit() || fct.param_types != [testing_cls] {
+        let name = ctxt.interner.str(fct.name).to_string();
+        let msg = Msg::InvalidTestFunction(name);
+        ctxt.diag.lock().report_without_path(fct.pos, msg);
+    }
+}
+
 fn check_static<'ast>(ctxt: &SemContext<'ast>, fct: &Fct<'ast>) {
     if !fct.is_static {
         return;
@@ -365,6 +470,27 @@ mod tests {
         err("fun f<>() {}", pos(1, 1), Msg::TypeParamsExpected);
     }
 
+    #[test]
+    fn fct_with_where_clauses() {
+        ok("trait Foo {} trait Bar {} fun f<T>() where T: Foo + Bar {}");
+        ok("trait Foo {} class A<T> { fun f() where T: Foo {} }");
+        err(
+            "trait Foo {} fun f() where T: Foo {}",
+            pos(1, 28),
+            Msg::UnknownTypeParam("T".into()),
+        );
+        err(
+            "class Foo fun f<T>() where T: Foo {}",
+            pos(1, 31),
+            Msg::TraitBoundExpected,
+        );
+        err(
+            "trait Foo {} fun f<T>() where T: Foo + Foo {}",
+            pos(1, 31),
+            Msg::DuplicateTraitBound,
+        );
+    }
+
     #[test]
     fn fct_with_type_param_in_annotation() {
         ok("fun f<T>(val: T) {}");
@@ -472,4 +598,20 @@ mod tests {
         // such that this fct definition is reported as an error
         err("fun f(a: T) {}", pos(1, 10), Msg::UnknownType("T".into()));
     }
+
+    #[test]
+    fn test_attribute() {
+        ok("#[test] fun foo(t: Testing) {}");
+        ok("#[test] #[ignore] fun foo(t: Testing) {}");
+        err(
+            "#[test] fun foo() {}",
+            pos(1, 9),
+            Msg::InvalidTestFunction("foo".into()),
+        );
+        err(
+            "#[test] fun foo(t: Testing) -> Int { return 0; }",
+            pos(1, 9),
+            Msg::InvalidTestFunction("foo".into()),
+        );
+    }
 }
@@ -1,15 +1,17 @@
 use crate::ctxt::{Fct, FctSrc, SemContext};
+use crate::semck;
 use dora_parser::error::msg::Msg;
 
 use dora_parser::ast::visit::*;
 use dora_parser::ast::Stmt::*;
 use dora_parser::ast::*;
+use dora_parser::interner::Name;
 
 pub fn check<'ast>(ctxt: &SemContext<'ast>) {
     for fct in ctxt.fcts.iter() {
         let fct = fct.read();
 
-        if !fct.is_src() {
+        if !fct.is_src() || semck::skip_default_impl(ctxt, &*fct) {
             continue;
         }
 
@@ -22,7 +24,7 @@ pub fn check<'ast>(ctxt: &SemContext<'ast>) {
             fct: &fct,
             src: &mut src,
             ast: ast,
-            in_loop: false,
+            loops: Vec::new(),
         };
 
         flowck.check();
@@ -34,7 +36,9 @@ struct FlowCheck<'a, 'ast: 'a> {
     fct: &'a Fct<'ast>,
     src: &'a mut FctSrc,
     ast: &'ast Function,
-    in_loop: bool,
+
+    // labels of all enclosing loops, innermost loop last
+    loops: Vec<Option<Name>>,
 }
 
 impl<'a, 'ast> FlowCheck<'a, 'ast> {
@@ -42,20 +46,29 @@ impl<'a, 'ast> FlowCheck<'a, 'ast> {
         self.visit_fct(self.ast);
     }
 
-    fn handle_loop(&mut self, block: &'ast Stmt) {
-        let old_in_loop = self.in_loop;
-
-        self.in_loop = true;
+    fn handle_loop(&mut self, block: &'ast Stmt, label: Option<Name>) {
+        self.loops.push(label);
         visit::walk_stmt(self, block);
-        self.in_loop = old_in_loop;
+        self.loops.pop();
     }
 
-    fn handle_flow(&mut self, s: &'ast Stmt) {
-        if !self.in_loop {
+    fn handle_flow(&mut self, s: &'ast Stmt, label: Option<Name>) {
+        if self.loops.is_empty() {
             self.ctxt
                 .diag
                 .lock()
                 .report_without_path(s.pos(), Msg::OutsideLoop);
+            return;
+        }
+
+        if let Some(label) = label {
+            if !self.loops.contains(&Some(label)) {
+                let name = self.ctxt.interner.str(label).to_string();
+                self.ctxt
+                    .diag
+                    .lock()
+                    .report_without_path(s.pos(), Msg::UnknownLabel(name));
+            }
         }
     }
 }
@@ -63,11 +76,11 @@ impl<'a, 'ast> FlowCheck<'a, 'ast> {
 impl<'a, 'ast> Visitor<'ast> for FlowCheck<'a, 'ast> {
     fn visit_stmt(&mut self, s: &'ast Stmt) {
         match *s {
-            StmtLoop(_) => self.handle_loop(s),
-            StmtWhile(_) => self.handle_loop(s),
-            StmtFor(_) => self.handle_loop(s),
-            StmtBreak(_) => self.handle_flow(s),
-            StmtContinue(_) => self.handle_flow(s),
+            StmtLoop(ref stmt) => self.handle_loop(s, stmt.label),
+            StmtWhile(ref stmt) => self.handle_loop(s, stmt.label),
+            StmtFor(ref stmt) => self.handle_loop(s, stmt.label),
+            StmtBreak(ref stmt) => self.handle_flow(s, stmt.label),
+            StmtContinue(ref stmt) => self.handle_flow(s, stmt.label),
 
             _ => visit::walk_stmt(self, s),
         }
@@ -122,4 +135,22 @@ mod tests {
             Msg::OutsideLoop,
         );
     }
+
+    #[test]
+    fn flowck_labels() {
+        ok("fun a() { outer: while true { loop { break outer; } } }");
+        ok("fun a() { outer: loop { while true { continue outer; } } }");
+        ok("fun a(x: Array<Int>) { outer: for i in x { inner: loop { break inner; } } }");
+        err(
+            "fun a() { outer: loop { break inner; } }",
+            pos(1, 25),
+            Msg::UnknownLabel("inner".into()),
+        );
+        err(
+            "fun a() { outer: loop { } loop { continue outer; } }",
+            pos(1, 34),
+            Msg::UnknownLabel("outer".into()),
+        );
+        err("fun a() { break outer; }", pos(1, 11), Msg::OutsideLoop);
+    }
 }
@@ -2,13 +2,18 @@ use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::class::{self, ClassId};
+use crate::class::{self, ClassId, TypeParams};
 use crate::ctxt;
 use crate::ctxt::*;
 use crate::gc::Address;
-use crate::sym::Sym::{self, SymClass, SymConst, SymFct, SymGlobal, SymStruct, SymTrait};
+use crate::semck;
+use crate::sym::Sym::{
+    self, SymClass, SymConst, SymFct, SymGlobal, SymModule, SymStruct, SymTrait,
+};
+use crate::sym::{ModuleId, PathError};
 use crate::ty::BuiltinType;
 use dora_parser::ast::visit::*;
+use dora_parser::ast::Elem::ElemImport;
 use dora_parser::ast::*;
 use dora_parser::error::msg::Msg;
 use dora_parser::interner::Name;
@@ -34,7 +39,11 @@ pub fn check<'ast>(
         map_const_defs: map_const_defs,
     };
 
+    gdef.define_modules(ast);
     gdef.visit_ast(ast);
+    gdef.define_imports(ast);
+
+    gdef.ctxt.sym.lock().set_module(None);
 }
 
 struct GlobalDef<'x, 'ast: 'x> {
@@ -47,27 +56,88 @@ struct GlobalDef<'x, 'ast: 'x> {
     map_const_defs: &'x mut NodeMap<ConstId>,
 }
 
-impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
-    fn visit_trait(&mut self, t: &'ast Trait) {
-        let id: TraitId = (self.ctxt.traits.len() as u32).into();
-        let xtrait = TraitData {
-            id: id,
-            pos: t.pos,
-            name: t.name,
-            methods: Vec::new(),
-        };
+impl<'x, 'ast> GlobalDef<'x, 'ast> {
+    // all modules exist before any item is defined, so that items
+    // can't silently shadow modules
+    fn define_modules(&mut self, ast: &'ast Ast) {
+        for file in &ast.files {
+            let mut module = None;
+
+            // a module is declared by the files in its directory, clashes
+            // are reported at the first item of the declaring file
+            let pos = file
+                .elements
+                .first()
+                .map(|elem| elem.pos())
+                .unwrap_or(Position::new(1, 1));
+
+            for name in semck::module_path(self.ctxt, file) {
+                let result = self.ctxt.sym.lock().add_module(module, name);
+
+                module = match result {
+                    Ok(module) => Some(module),
+                    Err(sym) => {
+                        let msg = shadow_msg(self.ctxt, name, sym);
+                        self.ctxt.diag.lock().report(file.path.clone(), pos, msg);
+                        break;
+                    }
+                };
+            }
+        }
+    }
 
-        self.ctxt.traits.push(RwLock::new(xtrait));
-        let sym = SymTrait(id);
+    // imports are defined after all items, so that the order of
+    // files does not matter
+    fn define_imports(&mut self, ast: &'ast Ast) {
+        for file in &ast.files {
+            let module = semck::file_module(self.ctxt, file);
+            self.ctxt.sym.lock().set_module(module);
 
-        self.map_trait_defs.insert(t.id, id);
+            for elem in &file.elements {
+                if let ElemImport(ref import) = *elem {
+                    self.define_import(import);
+                }
+            }
+        }
+    }
+
+    fn define_import(&mut self, import: &'ast Import) {
+        let path = &import.path.path;
+        let result = self.ctxt.sym.lock().get_path(path);
+
+        let sym = match result {
+            Ok(sym) => sym,
 
-        if let Some(sym) = self.ctxt.sym.lock().insert(t.name, sym) {
-            report(self.ctxt, t.name, t.pos, sym);
+            Err(PathError::NotPublic(idx)) => {
+                let name = semck::path_name(self.ctxt, &path[..idx + 1]);
+                report_msg(self.ctxt, import.pos, Msg::NotPublic(name));
+                return;
+            }
+
+            Err(_) => {
+                let name = semck::path_name(self.ctxt, path);
+                report_msg(self.ctxt, import.pos, Msg::UnresolvedImport(name));
+                return;
+            }
+        };
+
+        let name = import.name();
+
+        // importing an item under the name it is already visible as is allowed
+        if let Some(old) = self.ctxt.sym.lock().insert(name, sym.clone()) {
+            if old != sym {
+                report(self.ctxt, name, import.pos, old);
+            }
         }
     }
 
-    fn visit_global(&mut self, g: &'ast Global) {
+    fn add_global(&mut self, g: &'ast Global) -> GlobalId {
+        let module = self.ctxt.sym.lock().module();
+        let getter = g
+            .initializer
+            .as_ref()
+            .map(|initializer| self.add_initializer(initializer, module));
+
         let id = {
             let mut globals = self.ctxt.globals.lock();
             let id: GlobalId = (globals.len() as u32).into();
@@ -78,7 +148,9 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
                 name: g.name,
                 ty: BuiltinType::Unit,
                 reassignable: g.reassignable,
-                getter: None,
+                is_pub: g.is_pub,
+                module: module,
+                getter: getter,
                 address_init: Address::null(),
                 address_value: Address::null(),
             };
@@ -88,12 +160,91 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             id
         };
 
-        let sym = SymGlobal(id);
         self.map_global_defs.insert(g.id, id);
 
-        if let Some(sym) = self.ctxt.sym.lock().insert(g.name, sym) {
-            report(self.ctxt, g.name, g.pos, sym);
+        id
+    }
+
+    // the initializer of a global is a function without a symbol,
+    // generated code calls it on the first access of the global
+    fn add_initializer(&mut self, f: &'ast Function, module: Option<ModuleId>) -> FctId {
+        let fct = Fct {
+            id: FctId(0),
+            pos: f.pos,
+            ast: f,
+            name: f.name,
+            param_types: Vec::new(),
+            return_type: BuiltinType::Unit,
+            parent: FctParent::None,
+            module: module,
+            has_override: false,
+            has_open: false,
+            has_final: false,
+            is_extern: false,
+            is_pub: false,
+            is_static: false,
+            is_abstract: false,
+            internal: false,
+            internal_resolved: false,
+            overrides: None,
+            throws: false,
+            is_constructor: false,
+            vtable_index: None,
+            initialized: false,
+            impl_for: None,
+            default_impl: false,
+
+            type_params: Vec::new(),
+            where_bounds: HashMap::new(),
+            kind: FctKind::Source(RwLock::new(FctSrc::new())),
+        };
+
+        self.ctxt.add_fct(fct)
+    }
+
+    fn insert(&mut self, name: Name, pos: Position, is_pub: bool, sym: Sym) {
+        let mut table = self.ctxt.sym.lock();
+
+        if let Some(sym) = table.insert(name, sym) {
+            report(self.ctxt, name, pos, sym);
         }
+
+        if is_pub {
+            table.set_public(name);
+        }
+    }
+}
+
+impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
+    fn visit_file(&mut self, f: &'ast File) {
+        let module = semck::file_module(self.ctxt, f);
+        self.ctxt.sym.lock().set_module(module);
+
+        walk_file(self, f);
+    }
+
+    fn visit_trait(&mut self, t: &'ast Trait) {
+        let id: TraitId = (self.ctxt.traits.len() as u32).into();
+        let xtrait = TraitData {
+            id: id,
+            pos: t.pos,
+            name: t.name,
+            type_params: Vec::new(),
+            methods: Vec::new(),
+        };
+
+        self.ctxt.traits.push(RwLock::new(xtrait));
+        let sym = SymTrait(id);
+
+        self.map_trait_defs.insert(t.id, id);
+        self.insert(t.name, t.pos, t.is_pub, sym);
+    }
+
+    fn visit_global(&mut self, g: &'ast Global) {
+        let id = self.add_global(g);
+
+        let sym = SymGlobal(id);
+        self.insert(g.name, g.pos, g.is_pub, sym);
     }
 
     fn visit_impl(&mut self, i: &'ast Impl) {
@@ -104,6 +255,8 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             trait_id: None,
             class_id: None,
             methods: Vec::new(),
+            type_params: Vec::new(),
+            trait_type_params: TypeParams::empty(),
         };
 
         self.ctxt.impls.push(RwLock::new(ximpl));
@@ -131,10 +284,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
         self.map_const_defs.insert(c.id, id);
 
         let sym = SymConst(id);
-
-        if let Some(sym) = self.ctxt.sym.lock().insert(c.name, sym) {
-            report(self.ctxt, c.name, c.pos, sym);
-        }
+        self.insert(c.name, c.pos, c.is_pub, sym);
     }
 
     fn visit_class(&mut self, c: &'ast Class) {
@@ -153,9 +303,11 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
                 internal: c.internal,
                 internal_resolved: false,
                 has_constructor: c.has_constructor,
+                module: self.ctxt.sym.lock().module(),
 
                 constructor: None,
                 fields: Vec::new(),
+                static_fields: Vec::new(),
                 methods: Vec::new(),
 
                 traits: Vec::new(),
@@ -180,13 +332,24 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             id
         };
 
-        let sym = SymClass(id);
+        for field in &c.static_fields {
+            let global_id = self.add_global(field);
 
-        self.map_cls_defs.insert(c.id, id);
+            let cls = self.ctxt.classes.idx(id);
+            let mut cls = cls.write();
+
+            if cls.find_static_field(self.ctxt, field.name).is_some() {
+                let name = self.ctxt.interner.str(field.name).to_string();
+                report_msg(self.ctxt, field.pos, Msg::ShadowField(name));
+            }
 
-        if let Some(sym) = self.ctxt.sym.lock().insert(c.name, sym) {
-            report(self.ctxt, c.name, c.pos, sym);
+            cls.static_fields.push(global_id);
         }
+
+        let sym = SymClass(id);
+
+        self.map_cls_defs.insert(c.id, id);
+        self.insert(c.name, c.pos, c.is_pub, sym);
     }
 
     fn visit_struct(&mut self, s: &'ast Struct) {
@@ -209,10 +372,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
         let sym = SymStruct(id);
 
         self.map_struct_defs.insert(s.id, id);
-
-        if let Some(sym) = self.ctxt.sym.lock().insert(s.name, sym) {
-            report(self.ctxt, s.name, s.pos, sym);
-        }
+        self.insert(s.name, s.pos, s.is_pub, sym);
     }
 
     fn visit_fct(&mut self, f: &'ast Function) {
@@ -232,11 +392,12 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             param_types: Vec::new(),
             return_type: BuiltinType::Unit,
             parent: FctParent::None,
+            module: self.ctxt.sym.lock().module(),
             has_override: f.has_override,
             has_open: f.has_open,
             has_final: f.has_final,
             is_extern: f.is_external,
-            is_pub: true,
+            is_pub: f.is_pub,
             is_static: false,
             is_abstract: false,
             internal: f.internal,
@@ -247,30 +408,42 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             vtable_index: None,
             initialized: false,
             impl_for: None,
+            default_impl: false,
 
             type_params: Vec::new(),
+            where_bounds: HashMap::new(),
             kind: kind,
         };
 
-        if let Err(sym) = self.ctxt.add_fct_to_sym(fct) {
-            report(self.ctxt, f.name, f.pos, sym);
+        match self.ctxt.add_fct_to_sym(fct) {
+            Ok(_) if f.is_pub => self.ctxt.sym.lock().set_public(f.name),
+            Ok(_) => {}
+            Err(sym) => report(self.ctxt, f.name, f.pos, sym),
         }
     }
 }
 
 fn report(ctxt: &SemContext, name: Name, pos: Position, sym: Sym) {
+    let msg = shadow_msg(ctxt, name, sym);
+    report_msg(ctxt, pos, msg);
+}
+
+fn shadow_msg(ctxt: &SemContext, name: Name, sym: Sym) -> Msg {
     let name = ctxt.interner.str(name).to_string();
 
-    let msg = match sym {
+    match sym {
         SymClass(_) => Msg::ShadowClass(name),
         SymStruct(_) => Msg::ShadowStruct(name),
         SymFct(_) => Msg::ShadowFunction(name),
         SymTrait(_) => Msg::ShadowTrait(name),
         SymGlobal(_) => Msg::ShadowGlobal(name),
         SymConst(_) => Msg::ShadowConst(name),
+        SymModule(_) => Msg::ShadowModule(name),
         _ => unimplemented!(),
-    };
+    }
+}
 
+fn report_msg(ctxt: &SemContext, pos: Position, msg: Msg) {
     ctxt.diag.lock().report_without_path(pos, msg);
 }
 
@@ -338,4 +511,26 @@ mod tests {
             Msg::ShadowConst("foo".into()),
         );
     }
+
+    #[test]
+    fn test_pub() {
+        ok("pub fun foo() {} pub class Foo pub struct Bar {} pub trait Baz {}");
+        ok("pub const x: Int = 0; pub var y: Int;");
+    }
+
+    #[test]
+    fn test_import() {
+        ok("fun foo() {} import foo as bar; fun f() { bar(); }");
+        ok("import foo; fun foo() {}");
+        err(
+            "import foo::bar;",
+            pos(1, 1),
+            Msg::UnresolvedImport("foo::bar".into()),
+        );
+        err(
+            "fun foo() {} fun bar() {} import foo as bar;",
+            pos(1, 27),
+            Msg::ShadowFunction("bar".into()),
+        );
+    }
 }
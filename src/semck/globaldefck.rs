@@ -2,9 +2,8 @@ use crate::ctxt::{GlobalId, NodeMap, SemContext};
 use crate::semck;
 use crate::ty::BuiltinType;
 use dora_parser::ast::visit::Visitor;
-use dora_parser::ast::Elem::ElemGlobal;
+use dora_parser::ast::Elem::{ElemClass, ElemGlobal};
 use dora_parser::ast::{Ast, File, Global};
-use dora_parser::error::msg::Msg;
 
 pub fn check<'a, 'ast>(
     ctxt: &SemContext<'ast>,
@@ -18,6 +17,7 @@ pub fn check<'a, 'ast>(
     };
 
     checker.visit_ast(ast);
+    ctxt.sym.lock().set_module(None);
 }
 
 struct GlobalDefCheck<'a, 'ast: 'a> {
@@ -28,9 +28,17 @@ struct GlobalDefCheck<'a, 'ast: 'a> {
 
 impl<'a, 'ast> Visitor<'ast> for GlobalDefCheck<'a, 'ast> {
     fn visit_file(&mut self, f: &'ast File) {
+        let module = semck::file_module(self.ctxt, f);
+        self.ctxt.sym.lock().set_module(module);
+
         for e in &f.elements {
             match *e {
                 ElemGlobal(ref g) => self.visit_global(g),
+                ElemClass(ref c) => {
+                    for g in &c.static_fields {
+                        self.visit_global(g);
+                    }
+                }
                 _ => {}
             }
         }
@@ -42,13 +50,6 @@ impl<'a, 'ast> Visitor<'ast> for GlobalDefCheck<'a, 'ast> {
         let ty = semck::read_type(self.ctxt, &g.data_type).unwrap_or(BuiltinType::Unit);
         let glob = self.ctxt.globals.idx(global_id);
         glob.lock().ty = ty;
-
-        if g.expr.is_some() {
-            self.ctxt
-                .diag
-                .lock()
-                .report_without_path(g.pos, Msg::GlobalInitializerNotSupported);
-        }
     }
 }
 
@@ -59,10 +60,18 @@ mod tests {
 
     #[test]
     fn check_initializer() {
+        ok("let a: Int = 0;");
+        ok("var a: Int = b + 1; let b: Int = 1;");
+        ok("let a: String = foo(); fun foo() -> String { return \"foo\"; }");
         err(
-            "let a: Int = 0;",
-            pos(1, 1),
-            Msg::GlobalInitializerNotSupported,
+            "let a: Int = true;",
+            pos(1, 14),
+            Msg::ReturnType("Int".into(), "Bool".into()),
+        );
+        err(
+            "let a: Int = b;",
+            pos(1, 14),
+            Msg::UnknownIdentifier("b".into()),
         );
     }
 
@@ -70,4 +79,26 @@ mod tests {
     fn check_type() {
         err("var x: Foo;", pos(1, 8), Msg::UnknownType("Foo".into()));
     }
+
+    #[test]
+    fn check_static_field() {
+        ok("class A { static var x: Int; static let y: Str; }");
+        ok("class A { static let x: Int = 1; static var y: String = \"y\"; }");
+        ok("class A { static let x: Int = A::y * 2; static let y: Int = 1; }");
+        err(
+            "class A { static let x: Int = \"x\"; }",
+            pos(1, 31),
+            Msg::ReturnType("Int".into(), "String".into()),
+        );
+        err(
+            "class A { static var x: Foo; }",
+            pos(1, 25),
+            Msg::UnknownType("Foo".into()),
+        );
+        err(
+            "class A { static var x: Int; static var x: Int; }",
+            pos(1, 37),
+            Msg::ShadowField("x".into()),
+        );
+    }
 }
@@ -1,13 +1,22 @@
 use std::collections::HashSet;
 
 use crate::ctxt::SemContext;
+use crate::semck::specialize::specialize_default_method;
 use dora_parser::error::msg::Msg;
 use dora_parser::lexer::position::Position;
 
 pub fn check<'ast>(ctxt: &mut SemContext<'ast>) {
+    let mut defaults = Vec::new();
+
     for ximpl in &ctxt.impls {
         let ximpl = ximpl.read();
         let xtrait = ctxt.traits[ximpl.trait_id()].read();
+
+        // wrong number of type params was already reported
+        if xtrait.type_params.len() != ximpl.trait_type_params.len() {
+            continue;
+        }
+
         let cls = ctxt.classes.idx(ximpl.cls_id());
         let cls = cls.read();
         let cls = cls.ty;
@@ -19,11 +28,12 @@ pub fn check<'ast>(ctxt: &mut SemContext<'ast>) {
             let method = ctxt.fcts.idx(method_id);
             let mut method = method.write();
 
-            if let Some(fid) = xtrait.find_method(
+            if let Some(fid) = xtrait.find_method_with_type_params(
                 ctxt,
                 method.is_static,
                 method.name,
                 Some(cls),
+                &ximpl.trait_type_params,
                 method.params_without_self(),
             ) {
                 method.impl_for = Some(fid);
@@ -51,6 +61,12 @@ pub fn check<'ast>(ctxt: &mut SemContext<'ast>) {
             let method = ctxt.fcts.idx(method_id);
             let method = method.read();
 
+            if method.is_src() {
+                let fct = specialize_default_method(ctxt, ximpl.id, cls, &*method);
+                defaults.push((ximpl.id, fct));
+                continue;
+            }
+
             let args = method
                 .params_without_self()
                 .iter()
@@ -68,6 +84,13 @@ pub fn check<'ast>(ctxt: &mut SemContext<'ast>) {
             report(ctxt, ximpl.pos, msg);
         }
     }
+
+    for (impl_id, fct) in defaults {
+        let fct_id = ctxt.add_fct(fct);
+
+        let mut ximpl = ctxt.impls[impl_id].write();
+        ximpl.methods.push(fct_id);
+    }
 }
 
 fn report(ctxt: &SemContext, pos: Position, msg: Msg) {
@@ -147,4 +170,33 @@ mod tests {
             Msg::StaticMethodMissingFromTrait("Foo".into(), "bar".into(), vec![]),
         );
     }
+
+    #[test]
+    fn default_method() {
+        ok("trait Foo {
+                fun foo() -> Int;
+                fun bar() -> Int = self.foo() + 1;
+            }
+            class A
+            impl Foo for A {
+                fun foo() -> Int = 1;
+            }
+            fun f(a: A) -> Int = a.bar();");
+
+        ok("trait Foo {
+                fun foo() -> Int = 1;
+            }
+            class A
+            impl Foo for A {
+                fun foo() -> Int = 2;
+            }
+            fun f(a: A) -> Int = a.foo();");
+
+        ok("trait Foo {
+                fun foo(other: Self) -> Self = other;
+            }
+            class A
+            impl Foo for A {}
+            fun f(a: A) -> A = a.foo(a);");
+    }
 }
@@ -1,6 +1,8 @@
 use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
 
-use crate::ctxt::{Fct, FctId, FctKind, FctParent, FctSrc, ImplId, NodeMap, SemContext};
+use crate::ctxt::{Fct, FctId, FctKind, FctParent, FctSrc, ImplData, ImplId, NodeMap, SemContext};
+use crate::semck;
 use crate::sym::Sym;
 use crate::ty::BuiltinType;
 
@@ -31,10 +33,77 @@ struct ImplCheck<'x, 'ast: 'x> {
 impl<'x, 'ast> ImplCheck<'x, 'ast> {
     fn check(&mut self) {
         self.visit_ast(self.ast);
+        self.ctxt.sym.lock().set_module(None);
+    }
+
+    // resolves the type arguments of the trait, the names for the class's
+    // type params given in the impl header are in scope for them
+    fn check_type_params(&self, i: &'ast ast::Impl, ximpl: &mut ImplData) {
+        let cls = self.ctxt.classes.idx(ximpl.cls_id());
+        let cls = cls.read();
+
+        if let Some(ref type_params) = i.class_type_params {
+            if type_params.len() != cls.type_params.len() {
+                let msg = Msg::WrongNumberTypeParams(cls.type_params.len(), type_params.len());
+                report(self.ctxt, i.pos, msg);
+            }
+
+            let mut names = HashSet::new();
+
+            for type_param in type_params {
+                if !names.insert(type_param.name) {
+                    let name = self.ctxt.interner.str(type_param.name).to_string();
+                    report(self.ctxt, type_param.pos, Msg::TypeParamNameNotUnique(name));
+                }
+
+                if type_param.bounds.len() > 0 {
+                    report(self.ctxt, type_param.pos, Msg::BoundNotAllowed);
+                }
+            }
+
+            ximpl.type_params = type_params
+                .iter()
+                .take(cls.type_params.len())
+                .map(|type_param| type_param.name)
+                .collect();
+        }
+
+        self.ctxt.sym.lock().push_level();
+
+        for (type_param_id, &name) in ximpl.type_params.iter().enumerate() {
+            let sym = Sym::SymClassTypeParam(cls.id, type_param_id.into());
+            self.ctxt.sym.lock().insert(name, sym);
+        }
+
+        let mut trait_type_params = Vec::new();
+
+        for ty in &i.trait_type_params {
+            let ty = semck::read_type(self.ctxt, ty).unwrap_or(BuiltinType::Error);
+            trait_type_params.push(ty);
+        }
+
+        self.ctxt.sym.lock().pop_level();
+
+        let xtrait = self.ctxt.traits[ximpl.trait_id()].read();
+
+        if xtrait.type_params.len() != trait_type_params.len() {
+            let msg = Msg::WrongNumberTypeParams(xtrait.type_params.len(), trait_type_params.len());
+            report(self.ctxt, i.pos, msg);
+            return;
+        }
+
+        ximpl.trait_type_params = trait_type_params.into();
     }
 }
 
 impl<'x, 'ast> Visitor<'ast> for ImplCheck<'x, 'ast> {
+    fn visit_file(&mut self, f: &'ast ast::File) {
+        let module = semck::file_module(self.ctxt, f);
+        self.ctxt.sym.lock().set_module(module);
+
+        visit::walk_file(self, f);
+    }
+
     fn visit_impl(&mut self, i: &'ast ast::Impl) {
         self.impl_id = Some(*self.map_impl_defs.get(i.id).unwrap());
 
@@ -57,6 +126,8 @@ impl<'x, 'ast> Visitor<'ast> for ImplCheck<'x, 'ast> {
         }
 
         if ximpl.trait_id.is_some() && ximpl.class_id.is_some() {
+            self.check_type_params(i, &mut *ximpl);
+
             let cls = self.ctxt.classes.idx(ximpl.cls_id());
             let mut cls = cls.write();
             cls.traits.push(ximpl.trait_id());
@@ -89,6 +160,7 @@ impl<'x, 'ast> Visitor<'ast> for ImplCheck<'x, 'ast> {
             param_types: Vec::new(),
             return_type: BuiltinType::Unit,
             parent: FctParent::Impl(self.impl_id.unwrap()),
+            module: self.ctxt.sym.lock().module(),
             has_override: f.has_override,
             has_open: f.has_open,
             has_final: f.has_final,
@@ -104,8 +176,10 @@ impl<'x, 'ast> Visitor<'ast> for ImplCheck<'x, 'ast> {
             vtable_index: None,
             initialized: false,
             impl_for: None,
+            default_impl: false,
 
             type_params: Vec::new(),
+            where_bounds: HashMap::new(),
             kind: kind,
         };
 
@@ -174,6 +248,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn generic_impl_definitions() {
+        ok("trait Foo<T> { fun get() -> T; }
+            class A
+            impl Foo<Int> for A { fun get() -> Int { return 1; } }");
+        ok("trait Foo<T> { fun get() -> T; }
+            class A<T>(let value: T)
+            impl Foo<T> for A<T> { fun get() -> T { return self.value; } }");
+
+        err(
+            "trait Foo<T> {} class A impl Foo for A {}",
+            pos(1, 25),
+            Msg::WrongNumberTypeParams(1, 0),
+        );
+
+        err(
+            "trait Foo<T> {} class A<T> impl Foo<T> for A<T, U> {}",
+            pos(1, 28),
+            Msg::WrongNumberTypeParams(1, 2),
+        );
+
+        err(
+            "trait Foo<T> {} class A impl Foo<T> for A {}",
+            pos(1, 34),
+            Msg::UnknownType("T".into()),
+        );
+    }
+
     #[test]
     fn impl_definitions() {
         ok("trait Foo {} class A {} impl Foo for A {}");
@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
-use crate::class::TypeParams;
+use crate::class::{self, TypeParams};
 use crate::ctxt::*;
+use crate::semck;
 use dora_parser::error::msg::Msg;
 
 use dora_parser::ast::visit::*;
@@ -11,6 +12,7 @@ use dora_parser::ast::*;
 use dora_parser::interner::Name;
 use dora_parser::lexer::position::Position;
 
+use crate::sym::PathError;
 use crate::sym::Sym;
 use crate::sym::Sym::*;
 use crate::ty::BuiltinType;
@@ -27,6 +29,8 @@ pub fn check<'ast>(ctxt: &SemContext<'ast>) {
         let mut src = src.write();
         let ast = fct.ast;
 
+        ctxt.sym.lock().set_module(fct.module);
+
         let mut nameck = NameCheck {
             ctxt: ctxt,
             fct: &fct,
@@ -36,6 +40,8 @@ pub fn check<'ast>(ctxt: &SemContext<'ast>) {
 
         nameck.check();
     }
+
+    ctxt.sym.lock().set_module(None);
 }
 
 struct NameCheck<'a, 'ast: 'a> {
@@ -80,6 +86,8 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
                 cls.ty
             }
 
+            FctParent::Trait(_) => BuiltinType::This,
+
             _ => unreachable!(),
         };
 
@@ -153,6 +161,31 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
         }
     }
 
+    fn check_stmt_var_tuple(&mut self, var: &'ast StmtVarTupleType) {
+        self.visit_expr(&var.expr);
+
+        for binding in &var.names {
+            let var_ctxt = Var {
+                id: VarId(0),
+                name: binding.name,
+                reassignable: var.reassignable,
+                ty: BuiltinType::Unit,
+                node_id: binding.id,
+            };
+
+            match self.add_var(var_ctxt, |sym| !sym.is_class()) {
+                Ok(var_id) => {
+                    self.src.map_vars.insert(binding.id, var_id);
+                }
+
+                Err(_) => {
+                    let name = str(self.ctxt, binding.name);
+                    report(self.ctxt, binding.pos, Msg::ShadowClass(name));
+                }
+            }
+        }
+    }
+
     fn check_stmt_for(&mut self, for_loop: &'ast StmtForType) {
         self.visit_expr(&for_loop.expr);
 
@@ -278,6 +311,23 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
         }
 
         if call.path.len() > 1 {
+            let sym = self.ctxt.sym.lock().get_path(&call.path.path);
+
+            match sym {
+                Ok(SymFct(fct_id)) => {
+                    let call_type = CallType::Fct(fct_id, TypeParams::empty(), TypeParams::empty());
+                    self.src.map_calls.insert(call.id, Arc::new(call_type));
+                }
+
+                Ok(SymClass(cls_id)) => {
+                    let call_type = CallType::CtorNew(cls_id, FctId(0), TypeParams::empty());
+                    self.src.map_calls.insert(call.id, Arc::new(call_type));
+                }
+
+                // static method calls like `Foo::bar()` are resolved in typeck
+                _ => {}
+            }
+
             for arg in &call.args {
                 self.visit_expr(arg);
             }
@@ -316,12 +366,95 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
         }
     }
 
+    fn check_expr_path(&mut self, e: &'ast ExprPathType) {
+        let mut path = Vec::new();
+
+        if !path_names(&e.lhs, &mut path) || !path_names(&e.rhs, &mut path) {
+            report(self.ctxt, e.pos, Msg::ExpectedSomeIdentifier);
+            return;
+        }
+
+        let (&name, cls_path) = path.split_last().unwrap();
+        let cls_sym = self.ctxt.sym.lock().get_path(cls_path);
+
+        if let Ok(SymClass(cls_id)) = cls_sym {
+            let cls = self.ctxt.classes.idx(cls_id);
+            let cls = cls.read();
+
+            if let Some(id) = cls.find_static_field(self.ctxt, name) {
+                self.check_static_field_visible(id, &cls, name, e.pos);
+                self.src.map_idents.insert(e.id, IdentType::Global(id));
+            } else {
+                let field = self.ctxt.interner.str(name).to_string();
+                let cls = cls.long_name(self.ctxt);
+                report(self.ctxt, e.pos, Msg::UnknownField(field, cls));
+            }
+
+            return;
+        }
+
+        let sym = self.ctxt.sym.lock().get_path(&path);
+
+        match sym {
+            Ok(SymGlobal(id)) => {
+                self.src.map_idents.insert(e.id, IdentType::Global(id));
+            }
+
+            Ok(SymConst(id)) => {
+                self.src.map_idents.insert(e.id, IdentType::Const(id));
+            }
+
+            Ok(_) => {
+                let name = semck::path_name(self.ctxt, &path);
+                report(self.ctxt, e.pos, Msg::UnknownIdentifier(name));
+            }
+
+            Err(err) => {
+                semck::report_path_error(self.ctxt, &path, err, e.pos);
+            }
+        }
+    }
+
+    // static fields of classes in modules are only accessible from other modules when public
+    fn check_static_field_visible(
+        &mut self,
+        id: GlobalId,
+        cls: &class::Class,
+        name: Name,
+        pos: Position,
+    ) {
+        let glob = self.ctxt.globals.idx(id);
+        let glob = glob.lock();
+
+        if glob.is_pub || glob.module.is_none() || glob.module == self.fct.module {
+            return;
+        }
+
+        let name = format!("{}::{}", cls.long_name(self.ctxt), str(self.ctxt, name));
+        report(self.ctxt, pos, Msg::NotPublic(name));
+    }
+
     fn check_expr_struct(&mut self, struc: &'ast ExprLitStructType) {
-        if let Some(sid) = self.ctxt.sym.lock().get_struct(struc.path.name()) {
-            self.src.map_idents.insert(struc.id, IdentType::Struct(sid));
-        } else {
-            let name = self.ctxt.interner.str(struc.path.name()).to_string();
-            report(self.ctxt, struc.pos, Msg::UnknownStruct(name));
+        let sym = self.ctxt.sym.lock().get_path(&struc.path.path);
+
+        match sym {
+            Ok(SymStruct(sid)) => {
+                self.src.map_idents.insert(struc.id, IdentType::Struct(sid));
+            }
+
+            Err(PathError::NotPublic(idx)) => {
+                let name = semck::path_name(self.ctxt, &struc.path.path[..idx + 1]);
+                report(self.ctxt, struc.pos, Msg::NotPublic(name));
+            }
+
+            _ => {
+                let name = semck::path_name(self.ctxt, &struc.path.path);
+                report(self.ctxt, struc.pos, Msg::UnknownStruct(name));
+            }
+        }
+
+        for arg in &struc.args {
+            self.visit_expr(&arg.expr);
         }
     }
 }
@@ -359,6 +492,7 @@ impl<'a, 'ast> Visitor<'ast> for NameCheck<'a, 'ast> {
     fn visit_stmt(&mut self, s: &'ast Stmt) {
         match *s {
             StmtVar(ref stmt) => self.check_stmt_var(stmt),
+            StmtVarTuple(ref stmt) => self.check_stmt_var_tuple(stmt),
             StmtBlock(ref stmt) => self.check_stmt_block(stmt),
             StmtDo(ref stmt) => self.check_stmt_do(stmt),
             StmtFor(ref stmt) => self.check_stmt_for(stmt),
@@ -373,6 +507,7 @@ impl<'a, 'ast> Visitor<'ast> for NameCheck<'a, 'ast> {
             &ExprIdent(ref ident) => self.check_expr_ident(ident),
             &ExprCall(ref call) => self.check_expr_call(call),
             &ExprLitStruct(ref lit) => self.check_expr_struct(lit),
+            &ExprPath(ref path) => self.check_expr_path(path),
 
             // no need to handle rest of expressions
             _ => visit::walk_expr(self, e),
@@ -388,6 +523,20 @@ fn str(ctxt: &SemContext, name: Name) -> String {
     ctxt.interner.str(name).to_string()
 }
 
+// collects the names of a qualified path like `util::CONST`
+fn path_names(e: &Expr, path: &mut Vec<Name>) -> bool {
+    match e {
+        &ExprIdent(ref ident) => {
+            path.push(ident.name);
+            true
+        }
+
+        &ExprPath(ref e) => path_names(&e.lhs, path) && path_names(&e.rhs, path),
+
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::semck::tests::*;
@@ -407,6 +556,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn redefine_function_keeps_first_definition() {
+        errors(
+            "fun f() -> Int { return 1; }\nfun f() {}\nfun g() -> Int { return f(); }",
+            &[(pos(2, 1), Msg::ShadowFunction("f".into()))],
+        );
+    }
+
     #[test]
     fn shadow_type_with_function() {
         err("fun Int() {}", pos(1, 1), Msg::ShadowClass("Int".into()));
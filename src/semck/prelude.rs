@@ -34,10 +34,15 @@ pub fn internal_classes<'ast>(ctxt: &mut SemContext<'ast>) {
     let mut cls = cls.write();
     cls.is_array = true;
 
+    ctxt.vips.string_buf_class = internal_class(ctxt, "StringBuf", None);
     ctxt.vips.testing_class = internal_class(ctxt, "Testing", None);
 
     ctxt.vips.exception_class = internal_class(ctxt, "Exception", None);
     ctxt.vips.stack_trace_element_class = internal_class(ctxt, "StackTraceElement", None);
+    ctxt.vips.out_of_memory_error_class = internal_class(ctxt, "OutOfMemoryError", None);
+
+    ctxt.vips.gc_stats_class = internal_class(ctxt, "GcStats", None);
+    ctxt.vips.gc_space_stats_class = internal_class(ctxt, "GcSpaceStats", None);
 
     *ctxt.vips.iterator_trait.lock() = Some(find_trait(ctxt, "Iterator"));
 
@@ -64,6 +69,7 @@ fn internal_free_classes<'ast>(ctxt: &mut SemContext<'ast>) {
             fields: Vec::new(),
             ref_fields: Vec::new(),
             vtable: None,
+            itables: Vec::new(),
         })));
 
         class_defs.push(Arc::new(RwLock::new(ClassDef {
@@ -75,6 +81,7 @@ fn internal_free_classes<'ast>(ctxt: &mut SemContext<'ast>) {
             fields: Vec::new(),
             ref_fields: Vec::new(),
             vtable: None,
+            itables: Vec::new(),
         })));
 
         {
@@ -136,6 +143,9 @@ fn find_trait<'ast>(ctxt: &mut SemContext<'ast>, name: &str) -> TraitId {
 }
 
 pub fn internal_functions<'ast>(ctxt: &mut SemContext<'ast>) {
+    let range = ctxt.interner.intern("range");
+    ctxt.vips.range_fct = ctxt.sym.lock().get_fct(range);
+
     native_fct(ctxt, "fatalError", stdlib::fatal_error as *const u8);
     native_fct(ctxt, "abort", stdlib::abort as *const u8);
     native_fct(ctxt, "exit", stdlib::exit as *const u8);
@@ -148,6 +158,7 @@ pub fn internal_functions<'ast>(ctxt: &mut SemContext<'ast>) {
     native_fct(ctxt, "argc", stdlib::argc as *const u8);
     native_fct(ctxt, "argv", stdlib::argv as *const u8);
     native_fct(ctxt, "forceCollect", stdlib::gc_collect as *const u8);
+    native_fct(ctxt, "gcStats", stdlib::gc_stats as *const u8);
     native_fct(ctxt, "timestamp", stdlib::timestamp as *const u8);
     native_fct(
         ctxt,
@@ -343,6 +354,14 @@ pub fn internal_functions<'ast>(ctxt: &mut SemContext<'ast>) {
     intrinsic_method(ctxt, clsid, "isNan", Intrinsic::DoubleIsNan);
     intrinsic_method(ctxt, clsid, "sqrt", Intrinsic::DoubleSqrt);
 
+    let clsid = ctxt.vips.object_class;
+    native_method(
+        ctxt,
+        clsid,
+        "identityHash",
+        stdlib::identity_hash as *const u8,
+    );
+
     let clsid = ctxt.vips.array_class;
     intrinsic_method(ctxt, clsid, "length", Intrinsic::GenericArrayLen);
     intrinsic_method(ctxt, clsid, "get", Intrinsic::GenericArrayGet);
@@ -1,17 +1,19 @@
 use crate::ctxt::{Fct, FctSrc, SemContext};
+use crate::semck;
 use dora_parser::error::msg::Msg;
 
 use crate::ty::BuiltinType;
 use dora_parser::ast::visit::*;
 use dora_parser::ast::Stmt::*;
 use dora_parser::ast::*;
+use dora_parser::interner::Name;
 use dora_parser::lexer::position::Position;
 
 pub fn check<'ast>(ctxt: &SemContext<'ast>) {
     for fct in ctxt.fcts.iter() {
         let fct = fct.read();
 
-        if !fct.is_src() {
+        if !fct.is_src() || semck::skip_default_impl(ctxt, &*fct) {
             continue;
         }
 
@@ -72,12 +74,13 @@ pub fn returns_value(s: &Stmt) -> Result<(), Position> {
         StmtReturn(_) => Ok(()),
         StmtBlock(ref stmt) => block_returns_value(stmt),
         StmtIf(ref stmt) => if_returns_value(stmt),
-        StmtLoop(ref stmt) => returns_value(&stmt.block),
+        StmtLoop(ref stmt) => loop_returns_value(stmt),
         StmtFor(ref stmt) => Err(stmt.pos),
         StmtWhile(ref stmt) => Err(stmt.pos),
         StmtBreak(ref stmt) => Err(stmt.pos),
         StmtContinue(ref stmt) => Err(stmt.pos),
         StmtVar(ref stmt) => Err(stmt.pos),
+        StmtVarTuple(ref stmt) => Err(stmt.pos),
         StmtExpr(ref stmt) => Err(stmt.pos),
         StmtSpawn(ref stmt) => Err(stmt.pos),
         StmtThrow(_) => Ok(()),
@@ -108,6 +111,63 @@ fn block_returns_value(s: &StmtBlockType) -> Result<(), Position> {
     Err(pos)
 }
 
+fn loop_returns_value(s: &StmtLoopType) -> Result<(), Position> {
+    // a break out of the loop continues after it
+    let mut finder = BreakFinder {
+        label: s.label,
+        depth: 0,
+        found: false,
+    };
+
+    finder.visit_stmt(&s.block);
+
+    if finder.found {
+        return Err(s.pos);
+    }
+
+    returns_value(&s.block)
+}
+
+// searches for break statements that leave the given loop
+struct BreakFinder {
+    label: Option<Name>,
+    depth: usize,
+    found: bool,
+}
+
+impl BreakFinder {
+    fn visit_loop(&mut self, s: &Stmt, label: Option<Name>) {
+        // a nested loop with the same label shadows the outer one
+        if label.is_some() && label == self.label {
+            return;
+        }
+
+        self.depth += 1;
+        visit::walk_stmt(self, s);
+        self.depth -= 1;
+    }
+}
+
+impl<'ast> Visitor<'ast> for BreakFinder {
+    fn visit_stmt(&mut self, s: &'ast Stmt) {
+        match *s {
+            StmtBreak(ref stmt) => {
+                if stmt.label.is_none() {
+                    self.found |= self.depth == 0;
+                } else {
+                    self.found |= stmt.label == self.label;
+                }
+            }
+
+            StmtLoop(ref stmt) => self.visit_loop(s, stmt.label),
+            StmtWhile(ref stmt) => self.visit_loop(s, stmt.label),
+            StmtFor(ref stmt) => self.visit_loop(s, stmt.label),
+
+            _ => visit::walk_stmt(self, s),
+        }
+    }
+}
+
 fn do_returns_value(s: &StmtDoType) -> Result<(), Position> {
     // return in finally-block is good enough
     if let Some(ref finally_block) = s.finally_block {
@@ -182,6 +242,17 @@ mod tests {
             Msg::NoReturnValue,
         );
         ok("fun f() -> Int { loop { return 1; } }");
+        ok("fun f() -> Int { loop { while true { break; } return 1; } }");
+        err(
+            "fun f() -> Int { loop { if true { break; } return 1; } }",
+            pos(1, 18),
+            Msg::NoReturnValue,
+        );
+        err(
+            "fun f() -> Int { outer: loop { while true { break outer; } return 1; } }",
+            pos(1, 25),
+            Msg::NoReturnValue,
+        );
         ok("fun f() -> Int { if true { return 1; } else { return 2; } }");
         ok("fun f() -> Int { return 1; 1+2; }");
         ok("fun f(x: Int) -> Int { if x == 0 { throw \"abc\"; } else { return -x; } }");
@@ -1,14 +1,20 @@
 use parking_lot::{Mutex, RwLock};
 use std::cmp::max;
+use std::collections::HashSet;
 use std::ptr;
 use std::sync::Arc;
 
 use crate::class::{self, ClassDef, ClassDefId, ClassId, ClassSize, FieldDef, TypeParams};
-use crate::ctxt::{SemContext, StructData, StructDef, StructDefId, StructFieldDef, StructId, VM};
+use crate::ctxt::{
+    Fct, FctId, FctKind, FctParent, ImplId, SemContext, StructData, StructDef, StructDefId,
+    StructFieldDef, StructId, TraitId, VM,
+};
 use crate::mem;
 use crate::object::Header;
-use crate::ty::BuiltinType;
+use crate::ty::{BuiltinType, TypeListId};
 use crate::vtable::{VTableBox, DISPLAY_SIZE};
+use dora_parser::error::msg::Msg;
+use dora_parser::lexer::position::Position;
 
 pub fn specialize_type(
     vm: &VM,
@@ -48,12 +54,195 @@ pub fn specialize_type(
             BuiltinType::Class(cls_id, list_id)
         }
 
+        BuiltinType::Trait(trait_id, list_id) => {
+            let params = vm.lists.lock().get(list_id);
+
+            let params: Vec<_> = params
+                .iter()
+                .map(|t| specialize_type(vm, t, cls_type_params, fct_type_params))
+                .collect();
+
+            let list_id = vm.lists.lock().insert(params.into());
+
+            BuiltinType::Trait(trait_id, list_id)
+        }
+
+        BuiltinType::Tuple(list_id) => {
+            let elements = vm.lists.lock().get(list_id);
+
+            let elements: Vec<_> = elements
+                .iter()
+                .map(|t| specialize_type(vm, t, cls_type_params, fct_type_params))
+                .collect();
+
+            let list_id = vm.lists.lock().insert(elements.into());
+
+            BuiltinType::Tuple(list_id)
+        }
+
         BuiltinType::Lambda(_) => unimplemented!(),
 
         _ => ty,
     }
 }
 
+// replaces Self and the trait's type params in types of a trait method,
+// pass `BuiltinType::This` as `self_ty` to keep Self
+pub fn specialize_for_trait(
+    vm: &VM,
+    ty: BuiltinType,
+    self_ty: BuiltinType,
+    trait_type_params: &TypeParams,
+) -> BuiltinType {
+    match ty {
+        BuiltinType::This => self_ty,
+
+        BuiltinType::TraitTypeParam(_, id) => trait_type_params[id.idx()],
+
+        BuiltinType::Class(cls_id, list_id) => {
+            let params = vm.lists.lock().get(list_id);
+
+            let params: Vec<_> = params
+                .iter()
+                .map(|t| specialize_for_trait(vm, t, self_ty, trait_type_params))
+                .collect();
+
+            let list_id = vm.lists.lock().insert(params.into());
+
+            BuiltinType::Class(cls_id, list_id)
+        }
+
+        BuiltinType::Trait(trait_id, list_id) => {
+            let params = vm.lists.lock().get(list_id);
+
+            let params: Vec<_> = params
+                .iter()
+                .map(|t| specialize_for_trait(vm, t, self_ty, trait_type_params))
+                .collect();
+
+            let list_id = vm.lists.lock().insert(params.into());
+
+            BuiltinType::Trait(trait_id, list_id)
+        }
+
+        BuiltinType::Tuple(list_id) => {
+            let elements = vm.lists.lock().get(list_id);
+
+            let elements: Vec<_> = elements
+                .iter()
+                .map(|t| specialize_for_trait(vm, t, self_ty, trait_type_params))
+                .collect();
+
+            let list_id = vm.lists.lock().insert(elements.into());
+
+            BuiltinType::Tuple(list_id)
+        }
+
+        _ => ty,
+    }
+}
+
+// Checks whether a concrete type argument satisfies the bounds of its type
+// param. Every bound that isn't satisfied is reported at `pos`.
+pub fn check_bounds(
+    vm: &VM,
+    pos: Position,
+    ty: BuiltinType,
+    class_bound: Option<ClassId>,
+    trait_bounds: &HashSet<TraitId>,
+) -> bool {
+    let mut succeeded = true;
+
+    if let Some(cls_id) = class_bound {
+        let cls = vm.cls(cls_id);
+
+        if !ty.subclass_from(vm, cls) {
+            let msg = Msg::ClassBoundNotSatisfied(ty.name(vm), cls.name(vm));
+            vm.diag.lock().report_without_path(pos, msg);
+            succeeded = false;
+        }
+    }
+
+    let mut missing = trait_bounds
+        .iter()
+        .filter(|&&trait_id| !ty.implements_trait(vm, trait_id))
+        .map(|&trait_id| {
+            let xtrait = vm.traits[trait_id].read();
+            vm.interner.str(xtrait.name).to_string()
+        })
+        .collect::<Vec<_>>();
+
+    // report in a stable order
+    missing.sort();
+
+    for trait_name in missing {
+        let msg = Msg::TraitBoundNotSatisfied(ty.name(vm), trait_name);
+        vm.diag.lock().report_without_path(pos, msg);
+        succeeded = false;
+    }
+
+    succeeded
+}
+
+// Creates the method of an impl that isn't overridden from a default method in
+// the trait. The copy shares the AST and the results of name resolution with the
+// default method but is type checked and compiled for the implementing class.
+pub fn specialize_default_method<'ast>(
+    ctxt: &SemContext<'ast>,
+    impl_id: ImplId,
+    self_ty: BuiltinType,
+    fct: &Fct<'ast>,
+) -> Fct<'ast> {
+    let trait_type_params = ctxt.impls[impl_id].read().trait_type_params.clone();
+    let mut src = fct.src().read().clone();
+
+    for var in &mut src.vars {
+        var.ty = specialize_for_trait(ctxt, var.ty, self_ty, &trait_type_params);
+    }
+
+    let tys: Vec<_> = src.map_tys.iter().map(|(&id, &ty)| (id, ty)).collect();
+
+    for (id, ty) in tys {
+        let ty = specialize_for_trait(ctxt, ty, self_ty, &trait_type_params);
+        src.map_tys.replace(id, ty);
+    }
+
+    Fct {
+        id: FctId(0),
+        ast: fct.ast,
+        pos: fct.pos,
+        name: fct.name,
+        param_types: fct
+            .param_types
+            .iter()
+            .map(|&ty| specialize_for_trait(ctxt, ty, self_ty, &trait_type_params))
+            .collect(),
+        return_type: specialize_for_trait(ctxt, fct.return_type, self_ty, &trait_type_params),
+        parent: FctParent::Impl(impl_id),
+        module: fct.module,
+        has_override: fct.has_override,
+        has_open: fct.has_open,
+        has_final: fct.has_final,
+        is_pub: fct.is_pub,
+        is_extern: fct.is_extern,
+        is_static: fct.is_static,
+        is_abstract: false,
+        internal: fct.internal,
+        internal_resolved: false,
+        overrides: None,
+        throws: fct.throws,
+        is_constructor: false,
+        vtable_index: None,
+        initialized: true,
+        impl_for: Some(fct.id),
+        default_impl: true,
+
+        type_params: fct.type_params.clone(),
+        where_bounds: fct.where_bounds.clone(),
+        kind: FctKind::Source(RwLock::new(src)),
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum SpecializeFor {
     Fct,
@@ -172,10 +361,89 @@ pub fn specialize_class_ty(ctxt: &SemContext, ty: BuiltinType) -> ClassDefId {
             specialize_class_id_params(ctxt, cls_id, &params)
         }
 
+        BuiltinType::Tuple(list_id) => specialize_tuple(ctxt, list_id),
+
         _ => unreachable!(),
     }
 }
 
+// Tuples are immutable objects on the heap. The class definition of a tuple
+// has no source class, its fields are the tuple's elements and are laid out
+// like the fields of a struct. Tuples are boxed because the baseline compiler
+// can't keep value structs in registers or stack slots yet, reference
+// elements are found by the GC through the class definition's ref_fields.
+pub fn specialize_tuple(ctxt: &SemContext, list_id: TypeListId) -> ClassDefId {
+    let mut tuple_defs = ctxt.tuple_defs.lock();
+
+    if let Some(&id) = tuple_defs.get(&list_id) {
+        return id;
+    }
+
+    let id = create_specialized_tuple(ctxt, list_id);
+    tuple_defs.insert(list_id, id);
+
+    id
+}
+
+fn create_specialized_tuple(ctxt: &SemContext, list_id: TypeListId) -> ClassDefId {
+    let elements = ctxt.lists.lock().get(list_id);
+
+    let mut csize = Header::size();
+    let mut fields = Vec::with_capacity(elements.len());
+    let mut ref_fields = Vec::new();
+
+    for ty in elements.iter() {
+        debug_assert!(!ty.contains_type_param(ctxt));
+
+        let field_size = ty.size(ctxt);
+        let field_align = ty.align(ctxt);
+
+        let offset = mem::align_i32(csize, field_align);
+        fields.push(FieldDef {
+            offset: offset,
+            ty: ty,
+        });
+
+        csize = offset + field_size;
+
+        if ty.reference_type() {
+            ref_fields.push(offset);
+        }
+    }
+
+    let size = ClassSize::Fixed(mem::align_i32(csize, mem::ptr_width()));
+
+    let id = {
+        let mut class_defs = ctxt.class_defs.lock();
+        let id: ClassDefId = class_defs.len().into();
+
+        class_defs.push(Arc::new(RwLock::new(ClassDef {
+            id: id,
+            cls_id: None,
+            type_params: elements,
+            parent_id: None,
+            size: size,
+            fields: fields,
+            ref_fields: ref_fields,
+            vtable: None,
+            itables: Vec::new(),
+        })));
+
+        id
+    };
+
+    let cls_def = ctxt.class_defs.idx(id);
+    let mut cls_def = cls_def.write();
+
+    let clsptr = (&*cls_def) as *const class::ClassDef as *mut class::ClassDef;
+    let vtable = VTableBox::new(clsptr, &[]);
+    cls_def.vtable = Some(vtable);
+
+    ensure_display(ctxt, &mut cls_def);
+
+    id
+}
+
 pub fn specialize_class(
     ctxt: &SemContext,
     cls: &class::Class,
@@ -209,6 +477,7 @@ fn create_specialized_class(
             fields: Vec::new(),
             ref_fields: Vec::new(),
             vtable: None,
+            itables: Vec::new(),
         })));
 
         id
@@ -280,11 +549,24 @@ fn create_specialized_class(
             }
         }
 
+        // boxes of primitive types store the value right after the header
+        if !cls.ty.is_cls() {
+            let ty = cls.ty;
+            let offset = mem::align_i32(csize, ty.align(ctxt));
+            fields.push(FieldDef {
+                offset: offset,
+                ty: ty,
+            });
+
+            csize = offset + ty.size(ctxt);
+        }
+
         size = ClassSize::Fixed(mem::align_i32(csize, mem::ptr_width()));
     }
 
     let stub = ctxt.compiler_thunk().to_usize();
     let vtable_entries = vec![stub; cls.vtable_len as usize];
+    let itables = create_itables(ctxt, cls, type_params, parent_id);
 
     let cls_def = ctxt.class_defs.idx(id);
     let mut cls_def = cls_def.write();
@@ -292,6 +574,7 @@ fn create_specialized_class(
     cls_def.fields = fields;
     cls_def.ref_fields = ref_fields;
     cls_def.parent_id = parent_id;
+    cls_def.itables = itables;
 
     let clsptr = (&*cls_def) as *const class::ClassDef as *mut class::ClassDef;
     let vtable = VTableBox::new(clsptr, &vtable_entries);
@@ -302,6 +585,37 @@ fn create_specialized_class(
     id
 }
 
+fn create_itables(
+    ctxt: &SemContext,
+    cls: &class::Class,
+    type_params: &TypeParams,
+    parent_id: Option<ClassDefId>,
+) -> Vec<class::ITable> {
+    let mut itables = Vec::new();
+
+    for &impl_id in &cls.impls {
+        let ximpl = ctxt.impls[impl_id].read();
+        let xtrait = ctxt.traits[ximpl.trait_id()].read();
+
+        itables.push(class::ITable {
+            trait_id: ximpl.trait_id(),
+            impl_id: impl_id,
+            type_params: type_params.clone(),
+            entries: vec![0; xtrait.methods.len()],
+        });
+    }
+
+    // traits implemented by super classes
+    if let Some(parent_id) = parent_id {
+        let parent = ctxt.class_defs.idx(parent_id);
+        let parent = parent.read();
+
+        itables.extend(parent.itables.iter().cloned());
+    }
+
+    itables
+}
+
 fn ensure_display<'ast>(ctxt: &SemContext<'ast>, cls_def: &mut ClassDef) -> usize {
     let vtable = cls_def.vtable.as_mut().unwrap();
 
@@ -34,10 +34,18 @@ struct StructCheck<'x, 'ast: 'x> {
 impl<'x, 'ast> StructCheck<'x, 'ast> {
     fn check(&mut self) {
         self.visit_ast(self.ast);
+        self.ctxt.sym.lock().set_module(None);
     }
 }
 
 impl<'x, 'ast> Visitor<'ast> for StructCheck<'x, 'ast> {
+    fn visit_file(&mut self, f: &'ast ast::File) {
+        let module = semck::file_module(self.ctxt, f);
+        self.ctxt.sym.lock().set_module(module);
+
+        visit::walk_file(self, f);
+    }
+
     fn visit_struct(&mut self, s: &'ast ast::Struct) {
         self.struct_id = Some(*self.map_struct_defs.get(s.id).unwrap());
 
@@ -0,0 +1,242 @@
+use crate::class::TypeParamId;
+use crate::cpu;
+use crate::ctxt::{CallType, Fct, FctSrc, SemContext};
+use crate::semck;
+use crate::ty::BuiltinType;
+use dora_parser::error::msg::Msg;
+
+use dora_parser::ast::visit::*;
+use dora_parser::ast::Expr::*;
+use dora_parser::ast::Stmt::*;
+use dora_parser::ast::*;
+
+// Recursive calls in functions marked with `tailrec` need to be compiled
+// as jumps. Report all recursive calls for which the baseline compiler
+// can't do that: calls that are not the expression of a return statement,
+// calls in do-blocks and calls whose arguments don't fit into registers.
+pub fn check<'ast>(ctxt: &SemContext<'ast>) {
+    for fct in ctxt.fcts.iter() {
+        let fct = fct.read();
+
+        if !fct.is_src() || !fct.ast.is_tailrec || semck::skip_default_impl(ctxt, &*fct) {
+            continue;
+        }
+
+        let src = fct.src();
+        let src = src.read();
+
+        let mut tailrecck = TailrecCheck {
+            ctxt: ctxt,
+            fct: &fct,
+            src: &src,
+            in_try: false,
+        };
+
+        tailrecck.visit_fct(fct.ast);
+    }
+}
+
+struct TailrecCheck<'a, 'ast: 'a> {
+    ctxt: &'a SemContext<'ast>,
+    fct: &'a Fct<'ast>,
+    src: &'a FctSrc,
+
+    // exception handlers and finally blocks need the frame of the function
+    in_try: bool,
+}
+
+impl<'a, 'ast> TailrecCheck<'a, 'ast> {
+    fn check_tail_call(&mut self, call: &'ast ExprCallType) {
+        let reason = if self.in_try {
+            Some("call is inside of do-block")
+        } else if self.fct.is_virtual() {
+            Some("method can be overridden")
+        } else if !cpu::args_in_registers(self.fct.params_with_self().iter().cloned()) {
+            Some("arguments are passed on the stack")
+        } else if !self.same_type_params(call) {
+            Some("call has different type arguments")
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            let name = self.ctxt.interner.str(self.fct.name).to_string();
+            let msg = Msg::TailCallNotPossible(name, reason.into());
+            self.ctxt.diag.lock().report_without_path(call.pos, msg);
+        }
+    }
+
+    fn is_recursive(&self, call: &ExprCallType) -> bool {
+        match self.src.map_calls.get(call.id) {
+            Some(call_type) => call_type.fct_id() == self.fct.id,
+            None => false,
+        }
+    }
+
+    // the call needs to use the same specialization of the function,
+    // otherwise a different function is called
+    fn same_type_params(&self, call: &ExprCallType) -> bool {
+        let fct_id = self.fct.id;
+        let (receiver, fct_type_params) = match **self.src.map_calls.get(call.id).unwrap() {
+            CallType::Fct(_, _, ref fct_type_params) => (None, fct_type_params),
+            CallType::Method(ty, _, ref fct_type_params) => (Some(ty), fct_type_params),
+            _ => return false,
+        };
+
+        let fct_type_params_same = fct_type_params
+            .iter()
+            .enumerate()
+            .all(|(idx, ty)| ty == BuiltinType::FctTypeParam(fct_id, TypeParamId::from(idx)));
+
+        let cls_type_params_same = match receiver {
+            Some(BuiltinType::Class(cls_id, list_id)) => {
+                let params = self.ctxt.lists.lock().get(list_id);
+
+                params.iter().enumerate().all(|(idx, ty)| {
+                    ty == BuiltinType::ClassTypeParam(cls_id, TypeParamId::from(idx))
+                })
+            }
+
+            _ => true,
+        };
+
+        fct_type_params_same && cls_type_params_same
+    }
+
+    fn visit_in_try(&mut self, s: &'ast Stmt) {
+        let old_in_try = self.in_try;
+
+        self.in_try = true;
+        self.visit_stmt(s);
+        self.in_try = old_in_try;
+    }
+}
+
+impl<'a, 'ast> Visitor<'ast> for TailrecCheck<'a, 'ast> {
+    fn visit_stmt(&mut self, s: &'ast Stmt) {
+        match *s {
+            StmtReturn(ref stmt) => match stmt.expr {
+                Some(ref expr) => match **expr {
+                    ExprCall(ref call) if self.is_recursive(call) => {
+                        self.check_tail_call(call);
+
+                        if let Some(ref object) = call.object {
+                            self.visit_expr(object);
+                        }
+
+                        for arg in &call.args {
+                            self.visit_expr(arg);
+                        }
+                    }
+
+                    _ => self.visit_expr(expr),
+                },
+
+                None => {}
+            },
+
+            StmtDo(ref stmt) => {
+                self.visit_in_try(&stmt.do_block);
+
+                for catch in &stmt.catch_blocks {
+                    if stmt.finally_block.is_some() {
+                        self.visit_in_try(&catch.block);
+                    } else {
+                        self.visit_stmt(&catch.block);
+                    }
+                }
+
+                if let Some(ref finally_block) = stmt.finally_block {
+                    self.visit_in_try(&finally_block.block);
+                }
+            }
+
+            _ => visit::walk_stmt(self, s),
+        }
+    }
+
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        if let ExprCall(ref call) = *e {
+            if self.is_recursive(call) {
+                let name = self.ctxt.interner.str(self.fct.name).to_string();
+                let msg = Msg::NotTailCall(name);
+                self.ctxt.diag.lock().report_without_path(call.pos, msg);
+            }
+        }
+
+        visit::walk_expr(self, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semck::tests::*;
+    use dora_parser::error::msg::Msg;
+
+    #[test]
+    fn tailrec_tail_call() {
+        ok("tailrec fun f(n: Int) -> Int {
+            if n == 0 { return 0; }
+            return f(n - 1);
+        }");
+        ok("tailrec fun f(n: Int, acc: Int) -> Int {
+            if n == 0 { return acc; }
+            return f(n - 1, acc + n);
+        }");
+        ok("class A { tailrec fun f(n: Int) -> Int {
+            if n == 0 { return 0; }
+            return self.f(n - 1);
+        } }");
+    }
+
+    #[test]
+    fn tailrec_not_tail_call() {
+        err(
+            "tailrec fun f(n: Int) -> Int {
+            if n == 0 { return 0; }
+            return f(n - 1) + 1;
+        }",
+            pos(3, 20),
+            Msg::NotTailCall("f".into()),
+        );
+        err(
+            "tailrec fun f(n: Int) {
+            if n == 0 { return; }
+            f(n - 1);
+        }",
+            pos(3, 13),
+            Msg::NotTailCall("f".into()),
+        );
+        err(
+            "tailrec fun f(n: Int) -> Int {
+            if n == 0 { return 0; }
+            return f(f(n - 1));
+        }",
+            pos(3, 22),
+            Msg::NotTailCall("f".into()),
+        );
+    }
+
+    #[test]
+    fn tailrec_tail_call_not_possible() {
+        err(
+            "tailrec fun f(n: Int) -> Int {
+            do {
+                return f(n - 1);
+            } catch x: String {
+                return 0;
+            }
+        }",
+            pos(3, 24),
+            Msg::TailCallNotPossible("f".into(), "call is inside of do-block".into()),
+        );
+        err(
+            "tailrec fun f(a: Int, b: Int, c: Int, d: Int, e: Int,
+                           g: Int, h: Int, i: Int, j: Int) -> Int {
+            return f(a, b, c, d, e, g, h, i, j);
+        }",
+            pos(3, 20),
+            Msg::TailCallNotPossible("f".into(), "arguments are passed on the stack".into()),
+        );
+    }
+}
@@ -1,4 +1,10 @@
-use crate::ctxt::{Fct, FctId, FctKind, FctParent, NodeMap, SemContext, TraitId};
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+
+use crate::ctxt::{
+    Fct, FctId, FctKind, FctParent, FctSrc, NodeMap, SemContext, TraitId, TypeParam,
+};
+use crate::semck;
 use crate::ty::BuiltinType;
 
 use dora_parser::ast::visit::{self, Visitor};
@@ -29,13 +35,48 @@ struct TraitCheck<'x, 'ast: 'x> {
 impl<'x, 'ast> TraitCheck<'x, 'ast> {
     fn check(&mut self) {
         self.visit_ast(self.ast);
+        self.ctxt.sym.lock().set_module(None);
+    }
+
+    fn check_type_params(&mut self, type_params: &'ast [ast::TypeParam], pos: Position) {
+        if type_params.len() == 0 {
+            report(self.ctxt, pos, Msg::TypeParamsExpected);
+            return;
+        }
+
+        let mut xtrait = self.ctxt.traits[self.trait_id.unwrap()].write();
+        let mut names = HashSet::new();
+
+        for type_param in type_params {
+            if !names.insert(type_param.name) {
+                let name = self.ctxt.interner.str(type_param.name).to_string();
+                report(self.ctxt, type_param.pos, Msg::TypeParamNameNotUnique(name));
+            }
+
+            if type_param.bounds.len() > 0 {
+                report(self.ctxt, type_param.pos, Msg::BoundNotAllowed);
+            }
+
+            xtrait.type_params.push(TypeParam::new(type_param.name));
+        }
     }
 }
 
 impl<'x, 'ast> Visitor<'ast> for TraitCheck<'x, 'ast> {
+    fn visit_file(&mut self, f: &'ast ast::File) {
+        let module = semck::file_module(self.ctxt, f);
+        self.ctxt.sym.lock().set_module(module);
+
+        visit::walk_file(self, f);
+    }
+
     fn visit_trait(&mut self, t: &'ast ast::Trait) {
         self.trait_id = Some(*self.map_trait_defs.get(t.id).unwrap());
 
+        if let Some(ref type_params) = t.type_params {
+            self.check_type_params(type_params, t.pos);
+        }
+
         visit::walk_trait(self, t);
 
         self.trait_id = None;
@@ -46,10 +87,17 @@ impl<'x, 'ast> Visitor<'ast> for TraitCheck<'x, 'ast> {
             return;
         }
 
-        if f.block.is_some() {
-            report(self.ctxt, f.pos, Msg::TraitMethodWithBody);
+        if f.block.is_some() && f.is_static {
+            report(self.ctxt, f.pos, Msg::StaticTraitMethodWithBody);
         }
 
+        // methods with a body are default implementations for impls
+        let kind = if f.block.is_some() {
+            FctKind::Source(RwLock::new(FctSrc::new()))
+        } else {
+            FctKind::Definition
+        };
+
         let fct = Fct {
             id: FctId(0),
             ast: f,
@@ -58,6 +106,7 @@ impl<'x, 'ast> Visitor<'ast> for TraitCheck<'x, 'ast> {
             param_types: Vec::new(),
             return_type: BuiltinType::Unit,
             parent: FctParent::Trait(self.trait_id.unwrap()),
+            module: self.ctxt.sym.lock().module(),
             has_override: f.has_override,
             has_open: f.has_open,
             has_final: f.has_final,
@@ -73,9 +122,11 @@ impl<'x, 'ast> Visitor<'ast> for TraitCheck<'x, 'ast> {
             vtable_index: None,
             initialized: false,
             impl_for: None,
+            default_impl: false,
 
             type_params: Vec::new(),
-            kind: FctKind::Definition,
+            where_bounds: HashMap::new(),
+            kind: kind,
         };
 
         let fctid = self.ctxt.add_fct(fct);
@@ -95,11 +146,11 @@ mod tests {
     use dora_parser::error::msg::Msg;
 
     #[test]
-    fn trait_method_with_body() {
+    fn static_trait_method_with_body() {
         err(
-            "trait Foo { fun foo() -> Int { return 1; } }",
-            pos(1, 13),
-            Msg::TraitMethodWithBody,
+            "trait Foo { static fun foo() -> Int { return 1; } }",
+            pos(1, 20),
+            Msg::StaticTraitMethodWithBody,
         );
     }
 
@@ -111,6 +162,7 @@ mod tests {
                 fun toFloat() -> Float;
                 fun toDouble() -> Double;
             }");
+        ok("trait Foo { fun foo() -> Int { return 1; } }");
 
         err(
             "trait Bar { fun foo() -> Unknown; }",
@@ -130,6 +182,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn generic_trait_definitions() {
+        ok("trait Foo<T> { fun get() -> T; fun set(val: T); }");
+        ok("trait Foo<A, B> { fun convert(val: A) -> B; }");
+
+        err(
+            "trait Foo<T, T> {}",
+            pos(1, 14),
+            Msg::TypeParamNameNotUnique("T".into()),
+        );
+
+        err("trait Foo<T: Equals> {}", pos(1, 11), Msg::BoundNotAllowed);
+    }
+
     #[test]
     fn trait_with_self() {
         err(
@@ -2,16 +2,19 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::{f32, f64};
 
-use crate::class::{ClassId, TypeParams};
+use crate::class::{self, ClassId, TypeParams};
+use crate::cpu::{FREG_PARAMS, REG_PARAMS};
 use crate::ctxt;
 use crate::ctxt::{
     CallType, ConstData, ConstValue, ConvInfo, Fct, FctId, FctParent, FctSrc, ForTypeInfo,
-    IdentType, SemContext, TraitId,
+    IdentType, SemContext, TemplateTypeInfo, TraitId,
 };
 use dora_parser::error::msg::Msg;
 
-use crate::semck::specialize::specialize_type;
-use crate::sym::Sym::{self, SymClass};
+use crate::semck;
+use crate::semck::specialize::{self, specialize_for_trait, specialize_type};
+use crate::sym::PathError;
+use crate::sym::Sym::{self, SymClass, SymModule};
 use crate::ty::BuiltinType;
 use dora_parser::ast::visit::Visitor;
 use dora_parser::ast::Expr::*;
@@ -25,7 +28,7 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
     for fct in ctxt.fcts.iter() {
         let fct = fct.read();
 
-        if !fct.is_src() {
+        if !fct.is_src() || semck::skip_default_impl(ctxt, &*fct) {
             continue;
         }
 
@@ -33,6 +36,8 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
         let mut src = src.write();
         let ast = fct.ast;
 
+        ctxt.sym.lock().set_module(fct.module);
+
         let mut typeck = TypeCheck {
             ctxt: ctxt,
             fct: &fct,
@@ -45,6 +50,8 @@ pub fn check<'a, 'ast>(ctxt: &SemContext<'ast>) {
         typeck.check();
     }
 
+    ctxt.sym.lock().set_module(None);
+
     for xconst in ctxt.consts.iter() {
         let mut xconst = xconst.lock();
 
@@ -119,6 +126,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 let expr_type = expr_type.name(self.ctxt);
                 let msg = Msg::AssignType(name, defined_type, expr_type);
                 self.ctxt.diag.lock().report_without_path(s.pos, msg);
+            } else {
+                self.check_box(defined_type, s.expr.as_ref().unwrap(), expr_type);
             }
 
         // let variable binding needs to be assigned
@@ -130,24 +139,72 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         }
     }
 
+    fn check_stmt_var_tuple(&mut self, s: &'ast StmtVarTupleType) {
+        self.visit_expr(&s.expr);
+        let expr_type = self.expr_type;
+
+        let elements = match expr_type {
+            BuiltinType::Tuple(_) => {
+                let elements = expr_type.type_params(self.ctxt);
+
+                if elements.len() == s.names.len() {
+                    Some(elements)
+                } else {
+                    let msg = Msg::WrongNumberOfTupleElements(elements.len(), s.names.len());
+                    self.ctxt.diag.lock().report_without_path(s.pos, msg);
+                    None
+                }
+            }
+
+            BuiltinType::Error => None,
+
+            _ => {
+                let msg = Msg::TupleExpected(expr_type.name(self.ctxt));
+                self.ctxt.diag.lock().report_without_path(s.pos, msg);
+                None
+            }
+        };
+
+        for (idx, binding) in s.names.iter().enumerate() {
+            let var = *self.src.map_vars.get(binding.id).unwrap();
+
+            self.src.vars[var].ty = match elements {
+                Some(ref elements) => elements[idx],
+                None => BuiltinType::Error,
+            };
+        }
+    }
+
     fn check_stmt_for(&mut self, s: &'ast StmtForType) {
         self.visit_expr(&s.expr);
         let object_type = self.expr_type;
 
         let name = self.ctxt.interner.intern("makeIterator");
 
-        let mut lookup = MethodLookup::new(self.ctxt)
-            .method(object_type)
-            .pos(s.pos)
-            .name(name)
-            .args(&[]);
+        // classes like Array<T> or Vec<T> only define makeIterator() in their
+        // Iterable<T> impl, the lookup finds impl methods as well
+        let make_iterator = if object_type.is_trait() {
+            self.find_trait_make_iterator(object_type, name, s.pos)
+        } else {
+            let mut lookup = MethodLookup::new(self.ctxt, self.fct)
+                .method(object_type)
+                .pos(s.pos)
+                .name(name)
+                .args(&[]);
 
-        if lookup.find() {
-            let make_iterator_id = lookup.found_fct_id().unwrap();
-            let make_iterator_ret = lookup.found_ret().unwrap();
+            if lookup.find() {
+                Some((lookup.found_fct_id().unwrap(), lookup.found_ret().unwrap()))
+            } else {
+                None
+            }
+        };
+
+        if let Some((make_iterator_id, make_iterator_ret)) = make_iterator {
             let iterator_trait_id = self.ctxt.vips.iterator();
 
-            if make_iterator_ret.implements_trait(self.ctxt, iterator_trait_id) {
+            if let Some(iterator_type_params) =
+                make_iterator_ret.trait_type_params(self.ctxt, iterator_trait_id)
+            {
                 // find fct next() & hasNext() in iterator-trait
                 let has_next_name = self.ctxt.interner.intern("hasNext");
                 let next_name = self.ctxt.interner.intern("next");
@@ -159,31 +216,38 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     .find_method(self.ctxt, false, has_next_name, None, &[])
                     .expect("hasNext() not found");
 
-                // find impl for ret that implements Iterator
-                let cls_id = make_iterator_ret.cls_id(self.ctxt).unwrap();
-                let cls = self.ctxt.classes.idx(cls_id);
-                let cls = cls.read();
-                let impl_id = cls
-                    .find_impl_for_trait(self.ctxt, iterator_trait_id)
-                    .expect("impl not found for Iterator");
-
-                // find method in impl that implements next()
-                let ximpl = self.ctxt.impls[impl_id].read();
-                let impl_next_id = ximpl
-                    .find_implements(self.ctxt, next_id)
-                    .expect("next() impl not found");
-
-                // find method in impl that implements hasNext();
-                let impl_has_next_id = ximpl
-                    .find_implements(self.ctxt, has_next_id)
-                    .expect("hasNext() impl not found");
+                let (has_next_id, next_id) = if make_iterator_ret.is_trait() {
+                    // iterator is a trait object, calls are dispatched at runtime
+                    (has_next_id, next_id)
+                } else {
+                    // find impl for ret that implements Iterator
+                    let cls_id = make_iterator_ret.cls_id(self.ctxt).unwrap();
+                    let cls = self.ctxt.classes.idx(cls_id);
+                    let cls = cls.read();
+                    let impl_id = cls
+                        .find_impl_for_trait(self.ctxt, iterator_trait_id)
+                        .expect("impl not found for Iterator");
+
+                    // find methods in impl that implement next() & hasNext()
+                    let ximpl = self.ctxt.impls[impl_id].read();
+                    let impl_next_id = ximpl
+                        .find_implements(self.ctxt, next_id)
+                        .expect("next() impl not found");
+                    let impl_has_next_id = ximpl
+                        .find_implements(self.ctxt, has_next_id)
+                        .expect("hasNext() impl not found");
+
+                    (impl_has_next_id, impl_next_id)
+                };
 
-                // get return type of next() in impl
-                let fct = self.ctxt.fcts.idx(impl_next_id);
-                let fct = fct.read();
-                let ret = fct.return_type;
+                // the type of the variable is the element type of the iterator,
+                // the impl already reported an error if it is missing
+                let ret = if iterator_type_params.len() == 1 {
+                    iterator_type_params[0]
+                } else {
+                    BuiltinType::Error
+                };
 
-                // set variable type to return type of next
                 let var_id = *self.src.map_vars.get(s.id).unwrap();
                 self.src.vars[var_id].ty = ret;
 
@@ -192,8 +256,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     s.id,
                     ForTypeInfo {
                         make_iterator: make_iterator_id,
-                        has_next: impl_has_next_id,
-                        next: impl_next_id,
+                        has_next: has_next_id,
+                        next: next_id,
                         iterator_type: make_iterator_ret,
                     },
                 );
@@ -207,6 +271,38 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         self.visit_stmt(&s.block);
     }
 
+    // makeIterator() of a trait object like Iterable<T>
+    fn find_trait_make_iterator(
+        &mut self,
+        object_type: BuiltinType,
+        name: Name,
+        pos: Position,
+    ) -> Option<(FctId, BuiltinType)> {
+        let trait_id = match object_type {
+            BuiltinType::Trait(trait_id, _) => trait_id,
+            _ => unreachable!(),
+        };
+
+        let xtrait = self.ctxt.traits[trait_id].read();
+
+        if let Some(fct_id) = xtrait.find_method(self.ctxt, false, name, None, &[]) {
+            let fct = self.ctxt.fcts.idx(fct_id);
+            let fct = fct.read();
+
+            let type_params = object_type.type_params(self.ctxt);
+            let ret = specialize_for_trait(self.ctxt, fct.return_type, object_type, &type_params);
+
+            return Some((fct_id, ret));
+        }
+
+        let type_name = object_type.name(self.ctxt);
+        let name = self.ctxt.interner.str(name).to_string();
+        let msg = Msg::UnknownMethod(type_name, name, Vec::new());
+        self.ctxt.diag.lock().report_without_path(pos, msg);
+
+        None
+    }
+
     fn check_stmt_while(&mut self, s: &'ast StmtWhileType) {
         self.visit_expr(&s.cond);
 
@@ -261,6 +357,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             };
 
             self.ctxt.diag.lock().report_without_path(s.pos, msg);
+        } else if let Some(ref expr) = s.expr {
+            self.check_box(fct_type, expr, expr_type);
         }
     }
 
@@ -306,19 +404,23 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
     }
 
     fn check_expr_ident(&mut self, e: &'ast ExprIdentType) {
-        let ident_type = *self.src.map_idents.get(e.id).unwrap();
+        self.check_ident_type(e.id, e.pos);
+    }
+
+    fn check_ident_type(&mut self, id: NodeId, pos: Position) {
+        let ident_type = *self.src.map_idents.get(id).unwrap();
 
         match ident_type {
             IdentType::Var(varid) => {
                 let ty = self.src.vars[varid].ty;
-                self.src.set_ty(e.id, ty);
+                self.src.set_ty(id, ty);
                 self.expr_type = ty;
             }
 
             IdentType::Global(globalid) => {
                 let glob = self.ctxt.globals.idx(globalid);
                 let ty = glob.lock().ty;
-                self.src.set_ty(e.id, ty);
+                self.src.set_ty(id, ty);
                 self.expr_type = ty;
             }
 
@@ -328,14 +430,14 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 let cls = cls.read();
                 let field = &cls.fields[fieldid];
 
-                self.src.set_ty(e.id, field.ty);
+                self.src.set_ty(id, field.ty);
                 self.expr_type = field.ty;
             }
 
             IdentType::Struct(sid) => {
                 let list_id = self.ctxt.lists.lock().insert(TypeParams::empty());
                 let ty = BuiltinType::Struct(sid, list_id);
-                self.src.set_ty(e.id, ty);
+                self.src.set_ty(id, ty);
                 self.expr_type = ty;
             }
 
@@ -343,7 +445,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 let xconst = self.ctxt.consts.idx(const_id);
                 let xconst = xconst.lock();
 
-                self.src.set_ty(e.id, xconst.ty);
+                self.src.set_ty(id, xconst.ty);
                 self.expr_type = xconst.ty;
             }
 
@@ -351,9 +453,9 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 self.ctxt
                     .diag
                     .lock()
-                    .report_without_path(e.pos, Msg::FctUsedAsIdentifier);
+                    .report_without_path(pos, Msg::FctUsedAsIdentifier);
 
-                self.src.set_ty(e.id, BuiltinType::Error);
+                self.src.set_ty(id, BuiltinType::Error);
                 self.expr_type = BuiltinType::Error;
             }
         }
@@ -409,6 +511,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     &TypeParams::empty(),
                 );
                 self.src.set_ty(array.id, element_type);
+                self.check_box(element_type, &e.rhs, value_type);
 
                 self.src.set_ty(e.id, return_type);
                 self.expr_type = return_type;
@@ -419,7 +522,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             }
         } else if e.lhs.is_field() {
             self.check_expr_assign_field(e);
-        } else if e.lhs.is_ident() {
+        } else if e.lhs.is_ident() || e.lhs.is_path() {
             let lhs_type;
 
             self.visit_expr(&e.rhs);
@@ -483,7 +586,10 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 }
 
                 if !lhs_type.allows(self.ctxt, rhs_type) {
-                    let ident = e.lhs.to_ident().unwrap();
+                    let ident = match e.lhs.to_path() {
+                        Some(path) => path.rhs.to_ident().unwrap(),
+                        None => e.lhs.to_ident().unwrap(),
+                    };
                     let name = self.ctxt.interner.str(ident.name).to_string();
                     let lhs_type = lhs_type.name(self.ctxt);
                     let rhs_type = rhs_type.name(self.ctxt);
@@ -493,6 +599,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
 
                     let msg = Msg::AssignType(name, lhs_type, rhs_type);
                     self.ctxt.diag.lock().report_without_path(e.pos, msg);
+                } else {
+                    self.check_box(lhs_type, &e.rhs, rhs_type);
                 }
 
                 return;
@@ -518,6 +626,18 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         self.visit_expr(&e.rhs);
         let rhs_type = self.expr_type;
 
+        // tuples are immutable
+        if object_type.is_tuple() {
+            self.ctxt
+                .diag
+                .lock()
+                .report_without_path(e.pos, Msg::LetReassigned);
+
+            self.src.set_ty(e.id, BuiltinType::Unit);
+            self.expr_type = BuiltinType::Unit;
+            return;
+        }
+
         let cls_id = object_type.cls_id(self.ctxt);
 
         if let Some(cls_id) = cls_id {
@@ -539,6 +659,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     .insert_or_replace(e.lhs.id(), ident_type);
 
                 let field = &cls.fields[field_id];
+                self.check_field_visible(&cls, field, field_expr.pos);
+
                 let class_type_params = ty.type_params(self.ctxt);
                 let fty = replace_type_param(
                     self.ctxt,
@@ -564,6 +686,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
 
                     let msg = Msg::AssignField(name, object_type, lhs_type, rhs_type);
                     self.ctxt.diag.lock().report_without_path(e.pos, msg);
+                } else {
+                    self.check_box(fty, &e.rhs, rhs_type);
                 }
 
                 self.src.set_ty(e.id, BuiltinType::Unit);
@@ -724,15 +848,31 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         let name = self.ctxt.interner.intern(name);
         let call_types = [rhs_type];
 
-        if let Some((_, fct_id, return_type)) = lookup_method(
-            self.ctxt,
-            lhs_type,
-            false,
-            name,
-            &call_types,
-            &TypeParams::empty(),
-            None,
-        ) {
+        let found = if lhs_type == BuiltinType::This {
+            // operators on Self in default methods use the methods of the trait
+            let xtrait = self.ctxt.traits[self.fct.trait_id()].read();
+
+            xtrait
+                .find_method(self.ctxt, false, name, None, &call_types)
+                .map(|fct_id| {
+                    let fct = self.ctxt.fcts.idx(fct_id);
+                    let return_type = fct.read().return_type;
+                    (fct_id, return_type)
+                })
+        } else {
+            lookup_method(
+                self.ctxt,
+                lhs_type,
+                false,
+                name,
+                &call_types,
+                &TypeParams::empty(),
+                None,
+            )
+            .map(|(_, fct_id, return_type)| (fct_id, return_type))
+        };
+
+        if let Some((fct_id, return_type)) = found {
             let call_type = CallType::Method(lhs_type, fct_id, TypeParams::empty());
             self.src
                 .map_calls
@@ -846,13 +986,25 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 return;
             }
 
+            if let BuiltinType::Trait(trait_id, _) = object_type {
+                self.check_trait_method_call(e, in_try, object_type, trait_id, &call_types);
+                return;
+            }
+
+            // only default methods of traits have receivers of type Self
+            if object_type == BuiltinType::This {
+                let trait_id = self.fct.trait_id();
+                self.check_trait_method_call(e, in_try, object_type, trait_id, &call_types);
+                return;
+            }
+
             if object_type.is_error() {
                 self.src.set_ty(e.id, BuiltinType::Error);
                 self.expr_type = BuiltinType::Error;
                 return;
             }
 
-            let mut lookup = MethodLookup::new(self.ctxt)
+            let mut lookup = MethodLookup::new(self.ctxt, self.fct)
                 .method(object_type)
                 .pos(e.pos)
                 .name(e.path.name())
@@ -861,6 +1013,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             if lookup.find() {
                 let fct_id = lookup.found_fct_id().unwrap();
                 let return_type = lookup.found_ret().unwrap();
+                self.check_fct_visible(fct_id, e.pos);
+                self.check_arg_boxes(&e.args, lookup.found_params().unwrap(), &call_types);
 
                 let call_type = CallType::Method(object_type, fct_id, TypeParams::empty());
                 self.src
@@ -887,20 +1041,27 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             return;
         }
 
-        let call_type = if e.path.len() > 1 {
-            match self.ctxt.sym.lock().get(e.path[0]) {
-                Some(SymClass(cls_id)) => {
-                    assert_eq!(2, e.path.len());
+        let call_type = if let Some(call_type) = self.src.map_calls.get(e.id) {
+            call_type.clone()
+        } else {
+            let path = &e.path.path;
+            let prefix = &path[..path.len() - 1];
+            let sym = self.ctxt.sym.lock().get_path(prefix);
 
-                    let mut lookup = MethodLookup::new(self.ctxt)
+            match sym {
+                Ok(SymClass(cls_id)) => {
+                    let mut lookup = MethodLookup::new(self.ctxt, self.fct)
                         .pos(e.pos)
                         .static_method(cls_id)
-                        .name(e.path[1])
+                        .name(path[path.len() - 1])
                         .args(&call_types)
                         .fct_type_params(&type_params);
 
                     if lookup.find() {
                         let fct_id = lookup.found_fct_id().unwrap();
+                        self.check_fct_visible(fct_id, e.pos);
+                        self.check_arg_boxes(&e.args, lookup.found_params().unwrap(), &call_types);
+
                         let call_type = Arc::new(CallType::Fct(
                             fct_id,
                             TypeParams::empty(),
@@ -915,22 +1076,39 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     }
                 }
 
-                _ => {
-                    let name = self.ctxt.interner.str(e.path[0]).to_string();
+                Ok(SymModule(_)) => {
+                    let name = semck::path_name(self.ctxt, path);
+                    let msg = match self.ctxt.sym.lock().get_path(path) {
+                        Err(PathError::NotPublic(_)) => Msg::NotPublic(name),
+                        _ => Msg::UnknownFunction(name),
+                    };
+                    self.ctxt.diag.lock().report_without_path(e.pos, msg);
+
+                    self.expr_type = BuiltinType::Error;
+                    return;
+                }
+
+                Ok(_) | Err(PathError::Unknown(_)) => {
+                    let name = semck::path_name(self.ctxt, prefix);
                     let msg = Msg::ClassExpected(name);
                     self.ctxt.diag.lock().report_without_path(e.pos, msg);
 
                     self.expr_type = BuiltinType::Error;
                     return;
                 }
+
+                Err(err) => {
+                    semck::report_path_error(self.ctxt, prefix, err, e.pos);
+
+                    self.expr_type = BuiltinType::Error;
+                    return;
+                }
             }
-        } else {
-            self.src.map_calls.get(e.id).unwrap().clone()
         };
 
         match *call_type {
             CallType::CtorNew(cls_id, _, _) => {
-                let mut lookup = MethodLookup::new(self.ctxt)
+                let mut lookup = MethodLookup::new(self.ctxt, self.fct)
                     .pos(e.pos)
                     .ctor(cls_id)
                     .args(&call_types)
@@ -944,6 +1122,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
 
                     let call_type = CallType::CtorNew(cls_id, fct_id, type_params.clone());
                     self.src.map_calls.replace(e.id, Arc::new(call_type));
+                    self.check_arg_boxes(&e.args, lookup.found_params().unwrap(), &call_types);
 
                     if cls.is_abstract {
                         let msg = Msg::NewAbstractClass;
@@ -960,7 +1139,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             }
 
             CallType::Fct(callee_id, _, _) => {
-                let mut lookup = MethodLookup::new(self.ctxt)
+                let mut lookup = MethodLookup::new(self.ctxt, self.fct)
                     .pos(e.pos)
                     .callee(callee_id)
                     .args(&call_types)
@@ -970,6 +1149,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     let call_type =
                         CallType::Fct(callee_id, TypeParams::empty(), type_params.clone());
                     self.src.map_calls.replace(e.id, Arc::new(call_type));
+                    self.check_arg_boxes(&e.args, lookup.found_params().unwrap(), &call_types);
 
                     lookup.found_ret().unwrap()
                 } else {
@@ -1060,7 +1240,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                         .map_calls
                         .insert(e.callee.id(), Arc::new(call_type));
 
-                    let mut lookup = MethodLookup::new(self.ctxt)
+                    let mut lookup = MethodLookup::new(self.ctxt, self.fct)
                         .pos(e.pos)
                         .callee(fct_id)
                         .args(&arg_types)
@@ -1122,7 +1302,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     let call_type = CallType::CtorNew(cls_id, FctId(0), TypeParams::empty());
                     self.src.map_calls.insert(e.id, Arc::new(call_type));
 
-                    let mut lookup = MethodLookup::new(self.ctxt)
+                    let mut lookup = MethodLookup::new(self.ctxt, self.fct)
                         .pos(e.pos)
                         .ctor(cls_id)
                         .args(arg_types)
@@ -1191,7 +1371,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             return;
         }
 
-        let mut lookup = MethodLookup::new(self.ctxt)
+        let mut lookup = MethodLookup::new(self.ctxt, self.fct)
             .method(object_type)
             .pos(e.pos)
             .name(name)
@@ -1232,80 +1412,90 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         arg_types: &[BuiltinType],
         in_try: bool,
     ) {
-        match object_type {
+        if let Some(fid) = self.find_type_param_method(e.pos, object_type, name, arg_types) {
+            let call_type = CallType::Method(object_type, fid, TypeParams::empty());
+            self.src.map_calls.insert(e.id, Arc::new(call_type));
+
+            let fct = self.ctxt.fcts.idx(fid);
+            let fct = fct.read();
+            let return_type = fct.return_type;
+
+            if fct.throws && !in_try {
+                let msg = Msg::ThrowingCallWithoutTry;
+                self.ctxt.diag.lock().report_without_path(e.pos, msg);
+            }
+
+            self.src.set_ty(e.id, return_type);
+            self.expr_type = return_type;
+        } else {
+            self.src.set_ty(e.id, BuiltinType::Error);
+            self.expr_type = BuiltinType::Error;
+        }
+    }
+
+    // trait bounds of a type param: its declared bounds plus the bounds
+    // added by the where clauses of the current function
+    fn type_param_trait_bounds(&self, ty: BuiltinType) -> HashSet<TraitId> {
+        match ty {
             BuiltinType::FctTypeParam(_, tpid) => {
-                let tp = &self.fct.type_params[tpid.idx()];
-                self.check_expr_call_generic_type_param(
-                    e,
-                    object_type,
-                    tp,
-                    name,
-                    arg_types,
-                    in_try,
-                );
+                self.fct.type_params[tpid.idx()].trait_bounds.clone()
             }
 
             BuiltinType::ClassTypeParam(cls_id, tpid) => {
                 let cls = self.ctxt.classes.idx(cls_id);
                 let cls = cls.read();
-                let tp = &cls.type_params[tpid.idx()];
-                self.check_expr_call_generic_type_param(
-                    e,
-                    object_type,
-                    tp,
-                    name,
-                    arg_types,
-                    in_try,
-                );
+                let mut bounds = cls.type_params[tpid.idx()].trait_bounds.clone();
+
+                if self.fct.owner_cls_id(self.ctxt) == Some(cls_id) {
+                    if let Some(where_bounds) = self.fct.where_bounds.get(&tpid) {
+                        bounds.extend(where_bounds.iter().cloned());
+                    }
+                }
+
+                bounds
             }
 
             _ => unreachable!(),
         }
     }
 
-    fn check_expr_call_generic_type_param(
-        &mut self,
-        e: &'ast ExprCall2Type,
+    fn find_type_param_method(
+        &self,
+        pos: Position,
         object_type: BuiltinType,
-        tp: &ctxt::TypeParam,
         name: Name,
         args: &[BuiltinType],
-        in_try: bool,
-    ) {
-        for &trait_id in &tp.trait_bounds {
-            let trai = self.ctxt.traits[trait_id].read();
+    ) -> Option<FctId> {
+        let mut candidates = Vec::new();
 
-            if let Some(fid) = trai.find_method(self.ctxt, false, name, None, args) {
-                let call_type = CallType::Method(object_type, fid, TypeParams::empty());
-                self.src.map_calls.insert(e.id, Arc::new(call_type));
-
-                let fct = self.ctxt.fcts.idx(fid);
-                let fct = fct.read();
-                let return_type = fct.return_type;
+        for trait_id in self.type_param_trait_bounds(object_type) {
+            let xtrait = self.ctxt.traits[trait_id].read();
 
-                if fct.throws && !in_try {
-                    let msg = Msg::ThrowingCallWithoutTry;
-                    self.ctxt.diag.lock().report_without_path(e.pos, msg);
-                }
-
-                self.src.set_ty(e.id, return_type);
-                self.expr_type = return_type;
-                return;
+            if let Some(fid) = xtrait.find_method(self.ctxt, false, name, None, args) {
+                candidates.push(fid);
             }
         }
 
         let type_name = object_type.name(self.ctxt);
         let name = self.ctxt.interner.str(name).to_string();
-        let param_names = args
-            .iter()
-            .map(|a| a.name(self.ctxt))
-            .collect::<Vec<String>>();
-        let msg = Msg::UnknownMethod(type_name, name, param_names);
 
-        self.ctxt.diag.lock().report_without_path(e.pos, msg);
+        let msg = match candidates.len() {
+            0 => {
+                let param_names = args
+                    .iter()
+                    .map(|a| a.name(self.ctxt))
+                    .collect::<Vec<String>>();
+                Msg::UnknownMethod(type_name, name, param_names)
+            }
 
-        self.src.set_ty(e.id, BuiltinType::Error);
-        self.expr_type = BuiltinType::Error;
+            1 => return Some(candidates[0]),
+
+            _ => Msg::MultipleCandidatesForMethod(type_name, name),
+        };
+
+        self.ctxt.diag.lock().report_without_path(pos, msg);
+
+        None
     }
 
     fn check_expr_call_path(
@@ -1351,7 +1541,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
 
         match self.ctxt.sym.lock().get(class) {
             Some(SymClass(cls_id)) => {
-                let mut lookup = MethodLookup::new(self.ctxt)
+                let mut lookup = MethodLookup::new(self.ctxt, self.fct)
                     .pos(e.pos)
                     .static_method(cls_id)
                     .name(method_name)
@@ -1462,41 +1652,84 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         obj: BuiltinType,
         args: &[BuiltinType],
     ) {
-        match obj {
-            BuiltinType::FctTypeParam(_, tpid) => {
-                let tp = &self.fct.type_params[tpid.idx()];
-                self.check_generic_method_call_for_type_param(e, in_try, obj, args, tp);
-            }
+        if let Some(fid) = self.find_type_param_method(e.pos, obj, e.path.name(), args) {
+            let call_type = CallType::Method(obj, fid, TypeParams::empty());
+            self.src.map_calls.insert(e.id, Arc::new(call_type));
 
-            BuiltinType::ClassTypeParam(cls_id, tpid) => {
-                let cls = self.ctxt.classes.idx(cls_id);
-                let cls = cls.read();
-                let tp = &cls.type_params[tpid.idx()];
-                self.check_generic_method_call_for_type_param(e, in_try, obj, args, tp);
+            let fct = self.ctxt.fcts.idx(fid);
+            let fct = fct.read();
+            let return_type = fct.return_type;
+
+            if fct.throws && !in_try {
+                let msg = Msg::ThrowingCallWithoutTry;
+                self.ctxt.diag.lock().report_without_path(e.pos, msg);
             }
 
-            _ => unreachable!(),
+            self.src.set_ty(e.id, return_type);
+            self.expr_type = return_type;
+        } else {
+            self.src.set_ty(e.id, BuiltinType::Unit);
+            self.expr_type = BuiltinType::Unit;
         }
     }
 
-    fn check_generic_method_call_for_type_param(
+    fn check_trait_method_call(
         &mut self,
         e: &'ast ExprCallType,
         in_try: bool,
-        obj: BuiltinType,
+        object_type: BuiltinType,
+        trait_id: TraitId,
         args: &[BuiltinType],
-        tp: &ctxt::TypeParam,
     ) {
-        for &trait_id in &tp.trait_bounds {
-            let trai = self.ctxt.traits[trait_id].read();
+        let xtrait = self.ctxt.traits[trait_id].read();
+
+        // type params of a trait object, inside the trait they stay as they are
+        let trait_type_params = if object_type.is_trait() {
+            object_type.type_params(self.ctxt)
+        } else {
+            TypeParams::empty()
+        };
+
+        if let Some(fid) = xtrait.find_method_with_type_params(
+            self.ctxt,
+            false,
+            e.path.name(),
+            None,
+            &trait_type_params,
+            args,
+        ) {
+            let fct = self.ctxt.fcts.idx(fid);
+            let fct = fct.read();
+
+            // the concrete type behind a trait object is unknown,
+            // so methods that mention Self can't be called on it
+            let uses_self = object_type.is_trait()
+                && (fct.return_type == BuiltinType::This
+                    || fct
+                        .params_without_self()
+                        .iter()
+                        .any(|&param| param == BuiltinType::This));
 
-            if let Some(fid) = trai.find_method(self.ctxt, false, e.path.name(), None, args) {
-                let call_type = CallType::Method(obj, fid, TypeParams::empty());
+            if !uses_self {
+                let call_type = CallType::Method(object_type, fid, TypeParams::empty());
                 self.src.map_calls.insert(e.id, Arc::new(call_type));
 
-                let fct = self.ctxt.fcts.idx(fid);
-                let fct = fct.read();
-                let return_type = fct.return_type;
+                let specialize = |ty| {
+                    if trait_type_params.len() > 0 {
+                        specialize_for_trait(self.ctxt, ty, BuiltinType::This, &trait_type_params)
+                    } else {
+                        ty
+                    }
+                };
+
+                let params: Vec<_> = fct
+                    .params_without_self()
+                    .iter()
+                    .map(|&ty| specialize(ty))
+                    .collect();
+                let return_type = specialize(fct.return_type);
+
+                self.check_arg_boxes(&e.args, &params, args);
 
                 if fct.throws && !in_try {
                     let msg = Msg::ThrowingCallWithoutTry;
@@ -1509,7 +1742,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             }
         }
 
-        let type_name = obj.name(self.ctxt);
+        let type_name = object_type.name(self.ctxt);
         let name = self.ctxt.interner.str(e.path.name()).to_string();
         let param_names = args
             .iter()
@@ -1519,12 +1752,90 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
 
         self.ctxt.diag.lock().report_without_path(e.pos, msg);
 
-        self.src.set_ty(e.id, BuiltinType::Unit);
-        self.expr_type = BuiltinType::Unit;
+        self.src.set_ty(e.id, BuiltinType::Error);
+        self.expr_type = BuiltinType::Error;
     }
 
-    fn check_expr_path(&mut self, _e: &'ast ExprPathType) {
-        unimplemented!();
+    fn check_arg_boxes(
+        &mut self,
+        args: &'ast [Box<Expr>],
+        params: &[BuiltinType],
+        arg_types: &[BuiltinType],
+    ) {
+        for ((arg, &param), &arg_type) in args.iter().zip(params).zip(arg_types) {
+            self.check_box(param, arg, arg_type);
+        }
+    }
+
+    // values of primitive types are boxed when they are used as trait object
+    fn check_box(&mut self, expected: BuiltinType, e: &'ast Expr, ty: BuiltinType) {
+        if !expected.is_trait() || ty.is_cls() {
+            return;
+        }
+
+        if ty.is_float() {
+            self.check_float_box(expected, e, ty);
+        }
+
+        if let Some(cls_id) = ty.cls_id(self.ctxt) {
+            self.src.map_boxes.insert_or_replace(e.id(), cls_id);
+        }
+    }
+
+    // the unbox thunk for a float receiver moves all arguments by one
+    // register, arguments passed on the stack are not supported
+    fn check_float_box(&mut self, expected: BuiltinType, e: &'ast Expr, ty: BuiltinType) {
+        let trait_id = match expected {
+            BuiltinType::Trait(trait_id, _) => trait_id,
+            _ => unreachable!(),
+        };
+
+        let xtrait = self.ctxt.traits[trait_id].read();
+
+        for &method in &xtrait.methods {
+            let method = self.ctxt.fcts.idx(method);
+            let method = method.read();
+
+            let params = method.params_without_self();
+            let fregs = params.iter().filter(|ty| ty.is_float()).count();
+            let regs = params.len() - fregs;
+
+            if fregs >= FREG_PARAMS.len() || regs >= REG_PARAMS.len() {
+                let name = self.ctxt.interner.str(method.name);
+                let reason = format!("method `{}` passes arguments on the stack", name);
+                let msg = Msg::BoxNotPossible(ty.name(self.ctxt), reason);
+                self.ctxt.diag.lock().report_without_path(e.pos(), msg);
+                return;
+            }
+        }
+    }
+
+    fn check_expr_path(&mut self, e: &'ast ExprPathType) {
+        self.check_ident_type(e.id, e.pos);
+    }
+
+    // methods of classes in modules are only accessible from other modules when public
+    fn check_fct_visible(&mut self, fct_id: FctId, pos: Position) {
+        let fct = self.ctxt.fcts.idx(fct_id);
+        let fct = fct.read();
+
+        if fct.is_pub || fct.module.is_none() || fct.module == self.fct.module {
+            return;
+        }
+
+        let msg = Msg::NotPublic(fct.full_name(self.ctxt));
+        self.ctxt.diag.lock().report_without_path(pos, msg);
+    }
+
+    // fields of classes in modules are only accessible from other modules when public
+    fn check_field_visible(&mut self, cls: &class::Class, field: &class::Field, pos: Position) {
+        if field.is_pub || cls.module.is_none() || cls.module == self.fct.module {
+            return;
+        }
+
+        let name = self.ctxt.interner.str(field.name);
+        let msg = Msg::NotPublic(format!("{}.{}", cls.long_name(self.ctxt), name));
+        self.ctxt.diag.lock().report_without_path(pos, msg);
     }
 
     fn check_expr_type_param(&mut self, _e: &'ast ExprTypeParamType) {
@@ -1536,6 +1847,11 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
 
         let ty = self.expr_type;
 
+        if ty.is_tuple() {
+            self.check_expr_tuple_element(e, ty);
+            return;
+        }
+
         let cls_id = ty.cls_id(self.ctxt);
 
         if let Some(cls_id) = cls_id {
@@ -1555,6 +1871,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 self.src.map_idents.insert_or_replace(e.id, ident_type);
 
                 let field = &cls.fields[field_id];
+                self.check_field_visible(&cls, field, e.pos);
+
                 let class_type_params = ty.type_params(self.ctxt);
                 let fty = replace_type_param(
                     self.ctxt,
@@ -1579,6 +1897,112 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         self.expr_type = BuiltinType::Error;
     }
 
+    // elements of a tuple are accessed by their index: `tuple.0`
+    fn check_expr_tuple_element(&mut self, e: &'ast ExprFieldType, ty: BuiltinType) {
+        let name = self.ctxt.interner.str(e.name).to_string();
+        let elements = ty.type_params(self.ctxt);
+
+        if let Ok(idx) = name.parse::<usize>() {
+            if idx < elements.len() {
+                let ident_type = IdentType::Field(ty, idx.into());
+                self.src.map_idents.insert_or_replace(e.id, ident_type);
+
+                let element_ty = elements[idx];
+                self.src.set_ty(e.id, element_ty);
+                self.expr_type = element_ty;
+                return;
+            }
+        }
+
+        let msg = Msg::UnknownField(name, ty.name(self.ctxt));
+        self.ctxt.diag.lock().report_without_path(e.pos, msg);
+
+        self.src.set_ty(e.id, BuiltinType::Error);
+        self.expr_type = BuiltinType::Error;
+    }
+
+    fn check_expr_tuple(&mut self, e: &'ast ExprTupleType) {
+        let mut elements = Vec::with_capacity(e.values.len());
+        let mut error = false;
+
+        for value in &e.values {
+            self.visit_expr(value);
+
+            if self.expr_type.is_error() {
+                error = true;
+            }
+
+            elements.push(self.expr_type);
+        }
+
+        let ty = if error {
+            BuiltinType::Error
+        } else {
+            let list_id = self.ctxt.lists.lock().insert(elements.into());
+            BuiltinType::Tuple(list_id)
+        };
+
+        self.src.set_ty(e.id, ty);
+        self.expr_type = ty;
+    }
+
+    fn check_expr_template(&mut self, e: &'ast ExprTemplateType) {
+        let str_ty = self.ctxt.cls(self.ctxt.vips.string_class);
+        let to_string_name = self.ctxt.interner.intern("toString");
+        let mut parts = Vec::with_capacity(e.parts.len());
+
+        for part in &e.parts {
+            self.visit_expr(part);
+            let part_type = self.expr_type;
+
+            // strings are appended directly, all other parts need `toString()`
+            if part_type == str_ty || part_type.is_error() {
+                parts.push(None);
+                continue;
+            }
+
+            let mut lookup = MethodLookup::new(self.ctxt, self.fct)
+                .method(part_type)
+                .pos(part.pos())
+                .name(to_string_name)
+                .args(&[]);
+
+            if lookup.find() {
+                let ret = lookup.found_ret().unwrap();
+
+                if ret != str_ty {
+                    let msg = Msg::ToStringReturnType(ret.name(self.ctxt));
+                    self.ctxt.diag.lock().report_without_path(part.pos(), msg);
+                }
+
+                parts.push(lookup.found_fct_id());
+            } else {
+                parts.push(None);
+            }
+        }
+
+        let info = TemplateTypeInfo {
+            empty: self.string_buf_method("empty", true),
+            append_string: self.string_buf_method("appendString", false),
+            to_string: self.string_buf_method("toString", false),
+            parts: parts,
+        };
+
+        self.src.map_templates.insert(e.id, info);
+        self.src.set_ty(e.id, str_ty);
+        self.expr_type = str_ty;
+    }
+
+    fn string_buf_method(&self, name: &str, is_static: bool) -> FctId {
+        let cls = self.ctxt.classes.idx(self.ctxt.vips.string_buf_class);
+        let cls = cls.read();
+        let name = self.ctxt.interner.intern(name);
+
+        cls.find_methods(self.ctxt, name, is_static)
+            .pop()
+            .expect("method of StringBuf not found")
+    }
+
     fn check_expr_this(&mut self, e: &'ast ExprSelfType) {
         match self.fct.parent {
             FctParent::Class(clsid) => {
@@ -1598,6 +2022,11 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 self.expr_type = ty;
             }
 
+            FctParent::Trait(_) => {
+                self.src.set_ty(e.id, BuiltinType::This);
+                self.expr_type = BuiltinType::This;
+            }
+
             _ => {
                 let msg = Msg::ThisUnavailable;
                 self.ctxt.diag.lock().report_without_path(e.pos, msg);
@@ -1871,12 +2300,15 @@ impl<'a, 'ast> Visitor<'ast> for TypeCheck<'a, 'ast> {
             ExprConv(ref expr) => self.check_expr_conv(expr),
             ExprTry(ref expr) => self.check_expr_try(expr),
             ExprLambda(ref expr) => self.check_expr_lambda(expr),
+            ExprTuple(ref expr) => self.check_expr_tuple(expr),
+            ExprTemplate(ref expr) => self.check_expr_template(expr),
         }
     }
 
     fn visit_stmt(&mut self, s: &'ast Stmt) {
         match *s {
             StmtVar(ref stmt) => self.check_stmt_var(stmt),
+            StmtVarTuple(ref stmt) => self.check_stmt_var_tuple(stmt),
             StmtWhile(ref stmt) => self.check_stmt_while(stmt),
             StmtFor(ref stmt) => self.check_stmt_for(stmt),
             StmtIf(ref stmt) => self.check_stmt_if(stmt),
@@ -1937,11 +2369,38 @@ fn arg_allows(
         | BuiltinType::Int
         | BuiltinType::Long
         | BuiltinType::Float
-        | BuiltinType::Double => def == arg,
+        | BuiltinType::Double
+        | BuiltinType::This => def == arg,
         BuiltinType::Nil => panic!("nil should not occur in fct definition."),
         BuiltinType::Ptr => panic!("ptr should not occur in fct definition."),
-        BuiltinType::This => panic!("this should not occur in fct definition."),
-        BuiltinType::Trait(_) => panic!("trait should not occur in fct definition."),
+        BuiltinType::Trait(trait_id, list_id) => {
+            if def == arg || arg.is_nil() {
+                return true;
+            }
+
+            let params = ctxt.lists.lock().get(list_id);
+
+            if params.len() == 0 {
+                return arg.implements_trait(ctxt, trait_id);
+            }
+
+            let other_params = match arg.trait_type_params(ctxt, trait_id) {
+                Some(other_params) => other_params,
+                None => return false,
+            };
+
+            if params.len() != other_params.len() {
+                return false;
+            }
+
+            for (tp, op) in params.iter().zip(other_params.iter()) {
+                if !arg_allows(ctxt, tp, op, global_cls_id, global_fct_id, cls_tps, fct_tps) {
+                    return false;
+                }
+            }
+
+            true
+        }
 
         BuiltinType::ClassTypeParam(cls_id, tpid) => {
             if def == arg {
@@ -1962,6 +2421,32 @@ fn arg_allows(
                 fct_tps,
             )
         }
+        BuiltinType::TraitTypeParam(_, _) => def == arg,
+        BuiltinType::Tuple(list_id) => {
+            if def == arg {
+                return true;
+            }
+
+            let other_list_id = match arg {
+                BuiltinType::Tuple(other_list_id) => other_list_id,
+                _ => return false,
+            };
+
+            let elements = ctxt.lists.lock().get(list_id);
+            let other_elements = ctxt.lists.lock().get(other_list_id);
+
+            if elements.len() != other_elements.len() {
+                return false;
+            }
+
+            for (tp, op) in elements.iter().zip(other_elements.iter()) {
+                if !arg_allows(ctxt, tp, op, global_cls_id, global_fct_id, cls_tps, fct_tps) {
+                    return false;
+                }
+            }
+
+            true
+        }
         BuiltinType::FctTypeParam(fct_id, tpid) => {
             if def == arg {
                 return true;
@@ -2198,6 +2683,7 @@ enum LookupKind {
 
 struct MethodLookup<'a, 'ast: 'a> {
     ctxt: &'a SemContext<'ast>,
+    caller: &'a Fct<'ast>,
     kind: Option<LookupKind>,
     name: Option<Name>,
     args: Option<&'a [BuiltinType]>,
@@ -2209,12 +2695,14 @@ struct MethodLookup<'a, 'ast: 'a> {
     found_fct_id: Option<FctId>,
     found_cls_id: Option<ClassId>,
     found_ret: Option<BuiltinType>,
+    found_params: Option<Vec<BuiltinType>>,
 }
 
 impl<'a, 'ast> MethodLookup<'a, 'ast> {
-    fn new(ctxt: &'a SemContext<'ast>) -> MethodLookup<'a, 'ast> {
+    fn new(ctxt: &'a SemContext<'ast>, caller: &'a Fct<'ast>) -> MethodLookup<'a, 'ast> {
         MethodLookup {
             ctxt: ctxt,
+            caller: caller,
             kind: None,
             name: None,
             args: None,
@@ -2226,6 +2714,7 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
             found_fct_id: None,
             found_cls_id: None,
             found_ret: None,
+            found_params: None,
         }
     }
 
@@ -2392,6 +2881,10 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
             return false;
         }
 
+        if !self.check_where_bounds(&fct, &cls_tps) {
+            return false;
+        }
+
         let fct_tps: TypeParams = if let Some(fct_tps) = self.fct_tps {
             if !self.check_fct_tps(fct_tps) {
                 return false;
@@ -2436,13 +2929,37 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
         };
 
         if self.ret.is_none() || self.ret.unwrap() == cmp_type {
+            let params = fct
+                .params_without_self()
+                .iter()
+                .map(|&param| replace_type_param(self.ctxt, param, &cls_tps, &fct_tps))
+                .collect();
+
+            self.check_deprecated(&fct);
             self.found_ret = Some(cmp_type);
+            self.found_params = Some(params);
             true
         } else {
             false
         }
     }
 
+    fn check_deprecated(&self, fct: &Fct<'ast>) {
+        // copies of default methods were already checked in the trait
+        if self.caller.default_impl {
+            return;
+        }
+
+        if let Some(ref msg) = fct.ast.deprecated {
+            let name = self.ctxt.interner.str(fct.name).to_string();
+            let msg = Msg::DeprecatedFct(name, msg.clone());
+            self.ctxt
+                .diag
+                .lock()
+                .warn_without_path(self.pos.expect("pos not set"), msg);
+        }
+    }
+
     fn find_fct(&self, _: Name) -> Option<FctId> {
         unimplemented!()
     }
@@ -2510,6 +3027,31 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
         self.check_tps(&fct_tps, tps)
     }
 
+    // checks the class type params of a call against the bounds
+    // from the where clauses of the called method
+    fn check_where_bounds(&self, fct: &Fct<'ast>, cls_tps: &TypeParams) -> bool {
+        let mut succeeded = true;
+
+        let cls_id = match self.found_cls_id {
+            Some(cls_id) => cls_id,
+            None => return true,
+        };
+
+        let cls = self.ctxt.classes.idx(cls_id);
+        let cls = cls.read();
+
+        for (&tpid, trait_bounds) in &fct.where_bounds {
+            let mut tp = ctxt::TypeParam::new(cls.type_params[tpid.idx()].name);
+            tp.trait_bounds = trait_bounds.clone();
+
+            if !self.check_tp_or_type_param(&tp, cls_tps[tpid.idx()]) {
+                succeeded = false;
+            }
+        }
+
+        succeeded
+    }
+
     fn check_tps(&self, specified_tps: &[ctxt::TypeParam], tps: &TypeParams) -> bool {
         if specified_tps.len() != tps.len() {
             let msg = Msg::WrongNumberTypeParams(specified_tps.len(), tps.len());
@@ -2523,27 +3065,7 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
         let mut succeeded = true;
 
         for (tp, ty) in specified_tps.iter().zip(tps.iter()) {
-            if ty.is_type_param() {
-                let ok = match ty {
-                    BuiltinType::ClassTypeParam(cls_id, tpid) => {
-                        let cls = self.ctxt.classes.idx(cls_id);
-                        let cls = cls.read();
-                        self.check_tp_against_tp(tp, &cls.type_params[tpid.idx()], ty)
-                    }
-
-                    BuiltinType::FctTypeParam(fct_id, tpid) => {
-                        let fct = self.ctxt.fcts.idx(fct_id);
-                        let fct = fct.read();
-                        self.check_tp_against_tp(tp, &fct.type_params[tpid.idx()], ty)
-                    }
-
-                    _ => unreachable!(),
-                };
-
-                if !ok {
-                    succeeded = false;
-                }
-            } else if !self.check_tp(tp, ty) {
+            if !self.check_tp_or_type_param(tp, ty) {
                 succeeded = false;
             }
         }
@@ -2551,29 +3073,37 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
         succeeded
     }
 
-    fn check_tp(&self, tp: &ctxt::TypeParam, ty: BuiltinType) -> bool {
-        let mut succeeded = true;
+    fn check_tp_or_type_param(&self, tp: &ctxt::TypeParam, ty: BuiltinType) -> bool {
+        match ty {
+            BuiltinType::ClassTypeParam(cls_id, tpid) => {
+                let cls = self.ctxt.classes.idx(cls_id);
+                let cls = cls.read();
+                let mut arg = cls.type_params[tpid.idx()].clone();
 
-        if let Some(cls_id) = tp.class_bound {
-            let cls = self.ctxt.cls(cls_id);
-            if !ty.subclass_from(self.ctxt, cls) {
-                self.fail_cls_bound(cls_id, ty);
-                succeeded = false;
-            }
-        }
+                // the where clauses of the caller add bounds to its class type params
+                if self.caller.owner_cls_id(self.ctxt) == Some(cls_id) {
+                    if let Some(where_bounds) = self.caller.where_bounds.get(&tpid) {
+                        arg.trait_bounds.extend(where_bounds.iter().cloned());
+                    }
+                }
 
-        let cls_id = ty.cls_id(self.ctxt).unwrap();
-        let cls = self.ctxt.classes.idx(cls_id);
-        let cls = cls.read();
+                self.check_tp_against_tp(tp, &arg, ty)
+            }
 
-        for &trait_bound in &tp.trait_bounds {
-            if !cls.traits.contains(&trait_bound) {
-                self.fail_trait_bound(trait_bound, ty);
-                succeeded = false;
+            BuiltinType::FctTypeParam(fct_id, tpid) => {
+                let fct = self.ctxt.fcts.idx(fct_id);
+                let fct = fct.read();
+                self.check_tp_against_tp(tp, &fct.type_params[tpid.idx()], ty)
             }
-        }
 
-        succeeded
+            _ => specialize::check_bounds(
+                self.ctxt,
+                self.pos.expect("pos not set"),
+                ty,
+                tp.class_bound,
+                &tp.trait_bounds,
+            ),
+        }
     }
 
     fn check_tp_against_tp(
@@ -2591,14 +3121,8 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
             }
         }
 
-        if tp.trait_bounds.len() == 0 {
-            return succeeded;
-        }
-
-        let traits_set = arg.trait_bounds.iter().collect::<HashSet<_>>();
-
         for &trait_bound in &tp.trait_bounds {
-            if !traits_set.contains(&trait_bound) {
+            if !arg.trait_bounds.contains(&trait_bound) {
                 self.fail_trait_bound(trait_bound, arg_ty);
                 succeeded = false;
             }
@@ -2642,6 +3166,10 @@ impl<'a, 'ast> MethodLookup<'a, 'ast> {
     fn found_ret(&self) -> Option<BuiltinType> {
         self.found_ret
     }
+
+    fn found_params(&self) -> Option<&[BuiltinType]> {
+        self.found_params.as_ref().map(|params| &params[..])
+    }
 }
 
 fn lookup_method<'ast>(
@@ -2734,6 +3262,32 @@ fn replace_type_param(
             BuiltinType::Class(cls_id, list_id)
         }
 
+        BuiltinType::Trait(trait_id, list_id) => {
+            let params = ctxt.lists.lock().get(list_id);
+
+            let params: TypeParams = params
+                .iter()
+                .map(|p| replace_type_param(ctxt, p, cls_tp, fct_tp))
+                .collect::<Vec<_>>()
+                .into();
+
+            let list_id = ctxt.lists.lock().insert(params);
+            BuiltinType::Trait(trait_id, list_id)
+        }
+
+        BuiltinType::Tuple(list_id) => {
+            let elements = ctxt.lists.lock().get(list_id);
+
+            let elements: TypeParams = elements
+                .iter()
+                .map(|p| replace_type_param(ctxt, p, cls_tp, fct_tp))
+                .collect::<Vec<_>>()
+                .into();
+
+            let list_id = ctxt.lists.lock().insert(elements);
+            BuiltinType::Tuple(list_id)
+        }
+
         BuiltinType::Lambda(_) => unimplemented!(),
 
         _ => ty,
@@ -3993,7 +4547,7 @@ mod tests {
             class X
             class A<T: Foo>
             fun f() -> A<X> { return nil; }",
-            pos(1, 1),
+            pos(4, 24),
             Msg::TraitBoundNotSatisfied("X".into(), "Foo".into()),
         );
 
@@ -4064,6 +4618,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_static_field() {
+        ok("class A { static var x: Int; } fun foo(a: Int) -> Int { A::x = a; return A::x; }");
+        err(
+            "class A { static let x: Int; } fun foo(a: Int) { A::x = a; }",
+            pos(1, 55),
+            Msg::LetReassigned,
+        );
+        err(
+            "class A { static var x: Int; } fun foo() { A::x = true; }",
+            pos(1, 49),
+            Msg::AssignType("x".into(), "Int".into(), "Bool".into()),
+        );
+        err(
+            "class A { static var x: Int; } fun foo() -> Int { return A::y; }",
+            pos(1, 58),
+            Msg::UnknownField("y".into(), "A".into()),
+        );
+        err(
+            "class A { var x: Int; } fun foo() -> Int { return A::x; }",
+            pos(1, 51),
+            Msg::UnknownField("x".into(), "A".into()),
+        );
+    }
+
     #[test]
     fn lambda_assignment() {
         ok("fun f() { let x = || {}; }");
@@ -4138,6 +4717,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_multiple_trait_bounds() {
+        ok("trait Foo { fun foo() -> Int; }
+            trait Bar { fun bar() -> Int; }
+            fun f<T: Foo + Bar>(t: T) -> Int { return t.foo() + t.bar(); }");
+
+        err(
+            "trait Foo { fun f(); }
+            trait Bar { fun f(); }
+            fun g<T: Foo + Bar>(t: T) { t.f(); }",
+            pos(3, 42),
+            Msg::MultipleCandidatesForMethod("T".into(), "f".into()),
+        );
+    }
+
+    #[test]
+    fn test_where_clause_on_class_type_param() {
+        ok("trait Foo { fun foo() -> Int; }
+            class A<T>(let x: T) {
+                fun f() -> Int where T: Foo { return self.x.foo(); }
+            }");
+
+        err(
+            "trait Foo { fun foo() -> Int; }
+            class X
+            class A<T>(let x: T) { fun f() -> Int where T: Foo { return self.x.foo(); } }
+            fun g(a: A<X>) -> Int { return a.f(); }",
+            pos(4, 45),
+            Msg::TraitBoundNotSatisfied("X".into(), "Foo".into()),
+        );
+
+        ok("trait Foo {}
+            fun needsFoo<X: Foo>(x: X) {}
+            class B<T>(let x: T) { fun f() where T: Foo { needsFoo::<T>(self.x); } }");
+
+        err(
+            "trait Foo {}
+            fun needsFoo<X: Foo>(x: X) {}
+            class B<T>(let x: T) { fun f() { needsFoo::<T>(self.x); } }",
+            pos(3, 46),
+            Msg::TraitBoundNotSatisfied("T".into(), "Foo".into()),
+        );
+    }
+
+    #[test]
+    fn test_array_contains_requires_equals() {
+        ok("fun f(a: Array<Int>) -> Bool { return a.contains(1); }");
+
+        err(
+            "class X
+            fun f(a: Array<X>) -> Bool { return a.contains(X()); }",
+            pos(2, 50),
+            Msg::TraitBoundNotSatisfied("X".into(), "Equals".into()),
+        );
+    }
+
     #[test]
     fn test_for_supports_make_iterator() {
         err(
@@ -4157,7 +4792,7 @@ mod tests {
         ok(
             "class Foo { fun makeIterator() -> FooIter { return FooIter(); } }
             class FooIter
-            impl Iterator for FooIter {
+            impl Iterator<Int> for FooIter {
                 fun hasNext() -> Bool { return false; }
                 fun next() -> Int { return 0; }
             }
@@ -4165,6 +4800,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_for_generic_iterator() {
+        ok("fun f(x: Vec<String>) -> String { for s in x { return s; } return \"\"; }");
+        ok("fun f(x: Array<Int>) -> Int { for i in x { return i; } return 0; }");
+        ok("fun f(x: Queue<Bool>) -> Bool { for b in x { return b; } return false; }");
+        ok("fun f(x: String) -> Char { for c in x.codePoints() { return c; } return 'a'; }");
+        ok("fun f<T>(x: Vec<T>) -> Int { var n = 0; for y in x { n = n + 1; } return n; }");
+
+        err(
+            "fun f(x: Vec<String>) -> Int { for s in x { return s; } return 0; }",
+            pos(1, 45),
+            Msg::ReturnType("Int".into(), "String".into()),
+        );
+
+        ok("class Foo { fun makeIterator() -> Iterator<Int> { return range(0, 2).makeIterator(); } }
+            fun f() -> Int { for i in Foo() { return i; } return 0; }");
+
+        ok("class Cell<T>(let value: T)
+            class CellIter<T>(let cell: Cell<T>) {
+                var done: Bool = false;
+                fun makeIterator() -> CellIter<T> = self;
+            }
+            impl Iterator<T> for CellIter<T> {
+                fun hasNext() -> Bool = !self.done;
+                fun next() -> T { self.done = true; return self.cell.value; }
+            }
+            fun f(x: Cell<Int>) -> Int {
+                let it = CellIter::<Int>(x);
+                for i in it { return i; }
+                return 0;
+            }");
+    }
+
+    #[test]
+    fn test_generic_trait_object() {
+        ok("fun f(x: Vec<Int>) -> Iterator<Int> { return x.makeIterator(); }");
+        ok("fun f(x: Iterator<String>) -> String { return x.next(); }");
+        ok("fun f(x: Array<Int>) -> Iterable<Int> { return x; }");
+
+        err(
+            "fun f(x: Vec<Int>) -> Iterator<String> { return x.makeIterator(); }",
+            pos(1, 42),
+            Msg::ReturnType("Iterator<String>".into(), "Iterator<Int>".into()),
+        );
+
+        err(
+            "fun f(x: Iterator) {}",
+            pos(1, 10),
+            Msg::WrongNumberTypeParams(1, 0),
+        );
+    }
+
     #[test]
     fn test_struct_field_missing() {
         err(
@@ -4245,4 +4932,161 @@ mod tests {
             Msg::FctReassigned,
         );
     }
+
+    #[test]
+    fn test_trait_object() {
+        ok("trait Foo { fun bar() -> Int; }
+            class A
+            impl Foo for A { fun bar() -> Int = 1; }
+            fun f(x: Foo) -> Int = x.bar();
+            fun g() -> Int = f(A());");
+        ok("fun f() -> Int { let x: Hash = 1; return x.hash(); }");
+        ok("fun f(x: Hash) { } fun g() { f(1L); f(\"abc\"); f(nil); }");
+
+        err(
+            "trait Foo { fun bar() -> Int; }
+            class A
+            fun f(x: Foo) {}
+            fun g() { f(A()); }",
+            pos(4, 23),
+            Msg::ParamTypesIncompatible("f".into(), vec!["Foo".into()], vec!["A".into()]),
+        );
+        err(
+            "fun f(x: Comparable) -> Int = x.compareTo(x);",
+            pos(1, 32),
+            Msg::UnknownMethod(
+                "Comparable".into(),
+                "compareTo".into(),
+                vec!["Comparable".into()],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_float_trait_object() {
+        ok("trait Foo { fun foo(a: Int, b: Double) -> Int; }
+            impl Foo for Double { fun foo(a: Int, b: Double) -> Int = a; }
+            fun f(x: Foo) -> Int = x.foo(1, 2.0);
+            fun g() -> Int = f(1.0);");
+
+        err(
+            "trait Foo { fun foo(a: Int, b: Int, c: Int, d: Int, e: Int, f: Int, g: Int, h: Int); }
+            impl Foo for Double { fun foo(a: Int, b: Int, c: Int, d: Int, e: Int, f: Int, g: Int, h: Int) {} }
+            fun f(x: Foo) {}
+            fun g() { f(1.0); }",
+            pos(4, 25),
+            Msg::BoxNotPossible(
+                "Double".into(),
+                "method `foo` passes arguments on the stack".into(),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_default_method() {
+        ok("trait Foo {
+                fun cmp(other: Self) -> Int;
+                fun smaller(other: Self) -> Self {
+                    if self.cmp(other) < 0 {
+                        return self;
+                    }
+
+                    return other;
+                }
+            }");
+        ok("trait Foo {
+                fun compareTo(other: Self) -> Int;
+                fun isLess(other: Self) -> Bool = self < other;
+            }");
+        ok("trait Foo {
+                fun foo() -> Int;
+                fun bar() -> Int = self.foo();
+            }
+            class A
+            impl Foo for A { fun foo() -> Int = 1; }
+            fun f<T: Foo>(x: T) -> Int = x.bar();
+            fun g(x: Foo) -> Int = x.bar();
+            fun h() -> Int = f::<A>(A()) + g(A());");
+
+        err(
+            "trait Foo { fun foo() -> Int = self.bar(); } class A impl Foo for A {}",
+            pos(1, 36),
+            Msg::UnknownMethod("Self".into(), "bar".into(), Vec::new()),
+        );
+    }
+
+    #[test]
+    fn test_tuple() {
+        ok("fun f() -> (Int, String) = (1, \"abc\");");
+        ok("fun f(x: (Int, Bool)) -> Bool = x.1;");
+        ok("fun f() -> Int { let (a, b) = (1, 2L); return a; }");
+        ok("fun f(x: (Int, (Bool, Int))) -> Int { let (a, b) = x; return a + b.1; }");
+        ok("fun f() -> Int { let x = (1); return x; }");
+        ok("fun f<T>(x: T) -> (T, T) = (x, x); fun g() -> (Int, Int) = f::<Int>(1);");
+
+        err(
+            "fun f() { let x: (Int, Bool) = (1, 2); }",
+            pos(1, 11),
+            Msg::AssignType("x".into(), "(Int, Bool)".into(), "(Int, Int)".into()),
+        );
+        err(
+            "fun f(x: (Int, Bool)) -> Int = x.2;",
+            pos(1, 33),
+            Msg::UnknownField("2".into(), "(Int, Bool)".into()),
+        );
+        err(
+            "fun f(x: (Int, Int)) { x.0 = 1; }",
+            pos(1, 28),
+            Msg::LetReassigned,
+        );
+        err(
+            "fun f() { let (a, b) = (1, 2, 3); }",
+            pos(1, 11),
+            Msg::WrongNumberOfTupleElements(3, 2),
+        );
+        err(
+            "fun f() { let (a, b) = 1; }",
+            pos(1, 11),
+            Msg::TupleExpected("Int".into()),
+        );
+    }
+
+    #[test]
+    fn test_template() {
+        ok("fun f(a: Int, b: String) -> String = \"a = ${a}, b = ${b}\";");
+        ok("fun f(a: Bool, b: Double) -> String = \"${a}${b + 1.0}\";");
+        ok("class A { fun toString() -> String = \"A\"; }
+            fun f(a: A) -> String = \"a: ${a} ${\"nested ${a}\"}\";");
+
+        err(
+            "class A fun f(a: A) -> String = \"x${a}\";",
+            pos(1, 37),
+            Msg::UnknownMethod("A".into(), "toString".into(), Vec::new()),
+        );
+        err(
+            "class A { fun toString() -> Int = 1; } fun f(a: A) -> String = \"${a}\";",
+            pos(1, 67),
+            Msg::ToStringReturnType("Int".into()),
+        );
+    }
+
+    #[test]
+    fn test_deprecated() {
+        warnings(
+            "#[deprecated(\"use g\")] fun f() {}
+            fun g() { f(); }",
+            &[(pos(2, 23), Msg::DeprecatedFct("f".into(), "use g".into()))],
+        );
+
+        warnings(
+            "class A { #[deprecated] fun f() {} }
+            fun g(a: A) { a.f(); a.f(); }",
+            &[
+                (pos(2, 28), Msg::DeprecatedFct("f".into(), "".into())),
+                (pos(2, 35), Msg::DeprecatedFct("f".into(), "".into())),
+            ],
+        );
+
+        warnings("fun f() {} fun g() { f(); }", &[]);
+    }
 }
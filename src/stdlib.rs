@@ -8,14 +8,16 @@ use std::str;
 use std::thread;
 use std::time::Duration;
 
+use crate::baseline::unload;
 use crate::class::TypeParams;
 use crate::ctxt::exception_set;
 use crate::ctxt::get_vm;
 use crate::exception::{alloc_exception, stacktrace_from_last_dtn};
 use crate::gc::{Address, GcReason};
 use crate::handle::root;
-use crate::object::{ByteArray, Obj, Ref, Str};
+use crate::object::{alloc, Array, ByteArray, GcSpaceStats, GcStats, Obj, Ref, Str};
 use crate::os::signal::Trap;
+use crate::safepoint;
 use crate::sym::Sym::SymFct;
 use crate::threads::{DoraThread, THREAD};
 
@@ -69,6 +71,10 @@ pub extern "C" fn addr(val: Ref<Obj>) -> u64 {
     val.raw() as usize as u64
 }
 
+pub extern "C" fn identity_hash(val: Ref<Obj>) -> i32 {
+    val.header().identity_hash()
+}
+
 pub extern "C" fn fatal_error(msg: Ref<Str>) {
     write!(&mut io::stderr(), "fatal error: ").expect("could not print to stderr");
     io::stderr().write(msg.content()).unwrap();
@@ -185,7 +191,24 @@ pub extern "C" fn gc_verify_refs(obj: Ref<Obj>, value: Ref<Obj>) {
 
 pub extern "C" fn gc_alloc(size: usize, array_ref: bool) -> *mut Obj {
     let vm = get_vm();
-    vm.gc.alloc(vm, size, array_ref).to_mut_ptr()
+    let ptr = vm.gc.alloc(vm, size, array_ref);
+
+    if ptr.is_null() {
+        if vm.args.flag_gc_verbose {
+            println!("GC: allocation of {} bytes failed", size);
+            vm.gc.dump_usage();
+        }
+
+        if vm.args.flag_gc_dump_heap_on_oom {
+            vm.gc.dump_heap(vm);
+        }
+
+        // let the alloc thunk throw the OutOfMemoryError
+        let oom_error = vm.gc.out_of_memory_error();
+        exception_set(oom_error.to_ptr());
+    }
+
+    ptr.to_mut_ptr()
 }
 
 pub extern "C" fn gc_collect() {
@@ -198,6 +221,41 @@ pub extern "C" fn gc_minor_collect() {
     vm.gc.minor_collect(vm, GcReason::ForceMinorCollect);
 }
 
+pub extern "C" fn gc_stats() -> Ref<GcStats> {
+    let vm = get_vm();
+    let stats = vm.gc.stats();
+
+    let array_def_id = vm.vips.gc_space_stats_array(vm);
+    let spaces = Array::alloc(vm, stats.spaces.len(), Ref::<Obj>::null(), array_def_id);
+    let mut spaces = root(spaces);
+
+    for (idx, space) in stats.spaces.iter().enumerate() {
+        let cls_def_id = vm.vips.gc_space_stats(vm);
+        let obj: Ref<GcSpaceStats> = alloc(vm, cls_def_id).cast();
+        let mut obj = root(obj);
+
+        obj.used = space.used as i64;
+        obj.committed = space.committed as i64;
+        obj.name = Str::from_buffer(vm, space.name.as_bytes());
+        vm.gc.write_barrier(obj.direct().address());
+
+        spaces.set_at(idx, obj.direct().cast());
+        vm.gc.write_barrier(spaces.direct().address());
+    }
+
+    let cls_def_id = vm.vips.gc_stats(vm);
+    let obj: Ref<GcStats> = alloc(vm, cls_def_id).cast();
+    let mut obj = root(obj);
+
+    obj.minor_collections = stats.minor_collections as i32;
+    obj.full_collections = stats.full_collections as i32;
+    obj.total_pause = stats.total_pause as f64;
+    obj.allocated = vm.gc.allocated_size() as i64;
+    obj.spaces = spaces.direct();
+
+    obj.direct()
+}
+
 pub extern "C" fn str_len(s: Ref<Str>) -> i32 {
     s.len() as i32
 }
@@ -285,6 +343,33 @@ pub extern "C" fn native_free(addr: *const u8) {
     unsafe { libc::free(addr as *mut libc::c_void) }
 }
 
+pub extern "C" fn global_init_start(global_id: u32) -> u32 {
+    let vm = get_vm();
+    let glob = vm.globals.idx(global_id.into());
+    let address_init = glob.lock().address_init;
+
+    // the native thunk's frame is the innermost frame known to the thread,
+    // its caller is the function accessing the global
+    let fp = THREAD.with(|thread| {
+        let dtn = thread.borrow().dtn();
+        unsafe { *((*dtn).fp as *const usize) }
+    });
+
+    if vm.global_inits.start(global_id.into(), address_init, fp) {
+        1
+    } else {
+        0
+    }
+}
+
+pub extern "C" fn global_init_finish(global_id: u32) {
+    let vm = get_vm();
+    let glob = vm.globals.idx(global_id.into());
+    let address_init = glob.lock().address_init;
+
+    vm.global_inits.finish(global_id.into(), address_init);
+}
+
 pub extern "C" fn trap(trap_id: u32) {
     let vm = get_vm();
     let trap = Trap::from(trap_id).expect("invalid trap id!");
@@ -345,6 +430,7 @@ pub extern "C" fn spawn_thread(obj: Ref<Obj>) {
 
             THREAD.with(|thread| {
                 thread.borrow().use_dtn(&mut dtn, || {
+                    unload::reclaim_if_needed(vm);
                     baseline::generate(vm, main, &type_params, &type_params)
                 })
             })
@@ -354,7 +440,10 @@ pub extern "C" fn spawn_thread(obj: Ref<Obj>) {
         let dora_entry_thunk = vm.dora_entry_thunk();
         let fct: extern "C" fn(Address, Address, Ref<Obj>) =
             unsafe { mem::transmute(dora_entry_thunk) };
+
+        safepoint::enter_dora(vm);
         fct(tld, fct_ptr, obj);
+        safepoint::leave_dora();
 
         // remove thread from list of all threads
         vm.threads.detach_current_thread();
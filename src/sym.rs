@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use self::Sym::*;
 
@@ -6,15 +6,51 @@ use crate::class::{ClassId, TypeParamId};
 use crate::ctxt::*;
 use dora_parser::interner::Name;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModuleId(usize);
+
+// The global module contains the standard library and all files in the
+// top-level directory of the program, its symbols are visible everywhere.
+// Subdirectories of the program become modules with their own symbols,
+// their items need to be imported or accessed through qualified paths.
 #[derive(Debug)]
 pub struct SymTable {
     levels: Vec<SymLevel>,
+    modules: Vec<Module>,
+    module_children: HashMap<(Option<ModuleId>, Name), ModuleId>,
+
+    // module of the currently checked item, `None` for the global module
+    module: Option<ModuleId>,
+}
+
+#[derive(Debug)]
+struct Module {
+    table: SymLevel,
+
+    // names of items declared with `pub`, only those are accessible
+    // from other modules
+    public: HashSet<Name>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PathError {
+    // name at this index of the path does not exist
+    Unknown(usize),
+
+    // name at this index is used as module but isn't one
+    NotModule(usize),
+
+    // name at this index is private to its module
+    NotPublic(usize),
 }
 
 impl SymTable {
     pub fn new() -> SymTable {
         SymTable {
             levels: vec![SymLevel::new()],
+            modules: Vec::new(),
+            module_children: HashMap::new(),
+            module: None,
         }
     }
 
@@ -32,14 +68,94 @@ impl SymTable {
         self.levels.len()
     }
 
+    pub fn module(&self) -> Option<ModuleId> {
+        self.module
+    }
+
+    pub fn set_module(&mut self, module: Option<ModuleId>) {
+        self.module = module;
+    }
+
+    // returns the submodule `name` of `parent`, the module is created
+    // and added to the symbols of `parent` if it doesn't exist yet
+    pub fn add_module(&mut self, parent: Option<ModuleId>, name: Name) -> Result<ModuleId, Sym> {
+        if let Some(&module) = self.module_children.get(&(parent, name)) {
+            return Ok(module);
+        }
+
+        let module = ModuleId(self.modules.len());
+        self.modules.push(Module {
+            table: SymLevel::new(),
+            public: HashSet::new(),
+        });
+        self.module_children.insert((parent, name), module);
+
+        let old = match parent {
+            Some(parent) => {
+                let parent = &mut self.modules[parent.0];
+                parent.public.insert(name);
+                parent.table.insert(name, SymModule(module))
+            }
+
+            None => self.levels[0].insert(name, SymModule(module)),
+        };
+
+        match old {
+            Some(sym) => Err(sym),
+            None => Ok(module),
+        }
+    }
+
+    pub fn module_by_path(&self, path: &[Name]) -> Option<ModuleId> {
+        let mut module = None;
+
+        for &name in path {
+            module = Some(*self.module_children.get(&(module, name))?);
+        }
+
+        module
+    }
+
     pub fn get(&self, name: Name) -> Option<Sym> {
-        for level in self.levels.iter().rev() {
+        for level in self.levels.iter().skip(1).rev() {
             if let Some(val) = level.get(name) {
                 return Some(val.clone());
             }
         }
 
-        None
+        if let Some(module) = self.module {
+            if let Some(val) = self.modules[module.0].table.get(name) {
+                return Some(val.clone());
+            }
+        }
+
+        self.levels[0].get(name).cloned()
+    }
+
+    // resolves qualified paths like `util::Foo`: the first name is looked
+    // up like any other name, all following names in the module before
+    pub fn get_path(&self, path: &[Name]) -> Result<Sym, PathError> {
+        let mut sym = self.get(path[0]).ok_or(PathError::Unknown(0))?;
+
+        for (idx, &name) in path.iter().enumerate().skip(1) {
+            let module_id = match sym {
+                SymModule(module_id) => module_id,
+                _ => return Err(PathError::NotModule(idx - 1)),
+            };
+
+            let module = &self.modules[module_id.0];
+            sym = module
+                .table
+                .get(name)
+                .cloned()
+                .ok_or(PathError::Unknown(idx))?;
+
+            if self.module != Some(module_id) && !module.public.contains(&name) {
+                return Err(PathError::NotPublic(idx));
+            }
+        }
+
+        Ok(sym)
     }
 
     pub fn get_var(&self, name: Name) -> Option<VarId> {
@@ -66,8 +182,40 @@ impl SymTable {
         self.get(name).and_then(|n| n.to_global())
     }
 
+    // items are added to the current module when no local scope exists
     pub fn insert(&mut self, name: Name, sym: Sym) -> Option<Sym> {
-        self.levels.last_mut().unwrap().insert(name, sym)
+        self.current_level().insert(name, sym)
+    }
+
+    // like insert, but keeps the existing symbol when the name is already
+    // taken in the current scope
+    pub fn try_insert(&mut self, name: Name, sym: Sym) -> Result<(), Sym> {
+        let level = self.current_level();
+
+        if let Some(old) = level.get(name) {
+            return Err(old.clone());
+        }
+
+        level.insert(name, sym);
+
+        Ok(())
+    }
+
+    fn current_level(&mut self) -> &mut SymLevel {
+        if self.levels.len() == 1 {
+            if let Some(module) = self.module {
+                return &mut self.modules[module.0].table;
+            }
+        }
+
+        self.levels.last_mut().unwrap()
+    }
+
+    // makes an item of the current module accessible from other modules
+    pub fn set_public(&mut self, name: Name) {
+        if let Some(module) = self.module {
+            self.modules[module.0].public.insert(name);
+        }
     }
 }
 
@@ -94,7 +242,7 @@ impl SymLevel {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Sym {
     SymFct(FctId),
     SymVar(VarId),
@@ -104,7 +252,9 @@ pub enum Sym {
     SymGlobal(GlobalId),
     SymClassTypeParam(ClassId, TypeParamId),
     SymFctTypeParam(FctId, TypeParamId),
+    SymTraitTypeParam(TraitId, TypeParamId),
     SymConst(ConstId),
+    SymModule(ModuleId),
 }
 
 impl Sym {
@@ -196,6 +346,7 @@ impl Sym {
         match *self {
             SymClassTypeParam(_, _) => true,
             SymFctTypeParam(_, _) => true,
+            SymTraitTypeParam(_, _) => true,
             _ => false,
         }
     }
@@ -213,4 +364,18 @@ impl Sym {
             _ => None,
         }
     }
+
+    pub fn is_module(&self) -> bool {
+        match *self {
+            SymModule(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn to_module(&self) -> Option<ModuleId> {
+        match *self {
+            SymModule(id) => Some(id),
+            _ => None,
+        }
+    }
 }
@@ -124,7 +124,7 @@ impl DoraThread {
     }
 
     pub fn state(&self) -> ThreadState {
-        let state = self.state.load(Ordering::Relaxed);
+        let state = self.state.load(Ordering::SeqCst);
 
         match state {
             0 => ThreadState::Uninitialized,
@@ -136,7 +136,7 @@ impl DoraThread {
     }
 
     pub fn set_state(&self, state: ThreadState) {
-        self.state.store(state as usize, Ordering::Relaxed);
+        self.state.store(state as usize, Ordering::SeqCst);
     }
 }
 
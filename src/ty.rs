@@ -6,6 +6,7 @@ use crate::ctxt::VM;
 use crate::ctxt::{FctId, StructId, TraitId};
 use crate::mem;
 use crate::semck;
+use crate::semck::specialize::specialize_type;
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum BuiltinType {
@@ -42,14 +43,18 @@ pub enum BuiltinType {
     Struct(StructId, TypeListId),
 
     // some trait
-    Trait(TraitId),
+    Trait(TraitId, TypeListId),
 
     // some type variable
     FctTypeParam(FctId, TypeParamId),
     ClassTypeParam(ClassId, TypeParamId),
+    TraitTypeParam(TraitId, TypeParamId),
 
     // some lambda
     Lambda(LambdaId),
+
+    // some tuple, stores the types of its elements
+    Tuple(TypeListId),
 }
 
 impl BuiltinType {
@@ -81,6 +86,20 @@ impl BuiltinType {
         }
     }
 
+    pub fn is_trait(&self) -> bool {
+        match *self {
+            BuiltinType::Trait(_, _) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        match *self {
+            BuiltinType::Tuple(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_float(&self) -> bool {
         match self {
             &BuiltinType::Float | &BuiltinType::Double => true,
@@ -118,18 +137,66 @@ impl BuiltinType {
     }
 
     pub fn implements_trait(&self, vm: &VM, trait_id: TraitId) -> bool {
-        if let Some(cls_id) = self.cls_id(vm) {
-            let cls = vm.classes.idx(cls_id);
+        let mut cls_id = self.cls_id(vm);
+
+        while let Some(id) = cls_id {
+            let cls = vm.classes.idx(id);
             let cls = cls.read();
-            return cls.traits.contains(&trait_id);
+
+            if cls.traits.contains(&trait_id) {
+                return true;
+            }
+
+            cls_id = cls.parent_class;
         }
 
         false
     }
 
+    // checks whether the type implements the trait with the given type
+    // params, for a generic trait the type params of the impl need to match
+    pub fn implements_trait_type(&self, vm: &VM, trait_id: TraitId, list_id: TypeListId) -> bool {
+        let params = vm.lists.lock().get(list_id);
+
+        if params.len() == 0 {
+            return self.implements_trait(vm, trait_id);
+        }
+
+        self.trait_type_params(vm, trait_id) == Some(params)
+    }
+
+    // returns the type params the type implements the given trait with
+    pub fn trait_type_params(&self, vm: &VM, trait_id: TraitId) -> Option<TypeParams> {
+        if let BuiltinType::Trait(id, list_id) = *self {
+            return if id == trait_id {
+                Some(vm.lists.lock().get(list_id))
+            } else {
+                None
+            };
+        }
+
+        let cls_id = self.cls_id(vm)?;
+        let cls = vm.classes.idx(cls_id);
+        let cls = cls.read();
+
+        let impl_id = cls.find_impl_for_trait(vm, trait_id)?;
+        let ximpl = vm.impls[impl_id].read();
+        let cls_type_params = self.type_params(vm);
+
+        let params: Vec<_> = ximpl
+            .trait_type_params
+            .iter()
+            .map(|ty| specialize_type(vm, ty, &cls_type_params, &TypeParams::empty()))
+            .collect();
+
+        Some(params.into())
+    }
+
     pub fn type_params(&self, vm: &VM) -> TypeParams {
         match self {
             &BuiltinType::Class(_, list_id) => vm.lists.lock().get(list_id),
+            &BuiltinType::Trait(_, list_id) => vm.lists.lock().get(list_id),
+            &BuiltinType::Tuple(list_id) => vm.lists.lock().get(list_id),
 
             _ => TypeParams::empty(),
         }
@@ -139,8 +206,11 @@ impl BuiltinType {
         match self {
             &BuiltinType::ClassTypeParam(_, _) => true,
             &BuiltinType::FctTypeParam(_, _) => true,
+            &BuiltinType::TraitTypeParam(_, _) => true,
 
-            &BuiltinType::Class(_, list_id) => {
+            &BuiltinType::Class(_, list_id)
+            | &BuiltinType::Trait(_, list_id)
+            | &BuiltinType::Tuple(list_id) => {
                 let params = vm.lists.lock().get(list_id);
                 params.iter().any(|t| t.contains_type_param(vm))
             }
@@ -234,9 +304,22 @@ impl BuiltinType {
                     format!("{}<{}>", name, params)
                 }
             }
-            BuiltinType::Trait(tid) => {
+            BuiltinType::Trait(tid, list_id) => {
                 let xtrait = vm.traits[tid].read();
-                vm.interner.str(xtrait.name).to_string()
+                let base = vm.interner.str(xtrait.name);
+                let params = vm.lists.lock().get(list_id);
+
+                if params.len() == 0 {
+                    base.to_string()
+                } else {
+                    let params = params
+                        .iter()
+                        .map(|ty| ty.name(vm))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    format!("{}<{}>", base, params)
+                }
             }
             BuiltinType::ClassTypeParam(cid, id) => {
                 let cls = vm.classes.idx(cid);
@@ -250,6 +333,12 @@ impl BuiltinType {
                 vm.interner.str(fct.type_params[id.idx()].name).to_string()
             }
 
+            BuiltinType::TraitTypeParam(tid, id) => {
+                let xtrait = vm.traits[tid].read();
+                let name = xtrait.type_params[id.idx()].name;
+                vm.interner.str(name).to_string()
+            }
+
             BuiltinType::Lambda(id) => {
                 let lambda = vm.lambda_types.lock().get(id);
                 let params = lambda
@@ -262,6 +351,17 @@ impl BuiltinType {
 
                 format!("({}) -> {}", params, ret)
             }
+
+            BuiltinType::Tuple(list_id) => {
+                let elements = vm.lists.lock().get(list_id);
+                let elements = elements
+                    .iter()
+                    .map(|ty| ty.name(vm))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                format!("({})", elements)
+            }
         }
     }
 
@@ -281,14 +381,19 @@ impl BuiltinType {
             BuiltinType::Float | BuiltinType::Double => *self == other,
             BuiltinType::Nil => panic!("nil does not allow any other types"),
             BuiltinType::Ptr => panic!("ptr does not allow any other types"),
-            BuiltinType::This => unreachable!(),
+            BuiltinType::This => *self == other,
             BuiltinType::Class(_, _) => {
                 *self == other || other.is_nil() || other.subclass_from(vm, *self)
             }
-            BuiltinType::Trait(_) => unimplemented!(),
+            BuiltinType::Trait(trait_id, list_id) => {
+                *self == other
+                    || other.is_nil()
+                    || other.implements_trait_type(vm, trait_id, list_id)
+            }
 
             BuiltinType::ClassTypeParam(_, _) => *self == other,
             BuiltinType::FctTypeParam(_, _) => *self == other,
+            BuiltinType::TraitTypeParam(_, _) => *self == other,
 
             BuiltinType::Lambda(_) => {
                 // for now expect the exact same params and return types
@@ -296,6 +401,10 @@ impl BuiltinType {
                 //                             sub class for return type
                 *self == other
             }
+
+            // tuples are immutable, so their element types could be covariant,
+            // but for now the types need to match exactly
+            BuiltinType::Tuple(_) => *self == other,
         }
     }
 
@@ -320,9 +429,10 @@ impl BuiltinType {
             BuiltinType::Double => 8,
             BuiltinType::Nil => panic!("no size for nil."),
             BuiltinType::This => panic!("no size for Self."),
-            BuiltinType::Class(_, _) | BuiltinType::Lambda(_) | BuiltinType::Ptr => {
-                mem::ptr_width()
-            }
+            BuiltinType::Class(_, _)
+            | BuiltinType::Lambda(_)
+            | BuiltinType::Tuple(_)
+            | BuiltinType::Ptr => mem::ptr_width(),
             BuiltinType::Struct(sid, list_id) => {
                 let params = vm.lists.lock().get(list_id);
                 let sid = semck::specialize::specialize_struct_id_params(vm, sid, params);
@@ -331,8 +441,10 @@ impl BuiltinType {
 
                 struc.size
             }
-            BuiltinType::Trait(_) => 2 * mem::ptr_width(),
-            BuiltinType::ClassTypeParam(_, _) | BuiltinType::FctTypeParam(_, _) => {
+            BuiltinType::Trait(_, _) => mem::ptr_width(),
+            BuiltinType::ClassTypeParam(_, _)
+            | BuiltinType::FctTypeParam(_, _)
+            | BuiltinType::TraitTypeParam(_, _) => {
                 panic!("no size for type variable.")
             }
         }
@@ -351,9 +463,10 @@ impl BuiltinType {
             BuiltinType::Double => 8,
             BuiltinType::Nil => panic!("no alignment for nil."),
             BuiltinType::This => panic!("no alignment for Self."),
-            BuiltinType::Class(_, _) | BuiltinType::Lambda(_) | BuiltinType::Ptr => {
-                mem::ptr_width()
-            }
+            BuiltinType::Class(_, _)
+            | BuiltinType::Lambda(_)
+            | BuiltinType::Tuple(_)
+            | BuiltinType::Ptr => mem::ptr_width(),
             BuiltinType::Struct(sid, list_id) => {
                 let params = vm.lists.lock().get(list_id);
                 let sid = semck::specialize::specialize_struct_id_params(vm, sid, params);
@@ -362,8 +475,10 @@ impl BuiltinType {
 
                 struc.align
             }
-            BuiltinType::Trait(_) => mem::ptr_width(),
-            BuiltinType::ClassTypeParam(_, _) | BuiltinType::FctTypeParam(_, _) => {
+            BuiltinType::Trait(_, _) => mem::ptr_width(),
+            BuiltinType::ClassTypeParam(_, _)
+            | BuiltinType::FctTypeParam(_, _)
+            | BuiltinType::TraitTypeParam(_, _) => {
                 panic!("no alignment for type variable.")
             }
         }
@@ -382,12 +497,15 @@ impl BuiltinType {
             BuiltinType::Double => MachineMode::Float64,
             BuiltinType::Nil => panic!("no machine mode for nil."),
             BuiltinType::This => panic!("no machine mode for Self."),
-            BuiltinType::Class(_, _) | BuiltinType::Lambda(_) | BuiltinType::Ptr => {
-                MachineMode::Ptr
-            }
+            BuiltinType::Class(_, _)
+            | BuiltinType::Lambda(_)
+            | BuiltinType::Trait(_, _)
+            | BuiltinType::Tuple(_)
+            | BuiltinType::Ptr => MachineMode::Ptr,
             BuiltinType::Struct(_, _) => panic!("no machine mode for struct."),
-            BuiltinType::Trait(_) => unimplemented!(),
-            BuiltinType::ClassTypeParam(_, _) | BuiltinType::FctTypeParam(_, _) => {
+            BuiltinType::ClassTypeParam(_, _)
+            | BuiltinType::FctTypeParam(_, _)
+            | BuiltinType::TraitTypeParam(_, _) => {
                 panic!("no machine mode for type variable.")
             }
         }